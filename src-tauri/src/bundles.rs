@@ -0,0 +1,752 @@
+//! Portable `.ospbundle` archives for moving libraries, slide groups and
+//! playlists between machines.
+//!
+//! A bundle is a zip archive holding a manifest, the document JSON and every
+//! media file it references. On import all ids are regenerated and media is
+//! deduplicated against the local library by content hash.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use uuid::Uuid;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::history;
+use crate::media_references;
+use crate::migrations::{self, CURRENT_SCHEMA_VERSION};
+use crate::models::{Library, MediaItem, Playlist, SlideGroup};
+use crate::repository::{self, Repository};
+use crate::search;
+use crate::storage::{self, DocumentKind};
+
+/// File extension used for bundles
+pub const BUNDLE_EXTENSION: &str = "ospbundle";
+
+/// Bundle layout version written by this build
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const DOCUMENT_NAME: &str = "document.json";
+const FILES_PREFIX: &str = "files/";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleKind {
+    Library,
+    SlideGroup,
+    Playlist,
+}
+
+/// What to export
+#[derive(Debug, Deserialize, Clone)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum BundleSource {
+    Library {
+        library_id: String,
+    },
+    SlideGroup {
+        library_id: String,
+        slide_group_id: String,
+    },
+    Playlist {
+        playlist_id: String,
+    },
+}
+
+/// A media file stored in the bundle under `files/<fileName>`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleMediaFile {
+    pub file_name: String,
+    pub hash: String,
+    pub size_bytes: u64,
+    pub item: Option<MediaItem>, // Media library entry when the file is a media item source
+    #[serde(skip)]
+    pub path: PathBuf, // Where the file is read from on export; linked media lives outside the library
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub kind: BundleKind,
+    pub name: String,
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub fonts: Vec<String>, // Font families used by text, so the target can warn about missing fonts
+    pub media: Vec<BundleMediaFile>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleExportSummary {
+    pub path: String,
+    pub kind: BundleKind,
+    pub name: String,
+    pub media_files: usize,
+    pub fonts: Vec<String>,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportSummary {
+    pub kind: BundleKind,
+    pub name: String,
+    pub library_id: Option<String>,
+    pub playlist_id: Option<String>,
+    pub slide_group_ids: Vec<String>,
+    pub media_imported: usize,
+    pub media_reused: usize, // Already present locally with the same content hash
+    pub fonts: Vec<String>,
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+    match err {
+        ZipError::Io(err) => AppError::from(err),
+        ZipError::UnsupportedArchive(_) => {
+            AppError::unsupported_format(format!("Bundle archive error: {}", err))
+        }
+        _ => {
+            AppError::corrupt(format!("Bundle archive error: {}", err)).with_code("INVALID_ARCHIVE")
+        }
+    }
+}
+
+// ===== Export =====
+
+/// Write a bundle for `source` to `destination`
+pub fn export_bundle(
+    app: &AppHandle,
+    source: &BundleSource,
+    destination: &Path,
+) -> AppResult<BundleExportSummary> {
+    let repo = repository::current(app);
+
+    let (kind, name, document, groups): (_, _, JsonValue, Vec<SlideGroup>) = match source {
+        BundleSource::Library { library_id } => {
+            let library = get_library(repo.as_ref(), library_id)?;
+            (
+                BundleKind::Library,
+                library.name.clone(),
+                serde_json::to_value(&library)?,
+                library.slide_groups,
+            )
+        }
+        BundleSource::SlideGroup {
+            library_id,
+            slide_group_id,
+        } => {
+            let group = get_library(repo.as_ref(), library_id)?
+                .slide_groups
+                .into_iter()
+                .find(|g| &g.id == slide_group_id)
+                .ok_or_else(|| {
+                    AppError::not_found(format!("Slide group not found: {}", slide_group_id))
+                        .with_code("SLIDE_GROUP_NOT_FOUND")
+                        .with_entity(slide_group_id)
+                })?;
+            (
+                BundleKind::SlideGroup,
+                group.title.clone(),
+                serde_json::to_value(&group)?,
+                vec![group],
+            )
+        }
+        BundleSource::Playlist { playlist_id } => {
+            let playlist = repo
+                .get_playlist(playlist_id)?
+                .ok_or_else(|| AppError::document_not_found(DocumentKind::Playlist, playlist_id))?;
+            (
+                BundleKind::Playlist,
+                playlist.name.clone(),
+                serde_json::to_value(&playlist)?,
+                playlist.items.into_iter().map(|i| i.slide_group).collect(),
+            )
+        }
+    };
+
+    let mut fonts = BTreeSet::new();
+    let mut file_names = BTreeSet::new();
+    for slide in groups.iter().flat_map(|g| &g.slides) {
+        fonts.extend(
+            slide
+                .objects
+                .iter()
+                .flatten()
+                .filter_map(|object| object.font_family())
+                .map(|f| f.to_string()),
+        );
+        file_names.extend(
+            slide
+                .media_sources()
+                .into_iter()
+                .filter_map(|src| storage::media_file_name(src)),
+        );
+    }
+
+    let media = collect_media_files(app, repo.as_ref(), file_names)?;
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        kind,
+        name: name.clone(),
+        schema_version: CURRENT_SCHEMA_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        fonts: fonts.into_iter().collect(),
+        media,
+    };
+
+    write_bundle(destination, &manifest, &document)?;
+
+    Ok(BundleExportSummary {
+        path: destination.to_string_lossy().to_string(),
+        kind,
+        name,
+        media_files: manifest.media.len(),
+        fonts: manifest.fonts,
+        size_bytes: fs::metadata(destination)?.len(),
+    })
+}
+
+fn get_library(repo: &dyn Repository, id: &str) -> AppResult<Library> {
+    repo.get_library(id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::Library, id))
+}
+
+/// Resolve referenced file names to media files on disk, pulling in thumbnails of media items
+fn collect_media_files(
+    app: &AppHandle,
+    repo: &dyn Repository,
+    file_names: BTreeSet<String>,
+) -> AppResult<Vec<BundleMediaFile>> {
+    let media_files_dir = storage::get_media_files_dir(app)?;
+    let items: HashMap<String, MediaItem> = repo
+        .load_media_items()?
+        .items
+        .into_iter()
+        .filter_map(|item| Some((storage::media_file_name(&item.source)?, item)))
+        .collect();
+
+    let mut names = file_names.clone();
+    for name in &file_names {
+        if let Some(thumbnail) = items.get(name).and_then(|i| i.thumbnail.as_deref()) {
+            names.extend(storage::media_file_name(thumbnail));
+        }
+    }
+
+    let mut media = Vec::new();
+    for name in names {
+        let path = match items.get(&name) {
+            Some(item) => storage::media_item_path(app, item)?,
+            None => media_files_dir.join(&name),
+        };
+        if !path.is_file() {
+            eprintln!("Skipping missing media file in bundle: {}", name);
+            continue;
+        }
+
+        media.push(BundleMediaFile {
+            hash: storage::compute_file_hash(&path)?,
+            size_bytes: fs::metadata(&path)?.len(),
+            item: items.get(&name).cloned(),
+            file_name: name,
+            path,
+        });
+    }
+
+    Ok(media)
+}
+
+/// Stream the bundle into a temporary file next to `destination`, then move it into place
+fn write_bundle(
+    destination: &Path,
+    manifest: &BundleManifest,
+    document: &JsonValue,
+) -> AppResult<()> {
+    let partial_path = destination.with_extension(format!("{}.partial", BUNDLE_EXTENSION));
+
+    let result = (|| -> AppResult<()> {
+        let mut writer = ZipWriter::new(fs::File::create(&partial_path)?);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        writer
+            .start_file(MANIFEST_NAME, deflated)
+            .map_err(zip_error)?;
+        writer.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+
+        writer
+            .start_file(DOCUMENT_NAME, deflated)
+            .map_err(zip_error)?;
+        writer.write_all(&serde_json::to_vec_pretty(document)?)?;
+
+        // Images and videos are already compressed
+        for file in &manifest.media {
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Stored)
+                .large_file(file.size_bytes >= u32::MAX as u64);
+            writer
+                .start_file(format!("{}{}", FILES_PREFIX, file.file_name), options)
+                .map_err(zip_error)?;
+            io::copy(&mut fs::File::open(&file.path)?, &mut writer)?;
+        }
+
+        writer.finish().map_err(zip_error)?.sync_all()?;
+        fs::rename(&partial_path, destination)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path);
+    }
+    result
+}
+
+// ===== Import =====
+
+/// Import a bundle; slide group bundles go into `target_library_id` or a new library
+pub fn import_bundle(
+    app: &AppHandle,
+    source: &Path,
+    target_library_id: Option<&str>,
+) -> AppResult<BundleImportSummary> {
+    let file = fs::File::open(source).map_err(|e| AppError::io(e, source))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| zip_error(e).with_path(source))?;
+
+    let manifest: BundleManifest =
+        serde_json::from_reader(archive.by_name(MANIFEST_NAME).map_err(zip_error)?)?;
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(AppError::unsupported_format(format!(
+            "Bundle format v{} was created by a newer version of the app",
+            manifest.format_version
+        ))
+        .with_code("NEWER_BUNDLE_FORMAT")
+        .with_path(source));
+    }
+    let document: JsonValue =
+        serde_json::from_reader(archive.by_name(DOCUMENT_NAME).map_err(zip_error)?)?;
+
+    let repo = repository::current(app);
+    // Slide media has no library item, so it is unreferenced until the document is saved
+    let _imports = media_references::lock_media_imports();
+    // Nothing is saved until every bundled file has been extracted and verified
+    let media = import_media(app, repo.as_ref(), &mut archive, &manifest)?;
+    let result = save_bundle(
+        app,
+        repo.as_ref(),
+        &manifest,
+        document,
+        &media,
+        target_library_id,
+    );
+    if result.is_err() {
+        media.discard(repo.as_ref());
+    }
+    result
+}
+
+/// Save the media items and the document of a bundle whose files are in place
+fn save_bundle(
+    app: &AppHandle,
+    repo: &dyn Repository,
+    manifest: &BundleManifest,
+    document: JsonValue,
+    media: &ImportedMedia,
+    target_library_id: Option<&str>,
+) -> AppResult<BundleImportSummary> {
+    for item in &media.items {
+        repo.save_media_item(item)?;
+        search::index_media_item(app, item);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut summary = BundleImportSummary {
+        kind: manifest.kind,
+        name: manifest.name.clone(),
+        library_id: None,
+        playlist_id: None,
+        slide_group_ids: Vec::new(),
+        media_imported: media.imported,
+        media_reused: media.reused,
+        fonts: manifest.fonts.clone(),
+    };
+
+    match manifest.kind {
+        BundleKind::Library => {
+            let mut library: Library = upgrade(DocumentKind::Library, document)?;
+            library.id = Uuid::new_v4().to_string();
+            library.schema_version = Some(CURRENT_SCHEMA_VERSION);
+            library.order = None;
+            library.revision = 0;
+            library.lineage.clear();
+            library.created_at = now.clone();
+            library.updated_at = now;
+            for group in &mut library.slide_groups {
+                remap_slide_group(group, &media.file_names);
+                if let Some(meta) = group.meta.as_mut() {
+                    meta.library_id = Some(library.id.clone());
+                }
+                summary.slide_group_ids.push(group.id.clone());
+            }
+
+            repository::save_library_checked(repo, &mut library)?;
+            record_saved(app, DocumentKind::Library, &library.id, &library);
+            search::index_library(app, &library);
+            summary.library_id = Some(library.id);
+        }
+        BundleKind::SlideGroup => {
+            // Run slide migrations by wrapping the group in a library-shaped document
+            let wrapped = json!({
+                "schemaVersion": migrations::schema_version(&document),
+                "slideGroups": [document],
+            });
+            let mut upgraded: JsonValue = upgrade(DocumentKind::Library, wrapped)?;
+            let mut group: SlideGroup = serde_json::from_value(upgraded["slideGroups"][0].take())?;
+            remap_slide_group(&mut group, &media.file_names);
+
+            // Reloaded under the save lock so edits saved meanwhile are kept
+            let _guard = repository::lock_checked_saves();
+            let mut library = match target_library_id {
+                Some(id) => get_library(repo, id)?,
+                None => Library {
+                    id: Uuid::new_v4().to_string(),
+                    schema_version: Some(CURRENT_SCHEMA_VERSION),
+                    name: group.title.clone(),
+                    slide_groups: Vec::new(),
+                    order: None,
+                    revision: 0,
+                    lineage: Vec::new(),
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                },
+            };
+            if let Some(meta) = group.meta.as_mut() {
+                meta.library_id = Some(library.id.clone());
+            }
+            summary.slide_group_ids.push(group.id.clone());
+            library.slide_groups.push(group);
+            library.schema_version = Some(CURRENT_SCHEMA_VERSION);
+            library.updated_at = now;
+
+            // Moves past the stored revision, so stale windows can't drop the group
+            repository::save_library_replacing(repo, &mut library)?;
+            record_saved(app, DocumentKind::Library, &library.id, &library);
+            search::index_library(app, &library);
+            summary.library_id = Some(library.id);
+        }
+        BundleKind::Playlist => {
+            let mut playlist: Playlist = upgrade(DocumentKind::Playlist, document)?;
+            playlist.id = Uuid::new_v4().to_string();
+            playlist.schema_version = Some(CURRENT_SCHEMA_VERSION);
+            playlist.order = None;
+            playlist.revision = 0;
+            playlist.lineage.clear();
+            playlist.created_at = now.clone();
+            playlist.updated_at = now;
+            for item in &mut playlist.items {
+                item.id = Uuid::new_v4().to_string();
+                remap_slide_group(&mut item.slide_group, &media.file_names);
+                // Origins refer to libraries on the exporting machine
+                if let Some(meta) = item.slide_group.meta.as_mut() {
+                    meta.playlist_id = Some(playlist.id.clone());
+                    meta.origin_library_id = None;
+                    meta.origin_slide_group_id = None;
+                    meta.library_id = None;
+                }
+                summary.slide_group_ids.push(item.slide_group.id.clone());
+            }
+
+            repository::save_playlist_checked(repo, &mut playlist)?;
+            record_saved(app, DocumentKind::Playlist, &playlist.id, &playlist);
+            search::index_playlist(app, &playlist);
+            summary.playlist_id = Some(playlist.id);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Keep an imported document's version for folder sync and its history
+fn record_saved<T: Serialize>(app: &AppHandle, kind: DocumentKind, id: &str, document: &T) {
+    folder_sync::record_version(app, kind, id, document);
+    history::record_revision(app, kind, id, document);
+}
+
+/// Upgrade a bundled document to the current schema before typed parsing
+fn upgrade<T: serde::de::DeserializeOwned>(
+    kind: DocumentKind,
+    mut document: JsonValue,
+) -> AppResult<T> {
+    migrations::migrate_document(kind, &mut document);
+    Ok(serde_json::from_value(document)?)
+}
+
+/// Give a slide group, its slides and objects fresh ids and point media at local files
+fn remap_slide_group(group: &mut SlideGroup, file_names: &HashMap<String, String>) {
+    group.id = Uuid::new_v4().to_string();
+
+    for slide in &mut group.slides {
+        slide.id = Uuid::new_v4().to_string();
+
+        for object in slide.objects.iter_mut().flatten() {
+            *object.id_mut() = Uuid::new_v4().to_string();
+        }
+        for src in slide.media_sources_mut() {
+            if let Some(local) = storage::media_file_name(src).and_then(|n| file_names.get(&n)) {
+                *src = local.clone();
+            }
+        }
+    }
+}
+
+struct ImportedMedia {
+    file_names: HashMap<String, String>, // Bundle file name -> local file name
+    items: Vec<MediaItem>,               // New media library entries, saved with the document
+    written: Vec<PathBuf>,               // Files extracted into the media directory
+    imported: usize,
+    reused: usize,
+}
+
+impl ImportedMedia {
+    /// Remove the files and media items of a bundle that failed to import
+    fn discard(&self, repo: &dyn Repository) {
+        for item in &self.items {
+            if let Err(e) = repo.delete_media_item(&item.id) {
+                eprintln!(
+                    "Failed to remove imported media item {}: {}",
+                    item.id, e.message
+                );
+            }
+        }
+        for path in &self.written {
+            if let Err(e) = storage::delete_file(path) {
+                eprintln!("Failed to remove imported file {:?}: {}", path, e.message);
+            }
+        }
+    }
+}
+
+/// Copy bundled media into the media directory, reusing local files with the
+/// same hash. Fails, leaving nothing behind, unless every file verifies.
+fn import_media(
+    app: &AppHandle,
+    repo: &dyn Repository,
+    archive: &mut ZipArchive<fs::File>,
+    manifest: &BundleManifest,
+) -> AppResult<ImportedMedia> {
+    let mut imported = ImportedMedia {
+        file_names: HashMap::new(),
+        items: Vec::new(),
+        written: Vec::new(),
+        imported: 0,
+        reused: 0,
+    };
+    match extract_media(app, repo, archive, manifest, &mut imported) {
+        Ok(()) => Ok(imported),
+        Err(e) => {
+            imported.discard(repo);
+            Err(e)
+        }
+    }
+}
+
+fn extract_media(
+    app: &AppHandle,
+    repo: &dyn Repository,
+    archive: &mut ZipArchive<fs::File>,
+    manifest: &BundleManifest,
+    imported: &mut ImportedMedia,
+) -> AppResult<()> {
+    let media_files_dir = storage::get_media_files_dir(app)?;
+
+    // Thumbnails are handled together with the item that owns them
+    let thumbnails: HashMap<String, &BundleMediaFile> = manifest
+        .media
+        .iter()
+        .filter_map(|file| file.item.as_ref()?.thumbnail.as_deref())
+        .filter_map(storage::media_file_name)
+        .filter_map(|name| {
+            let file = manifest.media.iter().find(|f| f.file_name == name)?;
+            Some((name, file))
+        })
+        .collect();
+
+    for file in &manifest.media {
+        if thumbnails.contains_key(&file.file_name) {
+            continue;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let staged = stage_file(archive, &media_files_dir, file, &id, imported)?;
+        let hash = file.hash.clone();
+
+        let bundled_thumbnail = file
+            .item
+            .as_ref()
+            .and_then(|item| item.thumbnail.as_deref())
+            .and_then(storage::media_file_name)
+            .and_then(|name| thumbnails.get(&name).copied());
+
+        if let Some(existing) = repo.find_media_by_hash(&hash)? {
+            storage::delete_file(&staged)?;
+            imported
+                .file_names
+                .insert(file.file_name.clone(), existing.slide_source().to_string());
+
+            match (bundled_thumbnail, existing.thumbnail) {
+                (Some(thumb_file), Some(existing_thumb)) => {
+                    imported
+                        .file_names
+                        .insert(thumb_file.file_name.clone(), existing_thumb);
+                }
+                (Some(thumb_file), None) => {
+                    let stem = format!("{}_thumb", Uuid::new_v4());
+                    import_thumbnail(archive, &media_files_dir, thumb_file, &stem, imported)?;
+                }
+                (None, _) => {}
+            }
+            imported.reused += 1;
+            continue;
+        }
+
+        // Files referenced directly by slides have no media library entry
+        let Some(bundled_item) = &file.item else {
+            let local_name = match find_identical_file(&media_files_dir, &staged, &hash)? {
+                Some(existing) => {
+                    storage::delete_file(&staged)?;
+                    imported.reused += 1;
+                    existing
+                }
+                None => {
+                    imported.imported += 1;
+                    file_name_of(&staged)
+                }
+            };
+            imported
+                .file_names
+                .insert(file.file_name.clone(), local_name);
+            continue;
+        };
+
+        let local_name = file_name_of(&staged);
+        imported
+            .file_names
+            .insert(file.file_name.clone(), local_name.clone());
+        imported.imported += 1;
+
+        let thumbnail = match bundled_thumbnail {
+            Some(thumb_file) => Some(import_thumbnail(
+                archive,
+                &media_files_dir,
+                thumb_file,
+                &format!("{}_thumb", id),
+                imported,
+            )?),
+            None => None,
+        };
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let item = MediaItem {
+            id,
+            schema_version: Some(CURRENT_SCHEMA_VERSION),
+            source: local_name,
+            thumbnail,
+            created_at: now.clone(),
+            updated_at: now,
+            hash: Some(hash),
+            linked_path: None, // Bundled files are always copied in
+            missing: false,
+            ..bundled_item.clone()
+        };
+        imported.items.push(item);
+    }
+
+    Ok(())
+}
+
+/// Find another file in the media directory with the same content as `staged`.
+///
+/// Only files of the same size are hashed, so this stays cheap for large libraries.
+fn find_identical_file(
+    media_files_dir: &Path,
+    staged: &PathBuf,
+    hash: &str,
+) -> AppResult<Option<String>> {
+    let size = fs::metadata(staged)?.len();
+
+    for entry in fs::read_dir(media_files_dir)? {
+        let path = entry?.path();
+        if &path == staged || !path.is_file() || fs::metadata(&path)?.len() != size {
+            continue;
+        }
+        if storage::compute_file_hash(&path)? == hash {
+            return Ok(Some(file_name_of(&path)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Extract a bundled thumbnail and record its local file name
+fn import_thumbnail(
+    archive: &mut ZipArchive<fs::File>,
+    media_files_dir: &Path,
+    file: &BundleMediaFile,
+    stem: &str,
+    imported: &mut ImportedMedia,
+) -> AppResult<String> {
+    let name = file_name_of(&stage_file(archive, media_files_dir, file, stem, imported)?);
+    imported
+        .file_names
+        .insert(file.file_name.clone(), name.clone());
+    Ok(name)
+}
+
+/// Extract a bundled file into the media directory as `<stem>.<original
+/// extension>`, checking it against the hash in the manifest
+fn stage_file(
+    archive: &mut ZipArchive<fs::File>,
+    media_files_dir: &Path,
+    file: &BundleMediaFile,
+    stem: &str,
+    imported: &mut ImportedMedia,
+) -> AppResult<PathBuf> {
+    let extension = storage::get_file_extension(&PathBuf::from(&file.file_name))
+        .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or_else(|| "bin".to_string());
+    let path = media_files_dir.join(format!("{}.{}", stem, extension.to_lowercase()));
+
+    let mut entry = archive
+        .by_name(&format!("{}{}", FILES_PREFIX, file.file_name))
+        .map_err(zip_error)?;
+    imported.written.push(path.clone());
+    let mut output = fs::File::create(&path)?;
+    io::copy(&mut entry, &mut output)?;
+    output.sync_all()?;
+
+    if storage::compute_file_hash(&path)? != file.hash {
+        return Err(AppError::corrupt(format!(
+            "Bundled file {} is corrupted (hash mismatch)",
+            file.file_name
+        ))
+        .with_code("HASH_MISMATCH"));
+    }
+    Ok(path)
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,30 @@
+//! Commands for exporting and importing portable `.ospbundle` archives.
+
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::bundles::{self, BundleExportSummary, BundleImportSummary, BundleSource};
+use crate::error::AppResult;
+
+#[tauri::command]
+pub fn export_bundle(
+    app: AppHandle,
+    source: BundleSource,
+    destination_path: String,
+) -> AppResult<BundleExportSummary> {
+    bundles::export_bundle(&app, &source, &PathBuf::from(destination_path))
+}
+
+/// Import a bundle; slide group bundles are added to `target_library_id` or a new library
+#[tauri::command]
+pub fn import_bundle(
+    app: AppHandle,
+    source_path: String,
+    target_library_id: Option<String>,
+) -> AppResult<BundleImportSummary> {
+    bundles::import_bundle(
+        &app,
+        &PathBuf::from(source_path),
+        target_library_id.as_deref(),
+    )
+}
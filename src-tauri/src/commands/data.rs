@@ -1,155 +1,227 @@
 //! Commands for data persistence: libraries, playlists, media, and tag groups.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
-use uuid::Uuid;
 
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::history;
+use crate::media_references::{self, GarbageReport, MediaUsage};
+use crate::media_import::{self, FolderImportSummary, ImportSummary};
+use crate::media_probe;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
 use crate::models::{Library, MediaItem, MediaPlaylist, Playlist, SlideTagGroup};
-use crate::storage;
+use crate::quarantine::{self, LoadResult, QuarantinedFile, RecoveryOutcome};
+use crate::repository::{self, EngineMigrationSummary, Repository, StorageEngine};
+use crate::search;
+use crate::snapshots;
+use crate::storage::{self, DocumentKind};
+use crate::thumbnails;
 
 // ===== Storage Initialization =====
 
+// Interrupted saves are recovered once at startup (see lib.rs), not here:
+// windows open while other windows are saving
 #[tauri::command]
-pub fn initialize_storage(app: AppHandle) -> Result<(), String> {
-    storage::ensure_directories(&app).map_err(|e| e.message)
+pub fn initialize_storage(app: AppHandle) -> AppResult<()> {
+    storage::ensure_directories(&app)
 }
 
-// ===== Library Commands =====
+// ===== Quarantine Commands =====
 
 #[tauri::command]
-pub fn load_libraries(app: AppHandle) -> Result<Vec<Library>, String> {
-    let libraries_dir = storage::get_libraries_dir(&app).map_err(|e| e.message)?;
-    storage::read_all_json_files(&libraries_dir).map_err(|e| e.message)
+pub fn list_quarantined_files(app: AppHandle) -> AppResult<Vec<QuarantinedFile>> {
+    quarantine::list_quarantined_files(&app)
 }
 
 #[tauri::command]
-pub fn save_library(app: AppHandle, library: Library) -> Result<(), String> {
-    let libraries_dir = storage::get_libraries_dir(&app).map_err(|e| e.message)?;
-    let file_path = libraries_dir.join(format!("{}.json", library.id));
-    storage::write_json_file(&file_path, &library).map_err(|e| e.message)
+pub fn recover_quarantined_file(app: AppHandle, id: String) -> AppResult<RecoveryOutcome> {
+    let (file, document, dropped) = quarantine::repair_quarantined_file(&app, &id)?;
+
+    let document_id = storage::get_file_stem(&PathBuf::from(&file.file_name))
+        .ok_or_else(|| AppError::invalid_path(Path::new(&file.file_name)))?;
+
+    let repo = repository::current(&app);
+    let _guard = repository::lock_checked_saves();
+    if repository::document_exists(repo.as_ref(), file.kind, &document_id)? {
+        return Err(AppError::conflict(format!(
+            "{:?} {} already exists; refusing to overwrite it",
+            file.kind, document_id
+        ))
+        .with_code("ALREADY_EXISTS")
+        .with_entity(document_id));
+    }
+
+    let saved = repository::save_document_replacing(repo.as_ref(), file.kind, document)?;
+    history::record_restored(&app, file.kind, &document_id, &saved);
+    quarantine::release_quarantined_file(&app, &id)?;
+    search::invalidate(&app);
+
+    Ok(RecoveryOutcome { file, dropped })
 }
 
+// ===== Storage Engine Commands =====
+
 #[tauri::command]
-pub fn delete_library(app: AppHandle, id: String) -> Result<(), String> {
-    let libraries_dir = storage::get_libraries_dir(&app).map_err(|e| e.message)?;
-    let file_path = libraries_dir.join(format!("{}.json", id));
-    storage::delete_file(&file_path).map_err(|e| e.message)
+pub fn get_storage_engine(app: AppHandle) -> StorageEngine {
+    repository::current(&app).engine()
 }
 
-// ===== Playlist Commands =====
+#[tauri::command]
+pub fn migrate_storage_engine(
+    app: AppHandle,
+    engine: StorageEngine,
+) -> AppResult<EngineMigrationSummary> {
+    let summary = repository::migrate_engine(&app, engine)?;
+    search::invalidate(&app);
+    Ok(summary)
+}
+
+// ===== Library Commands =====
 
 #[tauri::command]
-pub fn load_playlists(app: AppHandle) -> Result<Vec<Playlist>, String> {
-    let playlists_dir = storage::get_playlists_dir(&app).map_err(|e| e.message)?;
-    storage::read_all_json_files(&playlists_dir).map_err(|e| e.message)
+pub fn load_libraries(app: AppHandle) -> AppResult<LoadResult<Library>> {
+    repository::current(&app).load_libraries()
 }
 
+/// Save a library edited from its `revision`, returning the new revision.
+/// Fails with a `SAVE_CONFLICT` if it was saved elsewhere in the meantime.
 #[tauri::command]
-pub fn save_playlist(app: AppHandle, playlist: Playlist) -> Result<(), String> {
-    let playlists_dir = storage::get_playlists_dir(&app).map_err(|e| e.message)?;
-    let file_path = playlists_dir.join(format!("{}.json", playlist.id));
-    storage::write_json_file(&file_path, &playlist).map_err(|e| e.message)
+pub fn save_library(app: AppHandle, mut library: Library) -> AppResult<u64> {
+    library.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    let revision =
+        repository::save_library_checked(repository::current(&app).as_ref(), &mut library)?;
+    folder_sync::record_version(&app, DocumentKind::Library, &library.id, &library);
+    history::record_revision(&app, DocumentKind::Library, &library.id, &library);
+    search::index_library(&app, &library);
+    Ok(revision)
 }
 
 #[tauri::command]
-pub fn delete_playlist(app: AppHandle, id: String) -> Result<(), String> {
-    let playlists_dir = storage::get_playlists_dir(&app).map_err(|e| e.message)?;
-    let file_path = playlists_dir.join(format!("{}.json", id));
-    storage::delete_file(&file_path).map_err(|e| e.message)
+pub fn delete_library(app: AppHandle, id: String) -> AppResult<()> {
+    snapshots::snapshot_before_delete(&app, DocumentKind::Library, &id)?;
+    repository::current(&app).delete_library(&id)?;
+    search::remove_document(&app, DocumentKind::Library, &id);
+    Ok(())
 }
 
-// ===== Media Commands =====
+// ===== Playlist Commands =====
 
 #[tauri::command]
-pub fn load_media_items(app: AppHandle) -> Result<Vec<MediaItem>, String> {
-    let metadata_dir = storage::get_media_metadata_dir(&app).map_err(|e| e.message)?;
-    storage::read_all_json_files(&metadata_dir).map_err(|e| e.message)
+pub fn load_playlists(app: AppHandle) -> AppResult<LoadResult<Playlist>> {
+    repository::current(&app).load_playlists()
 }
 
+/// Save a playlist edited from its `revision`, returning the new revision
 #[tauri::command]
-pub fn import_media_file(app: AppHandle, source_path: String) -> Result<MediaItem, String> {
-    let source = PathBuf::from(&source_path);
+pub fn save_playlist(app: AppHandle, mut playlist: Playlist) -> AppResult<u64> {
+    playlist.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    let revision =
+        repository::save_playlist_checked(repository::current(&app).as_ref(), &mut playlist)?;
+    folder_sync::record_version(&app, DocumentKind::Playlist, &playlist.id, &playlist);
+    history::record_revision(&app, DocumentKind::Playlist, &playlist.id, &playlist);
+    search::index_playlist(&app, &playlist);
+    Ok(revision)
+}
 
-    if !source.exists() {
-        return Err("Source file does not exist".to_string());
-    }
+#[tauri::command]
+pub fn delete_playlist(app: AppHandle, id: String) -> AppResult<()> {
+    snapshots::snapshot_before_delete(&app, DocumentKind::Playlist, &id)?;
+    repository::current(&app).delete_playlist(&id)?;
+    search::remove_document(&app, DocumentKind::Playlist, &id);
+    Ok(())
+}
+
+// ===== Media Commands =====
 
-    // Compute hash of source file for deduplication
-    let file_hash = storage::compute_file_hash(&source).map_err(|e| e.message)?;
+#[tauri::command]
+pub fn load_media_items(app: AppHandle) -> AppResult<LoadResult<MediaItem>> {
+    repository::current(&app).load_media_items()
+}
 
-    // Check if we already have this file in the media library
-    let metadata_dir = storage::get_media_metadata_dir(&app).map_err(|e| e.message)?;
-    let existing_items: Vec<MediaItem> =
-        storage::read_all_json_files(&metadata_dir).map_err(|e| e.message)?;
+/// Import a file, copying it into the library unless `link` is set
+#[tauri::command]
+pub fn import_media_file(
+    app: AppHandle,
+    source_path: String,
+    link: Option<bool>,
+) -> AppResult<MediaItem> {
+    let (media_item, duplicate) =
+        media_import::import_file(&app, &PathBuf::from(&source_path), link.unwrap_or(false))?;
 
-    // Look for existing media item with same hash
-    if let Some(existing_item) = existing_items
-        .iter()
-        .find(|item| item.hash.as_ref().map(|h| h == &file_hash).unwrap_or(false))
-    {
+    if duplicate {
         // File already exists, return the existing item
-        println!("Media file already exists with hash: {}", file_hash);
-        return Ok(existing_item.clone());
+        println!("Media file already exists with hash: {:?}", media_item.hash);
     }
 
-    // Generate unique ID for the media
-    let media_id = Uuid::new_v4().to_string();
-
-    // Get file extension
-    let extension = storage::get_file_extension(&source)
-        .ok_or_else(|| "Could not determine file extension".to_string())?;
-
-    // Get original file name
-    let file_name = storage::get_file_stem(&source)
-        .ok_or_else(|| "Could not determine file name".to_string())?;
-
-    // Determine media type based on extension
-    let media_type = match extension.to_lowercase().as_str() {
-        "jpg" | "jpeg" | "png" | "gif" | "webp" | "bmp" => "image",
-        "mp4" | "webm" | "mov" | "avi" | "mkv" => "video",
-        _ => return Err("Unsupported file format".to_string()),
-    };
-
-    // Copy file to media directory
-    let media_files_dir = storage::get_media_files_dir(&app).map_err(|e| e.message)?;
-    let dest_file_name = format!("{}.{}", media_id, extension);
-    let dest_path = media_files_dir.join(&dest_file_name);
-
-    storage::copy_file(&source, &dest_path).map_err(|e| e.message)?;
-
-    // Create media item
-    let now = chrono::Utc::now().to_rfc3339();
-    let media_item = MediaItem {
-        id: media_id.clone(),
-        name: file_name,
-        media_type: media_type.to_string(),
-        source: dest_file_name, // Store relative path
-        thumbnail: None,
-        duration: None,
-        created_at: now.clone(),
-        updated_at: now,
-        metadata: None,
-        hash: Some(file_hash),
-    };
-
-    // Save metadata
-    let metadata_path = metadata_dir.join(format!("{}.json", media_id));
-    storage::write_json_file(&metadata_path, &media_item).map_err(|e| e.message)?;
-
     Ok(media_item)
 }
 
+/// Import many files at once, reporting each one by a `media:import-progress` event.
+/// Async so a large batch runs off the main thread.
+#[tauri::command]
+pub async fn import_media_files(
+    app: AppHandle,
+    source_paths: Vec<String>,
+    link: Option<bool>,
+) -> AppResult<ImportSummary> {
+    Ok(media_import::import_files(
+        &app,
+        source_paths,
+        link.unwrap_or(false),
+    ))
+}
+
+/// Import every supported file in a folder (and its subfolders unless
+/// `recursive` is false), optionally into a new media playlist named after it
+#[tauri::command]
+pub async fn import_media_folder(
+    app: AppHandle,
+    folder_path: String,
+    recursive: Option<bool>,
+    create_playlist: Option<bool>,
+    link: Option<bool>,
+) -> AppResult<FolderImportSummary> {
+    media_import::import_folder(
+        &app,
+        &PathBuf::from(folder_path),
+        recursive.unwrap_or(true),
+        create_playlist.unwrap_or(false),
+        link.unwrap_or(false),
+    )
+}
+
+/// Delete a media item and its files. Linked originals are left alone; only
+/// the thumbnail is removed.
+///
+/// Refuses while slides still use the media unless `force` is set.
 #[tauri::command]
-pub fn delete_media_item(app: AppHandle, id: String) -> Result<(), String> {
+pub fn delete_media_item(app: AppHandle, id: String, force: Option<bool>) -> AppResult<()> {
+    if !force.unwrap_or(false) {
+        let usage = media_references::media_usage(&app, &id)?;
+        let slides = usage.references.iter().filter(|r| r.is_slide()).count();
+        if slides > 0 {
+            return Err(AppError::conflict(format!(
+                "Media item {} is still used by {} slide(s); delete with force to remove it anyway",
+                id, slides
+            ))
+            .with_code("MEDIA_IN_USE")
+            .with_entity(&id));
+        }
+    }
+
+    snapshots::snapshot_before_delete(&app, DocumentKind::MediaItem, &id)?;
+
     // Load media item to get file name
-    let metadata_dir = storage::get_media_metadata_dir(&app).map_err(|e| e.message)?;
-    let metadata_path = metadata_dir.join(format!("{}.json", id));
+    let repo = repository::current(&app);
 
-    if let Ok(media_item) = storage::read_json_file::<MediaItem>(&metadata_path) {
+    if let Ok(Some(media_item)) = repo.get_media_item(&id) {
         // Delete the actual media file
-        let media_files_dir = storage::get_media_files_dir(&app).map_err(|e| e.message)?;
-        let file_path = media_files_dir.join(&media_item.source);
-        let _ = storage::delete_file(&file_path); // Ignore error if file doesn't exist
+        let media_files_dir = storage::get_media_files_dir(&app)?;
+        if media_item.linked_path.is_none() {
+            let file_path = media_files_dir.join(&media_item.source);
+            let _ = storage::delete_file(&file_path); // Ignore error if file doesn't exist
+        }
 
         // Delete thumbnail if exists
         if let Some(thumb) = media_item.thumbnail {
@@ -159,35 +231,81 @@ pub fn delete_media_item(app: AppHandle, id: String) -> Result<(), String> {
     }
 
     // Delete metadata
-    storage::delete_file(&metadata_path).map_err(|e| e.message)
+    repo.delete_media_item(&id)?;
+    search::remove_document(&app, DocumentKind::MediaItem, &id);
+    Ok(())
+}
+
+/// Re-read duration, dimensions and codecs for an existing media item
+#[tauri::command]
+pub fn probe_media_item(app: AppHandle, media_id: String) -> AppResult<MediaItem> {
+    let repo = repository::current(&app);
+    let mut media_item = repo
+        .get_media_item(&media_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::MediaItem, &media_id))?;
+
+    let file_path = storage::media_item_path(&app, &media_item)?;
+    media_probe::probe_media_item(&mut media_item, &file_path)?;
+    media_item.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    media_item.updated_at = chrono::Utc::now().to_rfc3339();
+
+    repo.save_media_item(&media_item)?;
+
+    Ok(media_item)
+}
+
+#[tauri::command]
+pub fn get_media_usage(app: AppHandle, media_id: String) -> AppResult<MediaUsage> {
+    media_references::media_usage(&app, &media_id)
+}
+
+#[tauri::command]
+pub fn list_media_usage(app: AppHandle) -> AppResult<Vec<MediaUsage>> {
+    media_references::list_media_usage(&app)
+}
+
+/// Report media files nothing refers to; they are only deleted when `dry_run` is false
+#[tauri::command]
+pub fn collect_media_garbage(app: AppHandle, dry_run: Option<bool>) -> AppResult<GarbageReport> {
+    media_references::collect_garbage(&app, dry_run.unwrap_or(true))
 }
 
 #[tauri::command]
-pub fn get_media_file_path(app: AppHandle, file_name: String) -> Result<String, String> {
-    let media_files_dir = storage::get_media_files_dir(&app).map_err(|e| e.message)?;
+pub fn get_media_file_path(app: AppHandle, file_name: String) -> AppResult<String> {
+    let media_files_dir = storage::get_media_files_dir(&app)?;
     let file_path = media_files_dir.join(file_name);
 
     file_path
         .to_str()
-        .ok_or_else(|| "Invalid path".to_string())
+        .ok_or_else(|| AppError::invalid_path(&file_path))
         .map(|s| s.to_string())
 }
 
+/// Save a thumbnail the frontend rendered for a video and point the media
+/// item at it, returning the thumbnail's file name
 #[tauri::command]
 pub fn save_video_thumbnail(
     app: AppHandle,
     media_id: String,
     thumbnail_data: Vec<u8>,
-) -> Result<String, String> {
+) -> AppResult<String> {
+    // Garbage collection would delete the file until the item refers to it
+    let _imports = media_references::lock_media_imports();
+    let repo = repository::current(&app);
+    let media_item = repo
+        .get_media_item(&media_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::MediaItem, &media_id))?;
+
     // Generate thumbnail filename
     let thumbnail_filename = format!("{}_thumb.png", media_id);
 
     // Get media files directory
-    let media_files_dir = storage::get_media_files_dir(&app).map_err(|e| e.message)?;
+    let media_files_dir = storage::get_media_files_dir(&app)?;
     let thumbnail_path = media_files_dir.join(&thumbnail_filename);
 
     // Write thumbnail data to file
-    storage::write_file(&thumbnail_path, &thumbnail_data).map_err(|e| e.message)?;
+    storage::write_file(&thumbnail_path, &thumbnail_data)?;
+    set_thumbnail(repo.as_ref(), media_item, &thumbnail_filename)?;
 
     Ok(thumbnail_filename)
 }
@@ -197,65 +315,94 @@ pub fn update_media_thumbnail(
     app: AppHandle,
     media_id: String,
     thumbnail_filename: String,
-) -> Result<MediaItem, String> {
+) -> AppResult<MediaItem> {
+    let _imports = media_references::lock_media_imports();
+
     // Load existing media item metadata
-    let metadata_dir = storage::get_media_metadata_dir(&app).map_err(|e| e.message)?;
-    let metadata_path = metadata_dir.join(format!("{}.json", media_id));
+    let repo = repository::current(&app);
+    let media_item = repo
+        .get_media_item(&media_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::MediaItem, &media_id))?;
 
-    let mut media_item =
-        storage::read_json_file::<MediaItem>(&metadata_path).map_err(|e| e.message)?;
+    set_thumbnail(repo.as_ref(), media_item, &thumbnail_filename)
+}
 
+/// Point a media item at its thumbnail file and save it
+fn set_thumbnail(
+    repo: &dyn Repository,
+    mut media_item: MediaItem,
+    thumbnail_filename: &str,
+) -> AppResult<MediaItem> {
     // Update thumbnail field
-    media_item.thumbnail = Some(thumbnail_filename);
+    media_item.thumbnail = Some(thumbnail_filename.to_string());
+    media_item.schema_version = Some(CURRENT_SCHEMA_VERSION);
 
     // Update timestamp
     media_item.updated_at = chrono::Utc::now().to_rfc3339();
 
     // Save updated metadata
-    storage::write_json_file(&metadata_path, &media_item).map_err(|e| e.message)?;
+    repo.save_media_item(&media_item)?;
 
     Ok(media_item)
 }
 
+/// Queue thumbnail generation; progress is reported by `media:thumbnail-progress` events.
+///
+/// Given ids are regenerated even if they already have a thumbnail. Without
+/// ids, every media item with a missing thumbnail is queued. Returns how many
+/// items were queued.
+#[tauri::command]
+pub fn generate_thumbnails(app: AppHandle, media_ids: Option<Vec<String>>) -> AppResult<usize> {
+    match media_ids {
+        Some(ids) => Ok(thumbnails::enqueue(&app, ids)),
+        None => thumbnails::enqueue_missing(&app),
+    }
+}
+
 // ===== Media Playlist Commands =====
 
 #[tauri::command]
-pub fn load_media_playlists(app: AppHandle) -> Result<Vec<MediaPlaylist>, String> {
-    let playlists_dir = storage::get_media_playlists_dir(&app).map_err(|e| e.message)?;
-    storage::read_all_json_files(&playlists_dir).map_err(|e| e.message)
+pub fn load_media_playlists(app: AppHandle) -> AppResult<LoadResult<MediaPlaylist>> {
+    repository::current(&app).load_media_playlists()
 }
 
 #[tauri::command]
-pub fn save_media_playlist(app: AppHandle, playlist: MediaPlaylist) -> Result<(), String> {
-    let playlists_dir = storage::get_media_playlists_dir(&app).map_err(|e| e.message)?;
-    let file_path = playlists_dir.join(format!("{}.json", playlist.id));
-    storage::write_json_file(&file_path, &playlist).map_err(|e| e.message)
+pub fn save_media_playlist(app: AppHandle, mut playlist: MediaPlaylist) -> AppResult<()> {
+    playlist.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    let repo = repository::current(&app);
+
+    // The frontend may save a copy from before its thumbnail finished rendering
+    for copy in playlist.media_items.iter_mut().filter(|i| i.thumbnail.is_none()) {
+        if let Some(item) = repo.get_media_item(&copy.id)? {
+            copy.thumbnail = item.thumbnail;
+        }
+    }
+
+    repo.save_media_playlist(&playlist)
 }
 
 #[tauri::command]
-pub fn delete_media_playlist(app: AppHandle, id: String) -> Result<(), String> {
-    let playlists_dir = storage::get_media_playlists_dir(&app).map_err(|e| e.message)?;
-    let file_path = playlists_dir.join(format!("{}.json", id));
-    storage::delete_file(&file_path).map_err(|e| e.message)
+pub fn delete_media_playlist(app: AppHandle, id: String) -> AppResult<()> {
+    snapshots::snapshot_before_delete(&app, DocumentKind::MediaPlaylist, &id)?;
+    repository::current(&app).delete_media_playlist(&id)
 }
 
 // ===== Tag Group Commands =====
 
 #[tauri::command]
-pub fn load_tag_groups(app: AppHandle) -> Result<Vec<SlideTagGroup>, String> {
-    let file_path = storage::get_tag_groups_file(&app).map_err(|e| e.message)?;
+pub fn load_tag_groups(app: AppHandle) -> AppResult<Vec<SlideTagGroup>> {
+    let file_path = storage::get_tag_groups_file(&app)?;
 
     // Return empty array if file doesn't exist
     if !file_path.exists() {
         return Ok(Vec::new());
     }
 
-    storage::read_json_file(&file_path).map_err(|e| e.message)
+    storage::read_json_file(&file_path)
 }
 
 #[tauri::command]
-pub fn save_tag_groups(app: AppHandle, tag_groups: Vec<SlideTagGroup>) -> Result<(), String> {
-    let file_path = storage::get_tag_groups_file(&app).map_err(|e| e.message)?;
-    storage::write_json_file(&file_path, &tag_groups).map_err(|e| e.message)
+pub fn save_tag_groups(app: AppHandle, tag_groups: Vec<SlideTagGroup>) -> AppResult<()> {
+    let file_path = storage::get_tag_groups_file(&app)?;
+    storage::write_json_file(&file_path, &tag_groups)
 }
-
@@ -0,0 +1,56 @@
+//! Commands for editing the same data on several machines through a file-sync tool.
+
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::folder_sync::{self, MergeSide, SyncConflict};
+use crate::storage::DocumentKind;
+use crate::storage_watcher::{self, ChangeType, StorageChange};
+
+#[tauri::command]
+pub fn get_folder_sync(app: AppHandle) -> AppResult<bool> {
+    Ok(folder_sync::is_enabled(&app))
+}
+
+#[tauri::command]
+pub fn set_folder_sync(app: AppHandle, enabled: bool) -> AppResult<()> {
+    folder_sync::set_enabled(&app, enabled)
+}
+
+/// Merge conflicted copies whose edits don't clash and return the ones that need the user
+#[tauri::command]
+pub fn check_sync_conflicts(app: AppHandle) -> AppResult<Vec<SyncConflict>> {
+    let report = folder_sync::reconcile(&app)?;
+    let merged: Vec<StorageChange> = report
+        .merged
+        .into_iter()
+        .map(|(kind, id)| StorageChange {
+            kind: kind.into(),
+            id,
+            change: ChangeType::Modified,
+        })
+        .collect();
+    storage_watcher::broadcast_changes(&app, &merged);
+    Ok(report.conflicts)
+}
+
+/// Merge a conflicted copy, taking `resolutions` (conflict key -> side) for clashing edits
+#[tauri::command]
+pub fn resolve_sync_conflict(
+    app: AppHandle,
+    kind: DocumentKind,
+    copy_name: String,
+    resolutions: HashMap<String, MergeSide>,
+) -> AppResult<()> {
+    let id = folder_sync::resolve_conflict(&app, kind, &copy_name, &resolutions)?;
+    storage_watcher::broadcast_changes(
+        &app,
+        &[StorageChange {
+            kind: kind.into(),
+            id,
+            change: ChangeType::Modified,
+        }],
+    );
+    Ok(())
+}
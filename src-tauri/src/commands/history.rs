@@ -0,0 +1,65 @@
+//! Commands for browsing and restoring the revision history of libraries and playlists.
+
+use serde_json::Value as JsonValue;
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::history::{self, RevisionChange, RevisionInfo};
+use crate::search;
+use crate::storage::DocumentKind;
+use crate::storage_watcher::{self, ChangeType, StorageChange};
+
+/// List a library's or playlist's revisions, newest first
+#[tauri::command]
+pub fn list_revisions(
+    app: AppHandle,
+    kind: DocumentKind,
+    id: String,
+) -> AppResult<Vec<RevisionInfo>> {
+    history::list_revisions(&app, kind, &id)
+}
+
+#[tauri::command]
+pub fn get_revision(
+    app: AppHandle,
+    kind: DocumentKind,
+    id: String,
+    sequence: u64,
+) -> AppResult<JsonValue> {
+    history::read_revision(&app, kind, &id, sequence)
+}
+
+/// Compare history entry `from` with entry `to`, or with the current document when not given
+#[tauri::command]
+pub fn diff_revisions(
+    app: AppHandle,
+    kind: DocumentKind,
+    id: String,
+    from: u64,
+    to: Option<u64>,
+) -> AppResult<Vec<RevisionChange>> {
+    history::diff_revisions(&app, kind, &id, from, to)
+}
+
+/// Bring back one slide group (a playlist item for playlists) from a history
+/// entry, returning the document's new revision. Every window reloads it.
+#[tauri::command]
+pub fn restore_slide_group(
+    app: AppHandle,
+    kind: DocumentKind,
+    id: String,
+    sequence: u64,
+    group_id: String,
+) -> AppResult<u64> {
+    let new_revision = history::restore_slide_group(&app, kind, &id, sequence, &group_id)?;
+    search::invalidate(&app);
+    storage_watcher::broadcast_changes(
+        &app,
+        &[StorageChange {
+            kind: kind.into(),
+            id,
+            change: ChangeType::Modified,
+        }],
+    );
+    Ok(new_revision)
+}
@@ -0,0 +1,70 @@
+//! Commands for importing songs and presentations from other programs'
+//! files, and exporting songs back out.
+
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::importers::openlp::{self, ServiceImportSummary};
+use crate::importers::pptx::{self, PresentationImportSummary};
+use crate::importers::{self, openlyrics, ImportOptions, SongImportSummary};
+use crate::models::{CanvasSize, SlideGroup};
+
+/// Import song files (plain-text lyrics, ChordPro, OpenLyrics or ProPresenter 6) into a
+/// library, one slide group per song. Async so a large batch runs off the main thread.
+#[tauri::command]
+pub async fn import_songs(
+    app: AppHandle,
+    library_id: String,
+    source_paths: Vec<String>,
+    canvas_size: Option<CanvasSize>,
+    keep_chords: Option<bool>,
+) -> AppResult<SongImportSummary> {
+    let paths: Vec<PathBuf> = source_paths.into_iter().map(PathBuf::from).collect();
+    importers::import_songs(
+        &app,
+        &library_id,
+        &paths,
+        canvas_size,
+        ImportOptions {
+            keep_chords: keep_chords.unwrap_or(false),
+        },
+    )
+}
+
+/// Export a slide group from a library or playlist as an OpenLyrics song
+#[tauri::command]
+pub fn export_openlyrics(slide_group: SlideGroup, destination_path: String) -> AppResult<()> {
+    openlyrics::export_slide_group(&slide_group, &PathBuf::from(destination_path))
+}
+
+/// Import an OpenLP service file (.osz) as a new playlist, adding its songs to
+/// `library_id` when given. Async so unpacking its media runs off the main thread.
+#[tauri::command]
+pub async fn import_openlp_service(
+    app: AppHandle,
+    source_path: String,
+    library_id: Option<String>,
+    keep_chords: Option<bool>,
+) -> AppResult<ServiceImportSummary> {
+    openlp::import_service(
+        &app,
+        &PathBuf::from(source_path),
+        library_id.as_deref(),
+        ImportOptions {
+            keep_chords: keep_chords.unwrap_or(false),
+        },
+    )
+}
+
+/// Import PowerPoint presentations (.pptx) into a library, one slide group per
+/// presentation. Async so unpacking their media runs off the main thread.
+#[tauri::command]
+pub async fn import_powerpoint(
+    app: AppHandle,
+    library_id: String,
+    source_paths: Vec<String>,
+) -> AppResult<PresentationImportSummary> {
+    let paths: Vec<PathBuf> = source_paths.into_iter().map(PathBuf::from).collect();
+    pptx::import_presentations(&app, &library_id, &paths)
+}
@@ -0,0 +1,32 @@
+//! Commands for media linked in place and repairing missing files.
+
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::media_links::{self, LinkCheck, RelinkReport};
+use crate::models::MediaItem;
+
+/// Re-check every linked media file, flagging the ones that are missing
+#[tauri::command]
+pub fn check_linked_media(app: AppHandle) -> AppResult<LinkCheck> {
+    media_links::check_links(&app)
+}
+
+/// Search folders for missing linked files by name and content hash.
+/// Async since hashing candidates on a large drive takes a while.
+#[tauri::command]
+pub async fn relink_media(
+    app: AppHandle,
+    search_dirs: Vec<String>,
+    media_ids: Option<Vec<String>>,
+) -> AppResult<RelinkReport> {
+    let search_dirs: Vec<PathBuf> = search_dirs.into_iter().map(PathBuf::from).collect();
+    media_links::relink_media(&app, &search_dirs, media_ids.as_deref())
+}
+
+/// Point one linked media item at a file the user picked
+#[tauri::command]
+pub fn relink_media_item(app: AppHandle, media_id: String, path: String) -> AppResult<MediaItem> {
+    media_links::relink_media_item(&app, &media_id, Path::new(&path))
+}
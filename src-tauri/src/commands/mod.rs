@@ -1,11 +1,29 @@
 //! Tauri command handlers organized by domain.
 
+pub mod bundles;
 pub mod data;
+pub mod folder_sync;
+pub mod history;
+pub mod importers;
+pub mod media_links;
+pub mod search;
+pub mod snapshots;
 pub mod video_sync;
+pub mod watched_folders;
 pub mod windows;
+pub mod workspaces;
 
 // Re-export all commands for easy registration in lib.rs
+pub use bundles::*;
 pub use data::*;
+pub use folder_sync::*;
+pub use history::*;
+pub use importers::*;
+pub use media_links::*;
+pub use search::*;
+pub use snapshots::*;
 pub use video_sync::*;
+pub use watched_folders::*;
 pub use windows::*;
+pub use workspaces::*;
 
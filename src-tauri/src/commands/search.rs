@@ -0,0 +1,11 @@
+//! Commands for full-text search across libraries, playlists and media.
+
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::search::{self, SearchHit, DEFAULT_SEARCH_LIMIT};
+
+#[tauri::command]
+pub fn search(app: AppHandle, query: String, limit: Option<usize>) -> AppResult<Vec<SearchHit>> {
+    search::search(&app, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+}
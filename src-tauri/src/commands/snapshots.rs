@@ -0,0 +1,38 @@
+//! Commands for data directory snapshots and point-in-time restore.
+
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::snapshots::{self, RestoreResult, SnapshotDiffEntry, SnapshotInfo, SnapshotReason};
+use crate::storage::DocumentKind;
+
+#[tauri::command]
+pub fn list_snapshots(app: AppHandle) -> AppResult<Vec<SnapshotInfo>> {
+    snapshots::list_snapshots(&app)
+}
+
+/// Create a manual snapshot; returns `None` if nothing changed since the last one
+#[tauri::command]
+pub fn create_snapshot(app: AppHandle) -> AppResult<Option<SnapshotInfo>> {
+    snapshots::create_snapshot(&app, SnapshotReason::Manual, None)
+}
+
+#[tauri::command]
+pub fn diff_snapshot(app: AppHandle, id: String) -> AppResult<Vec<SnapshotDiffEntry>> {
+    snapshots::diff_snapshot(&app, &id)
+}
+
+#[tauri::command]
+pub fn restore_snapshot_document(
+    app: AppHandle,
+    snapshot_id: String,
+    kind: DocumentKind,
+    document_id: String,
+) -> AppResult<RestoreResult> {
+    snapshots::restore_document(&app, &snapshot_id, kind, &document_id)
+}
+
+#[tauri::command]
+pub fn restore_snapshot(app: AppHandle, id: String) -> AppResult<RestoreResult> {
+    snapshots::restore_snapshot(&app, &id)
+}
@@ -5,6 +5,7 @@ use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, State};
 
+use crate::error::{AppError, AppResult};
 use crate::models::VideoState;
 
 // ===== Video Sync State Management =====
@@ -77,7 +78,7 @@ pub fn update_video_state(
     app: AppHandle,
     state: VideoState,
     app_state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let video_sync = app_state.video_sync.clone();
 
     // Always emit the state immediately to the presenter
@@ -88,7 +89,7 @@ pub fn update_video_state(
 
     let mut manager = video_sync
         .lock()
-        .map_err(|e| format!("Failed to lock video sync state: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to lock video sync state: {}", e)))?;
 
     let was_paused = manager.state.as_ref().map(|s| s.paused).unwrap_or(true);
     let is_now_playing = !state.paused;
@@ -132,11 +133,11 @@ pub fn update_video_state(
 
 /// Clear video state and stop broadcast timer
 #[tauri::command]
-pub fn clear_video_state(app: AppHandle, app_state: State<'_, AppState>) -> Result<(), String> {
+pub fn clear_video_state(app: AppHandle, app_state: State<'_, AppState>) -> AppResult<()> {
     let mut manager = app_state
         .video_sync
         .lock()
-        .map_err(|e| format!("Failed to lock video sync state: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to lock video sync state: {}", e)))?;
 
     // Signal broadcast to stop
     if let Some(ref stop_signal) = manager.stop_signal {
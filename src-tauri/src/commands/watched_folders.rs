@@ -0,0 +1,41 @@
+//! Commands for media folders that are imported automatically.
+
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::watched_folders::{self, WatchedFolder, WatchedFolderImport};
+
+#[tauri::command]
+pub fn list_watched_folders(app: AppHandle) -> AppResult<Vec<WatchedFolder>> {
+    watched_folders::list_watched_folders(&app)
+}
+
+/// Watch a folder, importing into `media_playlist_id` or a new playlist named after the folder.
+/// With `link`, new files are referenced in place instead of copied.
+#[tauri::command]
+pub fn add_watched_folder(
+    app: AppHandle,
+    path: String,
+    media_playlist_id: Option<String>,
+    recursive: Option<bool>,
+    link: Option<bool>,
+) -> AppResult<WatchedFolder> {
+    watched_folders::add_watched_folder(
+        &app,
+        &path,
+        media_playlist_id.as_deref(),
+        recursive.unwrap_or(true),
+        link.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub fn remove_watched_folder(app: AppHandle, id: String) -> AppResult<()> {
+    watched_folders::remove_watched_folder(&app, &id)
+}
+
+/// Scan every watched folder now instead of waiting for the next poll
+#[tauri::command]
+pub async fn scan_watched_folders(app: AppHandle) -> AppResult<Vec<WatchedFolderImport>> {
+    watched_folders::scan_watched_folders(&app)
+}
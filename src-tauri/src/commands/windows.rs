@@ -3,36 +3,38 @@
 use tauri::webview::WebviewWindowBuilder;
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::error::{AppError, AppResult};
+
 // ===== Audience Window Commands =====
 // The audience window is created at app startup (configured in tauri.conf.json)
 // These commands show/hide it rather than creating/destroying it
 
 #[tauri::command]
-pub fn show_audience_window(app: AppHandle) -> Result<(), String> {
+pub fn show_audience_window(app: AppHandle) -> AppResult<()> {
     let window = app
         .get_webview_window("audience")
-        .ok_or_else(|| "Audience window not found".to_string())?;
+        .ok_or_else(|| AppError::window_missing("audience"))?;
 
     // Show the window
     window
         .show()
-        .map_err(|e| format!("Failed to show audience window: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to show audience window: {}", e)))?;
 
     // Set always on top
     window
         .set_always_on_top(true)
-        .map_err(|e| format!("Failed to set always on top: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to set always on top: {}", e)))?;
 
     // Focus the window
     window
         .set_focus()
-        .map_err(|e| format!("Failed to focus window: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to focus window: {}", e)))?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn hide_audience_window(app: AppHandle) -> Result<(), String> {
+pub fn hide_audience_window(app: AppHandle) -> AppResult<()> {
     if let Some(window) = app.get_webview_window("audience") {
         // Remove always on top
         let _ = window.set_always_on_top(false);
@@ -40,40 +42,40 @@ pub fn hide_audience_window(app: AppHandle) -> Result<(), String> {
         // Hide the window
         window
             .hide()
-            .map_err(|e| format!("Failed to hide audience window: {}", e))?;
+            .map_err(|e| AppError::internal(format!("Failed to hide audience window: {}", e)))?;
     }
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn is_audience_window_visible(app: AppHandle) -> Result<bool, String> {
+pub fn is_audience_window_visible(app: AppHandle) -> AppResult<bool> {
     let window = app
         .get_webview_window("audience")
-        .ok_or_else(|| "Audience window not found".to_string())?;
+        .ok_or_else(|| AppError::window_missing("audience"))?;
 
     window
         .is_visible()
-        .map_err(|e| format!("Failed to check window visibility: {}", e))
+        .map_err(|e| AppError::internal(format!("Failed to check window visibility: {}", e)))
 }
 
 // ===== Settings Window Commands =====
 
 /// Internal function to open settings window - used by both command and menu event
-pub fn open_settings_window_internal(app: &AppHandle, path: &str) -> Result<(), String> {
+pub fn open_settings_window_internal(app: &AppHandle, path: &str) -> AppResult<()> {
     // Check if window already exists
     if let Some(window) = app.get_webview_window("settings") {
         // Window exists, show and focus it
         window
             .show()
-            .map_err(|e| format!("Failed to show settings window: {}", e))?;
+            .map_err(|e| AppError::internal(format!("Failed to show settings window: {}", e)))?;
         window
             .set_focus()
-            .map_err(|e| format!("Failed to focus window: {}", e))?;
+            .map_err(|e| AppError::internal(format!("Failed to focus window: {}", e)))?;
         // Navigate to the specific section by emitting a navigation event
         window
             .emit("settings:navigate", serde_json::json!({ "path": path }))
-            .map_err(|e| format!("Failed to emit navigation event: {}", e))?;
+            .map_err(|e| AppError::internal(format!("Failed to emit navigation event: {}", e)))?;
         return Ok(());
     }
 
@@ -87,18 +89,18 @@ pub fn open_settings_window_internal(app: &AppHandle, path: &str) -> Result<(),
         .min_inner_size(500.0, 400.0)
         .resizable(true)
         .build()
-        .map_err(|e| format!("Failed to create settings window: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to create settings window: {}", e)))?;
 
     // Focus the new window
     window
         .set_focus()
-        .map_err(|e| format!("Failed to focus window: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to focus window: {}", e)))?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn show_settings_window(app: AppHandle, path: Option<String>) -> Result<(), String> {
+pub fn show_settings_window(app: AppHandle, path: Option<String>) -> AppResult<()> {
     let path = path.unwrap_or_else(|| "/settings".to_string());
     open_settings_window_internal(&app, &path)
 }
@@ -0,0 +1,47 @@
+//! Commands for managing separate workspaces and where their data lives.
+
+use tauri::AppHandle;
+
+use crate::error::AppResult;
+use crate::workspaces::{self, Workspace, WorkspaceList};
+
+#[tauri::command]
+pub fn list_workspaces(app: AppHandle) -> AppResult<WorkspaceList> {
+    workspaces::list_workspaces(&app)
+}
+
+/// Add a workspace in `path`, or in the app data directory when not given
+#[tauri::command]
+pub fn create_workspace(
+    app: AppHandle,
+    name: String,
+    path: Option<String>,
+) -> AppResult<Workspace> {
+    workspaces::create_workspace(&app, &name, path.as_deref())
+}
+
+#[tauri::command]
+pub fn rename_workspace(app: AppHandle, id: String, name: String) -> AppResult<Workspace> {
+    workspaces::rename_workspace(&app, &id, &name)
+}
+
+/// Forget a workspace without deleting its files
+#[tauri::command]
+pub fn remove_workspace(app: AppHandle, id: String) -> AppResult<()> {
+    workspaces::remove_workspace(&app, &id)
+}
+
+#[tauri::command]
+pub fn switch_workspace(app: AppHandle, id: String) -> AppResult<Workspace> {
+    workspaces::switch_workspace(&app, &id)
+}
+
+/// Move a workspace's data to another folder. Async so copying runs off the main thread.
+#[tauri::command]
+pub async fn move_workspace(
+    app: AppHandle,
+    id: String,
+    destination: String,
+) -> AppResult<Workspace> {
+    workspaces::move_workspace(&app, &id, &destination)
+}
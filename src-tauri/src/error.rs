@@ -0,0 +1,182 @@
+//! Errors returned by storage code and every command.
+//!
+//! Commands fail with an [`AppError`], which reaches the frontend as
+//! `{ kind, code, message, entityId?, path?, current? }`. `kind` is the broad
+//! category the UI picks a recovery action from, `code` narrows it down (a full
+//! disk and a missing file are both failed writes), and `message` is for people.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::storage::DocumentKind;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    NotFound,          // Document, file or folder does not exist
+    Corrupt,           // Data exists but cannot be read back
+    PermissionDenied,  // The OS refused access, or the volume is read-only
+    UnsupportedFormat, // Unknown file type, or written by a newer version
+    Conflict,          // Clashes with existing state
+    WindowMissing,     // A window the command needs has not been created
+    Io,                // Any other file system or database failure
+    Internal,          // A bug or an unexpected platform failure
+}
+
+impl ErrorKind {
+    /// Code used when nothing more specific is known
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::NotFound => "NOT_FOUND",
+            ErrorKind::Corrupt => "CORRUPT",
+            ErrorKind::PermissionDenied => "PERMISSION_DENIED",
+            ErrorKind::UnsupportedFormat => "UNSUPPORTED_FORMAT",
+            ErrorKind::Conflict => "CONFLICT",
+            ErrorKind::WindowMissing => "WINDOW_MISSING",
+            ErrorKind::Io => "IO_ERROR",
+            ErrorKind::Internal => "INTERNAL",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub code: String, // SCREAMING_SNAKE_CASE, e.g. "LIBRARY_NOT_FOUND" or "DISK_FULL"
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>, // Document, media item or window concerned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>, // File or folder concerned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<Box<JsonValue>>, // Stored document a save conflicted with
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        AppError {
+            kind,
+            code: kind.code().to_string(),
+            message: message.into(),
+            entity_id: None,
+            path: None,
+            current: None,
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn corrupt(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Corrupt, message)
+    }
+
+    pub fn unsupported_format(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::UnsupportedFormat, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Conflict, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+
+    /// A stored document that does not exist, e.g. `LIBRARY_NOT_FOUND`
+    pub fn document_not_found(kind: DocumentKind, id: &str) -> Self {
+        let (label, code) = match kind {
+            DocumentKind::Library => ("Library", "LIBRARY_NOT_FOUND"),
+            DocumentKind::Playlist => ("Playlist", "PLAYLIST_NOT_FOUND"),
+            DocumentKind::MediaItem => ("Media item", "MEDIA_ITEM_NOT_FOUND"),
+            DocumentKind::MediaPlaylist => ("Media playlist", "MEDIA_PLAYLIST_NOT_FOUND"),
+        };
+        Self::not_found(format!("{} not found: {}", label, id))
+            .with_code(code)
+            .with_entity(id)
+    }
+
+    /// A window looked up by label that does not exist
+    pub fn window_missing(label: &str) -> Self {
+        Self::new(
+            ErrorKind::WindowMissing,
+            format!("Window not found: {}", label),
+        )
+        .with_entity(label)
+    }
+
+    /// A path without a file name or parent directory
+    pub fn invalid_path(path: &Path) -> Self {
+        Self::internal(format!("Invalid file path: {:?}", path))
+            .with_code("INVALID_PATH")
+            .with_path(path)
+    }
+
+    /// A file system error, attributed to the path it happened on
+    pub fn io(err: io::Error, path: impl AsRef<Path>) -> Self {
+        AppError::from(err).with_path(path)
+    }
+
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = code.to_string();
+        self
+    }
+
+    pub fn with_entity(mut self, id: impl Into<String>) -> Self {
+        self.entity_id = Some(id.into());
+        self
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    pub fn with_current(mut self, document: JsonValue) -> Self {
+        self.current = Some(Box::new(document));
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        let (kind, code) = match err.kind() {
+            io::ErrorKind::NotFound => (ErrorKind::NotFound, "FILE_NOT_FOUND"),
+            io::ErrorKind::PermissionDenied => (ErrorKind::PermissionDenied, "PERMISSION_DENIED"),
+            io::ErrorKind::ReadOnlyFilesystem => (ErrorKind::PermissionDenied, "READ_ONLY"),
+            io::ErrorKind::AlreadyExists => (ErrorKind::Conflict, "ALREADY_EXISTS"),
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => {
+                (ErrorKind::Io, "DISK_FULL")
+            }
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                (ErrorKind::Corrupt, "CORRUPT")
+            }
+            _ => (ErrorKind::Io, "IO_ERROR"),
+        };
+        AppError::new(kind, err.to_string()).with_code(code)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        if err.is_io() {
+            return AppError::from(io::Error::from(err));
+        }
+        AppError::corrupt(err.to_string()).with_code("INVALID_JSON")
+    }
+}
@@ -0,0 +1,717 @@
+//! Editing the same data on several machines through a file-sync tool.
+//!
+//! Teams keep a rehearsal laptop and a booth PC in step by syncing the data
+//! directory with Dropbox, Syncthing and the like. When both machines save a
+//! document before the tool catches up, it keeps one as `<id>.json` and
+//! renames the other to a conflicted copy. With folder sync on, the two are
+//! merged against their common ancestor, slide group by slide group, slide by
+//! slide and object by object, and only edits that clash are left for the user.
+//!
+//! Every saved library and playlist lists the hashes of the versions it was
+//! edited from (its lineage), and each machine keeps copies of the versions it
+//! saved or received. The newest version in both lineages is the merge base.
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::error::{AppError, AppResult};
+use crate::migrations;
+use crate::models::{Library, Playlist};
+use crate::repository::{self, Repository, StorageEngine};
+use crate::storage::{self, DocumentKind};
+
+pub const SYNC_CONFLICTS_EVENT: &str = "sync:conflicts";
+
+/// Versions kept in a document's lineage, and copies kept per document
+const LINEAGE_LIMIT: usize = 32;
+
+/// Kinds merged when a sync tool leaves conflicted copies
+const MERGED_KINDS: [DocumentKind; 2] = [DocumentKind::Library, DocumentKind::Playlist];
+
+/// Lists merged item by item, matched by id
+const ID_LISTS: [&str; 4] = ["slideGroups", "items", "slides", "objects"];
+
+// ===== Configuration =====
+
+/// Folder sync configuration persisted in the settings directory
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSyncConfig {
+    pub enabled: bool,
+}
+
+fn get_config_path(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(storage::get_settings_dir(app)?.join("folder-sync.json"))
+}
+
+/// Whether folder sync is on. Only JSON storage can be synced file by file.
+pub fn is_enabled(app: &AppHandle) -> bool {
+    let Ok(path) = get_config_path(app) else {
+        return false;
+    };
+    path.exists()
+        && storage::read_json_file::<FolderSyncConfig>(&path).is_ok_and(|config| config.enabled)
+        && repository::current(app).engine() == StorageEngine::Json
+}
+
+/// Turn folder sync on or off. Turning it on keeps a copy of every library
+/// and playlist as the common ancestor of the first edits on each machine.
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> AppResult<()> {
+    let repo = repository::current(app);
+    if enabled && repo.engine() != StorageEngine::Json {
+        return Err(
+            AppError::conflict("Folder sync needs the JSON storage engine")
+                .with_code("UNSUPPORTED_ENGINE"),
+        );
+    }
+
+    storage::write_json_file(&get_config_path(app)?, &FolderSyncConfig { enabled })?;
+    if enabled {
+        for library in repo.load_libraries()?.items {
+            keep_version(app, DocumentKind::Library, &library.id, &library)?;
+        }
+        for playlist in repo.load_playlists()?.items {
+            keep_version(app, DocumentKind::Playlist, &playlist.id, &playlist)?;
+        }
+    }
+    Ok(())
+}
+
+// ===== Lineage =====
+
+/// Hash identifying a document's content, ignoring its revision and lineage
+pub fn version_hash<T: Serialize>(document: &T) -> AppResult<String> {
+    let mut value = serde_json::to_value(document)?;
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("revision");
+        fields.remove("lineage");
+    }
+    let mut hash = storage::hash_bytes(&serde_json::to_vec(&value)?);
+    hash.truncate(16);
+    Ok(hash)
+}
+
+/// Lineage of a document edited from the version hashed `parent`
+pub fn extend_lineage(lineage: &[String], parent: String) -> Vec<String> {
+    let mut extended: Vec<String> = lineage.iter().filter(|h| **h != parent).cloned().collect();
+    extended.push(parent);
+    let excess = extended.len().saturating_sub(LINEAGE_LIMIT);
+    extended.drain(..excess);
+    extended
+}
+
+fn lineage_of(document: &JsonValue) -> Vec<String> {
+    serde_json::from_value(document["lineage"].clone()).unwrap_or_default()
+}
+
+fn revision_of(document: &JsonValue) -> u64 {
+    document["revision"].as_u64().unwrap_or(0)
+}
+
+// ===== Version Copies =====
+
+fn versions_dir(app: &AppHandle, kind: DocumentKind, id: &str) -> AppResult<PathBuf> {
+    Ok(storage::get_sync_versions_dir(app)?
+        .join(kind.dir_name())
+        .join(id))
+}
+
+/// Keep a copy of a document version, dropping the oldest copies
+fn keep_version<T: Serialize>(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    document: &T,
+) -> AppResult<()> {
+    let dir = versions_dir(app, kind, id)?;
+    let path = dir.join(format!("{}.json", version_hash(document)?));
+    if path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&dir)?;
+    storage::write_json_file(&path, document)?;
+
+    let mut copies: Vec<_> = storage::list_json_files(&dir)?
+        .into_iter()
+        .filter_map(|p| Some((fs::metadata(&p).ok()?.modified().ok()?, p)))
+        .collect();
+    if copies.len() > LINEAGE_LIMIT {
+        copies.sort();
+        for (_, old) in &copies[..copies.len() - LINEAGE_LIMIT] {
+            storage::delete_file(old)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_version(app: &AppHandle, kind: DocumentKind, id: &str, hash: &str) -> Option<JsonValue> {
+    let path = versions_dir(app, kind, id)
+        .ok()?
+        .join(format!("{}.json", hash));
+    storage::read_json_file(&path).ok()
+}
+
+/// Keep a copy of a library or playlist version saved on this machine
+pub fn record_version<T: Serialize>(app: &AppHandle, kind: DocumentKind, id: &str, document: &T) {
+    if !is_enabled(app) {
+        return;
+    }
+    if let Err(e) = keep_version(app, kind, id, document) {
+        eprintln!(
+            "Failed to keep {:?} {} for folder sync: {}",
+            kind, id, e.message
+        );
+    }
+}
+
+/// Keep a copy of a library or playlist version another machine wrote
+pub fn record_received(app: &AppHandle, kind: DocumentKind, id: &str) {
+    if !MERGED_KINDS.contains(&kind) || !is_enabled(app) {
+        return;
+    }
+    let kept = get_value(repository::current(app).as_ref(), kind, id).and_then(|document| {
+        document.map_or(Ok(()), |document| keep_version(app, kind, id, &document))
+    });
+    if let Err(e) = kept {
+        eprintln!(
+            "Failed to keep {:?} {} for folder sync: {}",
+            kind, id, e.message
+        );
+    }
+}
+
+// ===== Three-Way Merge =====
+
+/// Which version of a clashing edit to keep
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeSide {
+    Current, // The document file
+    Copy,    // The conflicted copy
+}
+
+/// An edit made differently in both versions since their common ancestor
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    pub key: String,   // Resolution key, e.g. "slideGroups[<id>].slides[<id>]"
+    pub label: String, // e.g. "Sunday › Amazing Grace › Slide 2"
+    pub current: Option<JsonValue>, // None when deleted
+    pub copy: Option<JsonValue>,
+}
+
+struct Merge<'a> {
+    resolutions: &'a HashMap<String, MergeSide>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merge<'_> {
+    /// Keep whichever side changed a value, or the resolution when both did
+    fn value(
+        &mut self,
+        key: &str,
+        label: &str,
+        base: Option<&JsonValue>,
+        current: Option<&JsonValue>,
+        copy: Option<&JsonValue>,
+    ) -> Option<JsonValue> {
+        if current == copy || copy == base {
+            return current.cloned();
+        }
+        if current == base {
+            return copy.cloned();
+        }
+        match self.resolutions.get(key) {
+            Some(MergeSide::Current) => current.cloned(),
+            Some(MergeSide::Copy) => copy.cloned(),
+            None => {
+                self.conflicts.push(MergeConflict {
+                    key: key.to_string(),
+                    label: label.to_string(),
+                    current: current.cloned(),
+                    copy: copy.cloned(),
+                });
+                current.cloned()
+            }
+        }
+    }
+
+    /// Merge an object field by field
+    fn fields(
+        &mut self,
+        key: &str,
+        label: &str,
+        base: Option<&JsonValue>,
+        current: &JsonValue,
+        copy: &JsonValue,
+    ) -> JsonValue {
+        let (Some(current_fields), Some(copy_fields)) = (current.as_object(), copy.as_object())
+        else {
+            return self
+                .value(key, label, base, Some(current), Some(copy))
+                .unwrap_or_default();
+        };
+        let base_fields = base.and_then(JsonValue::as_object);
+
+        let mut merged = Map::new();
+        let names: BTreeSet<&String> = current_fields.keys().chain(copy_fields.keys()).collect();
+        for name in names {
+            let field_key = match key {
+                "" => name.clone(),
+                _ => format!("{}.{}", key, name),
+            };
+            let base_value = base_fields.and_then(|fields| fields.get(name));
+            let value = match (
+                name.as_str(),
+                current_fields.get(name),
+                copy_fields.get(name),
+            ) {
+                // Rewritten once the whole document is merged
+                ("revision" | "lineage", current, _) => current.cloned(),
+                ("schemaVersion" | "updatedAt", current, copy) => newest(current, copy),
+                (field, Some(current), Some(copy)) if ID_LISTS.contains(&field) => {
+                    Some(self.list(field, &field_key, label, base_value, current, copy))
+                }
+                // A playlist item's copy of a slide group
+                ("slideGroup", Some(current), Some(copy)) => {
+                    Some(self.fields(&field_key, label, base_value, current, copy))
+                }
+                (field, current, copy) => {
+                    let field_label = format!("{} › {}", label, field);
+                    self.value(&field_key, &field_label, base_value, current, copy)
+                }
+            };
+            if let Some(value) = value {
+                merged.insert(name.clone(), value);
+            }
+        }
+        JsonValue::Object(merged)
+    }
+
+    /// Merge a list item by item, keeping additions and deletions from both sides
+    fn list(
+        &mut self,
+        field: &str,
+        key: &str,
+        label: &str,
+        base: Option<&JsonValue>,
+        current: &JsonValue,
+        copy: &JsonValue,
+    ) -> JsonValue {
+        let no_items = Vec::new();
+        let base_items = base.and_then(JsonValue::as_array).unwrap_or(&no_items);
+        let (Some(current_items), Some(copy_items)) = (current.as_array(), copy.as_array()) else {
+            return self
+                .value(key, label, base, Some(current), Some(copy))
+                .unwrap_or_default();
+        };
+        let (Some(base_ids), Some(current_ids), Some(copy_ids)) = (
+            item_ids(base_items),
+            item_ids(current_items),
+            item_ids(copy_items),
+        ) else {
+            // Items without unique ids can only be merged as a whole
+            return self
+                .value(key, label, base, Some(current), Some(copy))
+                .unwrap_or_default();
+        };
+
+        let find = |items: &[JsonValue], ids: &[&str], id: &str| {
+            ids.iter()
+                .position(|i| *i == id)
+                .map(|p| (p, items[p].clone()))
+        };
+
+        let mut merged: HashMap<&str, JsonValue> = HashMap::new();
+        let mut seen = HashSet::new();
+        for id in current_ids.iter().chain(&copy_ids).copied() {
+            if !seen.insert(id) {
+                continue;
+            }
+            let base_item = find(base_items, &base_ids, id).map(|(_, item)| item);
+            let current_item = find(current_items, &current_ids, id);
+            let copy_item = find(copy_items, &copy_ids, id);
+
+            let item_key = format!("{}[{}]", key, id);
+            let item_label = match current_item.as_ref().or(copy_item.as_ref()) {
+                Some((index, item)) => item_label(field, label, item, *index),
+                None => continue,
+            };
+            let value = match (current_item, copy_item) {
+                (Some((_, current)), Some((_, copy))) if field != "objects" => {
+                    Some(self.fields(&item_key, &item_label, base_item.as_ref(), &current, &copy))
+                }
+                (current, copy) => self.value(
+                    &item_key,
+                    &item_label,
+                    base_item.as_ref(),
+                    current.map(|(_, item)| item).as_ref(),
+                    copy.map(|(_, item)| item).as_ref(),
+                ),
+            };
+            if let Some(value) = value {
+                merged.insert(id, value);
+            }
+        }
+
+        // The current order, unless only the copy reordered the items both kept
+        let shared_order = |ids: &[&str]| -> Vec<String> {
+            ids.iter()
+                .filter(|id| {
+                    merged.contains_key(*id) && current_ids.contains(id) && copy_ids.contains(id)
+                })
+                .map(|id| id.to_string())
+                .collect()
+        };
+        let base_order = shared_order(&base_ids);
+        let (primary, secondary) =
+            if shared_order(&current_ids) == base_order && shared_order(&copy_ids) != base_order {
+                (&copy_ids, &current_ids)
+            } else {
+                (&current_ids, &copy_ids)
+            };
+
+        let mut order: Vec<&str> = primary
+            .iter()
+            .copied()
+            .filter(|id| merged.contains_key(id))
+            .collect();
+        for (index, id) in secondary.iter().copied().enumerate() {
+            if !merged.contains_key(id) || order.contains(&id) {
+                continue;
+            }
+            // Right after the item it follows on its own side
+            let position = secondary[..index]
+                .iter()
+                .rev()
+                .find_map(|previous| order.iter().position(|o| o == previous))
+                .map_or(0, |p| p + 1);
+            order.insert(position, id);
+        }
+
+        JsonValue::Array(
+            order
+                .into_iter()
+                .filter_map(|id| merged.remove(id))
+                .collect(),
+        )
+    }
+}
+
+/// Ids of a list's items, or `None` if any is missing or repeated
+fn item_ids(items: &[JsonValue]) -> Option<Vec<&str>> {
+    let ids: Vec<&str> = items
+        .iter()
+        .map(|item| item.get("id")?.as_str())
+        .collect::<Option<_>>()?;
+    let unique: HashSet<&str> = ids.iter().copied().collect();
+    (unique.len() == ids.len()).then_some(ids)
+}
+
+/// Readable name of a list item for conflict prompts
+fn item_label(field: &str, parent: &str, item: &JsonValue, index: usize) -> String {
+    let name = match field {
+        "slideGroups" => item["title"].as_str().map(str::to_string),
+        "items" => item["slideGroup"]["title"].as_str().map(str::to_string),
+        "slides" => Some(format!("Slide {}", index + 1)),
+        "objects" => item["type"].as_str().map(|kind| format!("{} object", kind)),
+        _ => None,
+    };
+    format!(
+        "{} › {}",
+        parent,
+        name.unwrap_or_else(|| format!("{} {}", field, index + 1))
+    )
+}
+
+/// The later of two timestamps or version numbers
+fn newest(current: Option<&JsonValue>, copy: Option<&JsonValue>) -> Option<JsonValue> {
+    let copy_is_newer = match (current, copy) {
+        (Some(JsonValue::Number(c)), Some(JsonValue::Number(k))) => k.as_f64() > c.as_f64(),
+        (Some(JsonValue::String(c)), Some(JsonValue::String(k))) => {
+            match (
+                DateTime::parse_from_rfc3339(c),
+                DateTime::parse_from_rfc3339(k),
+            ) {
+                (Ok(c), Ok(k)) => k > c,
+                _ => k > c,
+            }
+        }
+        (None, Some(_)) => true,
+        _ => false,
+    };
+    if copy_is_newer {
+        copy.cloned()
+    } else {
+        current.cloned()
+    }
+}
+
+/// Merge two versions of a document against their common ancestor, or
+/// side by side when it is unknown. Edits both made differently take their
+/// side from `resolutions` (by conflict key), or are returned as conflicts
+/// with the current side kept for now.
+pub fn merge_documents(
+    base: Option<&JsonValue>,
+    current: &JsonValue,
+    copy: &JsonValue,
+    resolutions: &HashMap<String, MergeSide>,
+) -> (JsonValue, Vec<MergeConflict>) {
+    let mut merge = Merge {
+        resolutions,
+        conflicts: Vec::new(),
+    };
+    let name = current["name"].as_str().unwrap_or_default();
+    let merged = merge.fields("", name, base, current, copy);
+    (merged, merge.conflicts)
+}
+
+// ===== Conflicted Copies =====
+
+/// A conflicted copy with edits the user has to choose between
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub kind: DocumentKind,
+    pub id: String,
+    pub name: String,
+    pub copy_name: String, // File name of the conflicted copy
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Conflicted copies merged or found by [`reconcile`]
+#[derive(Debug, Default)]
+pub struct ReconcileReport {
+    pub merged: Vec<(DocumentKind, String)>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+enum CopyOutcome {
+    Merged,
+    Conflicted {
+        name: String,
+        conflicts: Vec<MergeConflict>,
+    },
+}
+
+/// Id of the document a sync tool's conflicted copy was made from, e.g.
+/// `<id> (Booth's conflicted copy 2024-05-01).json` (Dropbox, Nextcloud,
+/// Seafile) or `<id>.sync-conflict-20240501-101500-ABC1234.json` (Syncthing)
+pub fn conflict_copy_of(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let id = match stem.find(".sync-conflict-") {
+        Some(index) => &stem[..index],
+        None => {
+            let (id, note) = stem.split_once(" (")?;
+            if !note.to_lowercase().contains("conflict") {
+                return None;
+            }
+            id
+        }
+    };
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Conflicted copies of one kind as `(document id, path)` pairs
+fn conflict_copies(app: &AppHandle, kind: DocumentKind) -> AppResult<Vec<(String, PathBuf)>> {
+    let dir = kind.dir(app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut copies = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(id) = conflict_copy_of(&path) {
+            copies.push((id, path));
+        }
+    }
+    copies.sort();
+    Ok(copies)
+}
+
+/// Read a library or playlist from the repository as JSON
+fn get_value(
+    repository: &dyn Repository,
+    kind: DocumentKind,
+    id: &str,
+) -> AppResult<Option<JsonValue>> {
+    Ok(match kind {
+        DocumentKind::Library => repository
+            .get_library(id)?
+            .map(serde_json::to_value)
+            .transpose()?,
+        DocumentKind::Playlist => repository
+            .get_playlist(id)?
+            .map(serde_json::to_value)
+            .transpose()?,
+        DocumentKind::MediaItem | DocumentKind::MediaPlaylist => None,
+    })
+}
+
+/// Read a conflicted copy, upgrading it like any other document
+fn read_copy(app: &AppHandle, kind: DocumentKind, path: &Path) -> AppResult<JsonValue> {
+    Ok(match kind {
+        DocumentKind::Library => {
+            serde_json::to_value(migrations::read_document::<Library>(app, kind, path)?)?
+        }
+        DocumentKind::Playlist => {
+            serde_json::to_value(migrations::read_document::<Playlist>(app, kind, path)?)?
+        }
+        DocumentKind::MediaItem | DocumentKind::MediaPlaylist => {
+            return Err(AppError::unsupported_format(format!(
+                "Conflicted {:?} copies are not merged",
+                kind
+            ))
+            .with_path(path))
+        }
+    })
+}
+
+/// Merge a conflicted copy into its document and delete the copy, unless
+/// edits clash that `resolutions` doesn't settle
+fn merge_copy(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    copy_path: &Path,
+    resolutions: &HashMap<String, MergeSide>,
+) -> AppResult<CopyOutcome> {
+    let repo = repository::current(app);
+    let _guard = repository::lock_checked_saves();
+
+    let copy = read_copy(app, kind, copy_path)?;
+    let Some(current) = get_value(repo.as_ref(), kind, id)? else {
+        // Deleted here but edited there; keep the edits
+        repository::save_document(repo.as_ref(), kind, copy)?;
+        storage::delete_file(&copy_path.to_path_buf())?;
+        return Ok(CopyOutcome::Merged);
+    };
+
+    let current_hash = version_hash(&current)?;
+    let copy_hash = version_hash(&copy)?;
+    let current_lineage = lineage_of(&current);
+    let copy_lineage = lineage_of(&copy);
+
+    let merged = if copy_hash == current_hash || current_lineage.contains(&copy_hash) {
+        // The copy is the current version or one it was edited from
+        None
+    } else if copy_lineage.contains(&current_hash) {
+        // The copy was edited from the current version
+        Some(copy.clone())
+    } else {
+        let base = copy_lineage
+            .iter()
+            .rev()
+            .filter(|hash| current_lineage.contains(hash))
+            .find_map(|hash| read_version(app, kind, id, hash));
+        let (mut merged, conflicts) = merge_documents(base.as_ref(), &current, &copy, resolutions);
+        if !conflicts.is_empty() {
+            let name = current["name"].as_str().unwrap_or_default().to_string();
+            return Ok(CopyOutcome::Conflicted { name, conflicts });
+        }
+
+        let mut lineage = current_lineage.clone();
+        lineage.extend(
+            copy_lineage
+                .iter()
+                .filter(|hash| !current_lineage.contains(hash))
+                .cloned(),
+        );
+        let lineage = extend_lineage(&extend_lineage(&lineage, current_hash), copy_hash);
+        merged["lineage"] = json!(lineage);
+        Some(merged)
+    };
+
+    // Both versions are now ancestors of the document, usable as merge bases
+    keep_version(app, kind, id, &current)?;
+    keep_version(app, kind, id, &copy)?;
+    if let Some(mut merged) = merged {
+        // Past both, so stale windows of either machine get a save conflict
+        merged["revision"] = json!(revision_of(&current).max(revision_of(&copy)) + 1);
+        keep_version(app, kind, id, &merged)?;
+        repository::save_document(repo.as_ref(), kind, merged)?;
+    }
+    storage::delete_file(&copy_path.to_path_buf())?;
+    Ok(CopyOutcome::Merged)
+}
+
+/// Merge every conflicted library and playlist copy whose edits don't clash,
+/// returning the ones that need the user. Does nothing with folder sync off.
+pub fn reconcile(app: &AppHandle) -> AppResult<ReconcileReport> {
+    let mut report = ReconcileReport::default();
+    if !is_enabled(app) {
+        return Ok(report);
+    }
+
+    for kind in MERGED_KINDS {
+        for (id, copy_path) in conflict_copies(app, kind)? {
+            match merge_copy(app, kind, &id, &copy_path, &HashMap::new()) {
+                Ok(CopyOutcome::Merged) => report.merged.push((kind, id)),
+                Ok(CopyOutcome::Conflicted { name, conflicts }) => {
+                    report.conflicts.push(SyncConflict {
+                        kind,
+                        id,
+                        name,
+                        copy_name: copy_path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        conflicts,
+                    })
+                }
+                Err(e) => eprintln!(
+                    "Failed to merge conflicted copy {:?}: {}",
+                    copy_path, e.message
+                ),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Merge a conflicted copy with the user's side for every clashing edit,
+/// returning the id of the merged document
+pub fn resolve_conflict(
+    app: &AppHandle,
+    kind: DocumentKind,
+    copy_name: &str,
+    resolutions: &HashMap<String, MergeSide>,
+) -> AppResult<String> {
+    let copy_path = kind.dir(app)?.join(copy_name);
+    let id = match conflict_copy_of(&copy_path) {
+        Some(id)
+            if MERGED_KINDS.contains(&kind)
+                && copy_path.file_name() == Some(copy_name.as_ref()) =>
+        {
+            id
+        }
+        _ => return Err(AppError::invalid_path(&copy_path)),
+    };
+    if !copy_path.exists() {
+        return Err(
+            AppError::not_found(format!("Conflicted copy {} no longer exists", copy_name))
+                .with_code("FILE_NOT_FOUND")
+                .with_path(&copy_path),
+        );
+    }
+
+    match merge_copy(app, kind, &id, &copy_path, resolutions)? {
+        CopyOutcome::Merged => Ok(id),
+        CopyOutcome::Conflicted { conflicts, .. } => Err(AppError::conflict(format!(
+            "{} edits in {} still need a version picked",
+            conflicts.len(),
+            copy_name
+        ))
+        .with_code("UNRESOLVED_CONFLICTS")
+        .with_entity(id)),
+    }
+}
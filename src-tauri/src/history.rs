@@ -0,0 +1,667 @@
+//! Revision history of libraries and playlists, kept on every save.
+//!
+//! Undo only covers the live session; history lets a mistake found days later
+//! be looked at and undone one slide group at a time. Each distinct version of
+//! a document is stored once as a deflated archive, and an index per document
+//! maps its entries to those versions. Entries are keyed by a sequence number,
+//! since restores and imports can save the same revision number twice.
+//! Retention follows snapshots: the newest revisions plus one per day for a
+//! while.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::models::{Library, Playlist};
+use crate::repository;
+use crate::storage::{self, DocumentKind};
+
+/// The newest revisions of a document are always kept
+const KEEP_RECENT_REVISIONS: usize = 50;
+
+/// Beyond the recent ones, keep the newest revision of each day for this many days
+const KEEP_DAILY_REVISIONS_DAYS: i64 = 90;
+
+const INDEX_NAME: &str = "index.json";
+const DOCUMENT_NAME: &str = "document.json";
+
+/// Held while a document's history is updated
+static HISTORY_WRITES: Mutex<()> = Mutex::new(());
+
+/// One saved revision of a document
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionInfo {
+    #[serde(default)]
+    pub sequence: u64, // Unique within the document, counting up from its first entry
+    pub revision: u64,
+    pub saved_at: String,
+    pub name: String,    // Document name at the time
+    pub version: String, // Content hash naming the stored copy, see folder_sync::version_hash
+}
+
+/// A structural difference between two revisions. Playlist items count as
+/// slide groups, keyed by item id.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RevisionChange {
+    #[serde(rename_all = "camelCase")]
+    Renamed {
+        from: String,
+        to: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    GroupAdded {
+        group_id: String,
+        title: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    GroupRemoved {
+        group_id: String,
+        title: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    GroupRenamed {
+        group_id: String,
+        from: String,
+        to: String,
+    },
+    GroupsReordered,
+    #[serde(rename_all = "camelCase")]
+    SlideAdded {
+        group_id: String,
+        slide_id: String,
+        index: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    SlideRemoved {
+        group_id: String,
+        slide_id: String,
+        index: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    SlidesReordered {
+        group_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TextChanged {
+        group_id: String,
+        slide_id: String,
+        object_id: String,
+        from: String,
+        to: String,
+    },
+    /// Any other edit to a slide: layout, colors, media, tags
+    #[serde(rename_all = "camelCase")]
+    SlideChanged {
+        group_id: String,
+        slide_id: String,
+    },
+}
+
+// ===== Paths =====
+
+fn get_history_dir(app: &AppHandle, kind: DocumentKind, id: &str) -> AppResult<PathBuf> {
+    check_kind(kind)?;
+    Ok(storage::get_history_dir(app)?
+        .join(kind.dir_name())
+        .join(id))
+}
+
+fn check_kind(kind: DocumentKind) -> AppResult<()> {
+    match kind {
+        DocumentKind::Library | DocumentKind::Playlist => Ok(()),
+        _ => Err(
+            AppError::unsupported_format(format!("{:?} documents have no history", kind))
+                .with_code("NO_HISTORY"),
+        ),
+    }
+}
+
+fn archive_error(err: zip::result::ZipError) -> AppError {
+    AppError::corrupt(format!("History archive error: {}", err)).with_code("INVALID_ARCHIVE")
+}
+
+fn read_index(dir: &Path) -> AppResult<Vec<RevisionInfo>> {
+    let path = dir.join(INDEX_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut index: Vec<RevisionInfo> = storage::read_json_file(&path)?;
+
+    // Indexes written before entries had sequence numbers get them oldest first
+    if index.iter().any(|info| info.sequence == 0) {
+        let count = index.len() as u64;
+        for (position, info) in index.iter_mut().enumerate() {
+            info.sequence = count - position as u64;
+        }
+    }
+    Ok(index)
+}
+
+// ===== Recording =====
+
+/// Hold off history writes, e.g. while the workspace is moved
+pub fn lock_history() -> MutexGuard<'static, ()> {
+    HISTORY_WRITES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Add a just-saved document to its history. Failures are logged rather
+/// than failing the save.
+pub fn record_revision<T: Serialize>(app: &AppHandle, kind: DocumentKind, id: &str, document: &T) {
+    if let Err(e) = try_record_revision(app, kind, id, document) {
+        eprintln!(
+            "Failed to record history of {:?} {}: {}",
+            kind, id, e.message
+        );
+    }
+}
+
+/// Record a document a restore or recovery just wrote, in its history and its
+/// folder sync lineage, as for any save. Media documents keep neither.
+pub fn record_restored(app: &AppHandle, kind: DocumentKind, id: &str, document: &JsonValue) {
+    if check_kind(kind).is_ok() {
+        folder_sync::record_version(app, kind, id, document);
+        record_revision(app, kind, id, document);
+    }
+}
+
+fn try_record_revision<T: Serialize>(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    document: &T,
+) -> AppResult<()> {
+    let value = serde_json::to_value(document)?;
+    let version = folder_sync::version_hash(&value)?;
+
+    let _guard = lock_history();
+    let dir = get_history_dir(app, kind, id)?;
+    let mut index = read_index(&dir)?;
+    // Saves that changed nothing don't need a revision of their own
+    if index
+        .first()
+        .is_some_and(|newest| newest.version == version)
+    {
+        return Ok(());
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(e, &dir))?;
+    let path = dir.join(format!("{}.zip", version));
+    if !path.exists() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer
+            .start_file(DOCUMENT_NAME, options)
+            .map_err(archive_error)?;
+        writer.write_all(&serde_json::to_vec(&value)?)?;
+        let archive = writer.finish().map_err(archive_error)?.into_inner();
+        storage::write_file_atomic(&path, &archive)?;
+    }
+
+    let sequence = index.iter().map(|info| info.sequence).max().unwrap_or(0) + 1;
+    index.insert(
+        0,
+        RevisionInfo {
+            sequence,
+            revision: value
+                .get("revision")
+                .and_then(JsonValue::as_u64)
+                .unwrap_or(0),
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            name: value
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            version,
+        },
+    );
+    prune(&dir, &mut index)?;
+    storage::write_json_file(&dir.join(INDEX_NAME), &index)
+}
+
+/// Apply retention to `index`, then delete copies no revision refers to
+fn prune(dir: &Path, index: &mut Vec<RevisionInfo>) -> AppResult<()> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(KEEP_DAILY_REVISIONS_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut seen_days = HashSet::new();
+    let mut position = 0;
+    index.retain(|revision| {
+        // saved_at is RFC 3339, so the first ten characters are the day
+        let day = revision.saved_at.get(..10).unwrap_or("").to_string();
+        let first_of_day = seen_days.insert(day.clone());
+        let keep = position < KEEP_RECENT_REVISIONS || (first_of_day && day >= cutoff);
+        position += 1;
+        keep
+    });
+
+    let referenced: HashSet<String> = index
+        .iter()
+        .map(|revision| format!("{}.zip", revision.version))
+        .collect();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.ends_with(".zip") && !referenced.contains(name) {
+            storage::delete_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+// ===== Reading =====
+
+/// List a document's revisions, newest first
+pub fn list_revisions(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+) -> AppResult<Vec<RevisionInfo>> {
+    read_index(&get_history_dir(app, kind, id)?)
+}
+
+/// The document as it was saved at history entry `sequence`
+pub fn read_revision(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    sequence: u64,
+) -> AppResult<JsonValue> {
+    let dir = get_history_dir(app, kind, id)?;
+    let info = read_index(&dir)?
+        .into_iter()
+        .find(|info| info.sequence == sequence)
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "History entry {} of {:?} {} no longer exists",
+                sequence, kind, id
+            ))
+            .with_code("REVISION_NOT_FOUND")
+            .with_entity(id)
+        })?;
+
+    let path = dir.join(format!("{}.zip", info.version));
+    let mut archive = ZipArchive::new(fs::File::open(&path).map_err(|e| AppError::io(e, &path))?)
+        .map_err(archive_error)?;
+    let mut content = Vec::new();
+    archive
+        .by_name(DOCUMENT_NAME)
+        .map_err(archive_error)?
+        .read_to_end(&mut content)?;
+
+    // The stored copy carries the revision it was first saved with
+    let mut document: JsonValue = serde_json::from_slice(&content)?;
+    document["revision"] = info.revision.into();
+    Ok(document)
+}
+
+/// Pass the content of every stored version of every document to `visit`
+pub fn visit_stored_versions(app: &AppHandle, mut visit: impl FnMut(&[u8])) -> AppResult<()> {
+    for kind in [DocumentKind::Library, DocumentKind::Playlist] {
+        let kind_dir = storage::get_app_data_dir(app)?
+            .join("history")
+            .join(kind.dir_name());
+        if !kind_dir.exists() {
+            continue;
+        }
+
+        for document_dir in fs::read_dir(&kind_dir)? {
+            for entry in fs::read_dir(document_dir?.path())? {
+                let path = entry?.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("zip") {
+                    continue;
+                }
+                let mut archive =
+                    ZipArchive::new(fs::File::open(&path).map_err(|e| AppError::io(e, &path))?)
+                        .map_err(archive_error)?;
+                let mut content = Vec::new();
+                archive
+                    .by_name(DOCUMENT_NAME)
+                    .map_err(archive_error)?
+                    .read_to_end(&mut content)?;
+                visit(&content);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_current(app: &AppHandle, kind: DocumentKind, id: &str) -> AppResult<JsonValue> {
+    let repo = repository::current(app);
+    let document = match kind {
+        DocumentKind::Library => repo.get_library(id)?.map(serde_json::to_value),
+        DocumentKind::Playlist => repo.get_playlist(id)?.map(serde_json::to_value),
+        _ => return check_kind(kind).map(|_| JsonValue::Null),
+    };
+    document
+        .transpose()?
+        .ok_or_else(|| AppError::document_not_found(kind, id))
+}
+
+// ===== Diff =====
+
+/// Slide groups of a library, or of a playlist's items in order, as (id, group)
+fn slide_groups(document: &JsonValue) -> Vec<(String, &JsonValue)> {
+    let id_of = |value: &JsonValue| {
+        value
+            .get("id")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    if let Some(groups) = document.get("slideGroups").and_then(JsonValue::as_array) {
+        return groups.iter().map(|group| (id_of(group), group)).collect();
+    }
+
+    let mut items: Vec<&JsonValue> = document
+        .get("items")
+        .and_then(JsonValue::as_array)
+        .map(|items| items.iter().collect())
+        .unwrap_or_default();
+    items.sort_by_key(|item| item.get("order").and_then(JsonValue::as_i64).unwrap_or(0));
+    items
+        .into_iter()
+        .filter_map(|item| Some((id_of(item), item.get("slideGroup")?)))
+        .collect()
+}
+
+fn str_field<'a>(value: &'a JsonValue, field: &str) -> &'a str {
+    value
+        .get(field)
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default()
+}
+
+fn ids(values: &[JsonValue]) -> Vec<&str> {
+    values.iter().map(|value| str_field(value, "id")).collect()
+}
+
+/// Whether the ids both lists share appear in a different order
+fn reordered(from: &[&str], to: &[&str]) -> bool {
+    let shared_from: Vec<&&str> = from.iter().filter(|id| to.contains(id)).collect();
+    let shared_to: Vec<&&str> = to.iter().filter(|id| from.contains(id)).collect();
+    shared_from != shared_to
+}
+
+/// Text objects of a slide as id -> content
+fn texts(slide: &JsonValue) -> HashMap<&str, &str> {
+    slide
+        .get("objects")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|object| str_field(object, "type") == "text")
+        .map(|object| (str_field(object, "id"), str_field(object, "content")))
+        .collect()
+}
+
+/// A slide with its text objects' content blanked, to spot other edits
+fn without_text(slide: &JsonValue) -> JsonValue {
+    let mut slide = slide.clone();
+    if let Some(objects) = slide.get_mut("objects").and_then(JsonValue::as_array_mut) {
+        for object in objects {
+            if str_field(object, "type") == "text" {
+                object["content"] = JsonValue::Null;
+            }
+        }
+    }
+    slide
+}
+
+fn diff_slides(
+    group_id: &str,
+    from: &JsonValue,
+    to: &JsonValue,
+    changes: &mut Vec<RevisionChange>,
+) {
+    let empty = Vec::new();
+    let from_slides = from
+        .get("slides")
+        .and_then(JsonValue::as_array)
+        .unwrap_or(&empty);
+    let to_slides = to
+        .get("slides")
+        .and_then(JsonValue::as_array)
+        .unwrap_or(&empty);
+    let from_ids = ids(from_slides);
+    let to_ids = ids(to_slides);
+
+    for (index, slide_id) in from_ids.iter().enumerate() {
+        if !to_ids.contains(slide_id) {
+            changes.push(RevisionChange::SlideRemoved {
+                group_id: group_id.to_string(),
+                slide_id: slide_id.to_string(),
+                index,
+            });
+        }
+    }
+    for (index, slide_id) in to_ids.iter().enumerate() {
+        if !from_ids.contains(slide_id) {
+            changes.push(RevisionChange::SlideAdded {
+                group_id: group_id.to_string(),
+                slide_id: slide_id.to_string(),
+                index,
+            });
+        }
+    }
+    if reordered(&from_ids, &to_ids) {
+        changes.push(RevisionChange::SlidesReordered {
+            group_id: group_id.to_string(),
+        });
+    }
+
+    for old in from_slides {
+        let slide_id = str_field(old, "id");
+        let Some(new) = to_slides
+            .iter()
+            .find(|slide| str_field(slide, "id") == slide_id)
+        else {
+            continue;
+        };
+        if old == new {
+            continue;
+        }
+
+        let new_texts = texts(new);
+        let mut old_texts: Vec<(&str, &str)> = texts(old).into_iter().collect();
+        old_texts.sort();
+        for (object_id, old_text) in old_texts {
+            match new_texts.get(object_id) {
+                Some(new_text) if *new_text != old_text => {
+                    changes.push(RevisionChange::TextChanged {
+                        group_id: group_id.to_string(),
+                        slide_id: slide_id.to_string(),
+                        object_id: object_id.to_string(),
+                        from: old_text.to_string(),
+                        to: new_text.to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        if without_text(old) != without_text(new) {
+            changes.push(RevisionChange::SlideChanged {
+                group_id: group_id.to_string(),
+                slide_id: slide_id.to_string(),
+            });
+        }
+    }
+}
+
+/// Structural differences going from one version of a document to another
+pub fn diff_documents(from: &JsonValue, to: &JsonValue) -> Vec<RevisionChange> {
+    let mut changes = Vec::new();
+
+    let (from_name, to_name) = (str_field(from, "name"), str_field(to, "name"));
+    if from_name != to_name {
+        changes.push(RevisionChange::Renamed {
+            from: from_name.to_string(),
+            to: to_name.to_string(),
+        });
+    }
+
+    let from_groups = slide_groups(from);
+    let to_groups = slide_groups(to);
+    let from_ids: Vec<&str> = from_groups.iter().map(|(id, _)| id.as_str()).collect();
+    let to_ids: Vec<&str> = to_groups.iter().map(|(id, _)| id.as_str()).collect();
+
+    for (group_id, group) in &from_groups {
+        if !to_ids.contains(&group_id.as_str()) {
+            changes.push(RevisionChange::GroupRemoved {
+                group_id: group_id.clone(),
+                title: str_field(group, "title").to_string(),
+            });
+        }
+    }
+    for (group_id, group) in &to_groups {
+        if !from_ids.contains(&group_id.as_str()) {
+            changes.push(RevisionChange::GroupAdded {
+                group_id: group_id.clone(),
+                title: str_field(group, "title").to_string(),
+            });
+        }
+    }
+    if reordered(&from_ids, &to_ids) {
+        changes.push(RevisionChange::GroupsReordered);
+    }
+
+    for (group_id, old) in &from_groups {
+        let Some((_, new)) = to_groups.iter().find(|(id, _)| id == group_id) else {
+            continue;
+        };
+        let (old_title, new_title) = (str_field(old, "title"), str_field(new, "title"));
+        if old_title != new_title {
+            changes.push(RevisionChange::GroupRenamed {
+                group_id: group_id.clone(),
+                from: old_title.to_string(),
+                to: new_title.to_string(),
+            });
+        }
+        diff_slides(group_id, old, new, &mut changes);
+    }
+
+    changes
+}
+
+/// Compare history entry `from` with entry `to`, or with the current document
+pub fn diff_revisions(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    from: u64,
+    to: Option<u64>,
+) -> AppResult<Vec<RevisionChange>> {
+    let old = read_revision(app, kind, id, from)?;
+    let new = match to {
+        Some(to) => read_revision(app, kind, id, to)?,
+        None => read_current(app, kind, id)?,
+    };
+    Ok(diff_documents(&old, &new))
+}
+
+// ===== Restore =====
+
+/// Put `item` back into `list` by id: in place when it is still there,
+/// otherwise after the item that preceded it in `old`
+fn restore_item<T>(list: &mut Vec<T>, old: &[T], item: T, id_of: impl Fn(&T) -> &str) {
+    let id = id_of(&item).to_string();
+    if let Some(existing) = list.iter_mut().find(|existing| id_of(existing) == id) {
+        *existing = item;
+        return;
+    }
+
+    let old_index = old.iter().position(|o| id_of(o) == id).unwrap_or(0);
+    let index = old[..old_index]
+        .iter()
+        .rev()
+        .find_map(|previous| {
+            list.iter()
+                .position(|existing| id_of(existing) == id_of(previous))
+        })
+        .map(|position| position + 1)
+        .unwrap_or(0);
+    list.insert(index, item);
+}
+
+fn group_not_found(revision: u64, group_id: &str) -> AppError {
+    AppError::not_found(format!(
+        "Slide group {} is not in revision {}",
+        group_id, revision
+    ))
+    .with_code("GROUP_NOT_FOUND")
+    .with_entity(group_id)
+}
+
+/// Bring back one slide group as it was at history entry `sequence`, keeping
+/// every other change made since. Saved as a new revision, which is returned.
+pub fn restore_slide_group(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    sequence: u64,
+    group_id: &str,
+) -> AppResult<u64> {
+    let old = read_revision(app, kind, id, sequence)?;
+    let repo = repository::current(app);
+
+    match kind {
+        DocumentKind::Library => {
+            let old: Library = serde_json::from_value(old)?;
+            let group = old
+                .slide_groups
+                .iter()
+                .find(|group| group.id == group_id)
+                .cloned()
+                .ok_or_else(|| group_not_found(old.revision, group_id))?;
+
+            let mut library = repo
+                .get_library(id)?
+                .ok_or_else(|| AppError::document_not_found(kind, id))?;
+            restore_item(&mut library.slide_groups, &old.slide_groups, group, |g| {
+                &g.id
+            });
+            let revision = repository::save_library_checked(repo.as_ref(), &mut library)?;
+            folder_sync::record_version(app, kind, id, &library);
+            record_revision(app, kind, id, &library);
+            Ok(revision)
+        }
+        DocumentKind::Playlist => {
+            let old: Playlist = serde_json::from_value(old)?;
+            let item = old
+                .items
+                .iter()
+                .find(|item| item.id == group_id)
+                .cloned()
+                .ok_or_else(|| group_not_found(old.revision, group_id))?;
+
+            let mut playlist = repo
+                .get_playlist(id)?
+                .ok_or_else(|| AppError::document_not_found(kind, id))?;
+            playlist.items.sort_by_key(|item| item.order);
+            let mut old_items = old.items.clone();
+            old_items.sort_by_key(|item| item.order);
+            restore_item(&mut playlist.items, &old_items, item, |i| &i.id);
+            for (order, item) in playlist.items.iter_mut().enumerate() {
+                item.order = order as i32;
+            }
+            let revision = repository::save_playlist_checked(repo.as_ref(), &mut playlist)?;
+            folder_sync::record_version(app, kind, id, &playlist);
+            record_revision(app, kind, id, &playlist);
+            Ok(revision)
+        }
+        _ => check_kind(kind).map(|_| 0),
+    }
+}
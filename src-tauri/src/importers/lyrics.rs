@@ -0,0 +1,372 @@
+//! Plain-text lyrics and ChordPro songs.
+//!
+//! Plain text is split into stanzas at blank lines; a stanza starting with a
+//! section name ("Verse 1", "[Chorus]", "Bridge:") is labelled with it, and a
+//! section name on its own repeats that section when it was sung before.
+//! ChordPro adds `{title}`, comment labels and chorus, verse and bridge
+//! environments, and inline chords that are stripped unless asked to keep.
+
+use super::{ImportOptions, Song, SongSection};
+
+/// ChordPro file extensions
+const CHORDPRO_EXTENSIONS: [&str; 6] = ["cho", "crd", "chopro", "chordpro", "chord", "pro"];
+
+/// Section names recognized at the start of a stanza
+const SECTION_NAMES: [&str; 17] = [
+    "verse",
+    "chorus",
+    "refrain",
+    "pre-chorus",
+    "prechorus",
+    "pre chorus",
+    "bridge",
+    "intro",
+    "outro",
+    "tag",
+    "ending",
+    "interlude",
+    "coda",
+    "instrumental",
+    "vamp",
+    "hook",
+    "turnaround",
+];
+
+/// Metadata lines at the top of plain-text lyrics, and credit lines anywhere
+const METADATA_PREFIXES: [&str; 7] = [
+    "title:",
+    "author:",
+    "authors:",
+    "key:",
+    "tempo:",
+    "copyright",
+    "ccli",
+];
+
+/// Whether a file holds ChordPro rather than plain lyrics
+pub fn is_chordpro(extension: &str, text: &str) -> bool {
+    if CHORDPRO_EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+        return true;
+    }
+    text.lines().any(|line| {
+        let line = line.trim_start().to_lowercase();
+        ["{title", "{t:", "{soc", "{start_of_"]
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+    })
+}
+
+/// "pre-chorus 2" -> "Pre-Chorus 2"
+fn capitalize(label: &str) -> String {
+    let mut result = String::with_capacity(label.len());
+    let mut word_start = true;
+    for c in label.chars() {
+        if word_start {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        word_start = c == ' ' || c == '-';
+    }
+    result
+}
+
+/// The section label a line names, e.g. "[Verse 2]" or "Repeat Chorus:".
+///
+/// Only a number with an optional letter and a repeat mark ("x2", "(x2)",
+/// "repeat") may follow the name, so a lyric line starting with one is left
+/// alone. The repeat mark isn't part of the label.
+pub fn section_header(line: &str) -> Option<String> {
+    let trimmed = line
+        .trim()
+        .trim_start_matches(['[', '('])
+        .trim_end_matches([']', ')', ':'])
+        .trim();
+    let lower = trimmed.to_lowercase();
+    let lower = lower.strip_prefix("repeat ").unwrap_or(&lower);
+
+    let name = SECTION_NAMES.iter().find(|name| lower.starts_with(*name))?;
+    let after = &lower[name.len()..];
+    if !after.is_empty() && !after.starts_with(|c: char| c == ' ' || c.is_ascii_digit()) {
+        return None;
+    }
+    let mut words = after.split_whitespace().peekable();
+    let number = words.next_if(|word| is_section_number(word));
+    words.next_if(|word| is_repeat_mark(word));
+    if words.next().is_some() {
+        return None;
+    }
+
+    Some(capitalize(&match number {
+        Some(number) => format!("{} {}", name, number),
+        None => name.to_string(),
+    }))
+}
+
+/// "2" or "2a"
+fn is_section_number(word: &str) -> bool {
+    let digits = word
+        .strip_suffix(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(word);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// "x2", "(x2)" or "repeat"; the closing parenthesis may already be trimmed
+fn is_repeat_mark(word: &str) -> bool {
+    let mark = word.trim_start_matches('(').trim_end_matches(')');
+    mark == "repeat"
+        || mark
+            .strip_prefix('x')
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_metadata(line: &str) -> bool {
+    let lower = line.trim().to_lowercase();
+    lower.starts_with('©')
+        || METADATA_PREFIXES
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+}
+
+/// Add a section, or repeat an earlier one when `lines` is empty
+fn push_section(song: &mut Song, label: Option<String>, lines: Vec<String>) {
+    if !lines.is_empty() {
+        song.sections.push(SongSection { label, lines });
+        return;
+    }
+    let Some(label) = label else {
+        return;
+    };
+    let earlier = song
+        .sections
+        .iter()
+        .rev()
+        .find(|section| {
+            section
+                .label
+                .as_deref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(&label))
+        })
+        .cloned();
+    if let Some(section) = earlier {
+        song.sections.push(section);
+    }
+}
+
+fn sung_before(song: &Song, label: &str) -> bool {
+    song.sections.iter().any(|section| {
+        section
+            .label
+            .as_deref()
+            .is_some_and(|l| l.eq_ignore_ascii_case(label))
+    })
+}
+
+// ===== Plain Text =====
+
+/// Parse plain-text lyrics, titled `fallback_title` unless a "Title:" line says otherwise
+pub fn parse_plain_text(text: &str, fallback_title: &str) -> Song {
+    let mut song = Song {
+        title: fallback_title.to_string(),
+        ..Song::default()
+    };
+
+    let mut stanzas: Vec<Vec<String>> = vec![Vec::new()];
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            if stanzas.last().is_some_and(|s| !s.is_empty()) {
+                stanzas.push(Vec::new());
+            }
+            continue;
+        }
+        if is_metadata(line) {
+            if line.trim().to_lowercase().starts_with("title:") {
+                song.title = line.trim()["title:".len()..].trim().to_string();
+            }
+            continue;
+        }
+        if let Some(stanza) = stanzas.last_mut() {
+            stanza.push(line.trim().to_string());
+        }
+    }
+
+    // A section name standing alone labels the next stanza, or repeats it
+    let mut pending: Option<String> = None;
+    for stanza in stanzas.into_iter().filter(|s| !s.is_empty()) {
+        let (label, lines) = match section_header(&stanza[0]) {
+            Some(label) => (Some(label), stanza[1..].to_vec()),
+            None => (pending.take(), stanza),
+        };
+        if !lines.is_empty() {
+            if let Some(previous) = pending.take() {
+                push_section(&mut song, Some(previous), Vec::new());
+            }
+            push_section(&mut song, label, lines);
+            continue;
+        }
+
+        if let Some(previous) = pending.take() {
+            push_section(&mut song, Some(previous), Vec::new());
+        }
+        match label {
+            Some(label) if sung_before(&song, &label) => {
+                push_section(&mut song, Some(label), Vec::new())
+            }
+            label => pending = label,
+        }
+    }
+    if let Some(label) = pending {
+        push_section(&mut song, Some(label), Vec::new());
+    }
+
+    song
+}
+
+// ===== ChordPro =====
+
+/// Remove inline chords: "Amaz[D]ing [G]grace" -> "Amazing grace"
+pub fn strip_chords(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut in_chord = false;
+    for c in line.chars() {
+        match c {
+            '[' => in_chord = true,
+            ']' if in_chord => in_chord = false,
+            _ if !in_chord => result.push(c),
+            _ => {}
+        }
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Split "{name: value}" into its lowercased name and value
+fn directive(line: &str) -> Option<(String, String)> {
+    let inner = line.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+    let split = inner.find([':', ' ']).unwrap_or(inner.len());
+    let name = inner[..split].trim().to_lowercase();
+    let value = inner[split..]
+        .trim_start_matches([':', ' '])
+        .trim()
+        .to_string();
+    Some((name, value))
+}
+
+/// Collects lines until the section they belong to ends
+struct ChordProParser {
+    song: Song,
+    lines: Vec<String>,
+    label: Option<String>,
+    environment: Option<String>, // Label of the chorus, verse or bridge being read
+    skipping: bool,              // Inside a tab or grid block
+}
+
+impl ChordProParser {
+    fn flush(&mut self) {
+        let lines = std::mem::take(&mut self.lines);
+        if lines.is_empty() {
+            return;
+        }
+        let label = match &self.environment {
+            Some(environment) => Some(environment.clone()),
+            None => self.label.take(),
+        };
+        push_section(&mut self.song, label, lines);
+    }
+
+    /// Begin a chorus, verse or bridge, labelled by the directive, a comment
+    /// label just before it, or `default`
+    fn start(&mut self, value: String, default: &str) {
+        self.flush();
+        let label = Some(value)
+            .filter(|v| !v.is_empty())
+            .or_else(|| self.label.take())
+            .unwrap_or_else(|| default.to_string());
+        self.environment = Some(label);
+    }
+
+    fn directive(&mut self, name: &str, value: String) {
+        match name {
+            "title" | "t" => self.song.title = value,
+            "start_of_chorus" | "soc" => self.start(value, "Chorus"),
+            "start_of_verse" | "sov" => self.start(value, "Verse"),
+            "start_of_bridge" | "sob" => self.start(value, "Bridge"),
+            "end_of_chorus" | "eoc" | "end_of_verse" | "eov" | "end_of_bridge" | "eob" => {
+                self.flush();
+                self.environment = None;
+            }
+            "start_of_tab" | "sot" | "start_of_grid" | "sog" => {
+                self.flush();
+                self.skipping = true;
+            }
+            "end_of_tab" | "eot" | "end_of_grid" | "eog" => self.skipping = false,
+            "chorus" => {
+                self.flush();
+                let label = if value.is_empty() {
+                    "Chorus".to_string()
+                } else {
+                    value
+                };
+                push_section(&mut self.song, Some(label), Vec::new());
+            }
+            "comment" | "c" | "comment_italic" | "ci" | "comment_box" | "cb" => {
+                if let Some(label) = section_header(&value) {
+                    self.flush();
+                    self.label = Some(label);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parse a ChordPro song, titled `fallback_title` unless it has a `{title}`
+pub fn parse_chordpro(text: &str, fallback_title: &str, options: ImportOptions) -> Song {
+    let mut parser = ChordProParser {
+        song: Song {
+            title: fallback_title.to_string(),
+            ..Song::default()
+        },
+        lines: Vec::new(),
+        label: None,
+        environment: None,
+        skipping: false,
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some((name, value)) = directive(trimmed) {
+            parser.directive(&name, value);
+            continue;
+        }
+        if parser.skipping {
+            continue;
+        }
+        if trimmed.is_empty() {
+            parser.flush();
+            continue;
+        }
+        if parser.lines.is_empty() && parser.environment.is_none() {
+            if let Some(label) = section_header(trimmed) {
+                parser.flush();
+                parser.label = Some(label);
+                continue;
+            }
+        }
+
+        let lyric = if options.keep_chords {
+            trimmed.to_string()
+        } else {
+            strip_chords(trimmed)
+        };
+        // Lines holding only chords
+        if !lyric.is_empty() {
+            parser.lines.push(lyric);
+        }
+    }
+    parser.flush();
+    parser.song
+}
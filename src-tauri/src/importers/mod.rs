@@ -0,0 +1,539 @@
+//! Importing songs, services and presentations written for other programs.
+//!
+//! Each format's parser turns a file into a [`Song`]: a title, its credits
+//! and its sections in the order they are sung. Building slides from it,
+//! tagging them with the matching tag groups and saving into a library is
+//! shared. ProPresenter documents and PowerPoint presentations carry their
+//! own layout and media, so [`pro6`] and [`pptx`] build their slide groups
+//! themselves.
+
+pub mod lyrics;
+pub mod openlp;
+pub mod openlyrics;
+pub mod pptx;
+pub mod pro6;
+mod rtf;
+mod xml;
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::history;
+use crate::media_import::ImportFailure;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
+use crate::models::{
+    CanvasSize, ImageObject, MediaItem, Position, Size, SlideData, SlideGroup, SlideGroupMeta,
+    SlideObject, SlideTagGroup, SongCredits, TextAlignment, TextObject, VideoObject,
+};
+use crate::repository;
+use crate::storage::{self, DocumentKind};
+use crate::storage_watcher::{self, ChangeType, StorageChange, StorageEntity};
+
+/// Longer sections are split over several slides
+const MAX_SLIDE_LINES: usize = 6;
+
+/// Colors given to tag groups created for section names no tag group has yet
+const TAG_GROUP_COLORS: [&str; 6] = [
+    "#3b82f6", "#ef4444", "#22c55e", "#f59e0b", "#a855f7", "#14b8a6",
+];
+
+/// A song as parsed from a file, before it becomes slides
+#[derive(Debug, Clone, Default)]
+pub struct Song {
+    pub title: String,
+    pub credits: SongCredits,
+    pub sections: Vec<SongSection>,
+}
+
+/// A verse, chorus or other part, in singing order. Repeats appear again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SongSection {
+    pub label: Option<String>, // e.g. "Verse 1", "Chorus"
+    pub lines: Vec<String>,
+}
+
+/// Options shared by every format
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    pub keep_chords: bool, // Keep inline chords like "[G]" in the slide text
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SongImportSummary {
+    pub slide_groups: Vec<SlideGroup>, // Added to the library, in file order
+    pub failed: Vec<ImportFailure>,
+}
+
+/// Parse a file into songs, picking the format by extension and content
+fn parse_file(path: &Path, options: ImportOptions) -> AppResult<Vec<Song>> {
+    let extension = storage::get_file_extension(&path.to_path_buf()).unwrap_or_default();
+    let bytes = std::fs::read(path).map_err(|e| AppError::io(e, path))?;
+    let text = decode_text(&bytes);
+    let title = storage::get_file_stem(&path.to_path_buf()).unwrap_or_default();
+
+    if openlyrics::is_openlyrics(&extension, &text) {
+        return Ok(vec![
+            openlyrics::parse(&text, &title, options).map_err(|e| e.with_path(path))?
+        ]);
+    }
+    if lyrics::is_chordpro(&extension, &text) {
+        return Ok(vec![lyrics::parse_chordpro(&text, &title, options)]);
+    }
+    if extension == "txt" || extension.is_empty() {
+        return Ok(vec![lyrics::parse_plain_text(&text, &title)]);
+    }
+    Err(
+        AppError::unsupported_format(format!("Unsupported song file: {:?}", path))
+            .with_code("UNSUPPORTED_SONG_FORMAT")
+            .with_path(path),
+    )
+}
+
+/// Text of a file: UTF-8 (with or without BOM), falling back to Latin-1
+/// which older song files often use
+pub fn decode_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.replace("\r\n", "\n").replace('\r', "\n"),
+        Err(_) => bytes
+            .iter()
+            .map(|&b| b as char)
+            .collect::<String>()
+            .replace("\r\n", "\n")
+            .replace('\r', "\n"),
+    }
+}
+
+/// Where a file another program's document links to is: the path in its
+/// `file://` URL (relative to the document), or else a file of the same name
+/// next to the document. Only files in the document's folder or below are
+/// used; a link anywhere else is skipped with a warning.
+fn linked_file(source: &str, document: &Path) -> Option<PathBuf> {
+    let folder = document.parent()?.canonicalize().ok()?;
+    let inside = |path: PathBuf| {
+        let path = path.canonicalize().ok().filter(|p| p.is_file())?;
+        if !path.starts_with(&folder) {
+            eprintln!("Skipping linked file {:?} outside {:?}", path, folder);
+            return None;
+        }
+        Some(path)
+    };
+
+    let path = source
+        .strip_prefix("file://")
+        .map(|p| storage::percent_decode(p.strip_prefix("localhost").unwrap_or(p)))
+        .unwrap_or_else(|| source.to_string());
+    // Windows paths come as "file:///C:/..."
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => path[1..].to_string(),
+        _ => path,
+    };
+    if let Some(path) = inside(folder.join(path)) {
+        return Some(path);
+    }
+
+    let name = source.rsplit(['/', '\\']).next()?;
+    inside(folder.join(storage::percent_decode(name)))
+}
+
+// ===== Building Slides =====
+
+/// Canvas of imported slide groups when nothing suggests another
+fn default_canvas() -> CanvasSize {
+    CanvasSize {
+        width: 1920,
+        height: 1080,
+    }
+}
+
+/// Slide ids are `<library or playlist id>-<first block of a UUID>`
+fn slide_id(owner_id: &str) -> String {
+    let short_id = Uuid::new_v4().to_string();
+    format!(
+        "{}-{}",
+        owner_id,
+        short_id.split('-').next().unwrap_or_default()
+    )
+}
+
+/// Split `lines` into runs of at most MAX_SLIDE_LINES, as even as possible
+fn split_lines(lines: &[String]) -> Vec<&[String]> {
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let count = lines.len().div_ceil(MAX_SLIDE_LINES);
+    let size = lines.len().div_ceil(count);
+    lines.chunks(size).collect()
+}
+
+/// A centered text box filling most of the canvas, with the font sized so
+/// the longest line and every line fit
+fn text_object(canvas: &CanvasSize, lines: &[String]) -> TextObject {
+    let width = canvas.width as f64 * 0.9;
+    let height = canvas.height as f64 * 0.8;
+    let longest = lines
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(1)
+        .max(1) as f64;
+
+    let by_height = height / (lines.len().max(1) as f64 * 1.3);
+    let by_width = width / (longest * 0.55);
+    let preferred = canvas.height as f64 / 15.0;
+    let font_size = preferred.min(by_height).min(by_width).floor().max(12.0);
+
+    TextObject {
+        id: Uuid::new_v4().to_string(),
+        position: Position {
+            x: (canvas.width as f64 - width) / 2.0,
+            y: (canvas.height as f64 - height) / 2.0,
+        },
+        size: Size { width, height },
+        rotation: None,
+        scale_x: Some(1.0),
+        scale_y: Some(1.0),
+        z_index: 1,
+        is_locked: None,
+        content: lines.join("\n"),
+        font_size,
+        color: "rgba(255, 255, 255, 1)".to_string(),
+        alignment: TextAlignment {
+            horizontal: "center".to_string(),
+            vertical: "center".to_string(),
+        },
+        font_family: Some("Arial".to_string()),
+        font_weight: None,
+        bold: None,
+        font_style: Some("normal".to_string()),
+        underline: None,
+        text_transform: None,
+        text_stroke_color: None,
+        text_stroke_width: None,
+        background_color: None,
+        border_color: None,
+        border_width: None,
+        effect: None,
+        text_shadow: None,
+    }
+}
+
+/// An image or video object showing a media item, either as a locked
+/// background or as an object placed on the slide; None for audio
+fn media_object(
+    item: &MediaItem,
+    position: Position,
+    size: Size,
+    z_index: i32,
+    background: bool,
+) -> Option<SlideObject> {
+    let kind = if background { "background" } else { "object" };
+    match item.media_type.as_str() {
+        "image" => Some(SlideObject::Image(ImageObject {
+            id: Uuid::new_v4().to_string(),
+            position,
+            size,
+            rotation: Some(0.0),
+            scale_x: None,
+            scale_y: None,
+            z_index,
+            is_locked: background.then_some(true),
+            src: item.slide_source().to_string(),
+            image_type: Some(kind.to_string()),
+            object_fit: Some("contain".to_string()),
+            border_color: None,
+            border_width: None,
+            content: None,
+            font_size: None,
+            color: None,
+            alignment: None,
+            font_family: None,
+            font_weight: None,
+            bold: None,
+            font_style: None,
+            underline: None,
+            text_transform: None,
+            text_stroke_color: None,
+            text_stroke_width: None,
+            effect: None,
+            text_shadow: None,
+        })),
+        "video" => Some(SlideObject::Video(VideoObject {
+            id: Uuid::new_v4().to_string(),
+            position,
+            size,
+            rotation: Some(0.0),
+            scale_x: None,
+            scale_y: None,
+            z_index,
+            is_locked: background.then_some(true),
+            src: item.slide_source().to_string(),
+            video_type: Some(kind.to_string()),
+            thumbnail: item.thumbnail.clone(),
+            auto_play: Some(true),
+            loop_video: Some(background),
+            muted: Some(false),
+            border_color: None,
+            border_width: None,
+            content: None,
+            font_size: None,
+            color: None,
+            alignment: None,
+            font_family: None,
+            font_weight: None,
+            bold: None,
+            font_style: None,
+            underline: None,
+            text_transform: None,
+            text_stroke_color: None,
+            text_stroke_width: None,
+            effect: None,
+            text_shadow: None,
+        })),
+        _ => None,
+    }
+}
+
+/// Tag group name for a section label: "Verse 2" and "Verse" both tag as "Verse"
+pub fn tag_name(label: &str) -> String {
+    let label = label.trim().trim_end_matches(':').trim();
+    let words: Vec<&str> = label.split_whitespace().collect();
+    let keep = match words.last() {
+        // Numbers, letters ("Verse 1b") and repeat marks ("x2") after the name
+        Some(last)
+            if words.len() > 1
+                && (last.chars().any(|c| c.is_ascii_digit()) || last.chars().count() == 1) =>
+        {
+            words.len() - 1
+        }
+        _ => words.len(),
+    };
+    words[..keep].join(" ")
+}
+
+/// Find the tag group for a section label, creating it when missing with
+/// `color` or else the next of TAG_GROUP_COLORS
+fn tag_group_for(
+    label: &str,
+    color: Option<&str>,
+    tag_groups: &mut Vec<SlideTagGroup>,
+    created: &mut bool,
+) -> SlideTagGroup {
+    let name = tag_name(label);
+    if let Some(existing) = tag_groups
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(&name))
+    {
+        return existing.clone();
+    }
+
+    let tag_group = SlideTagGroup {
+        id: Uuid::new_v4().to_string(),
+        name,
+        color: color
+            .unwrap_or(TAG_GROUP_COLORS[tag_groups.len() % TAG_GROUP_COLORS.len()])
+            .to_string(),
+    };
+    tag_groups.push(tag_group.clone());
+    *created = true;
+    tag_group
+}
+
+/// Build a slide group for `library_id` from a parsed song
+pub fn build_slide_group(
+    song: &Song,
+    library_id: &str,
+    canvas: &CanvasSize,
+    tag_groups: &mut Vec<SlideTagGroup>,
+    created_tags: &mut bool,
+) -> SlideGroup {
+    let mut slides = Vec::new();
+    for section in &song.sections {
+        let tag_group = section
+            .label
+            .as_deref()
+            .filter(|label| !tag_name(label).is_empty())
+            .map(|label| tag_group_for(label, None, tag_groups, created_tags));
+
+        for lines in split_lines(&section.lines) {
+            slides.push(SlideData {
+                id: slide_id(library_id),
+                objects: Some(vec![SlideObject::Text(text_object(canvas, lines))]),
+                background_color: None,
+                tag_group: tag_group.clone(),
+                text: None,
+                background: None,
+                audio_cue: None,
+                section: section.label.clone(),
+            });
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    SlideGroup {
+        id: Uuid::new_v4().to_string(),
+        meta: Some(SlideGroupMeta {
+            playlist_id: None,
+            origin_library_id: None,
+            origin_slide_group_id: None,
+            library_id: Some(library_id.to_string()),
+        }),
+        title: song.title.clone(),
+        slides,
+        canvas_size: canvas.clone(),
+        credits: Some(song.credits.clone()).filter(|c| !c.is_empty()),
+        created_at: now.clone(),
+        updated_at: now,
+    }
+}
+
+// ===== Importing =====
+
+fn load_tag_groups(app: &AppHandle) -> AppResult<Vec<SlideTagGroup>> {
+    let path = storage::get_tag_groups_file(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    storage::read_json_file(&path)
+}
+
+/// Save tag groups created while importing and note the change for other windows
+fn save_tag_groups(
+    app: &AppHandle,
+    tag_groups: &[SlideTagGroup],
+    changes: &mut Vec<StorageChange>,
+) -> AppResult<()> {
+    storage::write_json_file(&storage::get_tag_groups_file(app)?, tag_groups)?;
+    changes.push(StorageChange {
+        kind: StorageEntity::Settings,
+        id: "tag-groups".to_string(),
+        change: ChangeType::Modified,
+    });
+    Ok(())
+}
+
+/// Import song files into a library as new slide groups, sized for `canvas`
+/// or else the library's newest slide group (ProPresenter documents keep
+/// their own size). Files that fail are reported and skipped; every window
+/// is told about the updated library.
+pub fn import_songs(
+    app: &AppHandle,
+    library_id: &str,
+    paths: &[PathBuf],
+    canvas: Option<CanvasSize>,
+    options: ImportOptions,
+) -> AppResult<SongImportSummary> {
+    let repo = repository::current(app);
+    let library = repo
+        .get_library(library_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::Library, library_id))?;
+    let canvas = canvas
+        .or_else(|| library.slide_groups.last().map(|g| g.canvas_size.clone()))
+        .unwrap_or_else(default_canvas);
+
+    let mut tag_groups = load_tag_groups(app)?;
+    let mut created_tags = false;
+    let mut summary = SongImportSummary {
+        slide_groups: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    let mut media = pro6::MediaCache::new();
+
+    for path in paths {
+        let extension = storage::get_file_extension(path).unwrap_or_default();
+        let imported = if pro6::is_pro6(&extension) {
+            pro6::import_document(
+                app,
+                path,
+                library_id,
+                &mut tag_groups,
+                &mut created_tags,
+                &mut media,
+                &mut summary.failed,
+            )
+            .map(|group| vec![group])
+        } else {
+            parse_file(path, options).map(|songs| {
+                songs
+                    .iter()
+                    .map(|song| {
+                        build_slide_group(
+                            song,
+                            library_id,
+                            &canvas,
+                            &mut tag_groups,
+                            &mut created_tags,
+                        )
+                    })
+                    .collect()
+            })
+        };
+        match imported {
+            Ok(groups) => summary.slide_groups.extend(groups),
+            Err(e) => summary.failed.push(ImportFailure {
+                source_path: path.to_string_lossy().into_owned(),
+                error: e.message,
+            }),
+        }
+    }
+    if summary.slide_groups.is_empty() {
+        return Ok(summary);
+    }
+
+    let mut changes = Vec::new();
+    if created_tags {
+        save_tag_groups(app, &tag_groups, &mut changes)?;
+    }
+    add_to_library(app, library_id, &summary.slide_groups, changes)?;
+    Ok(summary)
+}
+
+/// Append imported slide groups to a library and save it, telling every
+/// window about it along with the other `changes`
+fn add_to_library(
+    app: &AppHandle,
+    library_id: &str,
+    slide_groups: &[SlideGroup],
+    mut changes: Vec<StorageChange>,
+) -> AppResult<()> {
+    save_to_library(app, library_id, slide_groups, &mut changes)?;
+    storage_watcher::broadcast_changes(app, &changes);
+    Ok(())
+}
+
+/// Append imported slide groups to a library, read again under the save lock
+/// so edits saved while the files were imported are kept, and save it
+fn save_to_library(
+    app: &AppHandle,
+    library_id: &str,
+    slide_groups: &[SlideGroup],
+    changes: &mut Vec<StorageChange>,
+) -> AppResult<()> {
+    let repo = repository::current(app);
+    let library = {
+        let _guard = repository::lock_checked_saves();
+        let mut library = repo
+            .get_library(library_id)?
+            .ok_or_else(|| AppError::document_not_found(DocumentKind::Library, library_id))?;
+        library.schema_version = Some(CURRENT_SCHEMA_VERSION);
+        library.slide_groups.extend(slide_groups.iter().cloned());
+        library.updated_at = chrono::Utc::now().to_rfc3339();
+        repository::save_library_replacing(repo.as_ref(), &mut library)?;
+        library
+    };
+    folder_sync::record_version(app, DocumentKind::Library, &library.id, &library);
+    history::record_revision(app, DocumentKind::Library, &library.id, &library);
+
+    changes.push(StorageChange {
+        kind: StorageEntity::Library,
+        id: library.id.clone(),
+        change: ChangeType::Modified,
+    });
+    Ok(())
+}
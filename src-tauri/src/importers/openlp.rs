@@ -0,0 +1,552 @@
+//! OpenLP service files (`.osz`).
+//!
+//! A service file is a zip holding a JSON list of service items and, unless
+//! it was saved as a "lite" service, the images and media they show. Songs,
+//! custom slides and Bible readings become text slides and images and media
+//! become media slides, each item one playlist item. Items of other plugins
+//! are reported as skipped.
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use uuid::Uuid;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use super::{
+    build_slide_group, default_canvas, load_tag_groups, lyrics, media_object, openlyrics,
+    save_tag_groups, save_to_library, slide_id, ImportOptions, Song, SongSection,
+};
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::history;
+use crate::media_import;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
+use crate::models::{
+    AudioCue, AudioCueAction, CanvasSize, MediaItem, Playlist, PlaylistItem, Position, Size,
+    SlideData, SlideGroup, SlideGroupMeta, SongCredits,
+};
+use crate::repository;
+use crate::storage::{self, DocumentKind};
+use crate::storage_watcher::{self, ChangeType, StorageChange, StorageEntity};
+
+/// Extension of the service data; OpenLP 2.0 and older pickled it as `.osd`
+const SERVICE_DATA_EXTENSION: &str = ".osj";
+const LEGACY_SERVICE_DATA_EXTENSION: &str = ".osd";
+
+/// A service item that could not be converted
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedServiceItem {
+    pub title: String,
+    pub plugin: String, // OpenLP plugin that made the item, e.g. "presentations"
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceImportSummary {
+    pub playlist: Option<Playlist>, // None when no item could be converted
+    pub library_id: Option<String>, // Set when songs were added to a library
+    pub media_imported: usize,
+    pub media_reused: usize, // Already in the media library with the same content
+    pub skipped: Vec<SkippedServiceItem>,
+}
+
+/// One entry of the service data: a header describing the item and its slides
+struct ServiceItem {
+    header: JsonValue,
+    data: Vec<JsonValue>,
+}
+
+impl ServiceItem {
+    fn plugin(&self) -> &str {
+        self.header["name"].as_str().unwrap_or_default()
+    }
+
+    fn title(&self) -> String {
+        text(&self.header["title"]).unwrap_or_default()
+    }
+
+    fn skip(&self, title: String, reason: impl Into<String>) -> SkippedServiceItem {
+        SkippedServiceItem {
+            title,
+            plugin: self.plugin().to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+fn archive_error(err: ZipError) -> AppError {
+    match err {
+        ZipError::Io(err) => AppError::from(err),
+        _ => AppError::corrupt(format!("Service file archive error: {}", err))
+            .with_code("INVALID_SERVICE_FILE"),
+    }
+}
+
+/// A trimmed, non-empty string or number
+fn text(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        JsonValue::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Service items in service order
+fn read_items(archive: &mut ZipArchive<fs::File>) -> AppResult<Vec<ServiceItem>> {
+    let names: Vec<String> = archive.file_names().map(String::from).collect();
+    let Some(data_name) = names
+        .iter()
+        .find(|n| n.to_lowercase().ends_with(SERVICE_DATA_EXTENSION))
+    else {
+        if names
+            .iter()
+            .any(|n| n.to_lowercase().ends_with(LEGACY_SERVICE_DATA_EXTENSION))
+        {
+            return Err(AppError::unsupported_format(
+                "Service files from OpenLP 2.0 and older are not supported",
+            )
+            .with_code("UNSUPPORTED_SERVICE_VERSION"));
+        }
+        return Err(
+            AppError::corrupt("Not an OpenLP service file").with_code("INVALID_SERVICE_FILE")
+        );
+    };
+
+    let mut content = Vec::new();
+    archive
+        .by_name(data_name)
+        .map_err(archive_error)?
+        .read_to_end(&mut content)?;
+    let entries: Vec<JsonValue> = serde_json::from_slice(&content)?;
+
+    // The first entry describes the service itself
+    Ok(entries
+        .into_iter()
+        .filter_map(|mut entry| {
+            let mut item = entry.get_mut("serviceitem")?.take();
+            Some(ServiceItem {
+                header: item["header"].take(),
+                data: match item["data"].take() {
+                    JsonValue::Array(data) => data,
+                    _ => Vec::new(),
+                },
+            })
+        })
+        .collect())
+}
+
+// ===== Text Items =====
+
+/// A line without OpenLP formatting tags like "{st}" and "{/r}"
+fn strip_tags(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('{') {
+        let is_tag = |inner: &str| {
+            let name = inner.trim_start_matches('/');
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())
+        };
+        match rest[start..].find('}').map(|end| start + end) {
+            Some(end) if is_tag(&rest[start + 1..end]) => {
+                result.push_str(&rest[..start]);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                result.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Lines of a slide's text, without tags, optional split marks ("[---]") and,
+/// when `strip_chords` is set, inline chords
+fn slide_lines(raw: &str, strip_chords: bool) -> Vec<String> {
+    raw.lines()
+        .filter(|line| line.trim() != "[---]")
+        .map(strip_tags)
+        .map(|line| {
+            if strip_chords {
+                lyrics::strip_chords(&line)
+            } else {
+                line.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Credits from a song's audit entry: [title, [authors], copyright, ccli number]
+fn audit_credits(audit: &JsonValue) -> SongCredits {
+    SongCredits {
+        authors: audit[1]
+            .as_array()
+            .map(|authors| authors.iter().filter_map(text).collect())
+            .unwrap_or_default(),
+        copyright: text(&audit[2]),
+        ccli_number: text(&audit[3]),
+    }
+}
+
+/// A song, custom slide or Bible reading as a song, one section per slide.
+/// Only songs have verse tags ("V1", "C1") worth labelling.
+fn item_song(item: &ServiceItem, options: ImportOptions) -> Song {
+    let is_song = item.plugin() == "songs";
+    let sections = item
+        .data
+        .iter()
+        .filter_map(|slide| {
+            let lines = slide_lines(
+                slide["raw_slide"].as_str()?,
+                is_song && !options.keep_chords,
+            );
+            if lines.is_empty() {
+                return None;
+            }
+            let label = text(&slide["verseTag"])
+                .filter(|_| is_song)
+                .map(|tag| openlyrics::section_label(&tag));
+            Some(SongSection { label, lines })
+        })
+        .collect();
+
+    Song {
+        title: item.title(),
+        credits: if is_song {
+            audit_credits(&item.header["audit"])
+        } else {
+            SongCredits::default()
+        },
+        sections,
+    }
+}
+
+// ===== Media Items =====
+
+/// An archive-style path: forward slashes, no drive letter or leading slash
+fn archive_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = match path.split_once(':') {
+        Some((drive, rest)) if drive.len() == 1 => rest.to_string(),
+        _ => path,
+    };
+    path.trim_start_matches('/').to_string()
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// The files of a service, imported into the media library as items need them
+struct ServiceFiles {
+    archive: ZipArchive<fs::File>,
+    names: Vec<String>,
+    staging: PathBuf, // Extracted files wait here to be imported
+    extracted: usize,
+    imported: usize,
+    reused: usize,
+}
+
+impl ServiceFiles {
+    /// The archive entry of a slide's file. OpenLP stores files under their
+    /// original path, or under their content hash in newer versions.
+    fn find_entry(&self, title: &str, path: &str, hash: &str) -> Option<String> {
+        let files = self.names.iter().filter(|n| !n.ends_with('/'));
+        let full_paths = [
+            archive_path(path),
+            archive_path(&format!("{}/{}", path, title)),
+        ];
+        let stem = |name: &str| {
+            file_name(name)
+                .split('.')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        files
+            .clone()
+            .find(|n| full_paths.contains(&archive_path(n)))
+            .or_else(|| files.clone().find(|n| !hash.is_empty() && stem(n) == hash))
+            .or_else(|| {
+                files.clone().find(|n| {
+                    let name = file_name(n);
+                    name.eq_ignore_ascii_case(title) || name.eq_ignore_ascii_case(file_name(path))
+                })
+            })
+            .cloned()
+    }
+
+    /// Extract an entry under the name it had, or the entry's own name
+    fn extract(&mut self, entry: &str, title: &str) -> AppResult<PathBuf> {
+        let name = match file_name(title) {
+            name if Path::new(name).extension().is_some() => name,
+            _ => file_name(entry),
+        };
+        let name = match name {
+            "" | "." | ".." => "file",
+            name => name,
+        };
+
+        // Each file gets its own folder so equal names don't collide
+        let dir = self.staging.join(self.extracted.to_string());
+        self.extracted += 1;
+        fs::create_dir_all(&dir).map_err(|e| AppError::io(e, &dir))?;
+
+        let path = dir.join(name);
+        let mut source = self.archive.by_name(entry).map_err(archive_error)?;
+        let mut output = fs::File::create(&path).map_err(|e| AppError::io(e, &path))?;
+        io::copy(&mut source, &mut output).map_err(|e| AppError::io(e, &path))?;
+        Ok(path)
+    }
+
+    /// Import the file a slide of an image or media item shows. Lite
+    /// services leave files out; they may still be where OpenLP found them.
+    fn import(&mut self, app: &AppHandle, slide: &JsonValue) -> AppResult<MediaItem> {
+        let title = slide["title"].as_str().unwrap_or_default();
+        let path = slide["path"].as_str().unwrap_or_default();
+        let hash = slide["file_hash"].as_str().unwrap_or_default();
+
+        let source = match self.find_entry(title, path, hash) {
+            Some(entry) => self.extract(&entry, title)?,
+            None => [PathBuf::from(path), Path::new(path).join(title)]
+                .into_iter()
+                .find(|p| !path.is_empty() && p.is_file())
+                .ok_or_else(|| {
+                    AppError::not_found(format!("{} is not in the service file", title))
+                        .with_code("FILE_NOT_FOUND")
+                })?,
+        };
+
+        let (item, duplicate) = media_import::import_file(app, &source, false)?;
+        if duplicate {
+            self.reused += 1;
+        } else {
+            self.imported += 1;
+        }
+        Ok(item)
+    }
+}
+
+/// A full-canvas background slide for a media item, like the presenter
+/// makes when media is added to a playlist; audio becomes an audio cue
+fn media_slide(item: &MediaItem, canvas: &CanvasSize, playlist_id: &str) -> SlideData {
+    let position = Position { x: 0.0, y: 0.0 };
+    let size = Size {
+        width: canvas.width as f64,
+        height: canvas.height as f64,
+    };
+    let object = media_object(item, position, size, 0, true);
+    let audio_cue = object.is_none().then(|| AudioCue {
+        action: AudioCueAction::Play,
+        media_id: Some(item.id.clone()),
+        src: Some(item.slide_source().to_string()),
+        volume: None,
+        loop_audio: None,
+    });
+
+    SlideData {
+        id: slide_id(playlist_id),
+        objects: Some(object.into_iter().collect()),
+        background_color: None,
+        tag_group: None,
+        text: None,
+        background: None,
+        audio_cue,
+        section: None,
+    }
+}
+
+// ===== Importing =====
+
+fn playlist_meta(playlist_id: &str, origin: Option<&SlideGroup>) -> SlideGroupMeta {
+    SlideGroupMeta {
+        playlist_id: Some(playlist_id.to_string()),
+        origin_library_id: origin.and_then(|g| g.meta.as_ref()?.library_id.clone()),
+        origin_slide_group_id: origin.map(|g| g.id.clone()),
+        library_id: None,
+    }
+}
+
+/// A playlist's copy of a slide group, with new ids, pointing back at the
+/// library group it came from when there is one
+fn playlist_copy(group: &SlideGroup, playlist_id: &str, in_library: bool) -> SlideGroup {
+    let mut copy = group.clone();
+    copy.id = Uuid::new_v4().to_string();
+    copy.meta = Some(playlist_meta(playlist_id, in_library.then_some(group)));
+    for slide in &mut copy.slides {
+        slide.id = slide_id(playlist_id);
+    }
+    copy
+}
+
+/// Import an OpenLP service file as a new playlist named after it. Songs are
+/// also added to `library_id` when given, and bundled images and media to the
+/// media library. Items that can't be converted are reported and skipped.
+pub fn import_service(
+    app: &AppHandle,
+    source: &Path,
+    library_id: Option<&str>,
+    options: ImportOptions,
+) -> AppResult<ServiceImportSummary> {
+    let file = fs::File::open(source).map_err(|e| AppError::io(e, source))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| archive_error(e).with_path(source))?;
+    let items = read_items(&mut archive).map_err(|e| e.with_path(source))?;
+
+    let repo = repository::current(app);
+    let library = match library_id {
+        Some(id) => Some(
+            repo.get_library(id)?
+                .ok_or_else(|| AppError::document_not_found(DocumentKind::Library, id))?,
+        ),
+        None => None,
+    };
+    let canvas = library
+        .as_ref()
+        .and_then(|l| l.slide_groups.last())
+        .map(|g| g.canvas_size.clone())
+        .unwrap_or_else(default_canvas);
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut playlist = Playlist {
+        id: Uuid::new_v4().to_string(),
+        schema_version: Some(CURRENT_SCHEMA_VERSION),
+        name: storage::get_file_stem(&source.to_path_buf()).unwrap_or_default(),
+        items: Vec::new(),
+        order: None,
+        revision: 0,
+        lineage: Vec::new(),
+        created_at: now.clone(),
+        updated_at: now.clone(),
+    };
+
+    let mut tag_groups = load_tag_groups(app)?;
+    let mut created_tags = false;
+    let mut library_groups = Vec::new();
+    let mut skipped = Vec::new();
+    let mut files = ServiceFiles {
+        names: archive.file_names().map(String::from).collect(),
+        archive,
+        staging: std::env::temp_dir().join(format!("openlp-service-{}", Uuid::new_v4())),
+        extracted: 0,
+        imported: 0,
+        reused: 0,
+    };
+
+    for item in &items {
+        let group = match item.plugin() {
+            "songs" | "custom" | "bibles" => {
+                let song = item_song(item, options);
+                if song.sections.is_empty() {
+                    skipped.push(item.skip(item.title(), "The item has no text"));
+                    continue;
+                }
+                match library.as_ref().filter(|_| item.plugin() == "songs") {
+                    Some(library) => {
+                        let group = build_slide_group(
+                            &song,
+                            &library.id,
+                            &canvas,
+                            &mut tag_groups,
+                            &mut created_tags,
+                        );
+                        library_groups.push(group.clone());
+                        playlist_copy(&group, &playlist.id, true)
+                    }
+                    None => {
+                        let group = build_slide_group(
+                            &song,
+                            &playlist.id,
+                            &canvas,
+                            &mut tag_groups,
+                            &mut created_tags,
+                        );
+                        playlist_copy(&group, &playlist.id, false)
+                    }
+                }
+            }
+            "images" | "media" => {
+                let mut slides = Vec::new();
+                for slide in &item.data {
+                    match files.import(app, slide) {
+                        Ok(media) => slides.push(media_slide(&media, &canvas, &playlist.id)),
+                        Err(e) => {
+                            let title = text(&slide["title"]).unwrap_or_else(|| item.title());
+                            skipped.push(item.skip(title, e.message));
+                        }
+                    }
+                }
+                if slides.is_empty() {
+                    continue;
+                }
+                SlideGroup {
+                    id: Uuid::new_v4().to_string(),
+                    meta: Some(playlist_meta(&playlist.id, None)),
+                    title: item.title(),
+                    slides,
+                    canvas_size: canvas.clone(),
+                    credits: None,
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                }
+            }
+            "" => {
+                skipped.push(item.skip(item.title(), "Unknown item type"));
+                continue;
+            }
+            plugin => {
+                let reason = format!("OpenLP {} items can't be converted", plugin);
+                skipped.push(item.skip(item.title(), reason));
+                continue;
+            }
+        };
+        playlist.items.push(PlaylistItem {
+            id: Uuid::new_v4().to_string(),
+            slide_group: group,
+            order: playlist.items.len() as i32,
+        });
+    }
+    if let Err(e) = fs::remove_dir_all(&files.staging) {
+        if e.kind() != io::ErrorKind::NotFound {
+            eprintln!("Failed to remove {:?}: {}", files.staging, e);
+        }
+    }
+
+    let mut summary = ServiceImportSummary {
+        playlist: None,
+        library_id: None,
+        media_imported: files.imported,
+        media_reused: files.reused,
+        skipped,
+    };
+    let mut changes = Vec::new();
+    if created_tags {
+        save_tag_groups(app, &tag_groups, &mut changes)?;
+    }
+    if let Some(library) = library.filter(|_| !library_groups.is_empty()) {
+        save_to_library(app, &library.id, &library_groups, &mut changes)?;
+        summary.library_id = Some(library.id);
+    }
+    if !playlist.items.is_empty() {
+        repo.save_playlist(&playlist)?;
+        folder_sync::record_version(app, DocumentKind::Playlist, &playlist.id, &playlist);
+        history::record_revision(app, DocumentKind::Playlist, &playlist.id, &playlist);
+        changes.push(StorageChange {
+            kind: StorageEntity::Playlist,
+            id: playlist.id.clone(),
+            change: ChangeType::Created,
+        });
+        summary.playlist = Some(playlist);
+    }
+
+    storage_watcher::broadcast_changes(app, &changes);
+    Ok(summary)
+}
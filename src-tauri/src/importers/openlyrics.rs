@@ -0,0 +1,449 @@
+//! OpenLyrics XML songs, the open format OpenLP and most song databases use.
+//!
+//! A song's verses are named by a type letter and number ("v1", "c", "b2")
+//! and sung in its `verseOrder`, or in document order without one. Each
+//! `<lines>` of a verse is its own slide. Exporting maps slide sections and
+//! tag groups back to verse names, writing a section sung twice only once.
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::path::Path;
+
+use super::{tag_name, ImportOptions, Song, SongSection};
+use crate::error::{AppError, AppResult};
+use crate::models::{SlideData, SlideGroup, SlideObject, SongCredits};
+
+const NAMESPACE: &str = "http://openlyrics.info/namespace/2009/song";
+
+/// Verse type letters and the section names they stand for
+const VERSE_TYPES: [(char, &str); 7] = [
+    ('v', "Verse"),
+    ('c', "Chorus"),
+    ('p', "Pre-Chorus"),
+    ('b', "Bridge"),
+    ('i', "Intro"),
+    ('e', "Ending"),
+    ('o', "Other"),
+];
+
+/// Other section names and the verse type they export as
+const VERSE_TYPE_ALIASES: [(&str, char); 5] = [
+    ("refrain", 'c'),
+    ("prechorus", 'p'),
+    ("pre chorus", 'p'),
+    ("outro", 'e'),
+    ("coda", 'e'),
+];
+
+/// Whether a file holds an OpenLyrics song
+pub fn is_openlyrics(extension: &str, text: &str) -> bool {
+    text.contains("<song") && (extension.eq_ignore_ascii_case("xml") || text.contains(NAMESPACE))
+}
+
+/// "v1" -> "Verse 1", "c" -> "Chorus", "b2a" -> "Bridge 2a"; other names are kept
+pub fn section_label(name: &str) -> String {
+    let mut chars = name.chars();
+    let letter = chars.next().map(|c| c.to_ascii_lowercase());
+    let rest = chars.as_str();
+    match VERSE_TYPES.iter().find(|(l, _)| Some(*l) == letter) {
+        Some((_, section)) if rest.is_empty() => section.to_string(),
+        Some((_, section)) if rest.starts_with(|c: char| c.is_ascii_digit()) => {
+            format!("{} {}", section, rest)
+        }
+        _ => name.to_string(),
+    }
+}
+
+// ===== Import =====
+
+/// A verse as written: its name and the lines of each of its slides
+struct Verse {
+    name: String,
+    slides: Vec<Vec<String>>,
+}
+
+fn invalid_xml(error: impl std::fmt::Display) -> AppError {
+    AppError::corrupt(format!("Invalid OpenLyrics file: {}", error)).with_code("INVALID_OPENLYRICS")
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).into_owned()
+}
+
+fn attribute(element: &BytesStart, name: &str) -> AppResult<Option<String>> {
+    match element.try_get_attribute(name).map_err(invalid_xml)? {
+        Some(value) => Ok(Some(
+            value.unescape_value().map_err(invalid_xml)?.into_owned(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Collects properties and verses while the document is read
+struct OpenLyricsParser {
+    options: ImportOptions,
+    path: Vec<String>, // Local names of the open elements
+    titles: Vec<String>,
+    authors: Vec<String>,
+    copyright: String,
+    ccli_number: String,
+    verse_order: String,
+    verses: Vec<Verse>,
+    language: Option<String>, // Of the first verse; translations are skipped
+    skipping: bool,           // Inside a verse in another language
+    line: String,
+}
+
+impl OpenLyricsParser {
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() || self.skipping {
+            return;
+        }
+        if let Some(slide) = self.verses.last_mut().and_then(|v| v.slides.last_mut()) {
+            slide.push(line);
+        }
+    }
+
+    fn open(&mut self, element: &BytesStart) -> AppResult<()> {
+        let name = local_name(element);
+        match name.as_str() {
+            "title" => self.titles.push(String::new()),
+            "author" => self.authors.push(String::new()),
+            "verse" if self.path.last().is_some_and(|p| p == "lyrics") => {
+                let language = attribute(element, "lang")?;
+                if self.verses.is_empty() {
+                    self.language = language.clone();
+                }
+                self.skipping = language.is_some() && language != self.language;
+                if !self.skipping {
+                    self.verses.push(Verse {
+                        name: attribute(element, "name")?.unwrap_or_default(),
+                        slides: Vec::new(),
+                    });
+                }
+            }
+            "lines" if !self.skipping => {
+                if let Some(verse) = self.verses.last_mut() {
+                    verse.slides.push(Vec::new());
+                }
+            }
+            "br" => self.end_line(),
+            "chord" if self.options.keep_chords => {
+                // 0.9 names the chord, 1.0 gives its root
+                if let Some(chord) = attribute(element, "name")?.or(attribute(element, "root")?) {
+                    self.line.push_str(&format!("[{}]", chord));
+                }
+            }
+            _ => {}
+        }
+        self.path.push(name);
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        match self.path.pop().as_deref() {
+            // Lines of 0.8 documents are elements of their own
+            Some("line") | Some("lines") => self.end_line(),
+            Some("verse") => self.skipping = false,
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skipping || self.path.iter().any(|p| p == "comment") {
+            return;
+        }
+        if self.path.iter().any(|p| p == "lines") {
+            self.line.push_str(text);
+            return;
+        }
+        let target = match self.path.last().map(String::as_str) {
+            Some("title") => self.titles.last_mut(),
+            Some("author") => self.authors.last_mut(),
+            Some("copyright") => Some(&mut self.copyright),
+            Some("ccliNo") => Some(&mut self.ccli_number),
+            Some("verseOrder") => Some(&mut self.verse_order),
+            _ => None,
+        };
+        if let Some(target) = target {
+            target.push_str(text);
+        }
+    }
+
+    /// Sections in the verse order. An entry naming no verse exactly takes
+    /// every verse it prefixes, so "v1" plays "v1a" and "v1b".
+    fn sections(&self) -> Vec<SongSection> {
+        let in_order = |entry: &str| -> Vec<&Verse> {
+            let exact: Vec<&Verse> = self
+                .verses
+                .iter()
+                .filter(|v| v.name.eq_ignore_ascii_case(entry))
+                .collect();
+            if !exact.is_empty() {
+                return exact;
+            }
+            self.verses
+                .iter()
+                .filter(|v| v.name.to_lowercase().starts_with(&entry.to_lowercase()))
+                .collect()
+        };
+
+        let mut verses: Vec<&Verse> = self
+            .verse_order
+            .split_whitespace()
+            .flat_map(in_order)
+            .collect();
+        if verses.is_empty() {
+            verses = self.verses.iter().collect();
+        }
+
+        verses
+            .into_iter()
+            .flat_map(|verse| {
+                let label = Some(verse.name.trim())
+                    .filter(|name| !name.is_empty())
+                    .map(section_label);
+                verse
+                    .slides
+                    .iter()
+                    .filter(|lines| !lines.is_empty())
+                    .map(move |lines| SongSection {
+                        label: label.clone(),
+                        lines: lines.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    fn finish(self, fallback_title: &str) -> Song {
+        let present = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        Song {
+            title: self
+                .titles
+                .iter()
+                .find_map(|t| present(t))
+                .unwrap_or_else(|| fallback_title.to_string()),
+            credits: SongCredits {
+                authors: self.authors.iter().filter_map(|a| present(a)).collect(),
+                copyright: present(&self.copyright),
+                ccli_number: present(&self.ccli_number),
+            },
+            sections: self.sections(),
+        }
+    }
+}
+
+/// Parse an OpenLyrics song, titled `fallback_title` if it has no title
+pub fn parse(text: &str, fallback_title: &str, options: ImportOptions) -> AppResult<Song> {
+    let mut reader = Reader::from_str(text);
+    let mut parser = OpenLyricsParser {
+        options,
+        path: Vec::new(),
+        titles: Vec::new(),
+        authors: Vec::new(),
+        copyright: String::new(),
+        ccli_number: String::new(),
+        verse_order: String::new(),
+        verses: Vec::new(),
+        language: None,
+        skipping: false,
+        line: String::new(),
+    };
+
+    let mut found_song = false;
+    loop {
+        match reader.read_event().map_err(invalid_xml)? {
+            Event::Start(element) => {
+                found_song |= parser.path.is_empty() && local_name(&element) == "song";
+                parser.open(&element)?;
+            }
+            Event::Empty(element) => {
+                parser.open(&element)?;
+                parser.close();
+            }
+            Event::End(_) => parser.close(),
+            Event::Text(text) => parser.text(&text.unescape().map_err(invalid_xml)?),
+            Event::CData(data) => parser.text(&String::from_utf8_lossy(&data)),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !found_song {
+        return Err(invalid_xml("no <song> element"));
+    }
+    Ok(parser.finish(fallback_title))
+}
+
+// ===== Export =====
+
+/// Verse type letter for a section label, "Other" when it names no known type
+fn verse_type(label: &str) -> char {
+    let name = tag_name(label).to_lowercase();
+    VERSE_TYPES
+        .iter()
+        .find(|(_, section)| section.eq_ignore_ascii_case(&name))
+        .map(|(letter, _)| *letter)
+        .or_else(|| {
+            VERSE_TYPE_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == name)
+                .map(|(_, letter)| *letter)
+        })
+        .unwrap_or('o')
+}
+
+/// The number (and letter) after a section name: "Verse 1b" -> "1b"
+fn verse_number(label: &str) -> Option<String> {
+    let words = tag_name(label).split_whitespace().count();
+    let number = label.split_whitespace().skip(words).collect::<String>();
+    Some(number.to_lowercase()).filter(|n| n.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Lines of a slide's text, top to bottom
+fn slide_lines(slide: &SlideData) -> Vec<String> {
+    let mut texts: Vec<(f64, &str)> = slide
+        .objects
+        .iter()
+        .flatten()
+        .filter_map(|object| match object {
+            SlideObject::Text(text) => Some((text.position.y, text.content.as_str())),
+            _ => None,
+        })
+        .collect();
+    texts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if texts.is_empty() {
+        if let Some(text) = &slide.text {
+            texts.push((0.0, text.content.as_str()));
+        }
+    }
+
+    texts
+        .iter()
+        .flat_map(|(_, content)| content.lines())
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Verses of a slide group and the order they are sung in. Consecutive
+/// slides of one section (or tag group) form a verse; untagged slides are
+/// verses of their own.
+fn group_verses(group: &SlideGroup) -> (Vec<Verse>, Vec<String>) {
+    let mut sections: Vec<(Option<String>, Vec<Vec<String>>)> = Vec::new();
+    for slide in &group.slides {
+        let lines = slide_lines(slide);
+        if lines.is_empty() {
+            continue;
+        }
+        let label = slide
+            .section
+            .clone()
+            .or_else(|| slide.tag_group.as_ref().map(|t| t.name.clone()))
+            .filter(|label| !label.trim().is_empty());
+        match sections.last_mut() {
+            Some((last, slides)) if label.is_some() && *last == label => slides.push(lines),
+            _ => sections.push((label, vec![lines])),
+        }
+    }
+
+    let mut verses: Vec<Verse> = Vec::new();
+    let mut order = Vec::new();
+    for (label, slides) in sections {
+        let letter = label.as_deref().map(verse_type).unwrap_or('v');
+        let is_letter = |name: &str| name.starts_with(letter);
+
+        // A section sung again is written once and repeated in the order
+        if let Some(verse) = verses
+            .iter()
+            .find(|v| is_letter(&v.name) && v.slides == slides)
+        {
+            order.push(verse.name.clone());
+            continue;
+        }
+
+        let taken = |name: &str| verses.iter().any(|v| v.name == name);
+        let preferred = match (&label, label.as_deref().and_then(verse_number)) {
+            (_, Some(number)) => format!("{}{}", letter, number),
+            (Some(_), None) => letter.to_string(),
+            (None, None) => format!("{}1", letter),
+        };
+        let name = if taken(&preferred) {
+            (2..)
+                .map(|n| format!("{}{}", letter, n))
+                .find(|name| !taken(name))
+                .unwrap_or(preferred)
+        } else {
+            preferred
+        };
+        order.push(name.clone());
+        verses.push(Verse { name, slides });
+    }
+    (verses, order)
+}
+
+fn push_element(xml: &mut String, indent: usize, name: &str, text: &str) {
+    xml.push_str(&format!(
+        "{}<{}>{}</{}>\n",
+        "  ".repeat(indent),
+        name,
+        escape(text),
+        name
+    ));
+}
+
+/// A slide group as an OpenLyrics 0.9 document
+pub fn to_xml(group: &SlideGroup) -> String {
+    let (verses, order) = group_verses(group);
+    let credits = group.credits.clone().unwrap_or_default();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<song xmlns=\"{}\" version=\"0.9\" createdIn=\"OS Presenter {}\" modifiedDate=\"{}\">\n",
+        NAMESPACE,
+        env!("CARGO_PKG_VERSION"),
+        escape(group.updated_at.as_str())
+    ));
+
+    xml.push_str("  <properties>\n    <titles>\n");
+    push_element(&mut xml, 3, "title", &group.title);
+    xml.push_str("    </titles>\n");
+    if !credits.authors.is_empty() {
+        xml.push_str("    <authors>\n");
+        for author in &credits.authors {
+            push_element(&mut xml, 3, "author", author);
+        }
+        xml.push_str("    </authors>\n");
+    }
+    if let Some(copyright) = &credits.copyright {
+        push_element(&mut xml, 2, "copyright", copyright);
+    }
+    if let Some(ccli_number) = &credits.ccli_number {
+        push_element(&mut xml, 2, "ccliNo", ccli_number);
+    }
+    if !order.is_empty() {
+        push_element(&mut xml, 2, "verseOrder", &order.join(" "));
+    }
+    xml.push_str("  </properties>\n  <lyrics>\n");
+
+    for verse in &verses {
+        xml.push_str(&format!(
+            "    <verse name=\"{}\">\n",
+            escape(verse.name.as_str())
+        ));
+        for lines in &verse.slides {
+            let lines: Vec<_> = lines.iter().map(|line| escape(line.as_str())).collect();
+            xml.push_str(&format!("      <lines>{}</lines>\n", lines.join("<br/>")));
+        }
+        xml.push_str("    </verse>\n");
+    }
+    xml.push_str("  </lyrics>\n</song>\n");
+    xml
+}
+
+/// Write a slide group to `destination` as an OpenLyrics song
+pub fn export_slide_group(group: &SlideGroup, destination: &Path) -> AppResult<()> {
+    std::fs::write(destination, to_xml(group)).map_err(|e| AppError::io(e, destination))
+}
@@ -0,0 +1,1171 @@
+//! PowerPoint presentations (`.pptx`).
+//!
+//! A presentation is a zip of XML parts tied together by relationship
+//! files. Each slide becomes a slide: text boxes and placeholders become text
+//! objects, rectangles, ellipses and triangles shape objects, pictures and
+//! videos media objects, and the background (the slide's own, its layout's
+//! or its master's) a background color or image. Placeholders take their
+//! position and formatting from the layout and master when they don't set
+//! them. Anything else is reported as unsupported rather than dropped.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use uuid::Uuid;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use super::xml::{self, Element};
+use super::{add_to_library, decode_text, linked_file, media_object, slide_id, text_object};
+use crate::error::{AppError, AppResult};
+use crate::media_import::{self, ImportFailure};
+use crate::models::{
+    CanvasSize, MediaItem, Position, ShapeObject, Size, SlideData, SlideGroup, SlideGroupMeta,
+    SlideObject, TextAlignment, TextObject,
+};
+use crate::repository;
+use crate::storage::{self, DocumentKind};
+
+/// EMUs (English Metric Units) per pixel at 96 pixels per inch
+const EMU_PER_PIXEL: f64 = 9525.0;
+
+/// Font sizes are in points, slide objects in pixels
+const PIXELS_PER_POINT: f64 = 96.0 / 72.0;
+
+/// PowerPoint's font size when nothing sets one, in points
+const DEFAULT_FONT_SIZE: f64 = 18.0;
+
+/// Scheme colors the master's color map points at other scheme colors
+const COLOR_MAP_ALIASES: [(&str, &str); 4] = [
+    ("bg1", "lt1"),
+    ("tx1", "dk1"),
+    ("bg2", "lt2"),
+    ("tx2", "dk2"),
+];
+
+/// Something on a slide that was left out or changed
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedContent {
+    pub source_path: String,
+    pub slide: usize, // Slide number, from 1
+    pub name: String, // As PowerPoint's selection pane shows it, e.g. "Chart 3"
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentationImportSummary {
+    pub slide_groups: Vec<SlideGroup>, // Added to the library, in file order
+    pub failed: Vec<ImportFailure>,
+    pub unsupported: Vec<UnsupportedContent>,
+}
+
+fn invalid_presentation(message: impl Into<String>) -> AppError {
+    AppError::corrupt(message).with_code("INVALID_PPTX")
+}
+
+fn archive_error(err: ZipError) -> AppError {
+    match err {
+        ZipError::Io(err) => AppError::from(err),
+        _ => invalid_presentation(format!("Presentation archive error: {}", err)),
+    }
+}
+
+fn number(element: &Element, name: &str) -> Option<f64> {
+    element.attr(name)?.parse().ok()
+}
+
+fn flag(value: Option<&str>) -> Option<bool> {
+    value.map(|v| v == "1" || v == "true")
+}
+
+/// A part name resolved against the part whose relationship points to it
+fn resolve(base: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = match base.rsplit_once('/') {
+        Some((folder, _)) => folder.split('/').collect(),
+        None => Vec::new(),
+    };
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn parse_hex(value: &str) -> Option<[u8; 3]> {
+    let channel = |i: usize| u8::from_str_radix(value.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn rgba(rgb: [u8; 3], alpha: f64) -> String {
+    format!("rgba({}, {}, {}, {})", rgb[0], rgb[1], rgb[2], alpha)
+}
+
+// ===== Package =====
+
+struct Relationship {
+    id: String,
+    kind: String,   // Last segment of the type, e.g. "slideLayout"
+    target: String, // Part name, or the URL of an external target
+    external: bool,
+}
+
+/// An XML part and where its relationships point
+struct Part {
+    root: Element,
+    relationships: Vec<Relationship>,
+}
+
+impl Part {
+    fn relationship(&self, id: &str) -> Option<&Relationship> {
+        self.relationships.iter().find(|r| r.id == id)
+    }
+
+    fn related(&self, kind: &str) -> Option<&Relationship> {
+        self.relationships.iter().find(|r| r.kind == kind)
+    }
+}
+
+/// The zip package of a presentation, and the media imported from it
+struct Package {
+    archive: ZipArchive<fs::File>,
+    source: PathBuf,
+    staging: PathBuf, // Extracted media waits here to be imported
+    extracted: usize,
+    media: HashMap<String, Result<MediaItem, String>>, // By target
+}
+
+impl Package {
+    fn read(&mut self, name: &str) -> AppResult<Option<String>> {
+        let mut file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(archive_error(e)),
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(Some(decode_text(&bytes)))
+    }
+
+    /// Relationships of a part, or of the package itself for ""
+    fn relationships(&mut self, name: &str) -> AppResult<Vec<Relationship>> {
+        let (folder, file) = name.rsplit_once('/').unwrap_or(("", name));
+        let rels_name = match folder {
+            "" => format!("_rels/{}.rels", file),
+            folder => format!("{}/_rels/{}.rels", folder, file),
+        };
+        let Some(text) = self.read(&rels_name)? else {
+            return Ok(Vec::new());
+        };
+
+        Ok(xml::parse(&text)?
+            .children
+            .iter()
+            .filter(|r| r.name == "Relationship")
+            .map(|r| {
+                let target = r.attr("Target").unwrap_or_default();
+                let external = r.attr("TargetMode") == Some("External");
+                Relationship {
+                    id: r.attr("Id").unwrap_or_default().to_string(),
+                    kind: r
+                        .attr("Type")
+                        .and_then(|t| t.rsplit('/').next())
+                        .unwrap_or_default()
+                        .to_string(),
+                    target: if external {
+                        target.to_string()
+                    } else {
+                        resolve(name, target)
+                    },
+                    external,
+                }
+            })
+            .collect())
+    }
+
+    fn part(&mut self, name: &str) -> AppResult<Part> {
+        let text = self
+            .read(name)?
+            .ok_or_else(|| invalid_presentation(format!("Missing part {}", name)))?;
+        Ok(Part {
+            root: xml::parse(&text)?,
+            relationships: self.relationships(name)?,
+        })
+    }
+
+    /// The part of `kind` that `part` relates to. Presentations missing
+    /// layouts or themes still open in PowerPoint, so neither is an error.
+    fn related_part(&mut self, part: Option<&Part>, kind: &str) -> Option<Part> {
+        let target = part?.related(kind).filter(|r| !r.external)?.target.clone();
+        self.part(&target).ok()
+    }
+
+    /// A file to import: an embedded part extracted to the staging folder,
+    /// or a linked file where it is when that's in the presentation's folder
+    fn stage(&mut self, relationship: &Relationship) -> AppResult<PathBuf> {
+        if relationship.external {
+            return linked_file(&relationship.target, &self.source).ok_or_else(|| {
+                AppError::not_found(format!(
+                    "Linked file {} not found in the presentation's folder",
+                    relationship.target
+                ))
+                .with_code("FILE_NOT_FOUND")
+            });
+        }
+
+        // Each file gets its own folder so equal names don't collide
+        let dir = self.staging.join(self.extracted.to_string());
+        self.extracted += 1;
+        fs::create_dir_all(&dir).map_err(|e| AppError::io(e, &dir))?;
+
+        let name = relationship.target.rsplit('/').next().unwrap_or("media");
+        let path = dir.join(name);
+        let mut source = self
+            .archive
+            .by_name(&relationship.target)
+            .map_err(archive_error)?;
+        let mut output = fs::File::create(&path).map_err(|e| AppError::io(e, &path))?;
+        io::copy(&mut source, &mut output).map_err(|e| AppError::io(e, &path))?;
+        Ok(path)
+    }
+
+    /// Import the file a relationship points to, once per file
+    fn import_media(
+        &mut self,
+        app: &AppHandle,
+        relationship: &Relationship,
+    ) -> Result<MediaItem, String> {
+        if let Some(result) = self.media.get(&relationship.target) {
+            return result.clone();
+        }
+        let result = self
+            .stage(relationship)
+            .and_then(|path| media_import::import_file(app, &path, false))
+            .map(|(item, _)| item)
+            .map_err(|e| e.message);
+        self.media
+            .insert(relationship.target.clone(), result.clone());
+        result
+    }
+}
+
+// ===== Themes and Text Styles =====
+
+/// Colors and fonts that slides refer to by name
+#[derive(Debug, Default)]
+struct Theme {
+    colors: HashMap<String, [u8; 3]>, // Scheme colors, also under their aliases ("tx1")
+    major_font: Option<String>,       // Headings, "+mj-lt"
+    minor_font: Option<String>,       // Body text, "+mn-lt"
+}
+
+impl Theme {
+    fn read(theme: Option<&Part>, master: Option<&Part>) -> Theme {
+        let mut colors = HashMap::new();
+        if let Some(scheme) = theme.and_then(|t| t.root.find("clrScheme")) {
+            for entry in &scheme.children {
+                let rgb = entry
+                    .children
+                    .first()
+                    .and_then(|color| match color.name.as_str() {
+                        "srgbClr" => parse_hex(color.attr("val")?),
+                        "sysClr" => parse_hex(color.attr("lastClr")?),
+                        _ => None,
+                    });
+                if let Some(rgb) = rgb {
+                    colors.insert(entry.name.clone(), rgb);
+                }
+            }
+        }
+
+        let color_map = master.and_then(|m| m.root.find("clrMap"));
+        for (alias, default) in COLOR_MAP_ALIASES {
+            let target = color_map.and_then(|m| m.attr(alias)).unwrap_or(default);
+            if let Some(rgb) = colors.get(target).copied() {
+                colors.insert(alias.to_string(), rgb);
+            }
+        }
+
+        let font = |kind: &str| {
+            let typeface = theme?.root.find(kind)?.child("latin")?.attr("typeface")?;
+            Some(typeface.to_string()).filter(|t| !t.is_empty())
+        };
+        Theme {
+            colors,
+            major_font: font("majorFont"),
+            minor_font: font("minorFont"),
+        }
+    }
+
+    /// The color chosen inside `element`, e.g. a `solidFill`
+    fn color(&self, element: &Element) -> Option<String> {
+        element.children.iter().find_map(|c| self.color_value(c))
+    }
+
+    fn color_value(&self, color: &Element) -> Option<String> {
+        let rgb = match color.name.as_str() {
+            "srgbClr" => parse_hex(color.attr("val")?)?,
+            "sysClr" => parse_hex(color.attr("lastClr")?)?,
+            "schemeClr" => *self.colors.get(color.attr("val")?)?,
+            "prstClr" => match color.attr("val")? {
+                "black" => [0, 0, 0],
+                "white" => [255, 255, 255],
+                "red" => [255, 0, 0],
+                "green" => [0, 128, 0],
+                "blue" => [0, 0, 255],
+                "yellow" => [255, 255, 0],
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        // Modifiers are in thousandths of a percent
+        let modifier = |name: &str| Some(number(color.child(name)?, "val")? / 100_000.0);
+        let lum_mod = modifier("lumMod").unwrap_or(1.0);
+        let lum_off = modifier("lumOff").unwrap_or(0.0);
+        let rgb = rgb.map(|c| {
+            (c as f64 * lum_mod + 255.0 * lum_off)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        });
+        Some(rgba(rgb, modifier("alpha").unwrap_or(1.0)))
+    }
+
+    /// A typeface, resolving the theme's "+mj-lt" and "+mn-lt"
+    fn font(&self, typeface: &str) -> Option<String> {
+        match typeface {
+            "+mj-lt" => self.major_font.clone(),
+            "+mn-lt" => self.minor_font.clone(),
+            typeface if typeface.starts_with('+') || typeface.is_empty() => None,
+            typeface => Some(typeface.to_string()),
+        }
+    }
+
+    /// Formatting of a run (`rPr`) or a level's default run (`defRPr`)
+    fn run_style(&self, properties: &Element) -> TextStyle {
+        TextStyle {
+            size: number(properties, "sz").map(|s| s / 100.0),
+            color: properties.child("solidFill").and_then(|f| self.color(f)),
+            font: properties
+                .child("latin")
+                .and_then(|l| self.font(l.attr("typeface")?)),
+            bold: flag(properties.attr("b")),
+            italic: flag(properties.attr("i")),
+            underline: properties.attr("u").map(|u| u != "none"),
+            ..TextStyle::default()
+        }
+    }
+
+    /// Formatting of a paragraph (`pPr`) or list level (`lvl1pPr`)
+    fn paragraph_style(&self, properties: &Element) -> TextStyle {
+        let run = properties
+            .child("defRPr")
+            .map(|r| self.run_style(r))
+            .unwrap_or_default();
+        let align = properties.attr("algn").map(|a| {
+            match a {
+                "ctr" => "center",
+                "r" => "right",
+                "just" | "dist" => "justify",
+                _ => "left",
+            }
+            .to_string()
+        });
+        TextStyle { align, ..run }
+    }
+
+    /// Formatting a text body sets: its anchor and its first list level
+    fn body_style(&self, body: &Element) -> TextStyle {
+        let anchor = body
+            .child("bodyPr")
+            .and_then(|b| b.attr("anchor"))
+            .map(|a| {
+                match a {
+                    "ctr" => "center",
+                    "b" => "bottom",
+                    _ => "top",
+                }
+                .to_string()
+            });
+        let level = body
+            .child("lstStyle")
+            .and_then(|l| l.child("lvl1pPr"))
+            .map(|p| self.paragraph_style(p))
+            .unwrap_or_default();
+        TextStyle { anchor, ..level }
+    }
+
+    /// Fill of a shape's properties, falling back to its theme style
+    /// reference; the second value says what was not supported
+    fn fill(
+        &self,
+        properties: Option<&Element>,
+        reference: Option<&Element>,
+    ) -> (Option<String>, Option<&'static str>) {
+        for fill in properties
+            .map(|p| p.children.as_slice())
+            .unwrap_or_default()
+        {
+            match fill.name.as_str() {
+                "noFill" => return (None, None),
+                "solidFill" => return (self.color(fill), None),
+                "gradFill" => {
+                    let first = fill.find("gs").and_then(|gs| self.color(gs));
+                    return (
+                        first,
+                        Some("Gradient fills aren't supported, used its first color"),
+                    );
+                }
+                "pattFill" => {
+                    let foreground = fill.child("fgClr").and_then(|c| self.color(c));
+                    return (
+                        foreground,
+                        Some("Pattern fills aren't supported, used its color"),
+                    );
+                }
+                "blipFill" => return (None, Some("Picture fills aren't supported")),
+                _ => {}
+            }
+        }
+        let color = reference
+            .filter(|r| r.attr("idx") != Some("0"))
+            .and_then(|r| self.color(r));
+        (color, None)
+    }
+
+    /// Color and width in pixels of a shape's outline, falling back to its
+    /// theme style reference
+    fn line(
+        &self,
+        properties: Option<&Element>,
+        reference: Option<&Element>,
+    ) -> Option<(String, f64)> {
+        let line = properties.and_then(|p| p.child("ln"));
+        if line.is_some_and(|l| l.child("noFill").is_some()) {
+            return None;
+        }
+        let width = line
+            .and_then(|l| number(l, "w"))
+            .map(|w| w / EMU_PER_PIXEL)
+            .unwrap_or(1.0);
+        let color = line
+            .and_then(|l| l.child("solidFill"))
+            .and_then(|f| self.color(f))
+            .or_else(|| {
+                reference
+                    .filter(|r| r.attr("idx") != Some("0"))
+                    .and_then(|r| self.color(r))
+            })?;
+        Some((color, width))
+    }
+}
+
+/// Text formatting, each field taken from the first level that sets it
+#[derive(Debug, Clone, Default)]
+struct TextStyle {
+    size: Option<f64>, // Points
+    color: Option<String>,
+    font: Option<String>,
+    bold: Option<bool>,
+    italic: Option<bool>,
+    underline: Option<bool>,
+    align: Option<String>,  // "left", "center", "right" or "justify"
+    anchor: Option<String>, // "top", "center" or "bottom"
+}
+
+impl TextStyle {
+    fn or(self, fallback: TextStyle) -> TextStyle {
+        TextStyle {
+            size: self.size.or(fallback.size),
+            color: self.color.or(fallback.color),
+            font: self.font.or(fallback.font),
+            bold: self.bold.or(fallback.bold),
+            italic: self.italic.or(fallback.italic),
+            underline: self.underline.or(fallback.underline),
+            align: self.align.or(fallback.align),
+            anchor: self.anchor.or(fallback.anchor),
+        }
+    }
+}
+
+// ===== Shapes =====
+
+/// Maps shape coordinates (EMUs, or a group's own coordinates) to slide pixels
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    scale_x: f64,
+    scale_y: f64,
+    offset_x: f64,
+    offset_y: f64,
+}
+
+impl Transform {
+    const SLIDE: Transform = Transform {
+        scale_x: 1.0 / EMU_PER_PIXEL,
+        scale_y: 1.0 / EMU_PER_PIXEL,
+        offset_x: 0.0,
+        offset_y: 0.0,
+    };
+
+    /// Position, size and rotation of an `xfrm`
+    fn place(&self, xfrm: &Element) -> Option<(Position, Size, Option<f64>)> {
+        let offset = xfrm.child("off")?;
+        let extent = xfrm.child("ext")?;
+        let position = Position {
+            x: number(offset, "x")? * self.scale_x + self.offset_x,
+            y: number(offset, "y")? * self.scale_y + self.offset_y,
+        };
+        let size = Size {
+            width: number(extent, "cx")? * self.scale_x,
+            height: number(extent, "cy")? * self.scale_y,
+        };
+        // Rotation is in 60,000ths of a degree
+        let rotation = number(xfrm, "rot")
+            .map(|r| r / 60_000.0)
+            .filter(|r| *r != 0.0);
+        Some((position, size, rotation))
+    }
+
+    /// The transform for the shapes of a group with this `xfrm`
+    fn group(&self, xfrm: &Element) -> Transform {
+        let pair = |name: &str, x: &str, y: &str| {
+            let child = xfrm.child(name)?;
+            Some((number(child, x)?, number(child, y)?))
+        };
+        let (Some(offset), Some(extent), Some(child_offset), Some(child_extent)) = (
+            pair("off", "x", "y"),
+            pair("ext", "cx", "cy"),
+            pair("chOff", "x", "y"),
+            pair("chExt", "cx", "cy"),
+        ) else {
+            return *self;
+        };
+        let ratio = |extent: f64, child: f64| if child > 0.0 { extent / child } else { 1.0 };
+        let (ratio_x, ratio_y) = (
+            ratio(extent.0, child_extent.0),
+            ratio(extent.1, child_extent.1),
+        );
+        Transform {
+            scale_x: self.scale_x * ratio_x,
+            scale_y: self.scale_y * ratio_y,
+            offset_x: self.offset_x + self.scale_x * (offset.0 - child_offset.0 * ratio_x),
+            offset_y: self.offset_y + self.scale_y * (offset.1 - child_offset.1 * ratio_y),
+        }
+    }
+}
+
+/// Name of a shape, picture or group as PowerPoint shows it
+fn shape_name(shape: &Element) -> String {
+    shape
+        .find("cNvPr")
+        .and_then(|p| p.attr("name"))
+        .unwrap_or(&shape.name)
+        .to_string()
+}
+
+fn is_hidden(shape: &Element) -> bool {
+    flag(shape.find("cNvPr").and_then(|p| p.attr("hidden"))).unwrap_or(false)
+}
+
+fn placeholder(shape: &Element) -> Option<&Element> {
+    shape.child("nvSpPr")?.child("nvPr")?.child("ph")
+}
+
+/// Placeholder type, with the kinds a master only has one of merged
+fn placeholder_type(placeholder: &Element) -> &str {
+    match placeholder.attr("type") {
+        Some("title" | "ctrTitle") => "title",
+        Some("body" | "subTitle" | "obj") | None => "body",
+        Some(other) => other,
+    }
+}
+
+/// The placeholder on a layout or master a slide's placeholder inherits
+/// from: the one with the same index, or else of the same type
+fn inherited_placeholder<'a>(part: &'a Part, slide_placeholder: &Element) -> Option<&'a Element> {
+    let shapes = part.root.find("spTree")?.find_all("sp");
+    let index = slide_placeholder.attr("idx");
+    let kind = placeholder_type(slide_placeholder);
+    shapes
+        .iter()
+        .find(|s| index.is_some() && placeholder(s).and_then(|p| p.attr("idx")) == index)
+        .or_else(|| {
+            shapes
+                .iter()
+                .find(|s| placeholder(s).map(placeholder_type) == Some(kind))
+        })
+        .copied()
+}
+
+/// Text of a text body, a line per paragraph and line break
+fn text_lines(body: &Element) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in body.children.iter().filter(|c| c.name == "p") {
+        let mut line = String::new();
+        for run in &paragraph.children {
+            match run.name.as_str() {
+                "r" | "fld" => line.push_str(run.child("t").map(|t| t.text.as_str()).unwrap_or("")),
+                "br" => lines.push(std::mem::take(&mut line)),
+                _ => {}
+            }
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Shape types slides can draw for a preset geometry
+fn shape_type(geometry: &str) -> Option<&'static str> {
+    match geometry {
+        "rect" | "roundRect" => Some("rectangle"),
+        "ellipse" => Some("circle"),
+        "triangle" => Some("triangle"),
+        _ => None,
+    }
+}
+
+fn graphic_frame_reason(frame: &Element) -> &'static str {
+    let uri = frame
+        .find("graphicData")
+        .and_then(|d| d.attr("uri"))
+        .unwrap_or_default();
+    if uri.ends_with("/table") {
+        "Tables aren't supported"
+    } else if uri.contains("chart") {
+        "Charts aren't supported"
+    } else if uri.ends_with("/diagram") {
+        "SmartArt isn't supported"
+    } else {
+        "Embedded objects aren't supported"
+    }
+}
+
+// ===== Slides =====
+
+/// Converts one slide, reporting what it leaves out
+struct SlideConverter<'a> {
+    app: &'a AppHandle,
+    package: &'a mut Package,
+    theme: &'a Theme,
+    slide: &'a Part,
+    layout: Option<&'a Part>,
+    master: Option<&'a Part>,
+    default_text: Option<&'a Element>, // The presentation's defaultTextStyle
+    canvas: &'a CanvasSize,
+    source_path: &'a str,
+    number: usize,
+    objects: Vec<SlideObject>,
+    unsupported: &'a mut Vec<UnsupportedContent>,
+}
+
+impl SlideConverter<'_> {
+    fn report(&mut self, name: &str, reason: impl Into<String>) {
+        self.unsupported.push(UnsupportedContent {
+            source_path: self.source_path.to_string(),
+            slide: self.number,
+            name: name.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    fn z_index(&self) -> i32 {
+        self.objects.len() as i32 + 1
+    }
+
+    fn media(&mut self, part: &Part, id: Option<&str>) -> Result<MediaItem, String> {
+        let relationship = id
+            .and_then(|id| part.relationship(id))
+            .ok_or("Its file is missing from the presentation")?;
+        self.package.import_media(self.app, relationship)
+    }
+
+    /// The slide's background color, or else its layout's or master's. A
+    /// background picture is added as a background object instead.
+    fn background(&mut self) -> Option<String> {
+        let theme = self.theme;
+        let parts = [Some(self.slide), self.layout, self.master];
+        for part in parts.into_iter().flatten() {
+            let Some(background) = part.root.find("bg") else {
+                continue;
+            };
+            if let Some(reference) = background.child("bgRef") {
+                return theme.color(reference);
+            }
+            let Some(properties) = background.child("bgPr") else {
+                continue;
+            };
+            if let Some(picture) = properties.child("blipFill") {
+                let id = picture.child("blip").and_then(|b| b.attr("r:embed"));
+                match self.media(part, id) {
+                    Ok(item) => {
+                        let canvas_size = self.canvas_size();
+                        let position = Position { x: 0.0, y: 0.0 };
+                        self.objects
+                            .extend(media_object(&item, position, canvas_size, 0, true));
+                    }
+                    Err(reason) => self.report("Background", reason),
+                }
+                return None;
+            }
+            let (color, problem) = theme.fill(Some(properties), None);
+            if let Some(problem) = problem {
+                self.report("Background", problem);
+            }
+            return color;
+        }
+        // Without one, slides show the theme's first background color
+        theme.colors.get("bg1").map(|rgb| rgba(*rgb, 1.0))
+    }
+
+    fn canvas_size(&self) -> Size {
+        Size {
+            width: self.canvas.width as f64,
+            height: self.canvas.height as f64,
+        }
+    }
+
+    fn shapes(&mut self, tree: &Element, transform: Transform) {
+        for shape in &tree.children {
+            if is_hidden(shape) {
+                continue;
+            }
+            match shape.name.as_str() {
+                "sp" => self.shape(shape, transform),
+                "pic" => self.picture(shape, transform),
+                "grpSp" => {
+                    let group = shape
+                        .child("grpSpPr")
+                        .and_then(|p| p.child("xfrm"))
+                        .map(|xfrm| transform.group(xfrm))
+                        .unwrap_or(transform);
+                    self.shapes(shape, group);
+                }
+                // Newer content with a fallback for older readers
+                "AlternateContent" => {
+                    if let Some(fallback) = shape.child("Fallback") {
+                        self.shapes(fallback, transform);
+                    }
+                }
+                "graphicFrame" => self.report(&shape_name(shape), graphic_frame_reason(shape)),
+                "cxnSp" => self.report(&shape_name(shape), "Connector lines aren't supported"),
+                "contentPart" => self.report(&shape_name(shape), "Ink isn't supported"),
+                "nvGrpSpPr" | "grpSpPr" | "extLst" => {}
+                other => self.report(&shape_name(shape), format!("{} isn't supported", other)),
+            }
+        }
+    }
+
+    /// Formatting of a shape's text: its first run and paragraph, its own
+    /// text body, the placeholders it inherits from, and the defaults
+    fn text_style(
+        &self,
+        shape: &Element,
+        placeholder: Option<&Element>,
+        inherited: &[&Element],
+    ) -> TextStyle {
+        let theme = self.theme;
+        let body = shape.child("txBody");
+        let mut style = body
+            .and_then(|b| b.find("rPr"))
+            .map(|r| theme.run_style(r))
+            .unwrap_or_default();
+        if let Some(paragraph) = body.and_then(|b| b.find("pPr")) {
+            style = style.or(theme.paragraph_style(paragraph));
+        }
+        for body in body
+            .into_iter()
+            .chain(inherited.iter().filter_map(|s| s.child("txBody")))
+        {
+            style = style.or(theme.body_style(body));
+        }
+        if let Some(reference) = shape.child("style").and_then(|s| s.child("fontRef")) {
+            style = style.or(TextStyle {
+                color: theme.color(reference),
+                font: match reference.attr("idx") {
+                    Some("major") => theme.major_font.clone(),
+                    _ => theme.minor_font.clone(),
+                },
+                ..TextStyle::default()
+            });
+        }
+
+        let level = match placeholder {
+            Some(placeholder) => {
+                let kind = match placeholder_type(placeholder) {
+                    "title" => "titleStyle",
+                    _ => "bodyStyle",
+                };
+                self.master
+                    .and_then(|m| m.root.find("txStyles"))
+                    .and_then(|s| s.child(kind))
+                    .and_then(|s| s.child("lvl1pPr"))
+            }
+            None => self.default_text.and_then(|d| d.child("lvl1pPr")),
+        };
+        if let Some(level) = level {
+            style = style.or(theme.paragraph_style(level));
+        }
+        style.or(TextStyle {
+            size: Some(DEFAULT_FONT_SIZE),
+            color: theme.colors.get("tx1").map(|rgb| rgba(*rgb, 1.0)),
+            font: theme.minor_font.clone(),
+            align: Some("left".to_string()),
+            anchor: Some("top".to_string()),
+            ..TextStyle::default()
+        })
+    }
+
+    fn shape(&mut self, shape: &Element, transform: Transform) {
+        let name = shape_name(shape);
+        let theme = self.theme;
+        let (layout, master) = (self.layout, self.master);
+        let placeholder = placeholder(shape);
+        let inherited: Vec<&Element> = placeholder
+            .map(|p| {
+                [layout, master]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|part| inherited_placeholder(part, p))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let properties = shape.child("spPr");
+        let xfrm = properties.and_then(|p| p.child("xfrm")).or_else(|| {
+            inherited
+                .iter()
+                .find_map(|s| s.child("spPr")?.child("xfrm"))
+        });
+        let Some((position, size, rotation)) = xfrm.and_then(|x| transform.place(x)) else {
+            self.report(&name, "Has no position on the slide");
+            return;
+        };
+
+        let body = shape.child("txBody");
+        let lines = body.map(text_lines).unwrap_or_default();
+        let style = self.text_style(shape, placeholder, &inherited);
+        // Text shrunk to fit keeps its scale
+        let font_scale = body
+            .and_then(|b| b.child("bodyPr")?.child("normAutofit"))
+            .and_then(|a| number(a, "fontScale"))
+            .map(|s| s / 100_000.0)
+            .unwrap_or(1.0);
+        let font_size = (style.size.unwrap_or(DEFAULT_FONT_SIZE) * font_scale * PIXELS_PER_POINT)
+            .round()
+            .max(1.0);
+        let alignment = TextAlignment {
+            horizontal: style.align.clone().unwrap_or_else(|| "left".to_string()),
+            vertical: style.anchor.clone().unwrap_or_else(|| "top".to_string()),
+        };
+
+        let references = shape.child("style");
+        let (fill, problem) = theme.fill(properties, references.and_then(|r| r.child("fillRef")));
+        if let Some(problem) = problem {
+            self.report(&name, problem);
+        }
+        let line = theme.line(properties, references.and_then(|r| r.child("lnRef")));
+
+        if fill.is_some() || line.is_some() {
+            let geometry = match properties.and_then(|p| p.child("prstGeom")) {
+                Some(geometry) => geometry.attr("prst").unwrap_or("rect"),
+                None if properties.and_then(|p| p.child("custGeom")).is_some() => "custom",
+                None => "rect",
+            };
+            if let Some(shape_type) = shape_type(geometry) {
+                let has_text = !lines.is_empty();
+                let (stroke_color, stroke_width) = line.unzip();
+                self.objects.push(SlideObject::Shape(ShapeObject {
+                    id: Uuid::new_v4().to_string(),
+                    position,
+                    size,
+                    rotation,
+                    scale_x: Some(1.0),
+                    scale_y: Some(1.0),
+                    z_index: self.z_index(),
+                    is_locked: None,
+                    shape_type: shape_type.to_string(),
+                    fill_color: fill.unwrap_or_else(|| "rgba(0, 0, 0, 0)".to_string()),
+                    stroke_color,
+                    stroke_width,
+                    content: has_text.then(|| lines.join("\n")),
+                    font_size: has_text.then_some(font_size),
+                    color: style.color.clone().filter(|_| has_text),
+                    alignment: has_text.then_some(alignment),
+                    font_family: style.font.clone().filter(|_| has_text),
+                    font_weight: None,
+                    bold: style.bold.filter(|b| *b && has_text),
+                    font_style: style
+                        .italic
+                        .filter(|i| *i && has_text)
+                        .map(|_| "italic".to_string()),
+                    underline: style.underline.filter(|u| *u && has_text),
+                    text_transform: None,
+                    text_stroke_color: None,
+                    text_stroke_width: None,
+                    effect: None,
+                    text_shadow: None,
+                }));
+                return;
+            }
+            let kept = if lines.is_empty() {
+                ""
+            } else {
+                ", kept its text"
+            };
+            self.report(
+                &name,
+                format!("{} shapes aren't supported{}", geometry, kept),
+            );
+        }
+        if lines.is_empty() {
+            return;
+        }
+
+        let mut object: TextObject = text_object(self.canvas, &lines);
+        object.position = position;
+        object.size = size;
+        object.rotation = rotation;
+        object.z_index = self.z_index();
+        object.font_size = font_size;
+        if let Some(color) = style.color {
+            object.color = color;
+        }
+        object.alignment = alignment;
+        if let Some(font) = style.font {
+            object.font_family = Some(font);
+        }
+        object.bold = style.bold.filter(|b| *b);
+        if style.italic == Some(true) {
+            object.font_style = Some("italic".to_string());
+        }
+        object.underline = style.underline.filter(|u| *u);
+        self.objects.push(SlideObject::Text(object));
+    }
+
+    fn picture(&mut self, picture: &Element, transform: Transform) {
+        let name = shape_name(picture);
+        let slide = self.slide;
+        let placed = picture
+            .child("spPr")
+            .and_then(|p| p.child("xfrm"))
+            .and_then(|x| transform.place(x));
+        let Some((position, size, rotation)) = placed else {
+            self.report(&name, "Has no position on the slide");
+            return;
+        };
+
+        let media = picture.child("nvPicPr").and_then(|p| p.child("nvPr"));
+        if media.and_then(|m| m.find("audioFile")).is_some() {
+            self.report(&name, "Audio isn't supported");
+            return;
+        }
+        // Videos are pictures (their poster frame) with a media reference
+        let file = match media.and_then(|m| m.find("media").or_else(|| m.find("videoFile"))) {
+            Some(video) => video.attr("r:embed").or_else(|| video.attr("r:link")),
+            None => picture
+                .find("blip")
+                .and_then(|b| b.attr("r:embed").or_else(|| b.attr("r:link"))),
+        };
+
+        let item = match self.media(slide, file) {
+            Ok(item) => item,
+            Err(reason) => return self.report(&name, reason),
+        };
+        match media_object(&item, position, size, self.z_index(), false) {
+            Some(mut object) => {
+                match &mut object {
+                    SlideObject::Image(image) => {
+                        image.rotation = rotation;
+                        image.object_fit = Some("fill".to_string());
+                    }
+                    SlideObject::Video(video) => video.rotation = rotation,
+                    _ => {}
+                }
+                self.objects.push(object);
+            }
+            None => self.report(&name, "Audio isn't supported"),
+        }
+    }
+}
+
+// ===== Importing =====
+
+/// Convert the slides of a presentation, in presentation order
+fn convert_slides(
+    app: &AppHandle,
+    package: &mut Package,
+    library_id: &str,
+    source_path: &str,
+    unsupported: &mut Vec<UnsupportedContent>,
+) -> AppResult<(CanvasSize, Vec<SlideData>)> {
+    let document = package
+        .relationships("")?
+        .into_iter()
+        .find(|r| r.kind == "officeDocument")
+        .map(|r| r.target)
+        .unwrap_or_else(|| "ppt/presentation.xml".to_string());
+    if package.read(&document)?.is_none() {
+        return Err(invalid_presentation("Not a PowerPoint presentation"));
+    }
+    let presentation = package.part(&document)?;
+
+    let slide_size = presentation
+        .root
+        .find("sldSz")
+        .ok_or_else(|| invalid_presentation("The presentation has no slide size"))?;
+    let dimension = |name: &str| {
+        number(slide_size, name)
+            .filter(|v| *v > 0.0)
+            .map(|v| (v / EMU_PER_PIXEL).round() as i32)
+            .ok_or_else(|| invalid_presentation("The presentation has no slide size"))
+    };
+    let canvas = CanvasSize {
+        width: dimension("cx")?,
+        height: dimension("cy")?,
+    };
+    let default_text = presentation.root.find("defaultTextStyle");
+
+    let slide_ids = presentation
+        .root
+        .find("sldIdLst")
+        .map(|l| l.children.as_slice())
+        .unwrap_or_default();
+    let mut slides = Vec::new();
+    for (index, entry) in slide_ids.iter().enumerate() {
+        let Some(relationship) = entry
+            .attr("r:id")
+            .and_then(|id| presentation.relationship(id))
+        else {
+            continue;
+        };
+        let slide = package.part(&relationship.target.clone())?;
+        let layout = package.related_part(Some(&slide), "slideLayout");
+        let master = package.related_part(layout.as_ref(), "slideMaster");
+        let theme_part = package.related_part(master.as_ref(), "theme");
+        let theme = Theme::read(theme_part.as_ref(), master.as_ref());
+
+        let mut converter = SlideConverter {
+            app,
+            package,
+            theme: &theme,
+            slide: &slide,
+            layout: layout.as_ref(),
+            master: master.as_ref(),
+            default_text,
+            source_path,
+            number: index + 1,
+            canvas: &canvas,
+            objects: Vec::new(),
+            unsupported,
+        };
+        let background_color = converter.background();
+        if let Some(tree) = slide.root.find("spTree") {
+            converter.shapes(tree, Transform::SLIDE);
+        }
+
+        slides.push(SlideData {
+            id: slide_id(library_id),
+            objects: Some(converter.objects),
+            background_color,
+            tag_group: None,
+            text: None,
+            background: None,
+            audio_cue: None,
+            section: None,
+        });
+    }
+    Ok((canvas, slides))
+}
+
+/// Convert a presentation into a slide group for `library_id`
+fn import_presentation(
+    app: &AppHandle,
+    path: &Path,
+    library_id: &str,
+    unsupported: &mut Vec<UnsupportedContent>,
+) -> AppResult<SlideGroup> {
+    let file = fs::File::open(path).map_err(|e| AppError::io(e, path))?;
+    let archive = ZipArchive::new(file).map_err(|e| archive_error(e).with_path(path))?;
+    let mut package = Package {
+        archive,
+        source: path.to_path_buf(),
+        staging: std::env::temp_dir().join(format!("pptx-import-{}", Uuid::new_v4())),
+        extracted: 0,
+        media: HashMap::new(),
+    };
+
+    let source_path = path.to_string_lossy().into_owned();
+    let converted = convert_slides(app, &mut package, library_id, &source_path, unsupported);
+    if package.extracted > 0 {
+        if let Err(e) = fs::remove_dir_all(&package.staging) {
+            eprintln!("Failed to remove {:?}: {}", package.staging, e);
+        }
+    }
+    let (canvas, slides) = converted.map_err(|e| e.with_path(path))?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    Ok(SlideGroup {
+        id: Uuid::new_v4().to_string(),
+        meta: Some(SlideGroupMeta {
+            playlist_id: None,
+            origin_library_id: None,
+            origin_slide_group_id: None,
+            library_id: Some(library_id.to_string()),
+        }),
+        title: storage::get_file_stem(&path.to_path_buf()).unwrap_or_default(),
+        slides,
+        canvas_size: canvas,
+        credits: None,
+        created_at: now.clone(),
+        updated_at: now,
+    })
+}
+
+/// Import PowerPoint presentations into a library, one slide group each,
+/// sized like the presentation's slides. Files that fail are reported and
+/// skipped, as is content slides can't show; every window is told about the
+/// updated library.
+pub fn import_presentations(
+    app: &AppHandle,
+    library_id: &str,
+    paths: &[PathBuf],
+) -> AppResult<PresentationImportSummary> {
+    let repo = repository::current(app);
+    // Checked before anything is imported; the library is read again to save
+    repo.get_library(library_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::Library, library_id))?;
+
+    let mut summary = PresentationImportSummary {
+        slide_groups: Vec::new(),
+        failed: Vec::new(),
+        unsupported: Vec::new(),
+    };
+    for path in paths {
+        match import_presentation(app, path, library_id, &mut summary.unsupported) {
+            Ok(slide_group) => summary.slide_groups.push(slide_group),
+            Err(e) => summary.failed.push(ImportFailure {
+                source_path: path.to_string_lossy().into_owned(),
+                error: e.message,
+            }),
+        }
+    }
+    if !summary.slide_groups.is_empty() {
+        add_to_library(app, library_id, &summary.slide_groups, Vec::new())?;
+    }
+    Ok(summary)
+}
@@ -0,0 +1,464 @@
+//! ProPresenter 6 documents (`.pro6`).
+//!
+//! A document is XML: slide groups (named, colored and holding slides), the
+//! arrangements they are sung in, and per slide a background media cue and
+//! display elements. Text elements keep their text as base64 RTF, decoded
+//! with [`rtf`](super::rtf). Media is imported into the media library from
+//! where the document points, or else from next to the document.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use super::xml::{self, Element};
+use super::{
+    decode_text, linked_file, media_object, rtf, slide_id, tag_group_for, tag_name, text_object,
+};
+use crate::error::{AppError, AppResult};
+use crate::media_import::{self, ImportFailure};
+use crate::models::{
+    CanvasSize, MediaItem, Position, Size, SlideData, SlideGroup, SlideGroupMeta, SlideObject,
+    SlideTagGroup, SongCredits, TextObject,
+};
+use crate::storage;
+
+/// Media already imported (or found missing) by source, shared by every
+/// document of an import so each file is only imported once
+pub type MediaCache = HashMap<String, Option<MediaItem>>;
+
+pub fn is_pro6(extension: &str) -> bool {
+    extension.eq_ignore_ascii_case("pro6")
+}
+
+fn invalid_document(message: impl Into<String>) -> AppError {
+    AppError::corrupt(message).with_code("INVALID_PRO6")
+}
+
+/// Child named by `rvXMLIvarName`, the instance variable it was saved from
+fn ivar<'a>(element: &'a Element, name: &str) -> Option<&'a Element> {
+    element
+        .children
+        .iter()
+        .find(|c| c.attr("rvXMLIvarName") == Some(name))
+}
+
+/// Text of a base64 `NSString` child
+fn base64_text(element: &Element, name: &str) -> Option<String> {
+    let encoded: String = ivar(element, name)?
+        .text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let bytes = BASE64.decode(encoded).ok()?;
+    Some(decode_text(&bytes))
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+}
+
+fn is_true(value: Option<&str>) -> bool {
+    matches!(value, Some("true" | "1"))
+}
+
+// ===== Values =====
+
+/// Channels of a "r g b a" color, each 0 to 1
+fn parse_color(value: &str) -> Option<[f64; 4]> {
+    let channels: Vec<f64> = value
+        .split_whitespace()
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    match channels[..] {
+        [r, g, b, a] => Some([r, g, b, a]),
+        [r, g, b] => Some([r, g, b, 1.0]),
+        _ => None,
+    }
+}
+
+fn channel(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn rgba(color: [f64; 4]) -> String {
+    format!(
+        "rgba({}, {}, {}, {})",
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2]),
+        color[3].clamp(0.0, 1.0)
+    )
+}
+
+fn hex(color: [f64; 4]) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2])
+    )
+}
+
+/// Position and size of a "{x y z width height}" rectangle
+fn parse_rect(value: &str) -> Option<(Position, Size)> {
+    let values: Vec<f64> = value
+        .trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace())
+        .split_whitespace()
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    match values[..] {
+        [x, y, _, width, height] => Some((Position { x, y }, Size { width, height })),
+        _ => None,
+    }
+}
+
+/// Family of a PostScript font name and whether it is bold, e.g.
+/// "Arial-BoldMT" is bold "Arial" and "HelveticaNeue" is "Helvetica Neue"
+fn font_family(name: &str) -> (String, bool) {
+    let (family, style) = name.split_once('-').unwrap_or((name, ""));
+    let family = family.trim_end_matches("PSMT").trim_end_matches("MT");
+    let bold = ["Bold", "Black", "Heavy"].iter().any(|s| style.contains(s));
+    if family.contains(' ') {
+        return (family.to_string(), bold);
+    }
+
+    let mut spaced = String::new();
+    let mut previous = None;
+    for c in family.chars() {
+        if c.is_uppercase() && previous.is_some_and(char::is_lowercase) {
+            spaced.push(' ');
+        }
+        spaced.push(c);
+        previous = Some(c);
+    }
+    (spaced, bold)
+}
+
+// ===== Elements =====
+
+/// A text element as a text object. Elements without text are left out.
+fn text_element(element: &Element, canvas: &CanvasSize, z_index: i32) -> Option<TextObject> {
+    let rtf = base64_text(element, "RTFData")
+        .map(|data| rtf::parse(&data))
+        .filter(|rtf| !rtf.text.trim().is_empty())
+        .or_else(|| {
+            let text = base64_text(element, "PlainText")?;
+            Some(rtf::RtfText {
+                text: text.trim().to_string(),
+                ..rtf::RtfText::default()
+            })
+        })
+        .filter(|rtf| !rtf.text.is_empty())?;
+
+    let lines: Vec<String> = rtf.text.lines().map(String::from).collect();
+    let mut object = text_object(canvas, &lines);
+    object.z_index = z_index;
+    if let Some((position, size)) = element.find("RVRect3D").and_then(|r| parse_rect(&r.text)) {
+        object.position = position;
+        object.size = size;
+    }
+    object.rotation = element
+        .attr("rotation")
+        .and_then(|r| r.parse().ok())
+        .filter(|r: &f64| *r != 0.0);
+    if let Some(font_size) = rtf.font_size {
+        object.font_size = font_size;
+    }
+    if let Some(color) = rtf.color {
+        object.color = color;
+    }
+    if let Some(alignment) = rtf.alignment {
+        object.alignment.horizontal = alignment;
+    }
+    object.alignment.vertical = match element.attr("verticalAlignment") {
+        Some("0") => "top",
+        Some("2") => "bottom",
+        _ => "center",
+    }
+    .to_string();
+    if let Some(name) = rtf.font_family {
+        let (family, bold) = font_family(&name);
+        object.font_family = Some(family);
+        if bold || rtf.bold {
+            object.bold = Some(true);
+        }
+    }
+    if rtf.italic {
+        object.font_style = Some("italic".to_string());
+    }
+    if is_true(element.attr("drawingFill")) {
+        object.background_color = element.attr("fillColor").and_then(parse_color).map(rgba);
+    }
+    Some(object)
+}
+
+/// Everything one document import needs to read and write
+struct DocumentImport<'a> {
+    app: &'a AppHandle,
+    path: &'a Path,
+    title: String,
+    canvas: CanvasSize,
+    media: &'a mut MediaCache,
+    failed: &'a mut Vec<ImportFailure>,
+}
+
+impl DocumentImport<'_> {
+    /// Import the file of an image or video element, once per source
+    fn media_item(&mut self, element: &Element) -> Option<MediaItem> {
+        let source = non_empty(element.attr("source"))?;
+        if let Some(item) = self.media.get(&source) {
+            return item.clone();
+        }
+
+        let item = match linked_file(&source, self.path) {
+            Some(path) => match media_import::import_file(self.app, &path, false) {
+                Ok((item, _)) => Some(item),
+                Err(e) => {
+                    self.failed.push(ImportFailure {
+                        source_path: path.to_string_lossy().into_owned(),
+                        error: e.message,
+                    });
+                    None
+                }
+            },
+            None => {
+                self.failed.push(ImportFailure {
+                    source_path: source.clone(),
+                    error: format!(
+                        "Media file not found in the document's folder (used by {})",
+                        self.title
+                    ),
+                });
+                None
+            }
+        };
+        self.media.insert(source, item.clone());
+        item
+    }
+
+    /// An image or video element as an object, filling the canvas when it is
+    /// the slide's background
+    fn media_element(
+        &mut self,
+        element: &Element,
+        z_index: i32,
+        background: bool,
+    ) -> Option<SlideObject> {
+        let item = self.media_item(element)?;
+        let (position, size) = element
+            .find("RVRect3D")
+            .and_then(|r| parse_rect(&r.text))
+            .filter(|_| !background)
+            .unwrap_or((
+                Position { x: 0.0, y: 0.0 },
+                Size {
+                    width: self.canvas.width as f64,
+                    height: self.canvas.height as f64,
+                },
+            ));
+        media_object(&item, position, size, z_index, background)
+    }
+
+    /// Objects of a slide: its background media, then its display elements
+    /// in stacking order
+    fn slide_objects(&mut self, slide: &Element) -> Vec<SlideObject> {
+        let mut objects = Vec::new();
+        let background = ivar(slide, "backgroundMediaCue").and_then(|cue| {
+            if is_media_element(cue) {
+                Some(cue)
+            } else {
+                cue.children.iter().find(|c| is_media_element(c))
+            }
+        });
+        if let Some(object) = background.and_then(|e| self.media_element(e, 0, true)) {
+            objects.push(object);
+        }
+
+        let elements = ivar(slide, "displayElements")
+            .map(|array| array.children.as_slice())
+            .unwrap_or_default();
+        for element in elements {
+            let z_index = objects.len() as i32 + 1;
+            let object = match element.name.as_str() {
+                "RVTextElement" => {
+                    text_element(element, &self.canvas, z_index).map(SlideObject::Text)
+                }
+                _ if is_media_element(element) => self.media_element(element, z_index, false),
+                _ => None,
+            };
+            objects.extend(object);
+        }
+        objects
+    }
+}
+
+fn is_media_element(element: &Element) -> bool {
+    matches!(element.name.as_str(), "RVImageElement" | "RVVideoElement")
+}
+
+// ===== Documents =====
+
+/// Credits from the document's CCLI fields
+fn credits(root: &Element) -> SongCredits {
+    let authors = ["CCLIAuthor", "CCLIArtistCredits"]
+        .iter()
+        .filter_map(|name| non_empty(root.attr(name)))
+        .fold(Vec::new(), |mut authors, author| {
+            if !authors.contains(&author) {
+                authors.push(author);
+            }
+            authors
+        });
+    let copyright = [
+        non_empty(root.attr("CCLICopyrightYear")),
+        non_empty(root.attr("CCLIPublisher")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+    SongCredits {
+        authors,
+        copyright: Some(copyright).filter(|c| !c.is_empty()),
+        ccli_number: non_empty(root.attr("CCLISongNumber")),
+    }
+}
+
+/// Slide groups in the order of the selected arrangement, which may repeat
+/// them, or else as they appear in the document
+fn arranged_groups(root: &Element) -> Vec<&Element> {
+    let groups = root.find_all("RVSlideGrouping");
+    let arrangement = non_empty(root.attr("selectedArrangementID")).and_then(|id| {
+        root.find_all("RVSongArrangement")
+            .into_iter()
+            .find(|a| a.attr("uuid") == Some(id.as_str()))
+    });
+    let Some(arrangement) = arrangement else {
+        return groups;
+    };
+
+    let arranged: Vec<&Element> = arrangement
+        .find_all("NSString")
+        .into_iter()
+        .filter_map(|id| {
+            groups
+                .iter()
+                .find(|g| g.attr("uuid") == Some(id.text.trim()))
+                .copied()
+        })
+        .collect();
+    if arranged.is_empty() {
+        groups
+    } else {
+        arranged
+    }
+}
+
+/// Import a ProPresenter 6 document as a slide group for `library_id`. Slide
+/// groups become tag groups in their colors; media that can't be found or
+/// imported is reported in `failed` and left out.
+pub fn import_document(
+    app: &AppHandle,
+    path: &Path,
+    library_id: &str,
+    tag_groups: &mut Vec<SlideTagGroup>,
+    created_tags: &mut bool,
+    media: &mut MediaCache,
+    failed: &mut Vec<ImportFailure>,
+) -> AppResult<SlideGroup> {
+    let bytes = std::fs::read(path).map_err(|e| AppError::io(e, path))?;
+    let root = xml::parse(&decode_text(&bytes)).map_err(|e| e.with_path(path))?;
+    if root.name != "RVPresentationDocument" {
+        return Err(invalid_document("Not a ProPresenter 6 document").with_path(path));
+    }
+
+    let dimension = |name: &str| {
+        root.attr(name)
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .map(|v| v.round() as i32)
+    };
+    let canvas = match (dimension("width"), dimension("height")) {
+        (Some(width), Some(height)) => CanvasSize { width, height },
+        _ => super::default_canvas(),
+    };
+    let title = non_empty(root.attr("CCLISongTitle"))
+        .or_else(|| storage::get_file_stem(&path.to_path_buf()))
+        .unwrap_or_default();
+    let document_background = is_true(root.attr("drawingBackgroundColor"))
+        .then(|| root.attr("backgroundColor").and_then(parse_color))
+        .flatten()
+        .map(rgba);
+
+    let mut import = DocumentImport {
+        app,
+        path,
+        title: title.clone(),
+        canvas: canvas.clone(),
+        media,
+        failed,
+    };
+    let mut slides = Vec::new();
+    for group in arranged_groups(&root) {
+        let group_name = non_empty(group.attr("name"));
+        let group_color = group.attr("color").and_then(parse_color).map(hex);
+
+        for slide in group.find_all("RVDisplaySlide") {
+            // Unnamed groups fall back to the slide's own label and color
+            let (label, color) = match &group_name {
+                Some(name) => (Some(name.clone()), group_color.clone()),
+                None => (
+                    non_empty(slide.attr("label")),
+                    slide.attr("highlightColor").and_then(parse_color).map(hex),
+                ),
+            };
+            let tag_group = label
+                .as_deref()
+                .filter(|label| !tag_name(label).is_empty())
+                .map(|label| tag_group_for(label, color.as_deref(), tag_groups, created_tags));
+            let background_color = if is_true(slide.attr("drawingBackgroundColor")) {
+                slide
+                    .attr("backgroundColor")
+                    .and_then(parse_color)
+                    .map(rgba)
+            } else {
+                document_background.clone()
+            };
+
+            slides.push(SlideData {
+                id: slide_id(library_id),
+                objects: Some(import.slide_objects(slide)),
+                background_color,
+                tag_group,
+                text: None,
+                background: None,
+                audio_cue: None,
+                section: label,
+            });
+        }
+    }
+
+    let credits = credits(&root);
+    let now = chrono::Utc::now().to_rfc3339();
+    Ok(SlideGroup {
+        id: Uuid::new_v4().to_string(),
+        meta: Some(SlideGroupMeta {
+            playlist_id: None,
+            origin_library_id: None,
+            origin_slide_group_id: None,
+            library_id: Some(library_id.to_string()),
+        }),
+        title,
+        slides,
+        canvas_size: canvas,
+        credits: Some(credits).filter(|c| !c.is_empty()),
+        created_at: now.clone(),
+        updated_at: now,
+    })
+}
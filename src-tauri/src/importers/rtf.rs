@@ -0,0 +1,291 @@
+//! Just enough RTF for the text elements of ProPresenter documents: the
+//! plain text, and the font, size, color and alignment it starts with.
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Text of an RTF document and the formatting of its first visible character
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RtfText {
+    pub text: String,
+    pub font_family: Option<String>, // As named in the font table
+    pub font_size: Option<f64>,      // Points
+    pub color: Option<String>,       // "rgba(r, g, b, 1)"
+    pub alignment: Option<String>,   // "left", "center", "right" or "justify"
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// Destinations whose content is not text
+const SKIPPED_DESTINATIONS: [&str; 9] = [
+    "stylesheet",
+    "info",
+    "pict",
+    "header",
+    "footer",
+    "listtable",
+    "listoverridetable",
+    "generator",
+    "expandedcolortbl",
+];
+
+/// Windows-1252 characters in 0x80..0xA0, which RTF escapes as `\'hh`
+const CP1252_SPECIALS: [(u8, char); 12] = [
+    (0x80, '€'),
+    (0x85, '…'),
+    (0x91, '‘'),
+    (0x92, '’'),
+    (0x93, '“'),
+    (0x94, '”'),
+    (0x95, '•'),
+    (0x96, '–'),
+    (0x97, '—'),
+    (0x99, '™'),
+    (0x8A, 'Š'),
+    (0x9A, 'š'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Destination {
+    Text,
+    FontTable,
+    ColorTable,
+    Skipped,
+}
+
+/// Formatting in effect, saved at `{` and restored at `}`
+#[derive(Debug, Clone, Copy)]
+struct State {
+    destination: Destination,
+    font: Option<i32>,
+    font_size: Option<f64>,
+    color: Option<usize>,
+    alignment: Option<&'static str>,
+    bold: bool,
+    italic: bool,
+    unicode_skip: usize, // Fallback characters written after each `\u`
+}
+
+struct RtfParser {
+    state: State,
+    stack: Vec<State>,
+    fonts: HashMap<i32, String>,
+    table_font: Option<i32>, // Font table entry being read
+    font_name: String,
+    colors: Vec<Option<(u8, u8, u8)>>,
+    rgb: [Option<u8>; 3], // Color table entry being read
+    skip: usize,          // Fallback characters left to skip
+    result: RtfText,
+    started: bool, // A visible character was written
+}
+
+fn cp1252(byte: u8) -> char {
+    CP1252_SPECIALS
+        .iter()
+        .find(|(b, _)| *b == byte)
+        .map(|(_, c)| *c)
+        .unwrap_or(byte as char)
+}
+
+impl RtfParser {
+    fn character(&mut self, c: char) {
+        match self.state.destination {
+            Destination::Text => {
+                if self.skip > 0 {
+                    self.skip -= 1;
+                    return;
+                }
+                if !self.started && !c.is_whitespace() {
+                    self.capture();
+                }
+                self.result.text.push(c);
+            }
+            Destination::FontTable if c == ';' => {
+                if let Some(font) = self.table_font.take() {
+                    let name = std::mem::take(&mut self.font_name);
+                    self.fonts.insert(font, name.trim().to_string());
+                }
+            }
+            Destination::FontTable => self.font_name.push(c),
+            Destination::ColorTable if c == ';' => {
+                let [red, green, blue] = std::mem::take(&mut self.rgb);
+                let defined = red.is_some() || green.is_some() || blue.is_some();
+                self.colors.push(defined.then(|| {
+                    (
+                        red.unwrap_or_default(),
+                        green.unwrap_or_default(),
+                        blue.unwrap_or_default(),
+                    )
+                }));
+            }
+            Destination::ColorTable | Destination::Skipped => {}
+        }
+    }
+
+    /// Keep the formatting of the first visible character
+    fn capture(&mut self) {
+        self.started = true;
+        let state = self.state;
+        self.result.font_family = state.font.and_then(|f| self.fonts.get(&f)).cloned();
+        self.result.font_size = state.font_size;
+        self.result.color = state
+            .color
+            .and_then(|i| self.colors.get(i).copied().flatten())
+            .map(|(r, g, b)| format!("rgba({}, {}, {}, 1)", r, g, b));
+        self.result.alignment = state.alignment.map(String::from);
+        self.result.bold = state.bold;
+        self.result.italic = state.italic;
+    }
+
+    fn control_word(&mut self, word: &str, param: Option<i32>) {
+        match word {
+            "fonttbl" => self.state.destination = Destination::FontTable,
+            "colortbl" => self.state.destination = Destination::ColorTable,
+            _ if SKIPPED_DESTINATIONS.contains(&word) => {
+                self.state.destination = Destination::Skipped
+            }
+            _ => {}
+        }
+        let value = |default: i32| param.unwrap_or(default);
+        match self.state.destination {
+            Destination::FontTable if word == "f" => self.table_font = param,
+            Destination::ColorTable => {
+                let channel = match word {
+                    "red" => 0,
+                    "green" => 1,
+                    "blue" => 2,
+                    _ => return,
+                };
+                self.rgb[channel] = Some(value(0).clamp(0, 255) as u8);
+            }
+            Destination::Text => match word {
+                "par" | "line" => self.character('\n'),
+                "tab" => self.character('\t'),
+                "u" => {
+                    // Negative values stand for code points above 32767
+                    let code = value(0);
+                    let code = if code < 0 { code + 65536 } else { code };
+                    if let Some(c) = char::from_u32(code as u32) {
+                        self.character(c);
+                    }
+                    self.skip = self.state.unicode_skip;
+                }
+                "uc" => self.state.unicode_skip = value(1).max(0) as usize,
+                "f" => self.state.font = param,
+                "fs" => self.state.font_size = Some(value(24) as f64 / 2.0),
+                "cf" => self.state.color = Some(value(0).max(0) as usize),
+                "b" => self.state.bold = value(1) != 0,
+                "i" => self.state.italic = value(1) != 0,
+                "plain" => {
+                    self.state.bold = false;
+                    self.state.italic = false;
+                }
+                "pard" => self.state.alignment = None,
+                "ql" => self.state.alignment = Some("left"),
+                "qc" => self.state.alignment = Some("center"),
+                "qr" => self.state.alignment = Some("right"),
+                "qj" => self.state.alignment = Some("justify"),
+                "emdash" => self.character('—'),
+                "endash" => self.character('–'),
+                "lquote" => self.character('‘'),
+                "rquote" => self.character('’'),
+                "ldblquote" => self.character('“'),
+                "rdblquote" => self.character('”'),
+                "bullet" => self.character('•'),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Read what follows a backslash
+    fn control(&mut self, chars: &mut Peekable<Chars>) {
+        let Some(c) = chars.next() else {
+            return;
+        };
+        match c {
+            '\\' | '{' | '}' => self.character(c),
+            '~' => self.character('\u{a0}'),
+            '_' => self.character('-'),
+            // A backslash ending the line is a paragraph break, as Cocoa writes them
+            '\n' | '\r' => self.character('\n'),
+            '*' => self.state.destination = Destination::Skipped,
+            '\'' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                    self.character(cp1252(byte));
+                }
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    word.push(c);
+                }
+                let mut number = String::new();
+                if let Some(minus) = chars.next_if_eq(&'-') {
+                    number.push(minus);
+                }
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    number.push(digit);
+                }
+                // One space ends the control word and is not text
+                chars.next_if_eq(&' ');
+                self.control_word(&word, number.parse().ok());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decode an RTF document to plain text, lines separated by "\n"
+pub fn parse(rtf: &str) -> RtfText {
+    let mut parser = RtfParser {
+        state: State {
+            destination: Destination::Text,
+            font: None,
+            font_size: None,
+            color: None,
+            alignment: None,
+            bold: false,
+            italic: false,
+            unicode_skip: 1,
+        },
+        stack: Vec::new(),
+        fonts: HashMap::new(),
+        table_font: None,
+        font_name: String::new(),
+        colors: Vec::new(),
+        rgb: [None; 3],
+        skip: 0,
+        result: RtfText::default(),
+        started: false,
+    };
+
+    let mut chars = rtf.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => parser.stack.push(parser.state),
+            '}' => {
+                if let Some(state) = parser.stack.pop() {
+                    parser.state = state;
+                }
+            }
+            '\\' => parser.control(&mut chars),
+            // Line breaks in the source are formatting only
+            '\n' | '\r' => {}
+            c => parser.character(c),
+        }
+    }
+
+    let mut result = parser.result;
+    result.text = result
+        .text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string();
+    result
+}
@@ -0,0 +1,123 @@
+//! A small element tree over quick-xml, for formats that are easier to read
+//! whole than as a stream.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Default)]
+pub struct Element {
+    pub name: String,                      // Local name, without namespace prefix
+    pub attributes: Vec<(String, String)>, // Qualified names, e.g. "r:embed"
+    pub children: Vec<Element>,
+    pub text: String, // Text directly inside, unescaped
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// The first element named `name` below this one, depth first
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|c| {
+            if c.name == name {
+                Some(c)
+            } else {
+                c.find(name)
+            }
+        })
+    }
+
+    /// Every element named `name` below this one, in document order; those
+    /// found are not searched further
+    pub fn find_all<'a>(&'a self, name: &str) -> Vec<&'a Element> {
+        let mut found = Vec::new();
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            } else {
+                found.extend(child.find_all(name));
+            }
+        }
+        found
+    }
+}
+
+fn invalid_xml(error: impl std::fmt::Display) -> AppError {
+    AppError::corrupt(format!("Invalid XML: {}", error)).with_code("INVALID_XML")
+}
+
+fn element(start: &BytesStart) -> AppResult<Element> {
+    let mut attributes = Vec::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(invalid_xml)?;
+        attributes.push((
+            String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+            attribute
+                .unescape_value()
+                .map_err(invalid_xml)?
+                .into_owned(),
+        ));
+    }
+    Ok(Element {
+        name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+        attributes,
+        ..Element::default()
+    })
+}
+
+/// Parse a document into its root element
+pub fn parse(text: &str) -> AppResult<Element> {
+    let mut reader = Reader::from_str(text);
+    // The bottom entry only collects the root element
+    let mut stack = vec![Element::default()];
+
+    loop {
+        match reader.read_event().map_err(invalid_xml)? {
+            Event::Start(start) => stack.push(element(&start)?),
+            Event::Empty(start) => {
+                let element = element(&start)?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            Event::End(_) if stack.len() > 1 => {
+                if let Some(element) = stack.pop() {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(element);
+                    }
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(invalid_xml)?;
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if stack.len() > 1 {
+        return Err(invalid_xml("unexpected end of document"));
+    }
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().next())
+        .ok_or_else(|| invalid_xml("no root element"))
+}
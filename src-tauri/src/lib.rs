@@ -1,12 +1,31 @@
 //! OS Presenter - Tauri application entry point and configuration.
 
+mod bundles;
 mod commands;
+mod error;
+mod folder_sync;
+mod history;
+mod importers;
+mod media_import;
+mod media_links;
+mod media_probe;
+mod media_references;
+mod migrations;
 mod models;
+mod quarantine;
+mod repository;
+mod search;
+mod snapshots;
 mod storage;
+mod storage_watcher;
+mod thumbnails;
+mod watched_folders;
+mod workspaces;
 
 use commands::video_sync::AppState;
 use commands::windows::open_settings_window_internal;
 use tauri::menu::{Menu, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -64,6 +83,35 @@ pub fn run() {
             let menu = Menu::with_items(app, &[&app_submenu, &edit_submenu, &window_submenu])?;
             app.set_menu(menu)?;
 
+            // Open the workspace that was last active
+            app.manage(workspaces::WorkspaceState::load(app.handle())?);
+
+            // Finish or undo saves interrupted by a crash, before anything writes
+            if let Err(e) = storage::open_data_dirs(app.handle()) {
+                eprintln!("Failed to recover interrupted writes: {}", e);
+            }
+
+            // Open the configured storage engine
+            app.manage(repository::RepositoryState::load(app.handle()));
+            app.manage(search::SearchState::default());
+
+            // Periodically snapshot the data directory in the background
+            snapshots::start_auto_snapshots(app.handle().clone());
+
+            // Render missing and newly imported thumbnails in the background
+            app.manage(thumbnails::ThumbnailQueue::default());
+            thumbnails::start_worker(app.handle().clone());
+
+            // Poll watched media folders for new files
+            app.manage(watched_folders::WatchedFolderState::default());
+            watched_folders::start_watching(app.handle().clone());
+
+            // Flag linked media whose files have gone missing
+            media_links::start_link_check(app.handle().clone());
+
+            // Tell every window about documents changed outside the app
+            storage_watcher::start_watching(app.handle().clone());
+
             Ok(())
         })
         .on_menu_event(|app, event| {
@@ -75,6 +123,10 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Data commands
             commands::initialize_storage,
+            commands::list_quarantined_files,
+            commands::recover_quarantined_file,
+            commands::get_storage_engine,
+            commands::migrate_storage_engine,
             commands::load_libraries,
             commands::save_library,
             commands::delete_library,
@@ -83,15 +135,64 @@ pub fn run() {
             commands::delete_playlist,
             commands::load_media_items,
             commands::import_media_file,
+            commands::import_media_files,
+            commands::import_media_folder,
             commands::delete_media_item,
+            commands::probe_media_item,
+            commands::get_media_usage,
+            commands::list_media_usage,
+            commands::collect_media_garbage,
             commands::get_media_file_path,
             commands::save_video_thumbnail,
             commands::update_media_thumbnail,
+            commands::generate_thumbnails,
             commands::load_media_playlists,
             commands::save_media_playlist,
             commands::delete_media_playlist,
             commands::load_tag_groups,
             commands::save_tag_groups,
+            // Song and presentation import commands
+            commands::import_songs,
+            commands::export_openlyrics,
+            commands::import_openlp_service,
+            commands::import_powerpoint,
+            // Bundle commands
+            commands::export_bundle,
+            commands::import_bundle,
+            // Linked media commands
+            commands::check_linked_media,
+            commands::relink_media,
+            commands::relink_media_item,
+            // Watched folder commands
+            commands::list_watched_folders,
+            commands::add_watched_folder,
+            commands::remove_watched_folder,
+            commands::scan_watched_folders,
+            // Folder sync commands
+            commands::get_folder_sync,
+            commands::set_folder_sync,
+            commands::check_sync_conflicts,
+            commands::resolve_sync_conflict,
+            // Workspace commands
+            commands::list_workspaces,
+            commands::create_workspace,
+            commands::rename_workspace,
+            commands::remove_workspace,
+            commands::switch_workspace,
+            commands::move_workspace,
+            // History commands
+            commands::list_revisions,
+            commands::get_revision,
+            commands::diff_revisions,
+            commands::restore_slide_group,
+            // Search commands
+            commands::search,
+            // Snapshot commands
+            commands::list_snapshots,
+            commands::create_snapshot,
+            commands::diff_snapshot,
+            commands::restore_snapshot_document,
+            commands::restore_snapshot,
             // Window commands
             commands::show_audience_window,
             commands::hide_audience_window,
@@ -0,0 +1,434 @@
+//! Copying media files into the library, one at a time or in batches.
+//!
+//! Files are deduplicated by content hash against the repository's hash
+//! lookup. Batch imports hash files on several threads and then import them
+//! one by one as the hashes arrive, so identical files within the same batch
+//! are still caught as duplicates. Folders are imported by walking them for
+//! supported files and, optionally, collecting the results in a media playlist.
+//! Linked imports skip the copy and reference the original file where it is,
+//! which suits large video and assets on shared drives.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::media_probe;
+use crate::media_references;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
+use crate::models::{MediaItem, MediaPlaylist};
+use crate::repository;
+use crate::search;
+use crate::storage;
+use crate::thumbnails;
+
+pub const IMPORT_PROGRESS_EVENT: &str = "media:import-progress";
+
+/// Upper bound on hashing threads; beyond this the disk is the bottleneck
+const MAX_HASH_THREADS: usize = 8;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportStatus {
+    Imported,
+    Duplicate, // Same content as an existing item, which is returned instead
+    Failed,
+}
+
+/// Emitted after each file of a batch import
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    pub source_path: String,
+    pub status: ImportStatus,
+    pub media_item: Option<MediaItem>,
+    pub error: Option<String>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFailure {
+    pub source_path: String,
+    pub error: String,
+}
+
+/// Outcome of a batch import, each list in the order the files were given
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub imported: Vec<MediaItem>,
+    pub duplicates: Vec<MediaItem>,
+    pub failed: Vec<ImportFailure>,
+}
+
+/// Result of importing a folder
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderImportSummary {
+    #[serde(flatten)]
+    pub summary: ImportSummary,
+    pub media_playlist: Option<MediaPlaylist>, // Set when a playlist was requested
+}
+
+/// Media type for a supported file extension
+pub fn media_type_for_extension(extension: &str) -> Option<&'static str> {
+    match extension.to_lowercase().as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "webp" | "bmp" => Some("image"),
+        "mp4" | "webm" | "mov" | "avi" | "mkv" => Some("video"),
+        "mp3" | "wav" | "m4a" | "flac" | "ogg" => Some("audio"),
+        _ => None,
+    }
+}
+
+/// Check that a file can be imported and compute its content hash
+fn hash_source(source: &PathBuf) -> AppResult<String> {
+    if !source.exists() {
+        return Err(AppError::not_found("Source file does not exist")
+            .with_code("FILE_NOT_FOUND")
+            .with_path(source));
+    }
+
+    let extension = storage::get_file_extension(source).ok_or_else(|| {
+        AppError::unsupported_format("Could not determine file extension").with_path(source)
+    })?;
+    if media_type_for_extension(&extension).is_none() {
+        return Err(AppError::unsupported_format("Unsupported file format").with_path(source));
+    }
+
+    storage::compute_file_hash(source)
+}
+
+// ===== Single Import =====
+
+/// Import one file, returning the new item or the existing one with the same
+/// content. The flag is true when the file was a duplicate. With `link`, the
+/// file is used in place rather than copied into the library.
+pub fn import_file(app: &AppHandle, source: &Path, link: bool) -> AppResult<(MediaItem, bool)> {
+    let source = source.to_path_buf();
+    let file_hash = hash_source(&source)?;
+    import_hashed(app, &source, file_hash, link)
+}
+
+fn import_hashed(
+    app: &AppHandle,
+    source: &PathBuf,
+    file_hash: String,
+    link: bool,
+) -> AppResult<(MediaItem, bool)> {
+    // The copy is unreferenced until its item is saved
+    let _imports = media_references::lock_media_imports();
+
+    // Check if we already have this file in the media library
+    let repo = repository::current(app);
+    if let Some(existing_item) = repo.find_media_by_hash(&file_hash)? {
+        return Ok((existing_item, true));
+    }
+
+    // Generate unique ID for the media
+    let media_id = Uuid::new_v4().to_string();
+
+    let extension = storage::get_file_extension(source).ok_or_else(|| {
+        AppError::unsupported_format("Could not determine file extension").with_path(source)
+    })?;
+    let file_name = storage::get_file_stem(source).ok_or_else(|| AppError::invalid_path(source))?;
+    let media_type = media_type_for_extension(&extension)
+        .ok_or_else(|| AppError::unsupported_format("Unsupported file format").with_path(source))?;
+
+    // Linked files keep their own name as the source, which is what slides
+    // referencing them resolve to; copies are named after the item
+    let (dest_file_name, dest_path, linked_path) = if link {
+        let linked_path = fs::canonicalize(source).map_err(|e| AppError::io(e, source))?;
+        let file_name = linked_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::invalid_path(&linked_path))?
+            .to_string();
+        let path_string = linked_path.to_string_lossy().to_string();
+        (file_name, linked_path, Some(path_string))
+    } else {
+        // Copy file to media directory
+        let media_files_dir = storage::get_media_files_dir(app)?;
+        let dest_file_name = format!("{}.{}", media_id, extension);
+        let dest_path = media_files_dir.join(&dest_file_name);
+
+        storage::copy_file(source, &dest_path)?;
+        (dest_file_name, dest_path, None)
+    };
+
+    // Create media item
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut media_item = MediaItem {
+        id: media_id,
+        schema_version: Some(CURRENT_SCHEMA_VERSION),
+        name: file_name,
+        media_type: media_type.to_string(),
+        source: dest_file_name.clone(), // Store relative path
+        thumbnail: None,
+        duration: None,
+        created_at: now.clone(),
+        updated_at: now,
+        metadata: None,
+        hash: Some(file_hash),
+        linked_path,
+        missing: false,
+    };
+
+    // Fill in duration, dimensions and codecs from the container headers
+    if let Err(e) = media_probe::probe_media_item(&mut media_item, &dest_path) {
+        eprintln!(
+            "Failed to probe media file {}: {}",
+            dest_file_name, e.message
+        );
+    }
+
+    repo.save_media_item(&media_item)?;
+    search::index_media_item(app, &media_item);
+
+    // Thumbnails are rendered in the background and announced by event
+    thumbnails::enqueue(app, [media_item.id.clone()]);
+
+    Ok((media_item, false))
+}
+
+// ===== Batch Import =====
+
+/// Outcome of importing one file of a batch; the flag marks duplicates
+pub type FileImportResult = AppResult<(MediaItem, bool)>;
+
+/// Import many files, hashing them in parallel and emitting
+/// `media:import-progress` after each one. Failures don't stop the batch.
+pub fn import_files(app: &AppHandle, source_paths: Vec<String>, link: bool) -> ImportSummary {
+    summarize(import_each(app, source_paths, link))
+}
+
+/// Like [`import_files`], but returning each file's result in input order
+pub fn import_each(
+    app: &AppHandle,
+    source_paths: Vec<String>,
+    link: bool,
+) -> Vec<(String, FileImportResult)> {
+    let total = source_paths.len();
+    let threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_HASH_THREADS)
+        .min(total.max(1));
+
+    let mut results: Vec<Option<FileImportResult>> = (0..total).map(|_| None).collect();
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let (next, source_paths) = (&next, &source_paths);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = source_paths.get(index) else {
+                    break;
+                };
+                if sender
+                    .send((index, hash_source(&PathBuf::from(path))))
+                    .is_err()
+                {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Copy and save sequentially so duplicates within the batch are found
+        for (completed, (index, hashed)) in receiver.iter().enumerate() {
+            let source_path = &source_paths[index];
+            let result =
+                hashed.and_then(|hash| import_hashed(app, &PathBuf::from(source_path), hash, link));
+
+            let (status, media_item, error) = match &result {
+                Ok((item, false)) => (ImportStatus::Imported, Some(item.clone()), None),
+                Ok((item, true)) => (ImportStatus::Duplicate, Some(item.clone()), None),
+                Err(e) => (ImportStatus::Failed, None, Some(e.message.clone())),
+            };
+            let progress = ImportProgress {
+                source_path: source_path.clone(),
+                status,
+                media_item,
+                error,
+                completed: completed + 1,
+                total,
+            };
+            if let Err(e) = app.emit(IMPORT_PROGRESS_EVENT, &progress) {
+                eprintln!("Failed to emit import progress: {}", e);
+            }
+
+            results[index] = Some(result);
+        }
+    });
+
+    source_paths
+        .into_iter()
+        .zip(results)
+        .map(|(source_path, result)| {
+            let result =
+                result.unwrap_or_else(|| Err(AppError::internal("File was not processed")));
+            (source_path, result)
+        })
+        .collect()
+}
+
+fn summarize(results: Vec<(String, FileImportResult)>) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for (source_path, result) in results {
+        match result {
+            Ok((item, false)) => summary.imported.push(item),
+            Ok((item, true)) => summary.duplicates.push(item),
+            Err(e) => summary.failed.push(ImportFailure {
+                source_path,
+                error: e.message,
+            }),
+        }
+    }
+    summary
+}
+
+// ===== Folder Import =====
+
+/// Supported media files in a folder, sorted by path. Hidden files and
+/// folders are skipped.
+pub fn collect_media_files(folder: &Path, recursive: bool) -> AppResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_none_or(|n| n.starts_with('.'));
+            if hidden {
+                continue;
+            }
+
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if storage::get_file_extension(&path)
+                .is_some_and(|ext| media_type_for_extension(&ext).is_some())
+            {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Display name for a folder, used to name its media playlist
+pub fn folder_name(folder: &Path) -> String {
+    folder
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Imported Folder")
+        .to_string()
+}
+
+/// Append items to a media playlist, skipping ones it already holds.
+///
+/// The playlist is created with `name` when `playlist_id` is not given or no
+/// longer exists.
+pub fn add_to_media_playlist(
+    app: &AppHandle,
+    playlist_id: Option<&str>,
+    name: &str,
+    items: &[MediaItem],
+) -> AppResult<MediaPlaylist> {
+    let repo = repository::current(app);
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let existing = match playlist_id {
+        Some(id) => repo.get_media_playlist(id)?,
+        None => None,
+    };
+    let mut playlist = match existing {
+        Some(playlist) => playlist,
+        None => MediaPlaylist {
+            id: playlist_id
+                .map(str::to_string)
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            schema_version: None,
+            name: name.to_string(),
+            media_items: Vec::new(),
+            order: repo.load_media_playlists()?.items.len() as i32 + 1,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+        },
+    };
+
+    let mut present: HashSet<String> = playlist.media_items.iter().map(|i| i.id.clone()).collect();
+    for item in items {
+        if present.insert(item.id.clone()) {
+            playlist.media_items.push(item.clone());
+        }
+    }
+
+    playlist.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    playlist.updated_at = now;
+    repo.save_media_playlist(&playlist)?;
+
+    Ok(playlist)
+}
+
+/// Import every supported file in a folder, optionally into a new media
+/// playlist named after the folder
+pub fn import_folder(
+    app: &AppHandle,
+    folder: &Path,
+    recursive: bool,
+    create_playlist: bool,
+    link: bool,
+) -> AppResult<FolderImportSummary> {
+    if !folder.is_dir() {
+        return Err(
+            AppError::not_found(format!("Folder not found: {}", folder.display()))
+                .with_code("FOLDER_NOT_FOUND")
+                .with_path(folder),
+        );
+    }
+
+    let source_paths = collect_media_files(folder, recursive)?
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    let results = import_each(app, source_paths, link);
+
+    // Keep the folder's file order in the playlist
+    let media_playlist = if create_playlist {
+        let items: Vec<MediaItem> = results
+            .iter()
+            .filter_map(|(_, result)| result.as_ref().ok().map(|(item, _)| item.clone()))
+            .collect();
+        Some(add_to_media_playlist(
+            app,
+            None,
+            &folder_name(folder),
+            &items,
+        )?)
+    } else {
+        None
+    };
+
+    Ok(FolderImportSummary {
+        summary: summarize(results),
+        media_playlist,
+    })
+}
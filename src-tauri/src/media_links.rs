@@ -0,0 +1,343 @@
+//! Media used in place from outside the library, and repairing broken links.
+//!
+//! Linked media items store the absolute path of their file instead of a copy.
+//! That file can disappear from under us (an unplugged drive, a renamed
+//! share), so links are checked at startup and on demand and flagged as
+//! missing. Missing files are relinked by searching chosen folders for a file
+//! with the same name, or failing that the same size, and only accepting it
+//! when its content hash matches. Slides that point at the old path are
+//! rewritten to the new one.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::history;
+use crate::media_import;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
+use crate::models::{MediaItem, SlideGroup};
+use crate::repository::{self, Repository};
+use crate::search;
+use crate::storage::{self, DocumentKind};
+use crate::storage_watcher::{self, ChangeType, StorageChange, StorageEntity};
+use crate::thumbnails;
+
+pub const LINKS_CHECKED_EVENT: &str = "media:links-checked";
+
+/// Result of checking linked media, emitted as `media:links-checked` at startup
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCheck {
+    pub missing: Vec<MediaItem>,
+    pub restored: Vec<MediaItem>, // Flagged missing before, found again
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkReport {
+    pub relinked: Vec<MediaItem>,
+    pub missing: Vec<MediaItem>, // Still not found
+}
+
+// ===== Checking =====
+
+/// Flag linked media whose file is gone, and clear the flag on files that came back
+pub fn check_links(app: &AppHandle) -> AppResult<LinkCheck> {
+    let repo = repository::current(app);
+    let mut check = LinkCheck::default();
+
+    for mut item in repo.load_media_items()?.items {
+        let Some(linked_path) = &item.linked_path else {
+            continue;
+        };
+        let missing = !Path::new(linked_path).is_file();
+
+        if missing != item.missing {
+            item.missing = missing;
+            item.schema_version = Some(CURRENT_SCHEMA_VERSION);
+            repo.save_media_item(&item)?;
+            repository::update_media_playlist_copies(repo.as_ref(), &item)?;
+            if missing {
+                check.missing.push(item);
+            } else {
+                check.restored.push(item);
+            }
+        } else if missing {
+            check.missing.push(item);
+        }
+    }
+
+    Ok(check)
+}
+
+/// Check links once in the background so startup isn't held up by slow drives
+pub fn start_link_check(app: AppHandle) {
+    thread::spawn(move || match check_links(&app) {
+        Ok(check) => {
+            if let Err(e) = app.emit(LINKS_CHECKED_EVENT, &check) {
+                eprintln!("Failed to emit link check: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to check linked media: {}", e.message),
+    });
+}
+
+// ===== Relinking =====
+
+/// Search `search_dirs` (recursively) for the files of missing linked media.
+///
+/// Only the items in `media_ids` are considered when it is given. A candidate
+/// is accepted only when its content hash matches the item's.
+pub fn relink_media(
+    app: &AppHandle,
+    search_dirs: &[PathBuf],
+    media_ids: Option<&[String]>,
+) -> AppResult<RelinkReport> {
+    let repo = repository::current(app);
+    let broken: Vec<MediaItem> = repo
+        .load_media_items()?
+        .items
+        .into_iter()
+        .filter(|item| media_ids.is_none_or(|ids| ids.contains(&item.id)))
+        .filter(|item| {
+            item.linked_path
+                .as_deref()
+                .is_some_and(|path| !Path::new(path).is_file())
+        })
+        .collect();
+
+    let mut report = RelinkReport::default();
+    if broken.is_empty() {
+        return Ok(report);
+    }
+
+    // Index every supported file once rather than walking the folders per item
+    let mut by_name: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for dir in search_dirs {
+        if !dir.is_dir() {
+            return Err(
+                AppError::not_found(format!("Folder not found: {}", dir.display()))
+                    .with_code("FOLDER_NOT_FOUND")
+                    .with_path(dir),
+            );
+        }
+        for path in media_import::collect_media_files(dir, true)? {
+            if let Some(name) = lowercase_file_name(&path) {
+                by_name.entry(name).or_default().push(path.clone());
+            }
+            if let Ok(metadata) = fs::metadata(&path) {
+                by_size.entry(metadata.len()).or_default().push(path);
+            }
+        }
+    }
+
+    let mut hashes: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut changes = Vec::new();
+    for item in broken {
+        let Some(expected) = item.hash.clone() else {
+            report.missing.push(item); // Nothing to verify a candidate against
+            continue;
+        };
+
+        let named = item
+            .linked_path
+            .as_deref()
+            .and_then(|path| lowercase_file_name(Path::new(path)))
+            .and_then(|name| by_name.get(&name));
+        let sized = recorded_file_size(&item).and_then(|size| by_size.get(&size));
+
+        let found = named
+            .into_iter()
+            .chain(sized)
+            .flatten()
+            .find(|path| {
+                let hash = hashes
+                    .entry((*path).clone())
+                    .or_insert_with(|| storage::compute_file_hash(path).ok());
+                hash.as_deref() == Some(expected.as_str())
+            })
+            .cloned();
+
+        match found {
+            Some(path) => match apply_relink(app, repo.as_ref(), item, &path, &mut changes) {
+                Ok(item) => report.relinked.push(item),
+                Err(e) => {
+                    // Documents already rewritten still need announcing
+                    storage_watcher::broadcast_changes(app, &changes);
+                    return Err(e);
+                }
+            },
+            None => report.missing.push(item),
+        }
+    }
+
+    storage_watcher::broadcast_changes(app, &changes);
+    Ok(report)
+}
+
+/// Point a linked media item at a file the user picked, which must have the same content
+pub fn relink_media_item(app: &AppHandle, media_id: &str, path: &Path) -> AppResult<MediaItem> {
+    let repo = repository::current(app);
+    let item = repo
+        .get_media_item(media_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::MediaItem, media_id))?;
+    if item.linked_path.is_none() {
+        return Err(
+            AppError::conflict(format!("Media item is not linked: {}", media_id))
+                .with_code("NOT_LINKED")
+                .with_entity(media_id),
+        );
+    }
+    if !path.is_file() {
+        return Err(
+            AppError::not_found(format!("File not found: {}", path.display()))
+                .with_code("FILE_NOT_FOUND")
+                .with_path(path),
+        );
+    }
+
+    if let Some(expected) = &item.hash {
+        if &storage::compute_file_hash(&path.to_path_buf())? != expected {
+            return Err(AppError::conflict(format!(
+                "{} does not have the same content as {}",
+                path.display(),
+                item.name
+            ))
+            .with_code("HASH_MISMATCH")
+            .with_entity(media_id)
+            .with_path(path));
+        }
+    }
+
+    let mut changes = Vec::new();
+    let item = apply_relink(app, repo.as_ref(), item, path, &mut changes);
+    storage_watcher::broadcast_changes(app, &changes);
+    item
+}
+
+fn lowercase_file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_lowercase())
+}
+
+/// File size recorded when the item was probed
+fn recorded_file_size(item: &MediaItem) -> Option<u64> {
+    item.metadata.as_ref()?.get("fileSize")?.as_u64()
+}
+
+/// Save the new location and rewrite slides that referenced the old one,
+/// adding the rewritten documents to `changes`
+fn apply_relink(
+    app: &AppHandle,
+    repo: &dyn Repository,
+    mut item: MediaItem,
+    path: &Path,
+    changes: &mut Vec<StorageChange>,
+) -> AppResult<MediaItem> {
+    let path = fs::canonicalize(path)?;
+    let new_path = path.to_string_lossy().to_string();
+    let old_path = item.linked_path.replace(new_path.clone());
+
+    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        item.source = name.to_string();
+    }
+    item.missing = false;
+    item.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    item.updated_at = chrono::Utc::now().to_rfc3339();
+    repo.save_media_item(&item)?;
+    repository::update_media_playlist_copies(repo, &item)?;
+    search::index_media_item(app, &item);
+
+    if let Some(old_path) = old_path.filter(|old| *old != new_path) {
+        rewrite_slide_sources(app, repo, &old_path, &new_path, changes)?;
+    }
+    if item.thumbnail.is_none() {
+        thumbnails::enqueue(app, [item.id.clone()]);
+    }
+
+    Ok(item)
+}
+
+/// Replace a linked path in slide sources, which hold it either as-is or
+/// URL-encoded inside an asset URL
+fn rewrite_slide_sources(
+    app: &AppHandle,
+    repo: &dyn Repository,
+    old_path: &str,
+    new_path: &str,
+    changes: &mut Vec<StorageChange>,
+) -> AppResult<()> {
+    let replacements = [
+        (
+            storage::encode_uri_component(old_path),
+            storage::encode_uri_component(new_path),
+        ),
+        (old_path.to_string(), new_path.to_string()),
+    ];
+
+    // Documents are read and rewritten without another save landing in between
+    let _guard = repository::lock_checked_saves();
+    let now = chrono::Utc::now().to_rfc3339();
+    for mut library in repo.load_libraries()?.items {
+        let mut changed = false;
+        for group in &mut library.slide_groups {
+            changed |= rewrite_group(group, &replacements);
+        }
+        if changed {
+            library.schema_version = Some(CURRENT_SCHEMA_VERSION);
+            library.updated_at = now.clone();
+            repository::save_library_replacing(repo, &mut library)?;
+            folder_sync::record_version(app, DocumentKind::Library, &library.id, &library);
+            history::record_revision(app, DocumentKind::Library, &library.id, &library);
+            changes.push(StorageChange {
+                kind: StorageEntity::Library,
+                id: library.id,
+                change: ChangeType::Modified,
+            });
+        }
+    }
+
+    for mut playlist in repo.load_playlists()?.items {
+        let mut changed = false;
+        for item in &mut playlist.items {
+            changed |= rewrite_group(&mut item.slide_group, &replacements);
+        }
+        if changed {
+            playlist.schema_version = Some(CURRENT_SCHEMA_VERSION);
+            playlist.updated_at = now.clone();
+            repository::save_playlist_replacing(repo, &mut playlist)?;
+            folder_sync::record_version(app, DocumentKind::Playlist, &playlist.id, &playlist);
+            history::record_revision(app, DocumentKind::Playlist, &playlist.id, &playlist);
+            changes.push(StorageChange {
+                kind: StorageEntity::Playlist,
+                id: playlist.id,
+                change: ChangeType::Modified,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply the first matching replacement to each media source in a group
+fn rewrite_group(group: &mut SlideGroup, replacements: &[(String, String)]) -> bool {
+    let mut changed = false;
+
+    for slide in &mut group.slides {
+        for src in slide.media_sources_mut() {
+            if let Some((old, new)) = replacements.iter().find(|(old, _)| src.contains(old)) {
+                *src = src.replace(old, new);
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
@@ -0,0 +1,1097 @@
+//! Container header parsing for imported media.
+//!
+//! Reads just enough of MP4/MOV, WebM/MKV, AVI, the supported audio formats
+//! and the supported image formats to report duration, dimensions, frame rate,
+//! codecs, audio presence and orientation. Nothing is decoded; large files are
+//! navigated by seeking.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::error::{AppError, AppResult};
+use crate::models::MediaItem;
+
+/// Largest header structure (e.g. an MP4 `moov` box) read into memory
+const MAX_HEADER_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Facts about a media file, stored in `MediaItem.metadata`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaProbe {
+    pub container: String, // "mp4" | "mov" | "m4a" | "webm" | "matroska" | "avi" | "mp3" | "wav" | "flac" | "ogg" | "jpeg" | "png" | "gif" | "webp" | "bmp"
+    pub file_size: u64,
+    // Display dimensions, after applying orientation/rotation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>, // Seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>, // Hz, of the first audio stream
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_audio: Option<bool>, // Only set for video containers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<u16>, // EXIF orientation (1-8)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<u16>, // Video rotation in degrees (0, 90, 180, 270)
+}
+
+impl MediaProbe {
+    fn new(container: &str) -> Self {
+        Self {
+            container: container.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Swap dimensions when the content is displayed rotated by 90 or 270 degrees
+    fn apply_orientation(&mut self) {
+        let rotated = matches!(self.orientation, Some(5..=8))
+            || matches!(self.rotation, Some(90) | Some(270));
+        if rotated {
+            std::mem::swap(&mut self.width, &mut self.height);
+        }
+    }
+}
+
+fn unsupported() -> AppError {
+    AppError::unsupported_format("Unrecognized media format")
+}
+
+/// Probe a media file by inspecting its container headers
+pub fn probe_file(path: &Path) -> AppResult<MediaProbe> {
+    let mut file = fs::File::open(path).map_err(|e| AppError::io(e, path))?;
+    let file_size = file.metadata()?.len();
+
+    let mut header = [0u8; 32];
+    let length = read_up_to(&mut file, &mut header)?;
+    let header = &header[..length];
+    file.seek(SeekFrom::Start(0))?;
+
+    let has = |range: std::ops::Range<usize>, value: &[u8]| header.get(range) == Some(value);
+
+    let mut probe = if header.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        probe_matroska(&mut file, file_size)?
+    } else if has(4..8, b"ftyp")
+        || has(4..8, b"moov")
+        || has(4..8, b"mdat")
+        || has(4..8, b"wide")
+        || has(4..8, b"free")
+    {
+        probe_iso_bmff(&mut file, file_size)?
+    } else if has(0..4, b"RIFF") && has(8..12, b"AVI ") {
+        probe_avi(&mut file, file_size)?
+    } else if has(0..4, b"RIFF") && has(8..12, b"WAVE") {
+        probe_wav(&mut file, file_size)?
+    } else if has(0..4, b"RIFF") && has(8..12, b"WEBP") {
+        probe_webp(header)?
+    } else if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        probe_png(header)?
+    } else if header.starts_with(&[0xFF, 0xD8]) {
+        probe_jpeg(&mut file, file_size)?
+    } else if header.starts_with(b"GIF8") {
+        probe_gif(header)?
+    } else if header.starts_with(b"BM") {
+        probe_bmp(header)?
+    } else if header.starts_with(b"fLaC") {
+        probe_flac(&mut file)?
+    } else if header.starts_with(b"OggS") {
+        probe_ogg(&mut file, file_size)?
+    } else if header.starts_with(b"ID3") || is_mpeg_audio_sync(header) {
+        probe_mp3(&mut file, file_size)?
+    } else {
+        return Err(unsupported());
+    };
+
+    probe.file_size = file_size;
+    probe.apply_orientation();
+    Ok(probe)
+}
+
+/// Probe a media item's file and record the result in its `duration` and `metadata`.
+///
+/// Probe fields are merged into existing metadata so other keys are preserved.
+pub fn probe_media_item(item: &mut MediaItem, path: &Path) -> AppResult<MediaProbe> {
+    let probe = probe_file(path)?;
+
+    if probe.duration.is_some() {
+        item.duration = probe.duration;
+    }
+
+    let fields = serde_json::to_value(&probe)?;
+    match (item.metadata.as_mut(), fields) {
+        (Some(JsonValue::Object(metadata)), JsonValue::Object(fields)) => metadata.extend(fields),
+        (_, fields) => item.metadata = Some(fields),
+    }
+
+    Ok(probe)
+}
+
+// ===== Byte Helpers =====
+
+fn read_up_to<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn read_exact_at<R: Read + Seek>(reader: &mut R, offset: u64, length: u64) -> AppResult<Vec<u8>> {
+    if length > MAX_HEADER_BYTES {
+        return Err(AppError::corrupt("Media header is too large to read"));
+    }
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut buffer = vec![0; length as usize];
+    let read = read_up_to(reader, &mut buffer)?;
+    buffer.truncate(read);
+    Ok(buffer)
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn be_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn fourcc(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..offset + 4)?;
+    let code: String = bytes.iter().map(|&b| b as char).collect();
+    let code = code.trim_matches(|c: char| c == '\0' || c == ' ');
+    (!code.is_empty()).then(|| code.to_string())
+}
+
+/// Frames per second rounded to three decimals (e.g. 29.97)
+fn round_rate(rate: f64) -> Option<f64> {
+    (rate.is_finite() && rate > 0.0).then(|| (rate * 1000.0).round() / 1000.0)
+}
+
+// ===== MP4 / MOV =====
+
+/// Children of an in-memory ISO BMFF box body as `(type, body)` pairs
+fn child_boxes(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= data.len() {
+        let size = be_u32(data, offset).unwrap_or(0) as u64;
+        let kind: [u8; 4] = data[offset + 4..offset + 8].try_into().unwrap_or_default();
+        let (header, size) = match size {
+            0 => (8, (data.len() - offset) as u64),
+            1 => (16, be_u64(data, offset + 8).unwrap_or(0)),
+            size => (8, size),
+        };
+        if size < header {
+            break;
+        }
+
+        // A 64-bit size can be anything in a corrupt file
+        let Some(end) = (offset as u64).checked_add(size) else {
+            break;
+        };
+        let end = end.min(data.len() as u64) as usize;
+        boxes.push((kind, data.get(offset + header as usize..end).unwrap_or(&[])));
+        offset = end;
+    }
+
+    boxes
+}
+
+fn find_box<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    let (first, rest) = path.split_first()?;
+    let body = child_boxes(data)
+        .into_iter()
+        .find(|(kind, _)| kind == *first)
+        .map(|(_, body)| body)?;
+    if rest.is_empty() {
+        Some(body)
+    } else {
+        find_box(body, rest)
+    }
+}
+
+fn probe_iso_bmff<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("mov");
+    let mut moov = None;
+    let mut offset = 0;
+
+    // Walk top-level boxes by seeking; `mdat` can be gigabytes
+    while offset + 8 <= file_size {
+        let header = read_exact_at(reader, offset, 16)?;
+        let size = be_u32(&header, 0).unwrap_or(0) as u64;
+        let (header_length, size) = match size {
+            0 => (8, file_size - offset),
+            1 => (16, be_u64(&header, 8).unwrap_or(0)),
+            size => (8, size),
+        };
+        if size < header_length {
+            break;
+        }
+
+        match &header[4..8] {
+            b"ftyp" => {
+                probe.container = match header.get(8..12).unwrap_or_default() {
+                    b"qt  " => "mov",
+                    b"M4A " | b"M4B " => "m4a",
+                    _ => "mp4",
+                }
+                .to_string();
+            }
+            b"moov" => {
+                moov = Some(read_exact_at(
+                    reader,
+                    offset + header_length,
+                    size - header_length,
+                )?);
+                break;
+            }
+            _ => {}
+        }
+        offset = match offset.checked_add(size) {
+            Some(next) if next > offset => next,
+            _ => break, // A 64-bit size past the end of any file
+        };
+    }
+
+    let moov = moov.ok_or_else(|| AppError::corrupt("Media file has no movie header"))?;
+
+    if let Some(mvhd) = find_box(&moov, &[b"mvhd"]) {
+        let (timescale, duration) = if mvhd.first() == Some(&1) {
+            (be_u32(mvhd, 20), be_u64(mvhd, 24))
+        } else {
+            (be_u32(mvhd, 12), be_u32(mvhd, 16).map(u64::from))
+        };
+        if let (Some(timescale), Some(duration)) = (timescale, duration) {
+            if timescale > 0 && duration > 0 {
+                probe.duration = Some(duration as f64 / timescale as f64);
+            }
+        }
+    }
+
+    probe.has_audio = Some(false);
+    for (_, trak) in child_boxes(&moov).into_iter().filter(|(k, _)| k == b"trak") {
+        let handler = find_box(trak, &[b"mdia", b"hdlr"]).and_then(|h| h.get(8..12));
+        let sample_entry =
+            find_box(trak, &[b"mdia", b"minf", b"stbl", b"stsd"]).and_then(|stsd| stsd.get(8..));
+        let codec = sample_entry.and_then(|entry| fourcc(entry, 4));
+
+        match handler {
+            Some(b"vide") if probe.video_codec.is_none() => {
+                probe.video_codec = codec.map(|c| mp4_codec_name(&c));
+                read_track_header(trak, &mut probe);
+
+                // Fall back to the sample entry size when the track header has none
+                if probe.width.is_none() {
+                    probe.width = sample_entry.and_then(|e| be_u16(e, 32)).map(u32::from);
+                    probe.height = sample_entry.and_then(|e| be_u16(e, 34)).map(u32::from);
+                }
+
+                probe.frame_rate = track_frame_rate(trak);
+            }
+            Some(b"soun") => {
+                probe.has_audio = Some(true);
+                if probe.audio_codec.is_none() {
+                    probe.audio_codec = codec.map(|c| mp4_codec_name(&c));
+                    // Sound sample entries hold channels and a 16.16 sample rate
+                    probe.channels = sample_entry.and_then(|e| be_u16(e, 24)).filter(|&c| c > 0);
+                    probe.sample_rate = sample_entry
+                        .and_then(|e| be_u32(e, 32))
+                        .map(|r| r >> 16)
+                        .filter(|&r| r > 0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(probe)
+}
+
+/// Read display size and rotation from a track's `tkhd`
+fn read_track_header(trak: &[u8], probe: &mut MediaProbe) {
+    let Some(tkhd) = find_box(trak, &[b"tkhd"]) else {
+        return;
+    };
+    let matrix_offset = if tkhd.first() == Some(&1) { 52 } else { 40 };
+
+    let width = be_u32(tkhd, matrix_offset + 36).map(|w| w >> 16);
+    let height = be_u32(tkhd, matrix_offset + 40).map(|h| h >> 16);
+    if let (Some(width), Some(height)) = (width, height) {
+        if width > 0 && height > 0 {
+            probe.width = Some(width);
+            probe.height = Some(height);
+        }
+    }
+
+    // Transformation matrix entries a and b are 16.16 fixed point
+    let a = be_u32(tkhd, matrix_offset).map(|v| v as i32 as f64);
+    let b = be_u32(tkhd, matrix_offset + 4).map(|v| v as i32 as f64);
+    if let (Some(a), Some(b)) = (a, b) {
+        let degrees = b.atan2(a).to_degrees().round() as i32;
+        probe.rotation = Some(degrees.rem_euclid(360) as u16 / 90 * 90);
+    }
+}
+
+fn track_frame_rate(trak: &[u8]) -> Option<f64> {
+    let mdhd = find_box(trak, &[b"mdia", b"mdhd"])?;
+    let (timescale, duration) = if mdhd.first() == Some(&1) {
+        (be_u32(mdhd, 20)?, be_u64(mdhd, 24)?)
+    } else {
+        (be_u32(mdhd, 12)?, be_u32(mdhd, 16)? as u64)
+    };
+
+    let stts = find_box(trak, &[b"mdia", b"minf", b"stbl", b"stts"])?;
+    let entries = be_u32(stts, 4)? as usize;
+    let samples: u64 = (0..entries)
+        .filter_map(|i| be_u32(stts, 8 + i * 8).map(u64::from))
+        .sum();
+
+    if duration == 0 {
+        return None;
+    }
+    round_rate(samples as f64 * timescale as f64 / duration as f64)
+}
+
+fn mp4_codec_name(fourcc: &str) -> String {
+    match fourcc {
+        "avc1" | "avc3" => "h264",
+        "hvc1" | "hev1" => "hevc",
+        "av01" => "av1",
+        "vp08" => "vp8",
+        "vp09" => "vp9",
+        "mp4v" => "mpeg4",
+        "apch" | "apcn" | "apcs" | "apco" | "ap4h" | "ap4x" => "prores",
+        "mp4a" => "aac",
+        "ac-3" => "ac3",
+        "ec-3" => "eac3",
+        "Opus" => "opus",
+        "alac" => "alac",
+        "lpcm" | "sowt" | "twos" | "in24" | "in32" | "fl32" => "pcm",
+        other => return other.to_lowercase(),
+    }
+    .to_string()
+}
+
+// ===== WebM / Matroska =====
+
+const EBML_DOC_TYPE: u32 = 0x4282;
+const SEGMENT: u32 = 0x1853_8067;
+const SEGMENT_INFO: u32 = 0x1549_A966;
+const TIMECODE_SCALE: u32 = 0x2A_D7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_TYPE: u32 = 0x83;
+const CODEC_ID: u32 = 0x86;
+const DEFAULT_DURATION: u32 = 0x23_E383;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const CLUSTER: u32 = 0x1F43_B675;
+
+/// Read an EBML variable-length integer; ids keep their length marker, sizes do not.
+///
+/// Returns the value, its encoded length, and whether it is the reserved "unknown size".
+fn ebml_vint(data: &[u8], keep_marker: bool) -> Option<(u64, usize, bool)> {
+    let first = *data.first()?;
+    let length = first.leading_zeros() as usize + 1;
+    if length > 8 || data.len() < length {
+        return None;
+    }
+
+    let mut value = if keep_marker {
+        first as u64
+    } else {
+        (first as u64) & (0xFF >> length)
+    };
+    for byte in &data[1..length] {
+        value = (value << 8) | *byte as u64;
+    }
+
+    let all_ones = (1u64 << (7 * length)) - 1;
+    let unknown = !keep_marker && value == all_ones;
+    Some((value, length, unknown))
+}
+
+/// Children of an in-memory EBML master element as `(id, body)` pairs
+fn ebml_children(data: &[u8]) -> Vec<(u32, &[u8])> {
+    let mut children = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let Some((id, id_length, _)) = ebml_vint(&data[offset..], true) else {
+            break;
+        };
+        let Some((size, size_length, unknown)) = ebml_vint(&data[offset + id_length..], false)
+        else {
+            break;
+        };
+
+        let start = offset + id_length + size_length;
+        let end = if unknown {
+            data.len()
+        } else {
+            (start as u64 + size).min(data.len() as u64) as usize
+        };
+        children.push((id as u32, data.get(start..end).unwrap_or(&[])));
+        offset = end;
+    }
+
+    children
+}
+
+fn ebml_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .take(8)
+        .fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+fn ebml_float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f32::from_be_bytes(data.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Read an element header at `offset` from the file: `(id, body start, body size or None if unknown)`
+fn read_ebml_header<R: Read + Seek>(
+    reader: &mut R,
+    offset: u64,
+) -> AppResult<Option<(u32, u64, Option<u64>)>> {
+    let header = read_exact_at(reader, offset, 16)?;
+    let Some((id, id_length, _)) = ebml_vint(&header, true) else {
+        return Ok(None);
+    };
+    let Some((size, size_length, unknown)) = ebml_vint(&header[id_length..], false) else {
+        return Ok(None);
+    };
+
+    let start = offset + (id_length + size_length) as u64;
+    Ok(Some((id as u32, start, (!unknown).then_some(size))))
+}
+
+fn probe_matroska<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let (_, ebml_start, ebml_size) = read_ebml_header(reader, 0)?.ok_or_else(unsupported)?;
+    let ebml_size = ebml_size.ok_or_else(unsupported)?;
+    let ebml = read_exact_at(reader, ebml_start, ebml_size)?;
+
+    let doc_type = ebml_children(&ebml)
+        .into_iter()
+        .find(|(id, _)| *id == EBML_DOC_TYPE)
+        .map(|(_, body)| {
+            String::from_utf8_lossy(body)
+                .trim_end_matches('\0')
+                .to_string()
+        });
+    let mut probe = MediaProbe::new(if doc_type.as_deref() == Some("webm") {
+        "webm"
+    } else {
+        "matroska"
+    });
+
+    let (segment_id, segment_start, segment_size) =
+        read_ebml_header(reader, ebml_start + ebml_size)?.ok_or_else(unsupported)?;
+    if segment_id != SEGMENT {
+        return Err(unsupported());
+    }
+    let segment_end = segment_size
+        .map(|size| (segment_start + size).min(file_size))
+        .unwrap_or(file_size);
+
+    let mut info = None;
+    let mut tracks = None;
+    let mut offset = segment_start;
+
+    // Info and Tracks normally precede the clusters; seek over everything else
+    while offset < segment_end && (info.is_none() || tracks.is_none()) {
+        let Some((id, start, size)) = read_ebml_header(reader, offset)? else {
+            break;
+        };
+        let Some(size) = size else {
+            break; // Unknown-size element (live-streamed cluster); nothing more to find
+        };
+
+        match id {
+            SEGMENT_INFO => info = Some(read_exact_at(reader, start, size)?),
+            TRACKS => tracks = Some(read_exact_at(reader, start, size)?),
+            CLUSTER if info.is_some() && tracks.is_some() => break,
+            _ => {}
+        }
+        offset = start + size;
+    }
+
+    if let Some(info) = info {
+        let children = ebml_children(&info);
+        let scale = children
+            .iter()
+            .find(|(id, _)| *id == TIMECODE_SCALE)
+            .map(|(_, body)| ebml_uint(body))
+            .unwrap_or(1_000_000);
+        probe.duration = children
+            .iter()
+            .find(|(id, _)| *id == DURATION)
+            .and_then(|(_, body)| ebml_float(body))
+            .map(|ticks| ticks * scale as f64 / 1e9);
+    }
+
+    probe.has_audio = Some(false);
+    for (_, entry) in tracks
+        .as_deref()
+        .map(ebml_children)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(id, _)| *id == TRACK_ENTRY)
+    {
+        let fields = ebml_children(entry);
+        let field = |wanted: u32| fields.iter().find(|(id, _)| *id == wanted).map(|(_, b)| *b);
+        let codec = field(CODEC_ID).map(|body| matroska_codec_name(&String::from_utf8_lossy(body)));
+
+        match field(TRACK_TYPE).map(ebml_uint) {
+            Some(1) if probe.video_codec.is_none() => {
+                probe.video_codec = codec;
+                probe.frame_rate = field(DEFAULT_DURATION)
+                    .map(ebml_uint)
+                    .filter(|ns| *ns > 0)
+                    .and_then(|ns| round_rate(1e9 / ns as f64));
+
+                if let Some(video) = field(VIDEO) {
+                    for (id, body) in ebml_children(video) {
+                        match id {
+                            PIXEL_WIDTH => probe.width = Some(ebml_uint(body) as u32),
+                            PIXEL_HEIGHT => probe.height = Some(ebml_uint(body) as u32),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Some(2) => {
+                probe.has_audio = Some(true);
+                if probe.audio_codec.is_none() {
+                    probe.audio_codec = codec;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(probe)
+}
+
+fn matroska_codec_name(codec_id: &str) -> String {
+    let codec_id = codec_id.trim_end_matches('\0');
+    match codec_id {
+        "V_MPEG4/ISO/AVC" => "h264",
+        "V_MPEGH/ISO/HEVC" => "hevc",
+        "V_VP8" => "vp8",
+        "V_VP9" => "vp9",
+        "V_AV1" => "av1",
+        "A_OPUS" => "opus",
+        "A_VORBIS" => "vorbis",
+        "A_AC3" => "ac3",
+        "A_EAC3" => "eac3",
+        "A_FLAC" => "flac",
+        "A_MPEG/L3" => "mp3",
+        id if id.starts_with("A_AAC") => "aac",
+        id if id.starts_with("A_PCM") => "pcm",
+        id => {
+            return id
+                .trim_start_matches("V_")
+                .trim_start_matches("A_")
+                .to_lowercase()
+        }
+    }
+    .to_string()
+}
+
+// ===== AVI =====
+
+/// RIFF chunks of an in-memory body as `(fourcc, body)`; LIST chunks keep their list type
+fn riff_chunks(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= data.len() {
+        let id: [u8; 4] = data[offset..offset + 4].try_into().unwrap_or_default();
+        let size = le_u32(data, offset + 4).unwrap_or(0) as usize;
+        let start = offset + 8;
+        let end = start.saturating_add(size).min(data.len());
+        chunks.push((id, &data[start..end]));
+        offset = end + (size & 1); // Chunks are padded to an even length
+    }
+
+    chunks
+}
+
+fn probe_avi<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("avi");
+    let mut offset = 12;
+    let mut header_list = None;
+
+    while offset + 12 <= file_size {
+        let header = read_exact_at(reader, offset, 12)?;
+        let size = le_u32(&header, 4).unwrap_or(0) as u64;
+        if &header[0..4] == b"LIST" && &header[8..12] == b"hdrl" {
+            header_list = Some(read_exact_at(reader, offset + 12, size.saturating_sub(4))?);
+            break;
+        }
+        offset += 8 + size + (size & 1);
+    }
+
+    let header_list =
+        header_list.ok_or_else(|| AppError::corrupt("AVI file has no header list"))?;
+    probe.has_audio = Some(false);
+
+    for (id, body) in riff_chunks(&header_list) {
+        match &id {
+            b"avih" => {
+                let micros_per_frame = le_u32(body, 0).unwrap_or(0);
+                let total_frames = le_u32(body, 16).unwrap_or(0);
+                probe.width = le_u32(body, 32);
+                probe.height = le_u32(body, 36);
+                if micros_per_frame > 0 {
+                    probe.frame_rate = round_rate(1e6 / micros_per_frame as f64);
+                    if total_frames > 0 {
+                        probe.duration = Some(total_frames as f64 * micros_per_frame as f64 / 1e6);
+                    }
+                }
+            }
+            b"LIST" if body.get(0..4) == Some(b"strl") => {
+                let stream = riff_chunks(&body[4..]);
+                let strh = stream.iter().find(|(id, _)| id == b"strh").map(|(_, b)| *b);
+                let strf = stream.iter().find(|(id, _)| id == b"strf").map(|(_, b)| *b);
+
+                match strh.and_then(|h| h.get(0..4)) {
+                    Some(b"vids") if probe.video_codec.is_none() => {
+                        probe.video_codec = strh
+                            .and_then(|h| fourcc(h, 4))
+                            .or_else(|| strf.and_then(|f| fourcc(f, 16)))
+                            .map(|c| c.to_lowercase());
+                    }
+                    Some(b"auds") => {
+                        probe.has_audio = Some(true);
+                        if probe.audio_codec.is_none() {
+                            probe.audio_codec =
+                                strf.and_then(|f| le_u16(f, 0)).and_then(wave_format_name);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(probe)
+}
+
+fn wave_format_name(tag: u16) -> Option<String> {
+    Some(
+        match tag {
+            0x0001 | 0x0003 => "pcm",
+            0x0055 => "mp3",
+            0x00FF | 0x1610 => "aac",
+            0x2000 => "ac3",
+            _ => return None,
+        }
+        .to_string(),
+    )
+}
+
+// ===== Audio =====
+
+/// Walk the chunks of a WAVE file for its format and the size of its sample data
+fn probe_wav<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("wav");
+    let mut byte_rate = 0;
+    let mut data_size = None;
+    let mut offset = 12;
+
+    while offset + 8 <= file_size && data_size.is_none() {
+        let header = read_exact_at(reader, offset, 8)?;
+        let size = le_u32(&header, 4).unwrap_or(0) as u64;
+
+        match &header[0..4] {
+            b"fmt " => {
+                let fmt = read_exact_at(reader, offset + 8, size.min(40))?;
+                let mut tag = le_u16(&fmt, 0).unwrap_or(0);
+                if tag == 0xFFFE {
+                    // WAVE_FORMAT_EXTENSIBLE keeps the real tag at the start of its sub-format GUID
+                    tag = le_u16(&fmt, 24).unwrap_or(0);
+                }
+                probe.audio_codec = wave_format_name(tag);
+                probe.channels = le_u16(&fmt, 2);
+                probe.sample_rate = le_u32(&fmt, 4);
+                byte_rate = le_u32(&fmt, 8).unwrap_or(0);
+            }
+            // Streamed recordings may leave the size unset; use what's in the file
+            b"data" => data_size = Some(size.min(file_size - offset - 8)),
+            _ => {}
+        }
+        offset += 8 + size + (size & 1);
+    }
+
+    if let Some(data_size) = data_size.filter(|_| byte_rate > 0) {
+        probe.duration = Some(data_size as f64 / byte_rate as f64);
+    }
+    Ok(probe)
+}
+
+/// Read the STREAMINFO block, which every FLAC stream starts with
+fn probe_flac<R: Read + Seek>(reader: &mut R) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("flac");
+    probe.audio_codec = Some("flac".to_string());
+
+    let block = read_exact_at(reader, 4, 4 + 34)?;
+    if block.first().map(|b| b & 0x7F) != Some(0) {
+        return Err(AppError::corrupt("FLAC file has no stream info"));
+    }
+    read_flac_stream_info(&block[4..], &mut probe);
+    Ok(probe)
+}
+
+/// Sample rate (20 bits), channels - 1 (3), bits per sample - 1 (5) and total samples (36)
+fn read_flac_stream_info(info: &[u8], probe: &mut MediaProbe) {
+    let Some(packed) = be_u64(info, 10) else {
+        return;
+    };
+    let sample_rate = (packed >> 44) as u32;
+    let total_samples = packed & 0xF_FFFF_FFFF;
+
+    probe.sample_rate = (sample_rate > 0).then_some(sample_rate);
+    probe.channels = Some(((packed >> 41) & 0x7) as u16 + 1);
+    if sample_rate > 0 && total_samples > 0 {
+        probe.duration = Some(total_samples as f64 / sample_rate as f64);
+    }
+}
+
+/// Identify the codec from the first Ogg page and take the duration from the
+/// granule position of the stream's last page
+fn probe_ogg<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("ogg");
+
+    let first = read_exact_at(reader, 0, 27 + 255 + 64)?;
+    let serial = le_u32(&first, 14).ok_or_else(unsupported)?;
+    let segments = *first.get(26).ok_or_else(unsupported)? as usize;
+    let packet = first.get(27 + segments..).ok_or_else(unsupported)?;
+
+    // Granule positions count samples, at 48 kHz for Opus
+    let (granule_rate, pre_skip) = if packet.starts_with(b"\x01vorbis") {
+        probe.audio_codec = Some("vorbis".to_string());
+        probe.channels = packet.get(11).map(|&c| c as u16);
+        probe.sample_rate = le_u32(packet, 12);
+        (probe.sample_rate.unwrap_or(0), 0)
+    } else if packet.starts_with(b"OpusHead") {
+        probe.audio_codec = Some("opus".to_string());
+        probe.channels = packet.get(9).map(|&c| c as u16);
+        probe.sample_rate = le_u32(packet, 12).filter(|&rate| rate > 0);
+        (48_000, le_u16(packet, 10).unwrap_or(0) as u64)
+    } else if packet.starts_with(b"\x7FFLAC") {
+        // Ogg FLAC wraps a native STREAMINFO block after its own 13-byte header
+        probe.audio_codec = Some("flac".to_string());
+        if let Some(info) = packet.get(17..) {
+            read_flac_stream_info(info, &mut probe);
+        }
+        (probe.sample_rate.unwrap_or(0), 0)
+    } else {
+        return Err(unsupported());
+    };
+
+    // Pages are at most ~64 KiB, so the last one starts within this tail
+    let tail_length = file_size.min(65_307 + 27);
+    let tail = read_exact_at(reader, file_size - tail_length, tail_length)?;
+    let granule = (0..tail.len().saturating_sub(27))
+        .rev()
+        .filter(|&i| tail[i..].starts_with(b"OggS") && le_u32(&tail, i + 14) == Some(serial))
+        .find_map(|i| {
+            let granule = u64::from_le_bytes(tail.get(i + 6..i + 14)?.try_into().ok()?);
+            (granule != u64::MAX).then_some(granule) // -1 marks pages with no finished packet
+        });
+
+    if let Some(granule) = granule.filter(|_| granule_rate > 0) {
+        probe.duration = Some(granule.saturating_sub(pre_skip) as f64 / granule_rate as f64);
+    }
+    Ok(probe)
+}
+
+/// MPEG-1 bitrates in kbit/s by index, for layers III, II and I
+#[rustfmt::skip]
+const MPEG1_BITRATES: [[u32; 15]; 3] = [
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+];
+
+/// MPEG-2 and 2.5 bitrates in kbit/s by index, for layers II/III and layer I
+#[rustfmt::skip]
+const MPEG2_BITRATES: [[u32; 15]; 2] = [
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+];
+
+/// MPEG audio frame sync: 11 set bits, and a layer other than the reserved value
+fn is_mpeg_audio_sync(header: &[u8]) -> bool {
+    matches!(header, [0xFF, second, ..] if second & 0xE0 == 0xE0 && second & 0x06 != 0)
+}
+
+/// Decode the first MP3 frame header. The duration comes from a Xing/Info or
+/// VBRI frame count when there is one, otherwise from the bitrate (exact for
+/// constant bitrate files).
+fn probe_mp3<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("mp3");
+    probe.audio_codec = Some("mp3".to_string());
+
+    // Skip ID3v2 tags; their size is stored in 7-bit bytes
+    let mut offset = 0;
+    loop {
+        let tag = read_exact_at(reader, offset, 10)?;
+        if !tag.starts_with(b"ID3") || tag.len() < 10 {
+            break;
+        }
+        let size = tag[6..10]
+            .iter()
+            .fold(0u64, |size, &b| size << 7 | (b & 0x7F) as u64);
+        let footer = if tag[5] & 0x10 != 0 { 10 } else { 0 };
+        offset += 10 + size + footer;
+    }
+
+    let frame = read_exact_at(reader, offset, 4 + 32 + 4 + 16)?;
+    if frame.len() < 4 || !is_mpeg_audio_sync(&frame) {
+        return Err(AppError::corrupt("MP3 file has no audio frames"));
+    }
+
+    // Version: 3 = MPEG-1, 2 = MPEG-2, 0 = MPEG-2.5. Layer: 3 = I, 2 = II, 1 = III
+    let version = (frame[1] >> 3) & 0x3;
+    let layer = (frame[1] >> 1) & 0x3;
+    let bitrate_index = (frame[2] >> 4) as usize;
+    let rate_index = ((frame[2] >> 2) & 0x3) as usize;
+    let mono = frame[3] >> 6 == 0x3;
+
+    let base_rate = [44_100, 48_000, 32_000].get(rate_index).copied();
+    let sample_rate = base_rate.map(|rate| match version {
+        3 => rate,
+        2 => rate / 2,
+        _ => rate / 4,
+    });
+    let samples_per_frame: u64 = match (version, layer) {
+        (_, 3) => 384,
+        (3, _) | (_, 2) => 1152,
+        _ => 576,
+    };
+    let bitrate_kbps = match (version, layer) {
+        (3, layer) => MPEG1_BITRATES[layer as usize - 1].get(bitrate_index),
+        (_, 3) => MPEG2_BITRATES[1].get(bitrate_index),
+        _ => MPEG2_BITRATES[0].get(bitrate_index),
+    }
+    .copied()
+    .unwrap_or(0);
+
+    probe.sample_rate = sample_rate;
+    probe.channels = Some(if mono { 1 } else { 2 });
+
+    // A Xing/Info header sits after the side information, a VBRI header at a fixed offset
+    let side_info = match (version == 3, mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    let xing = frame.get(4 + side_info..).filter(|x| {
+        (x.starts_with(b"Xing") || x.starts_with(b"Info"))
+            && be_u32(x, 4).is_some_and(|f| f & 1 != 0)
+    });
+    let frames = match xing {
+        Some(xing) => be_u32(xing, 8),
+        None => frame
+            .get(4 + 32..)
+            .filter(|v| v.starts_with(b"VBRI"))
+            .and_then(|vbri| be_u32(vbri, 14)),
+    };
+
+    probe.duration = match (frames, sample_rate) {
+        (Some(frames), Some(rate)) if frames > 0 => {
+            Some(frames as f64 * samples_per_frame as f64 / rate as f64)
+        }
+        _ if bitrate_kbps > 0 => {
+            let id3v1 = read_exact_at(reader, file_size.saturating_sub(128), 3)?;
+            let trailer = if id3v1 == b"TAG" { 128 } else { 0 };
+            let audio_bytes = file_size.saturating_sub(offset + trailer);
+            Some(audio_bytes as f64 * 8.0 / (bitrate_kbps as f64 * 1000.0))
+        }
+        _ => None,
+    };
+    Ok(probe)
+}
+
+// ===== Images =====
+
+fn probe_png(header: &[u8]) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("png");
+    probe.width = Some(be_u32(header, 16).ok_or_else(unsupported)?);
+    probe.height = Some(be_u32(header, 20).ok_or_else(unsupported)?);
+    Ok(probe)
+}
+
+fn probe_gif(header: &[u8]) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("gif");
+    probe.width = Some(le_u16(header, 6).ok_or_else(unsupported)? as u32);
+    probe.height = Some(le_u16(header, 8).ok_or_else(unsupported)? as u32);
+    Ok(probe)
+}
+
+fn probe_bmp(header: &[u8]) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("bmp");
+    // Height is negative for top-down bitmaps
+    probe.width = Some((le_u32(header, 18).ok_or_else(unsupported)? as i32).unsigned_abs());
+    probe.height = Some((le_u32(header, 22).ok_or_else(unsupported)? as i32).unsigned_abs());
+    Ok(probe)
+}
+
+fn probe_webp(header: &[u8]) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("webp");
+    let le_u24 = |offset: usize| -> Option<u32> {
+        let b = header.get(offset..offset + 3)?;
+        Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+    };
+
+    let (width, height) = match header.get(12..16) {
+        Some(b"VP8 ") => (
+            le_u16(header, 26).map(|w| (w & 0x3FFF) as u32),
+            le_u16(header, 28).map(|h| (h & 0x3FFF) as u32),
+        ),
+        Some(b"VP8L") => {
+            let bits = le_u32(header, 21).ok_or_else(unsupported)?;
+            (Some((bits & 0x3FFF) + 1), Some(((bits >> 14) & 0x3FFF) + 1))
+        }
+        Some(b"VP8X") => (le_u24(24).map(|w| w + 1), le_u24(27).map(|h| h + 1)),
+        _ => return Err(unsupported()),
+    };
+
+    probe.width = width;
+    probe.height = height;
+    Ok(probe)
+}
+
+fn probe_jpeg<R: Read + Seek>(reader: &mut R, file_size: u64) -> AppResult<MediaProbe> {
+    let mut probe = MediaProbe::new("jpeg");
+    let mut offset = 2;
+
+    while offset + 4 <= file_size {
+        let header = read_exact_at(reader, offset, 4)?;
+        if header[0] != 0xFF {
+            break;
+        }
+        let marker = header[1];
+
+        // Fill bytes and standalone markers carry no length
+        if marker == 0xFF {
+            offset += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            offset += 2;
+            continue;
+        }
+        if marker == 0xD9 || marker == 0xDA {
+            break; // End of image or start of scan data
+        }
+
+        let length = be_u16(&header, 2).unwrap_or(0) as u64;
+        if length < 2 {
+            break;
+        }
+        let body_start = offset + 4;
+        let body_length = length - 2;
+
+        match marker {
+            0xE1 if probe.orientation.is_none() => {
+                let body = read_exact_at(reader, body_start, body_length)?;
+                if let Some(tiff) = body.strip_prefix(b"Exif\0\0") {
+                    probe.orientation = exif_orientation(tiff);
+                }
+            }
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let body = read_exact_at(reader, body_start, 5)?;
+                probe.height = be_u16(&body, 1).map(u32::from);
+                probe.width = be_u16(&body, 3).map(u32::from);
+            }
+            _ => {}
+        }
+
+        offset = body_start + body_length;
+    }
+
+    if probe.width.is_none() {
+        return Err(AppError::corrupt("JPEG file has no frame header"));
+    }
+    Ok(probe)
+}
+
+/// Orientation tag (0x0112) from the first IFD of an EXIF TIFF block
+fn exif_orientation(tiff: &[u8]) -> Option<u16> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| {
+        if little_endian {
+            le_u16(tiff, offset)
+        } else {
+            be_u16(tiff, offset)
+        }
+    };
+    let u32_at = |offset: usize| {
+        if little_endian {
+            le_u32(tiff, offset)
+        } else {
+            be_u32(tiff, offset)
+        }
+    };
+
+    let ifd = u32_at(4)? as usize;
+    let entries = u16_at(ifd)? as usize;
+    (0..entries)
+        .map(|i| ifd + 2 + i * 12)
+        .find(|&entry| u16_at(entry) == Some(0x0112))
+        .and_then(|entry| u16_at(entry + 8))
+        .filter(|orientation| (1..=8).contains(orientation))
+}
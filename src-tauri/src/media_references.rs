@@ -0,0 +1,351 @@
+//! Tracking which documents use which media files, and reclaiming unused ones.
+//!
+//! References are gathered on demand from slide objects, legacy slide
+//! backgrounds and media playlists rather than stored, so they can never
+//! drift out of sync with the documents themselves.
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tauri::AppHandle;
+
+use crate::error::{AppError, AppResult};
+use crate::history;
+use crate::models::{MediaItem, SlideData, SlideGroup};
+use crate::quarantine;
+use crate::repository;
+use crate::snapshots;
+use crate::storage::{self, DocumentKind};
+
+/// Shared by anything writing a media file before the document that refers
+/// to it is saved, and held exclusively while collecting garbage
+static MEDIA_IMPORTS: RwLock<()> = RwLock::new(());
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MediaReferenceKind {
+    Library,
+    Playlist,
+    MediaPlaylist,
+}
+
+/// A place where a media file is used
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaReference {
+    pub kind: MediaReferenceKind,
+    pub document_id: String,
+    pub document_name: String,
+    pub slide_group_id: Option<String>,
+    pub slide_group_title: Option<String>,
+    pub slide_id: Option<String>,
+    pub file_name: String, // Whole path for linked media
+}
+
+impl MediaReference {
+    /// Slides break when their media disappears; media playlists only hold copies of items
+    pub fn is_slide(&self) -> bool {
+        self.kind != MediaReferenceKind::MediaPlaylist
+    }
+}
+
+/// Everything that uses a media item's source file or thumbnail
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaUsage {
+    pub media_item_id: String,
+    pub file_names: Vec<String>,
+    pub references: Vec<MediaReference>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OrphanReason {
+    Unreferenced,
+    StaleThumbnail,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedFile {
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub reason: OrphanReason,
+}
+
+/// Result of a garbage collection pass
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GarbageReport {
+    pub dry_run: bool,
+    pub orphaned_files: Vec<OrphanedFile>,
+    pub unused_media_item_ids: Vec<String>, // In the media library but not used anywhere; never deleted
+    pub reclaimable_bytes: u64,
+    pub reclaimed_bytes: u64,
+}
+
+// ===== Reference Map =====
+
+/// Map every referenced media file name to the places that use it
+pub fn build_reference_map(app: &AppHandle) -> AppResult<HashMap<String, Vec<MediaReference>>> {
+    let repo = repository::current(app);
+    let linked = linked_paths(&repo.load_media_items()?.items);
+    let mut references: HashMap<String, Vec<MediaReference>> = HashMap::new();
+    let mut add = |reference: MediaReference| {
+        references
+            .entry(reference.file_name.clone())
+            .or_default()
+            .push(reference);
+    };
+
+    for library in repo.load_libraries()?.items {
+        for group in &library.slide_groups {
+            for (slide, file_name) in slide_group_sources(group, &linked) {
+                add(MediaReference {
+                    kind: MediaReferenceKind::Library,
+                    document_id: library.id.clone(),
+                    document_name: library.name.clone(),
+                    slide_group_id: Some(group.id.clone()),
+                    slide_group_title: Some(group.title.clone()),
+                    slide_id: Some(slide.id.clone()),
+                    file_name,
+                });
+            }
+        }
+    }
+
+    for playlist in repo.load_playlists()?.items {
+        for group in playlist.items.iter().map(|i| &i.slide_group) {
+            for (slide, file_name) in slide_group_sources(group, &linked) {
+                add(MediaReference {
+                    kind: MediaReferenceKind::Playlist,
+                    document_id: playlist.id.clone(),
+                    document_name: playlist.name.clone(),
+                    slide_group_id: Some(group.id.clone()),
+                    slide_group_title: Some(group.title.clone()),
+                    slide_id: Some(slide.id.clone()),
+                    file_name,
+                });
+            }
+        }
+    }
+
+    for playlist in repo.load_media_playlists()?.items {
+        for item in &playlist.media_items {
+            for file_name in item_file_names(item) {
+                add(MediaReference {
+                    kind: MediaReferenceKind::MediaPlaylist,
+                    document_id: playlist.id.clone(),
+                    document_name: playlist.name.clone(),
+                    slide_group_id: None,
+                    slide_group_title: None,
+                    slide_id: None,
+                    file_name,
+                });
+            }
+        }
+    }
+
+    Ok(references)
+}
+
+/// Media file names used by each slide of a group, including legacy backgrounds and audio cues
+fn slide_group_sources<'a>(
+    group: &'a SlideGroup,
+    linked: &HashSet<String>,
+) -> Vec<(&'a SlideData, String)> {
+    let mut sources = Vec::new();
+
+    for slide in &group.slides {
+        let mut seen = HashSet::new();
+        for file_name in slide
+            .media_sources()
+            .into_iter()
+            .filter_map(|src| source_file_name(src, linked))
+        {
+            if seen.insert(file_name.clone()) {
+                sources.push((slide, file_name));
+            }
+        }
+    }
+
+    sources
+}
+
+/// File names of a media item's source and thumbnail, naming a linked source
+/// by its whole path like `source_file_name` does
+fn item_file_names(item: &MediaItem) -> Vec<String> {
+    let source = match &item.linked_path {
+        Some(path) => Some(path.clone()),
+        None => storage::media_file_name(&item.source),
+    };
+    source
+        .into_iter()
+        .chain(item.thumbnail.as_deref().and_then(storage::media_file_name))
+        .collect()
+}
+
+/// Paths of linked media files
+fn linked_paths(items: &[MediaItem]) -> HashSet<String> {
+    items
+        .iter()
+        .filter_map(|item| item.linked_path.clone())
+        .collect()
+}
+
+/// File name a slide source refers to, or the whole path of a linked file,
+/// since two folders can each hold an `intro.mp4`
+fn source_file_name(src: &str, linked: &HashSet<String>) -> Option<String> {
+    let path = storage::media_source_path(src);
+    if linked.contains(&path) {
+        return Some(path);
+    }
+    storage::media_file_name(src)
+}
+
+fn usage_for(item: &MediaItem, references: &HashMap<String, Vec<MediaReference>>) -> MediaUsage {
+    let file_names = item_file_names(item);
+    let references = file_names
+        .iter()
+        .flat_map(|name| references.get(name).into_iter().flatten().cloned())
+        .collect();
+
+    MediaUsage {
+        media_item_id: item.id.clone(),
+        file_names,
+        references,
+    }
+}
+
+/// Everything that uses a single media item
+pub fn media_usage(app: &AppHandle, media_id: &str) -> AppResult<MediaUsage> {
+    let item = repository::current(app)
+        .get_media_item(media_id)?
+        .ok_or_else(|| AppError::document_not_found(DocumentKind::MediaItem, media_id))?;
+
+    Ok(usage_for(&item, &build_reference_map(app)?))
+}
+
+/// Usage of every media item in the library
+pub fn list_media_usage(app: &AppHandle) -> AppResult<Vec<MediaUsage>> {
+    let references = build_reference_map(app)?;
+
+    Ok(repository::current(app)
+        .load_media_items()?
+        .items
+        .iter()
+        .map(|item| usage_for(item, &references))
+        .collect())
+}
+
+// ===== Garbage Collection =====
+
+/// Hold off garbage collection while media files are written and the
+/// documents referring to them saved
+pub fn lock_media_imports() -> RwLockReadGuard<'static, ()> {
+    MEDIA_IMPORTS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Hold off everything that writes media files, e.g. while the workspace is moved
+pub fn pause_media_imports() -> RwLockWriteGuard<'static, ()> {
+    MEDIA_IMPORTS.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Media file names in every string of an archived document
+fn add_mentioned_file_names(content: &[u8], names: &mut HashSet<String>) {
+    fn walk(value: &JsonValue, names: &mut HashSet<String>) {
+        match value {
+            JsonValue::String(s) => names.extend(storage::media_file_name(s)),
+            JsonValue::Array(values) => values.iter().for_each(|v| walk(v, names)),
+            JsonValue::Object(fields) => fields.values().for_each(|v| walk(v, names)),
+            _ => {}
+        }
+    }
+
+    if let Ok(value) = serde_json::from_slice::<JsonValue>(content) {
+        walk(&value, names);
+    }
+}
+
+/// Find media files nothing refers to and, unless `dry_run`, delete them.
+///
+/// Files named by media library items are always kept, as are files mentioned
+/// by quarantined documents that may still be recovered and by snapshots and
+/// history revisions that may still be restored.
+pub fn collect_garbage(app: &AppHandle, dry_run: bool) -> AppResult<GarbageReport> {
+    let _imports = pause_media_imports();
+    let repo = repository::current(app);
+    let references = build_reference_map(app)?;
+    let items = repo.load_media_items()?.items;
+
+    let mut keep: HashSet<String> = references.keys().cloned().collect();
+    keep.extend(items.iter().flat_map(item_file_names));
+    snapshots::visit_archived_documents(app, |content| {
+        add_mentioned_file_names(content, &mut keep)
+    })?;
+    history::visit_stored_versions(app, |content| add_mentioned_file_names(content, &mut keep))?;
+
+    let quarantined = quarantine::list_quarantined_files(app)?
+        .iter()
+        .filter_map(|file| fs::read_to_string(&file.quarantine_path).ok())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut report = GarbageReport {
+        dry_run,
+        orphaned_files: Vec::new(),
+        unused_media_item_ids: items
+            .iter()
+            .filter(|item| usage_for(item, &references).references.is_empty())
+            .map(|item| item.id.clone())
+            .collect(),
+        reclaimable_bytes: 0,
+        reclaimed_bytes: 0,
+    };
+
+    let media_files_dir = storage::get_media_files_dir(app)?;
+    for entry in fs::read_dir(&media_files_dir)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        // Hidden files and in-flight atomic writes are not media
+        if !path.is_file()
+            || file_name.starts_with('.')
+            || file_name.ends_with(".tmp")
+            || file_name.ends_with(".journal")
+            || keep.contains(file_name)
+            || quarantined.contains(file_name)
+        {
+            continue;
+        }
+
+        let size_bytes = fs::metadata(&path)?.len();
+        report.reclaimable_bytes += size_bytes;
+        report.orphaned_files.push(OrphanedFile {
+            file_name: file_name.to_string(),
+            size_bytes,
+            reason: if file_name.contains("_thumb.") {
+                OrphanReason::StaleThumbnail
+            } else {
+                OrphanReason::Unreferenced
+            },
+        });
+
+        if !dry_run {
+            match fs::remove_file(&path) {
+                Ok(()) => report.reclaimed_bytes += size_bytes,
+                Err(e) => eprintln!("Failed to delete orphaned media file {}: {}", file_name, e),
+            }
+        }
+    }
+
+    report
+        .orphaned_files
+        .sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+    Ok(report)
+}
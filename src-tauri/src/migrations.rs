@@ -0,0 +1,286 @@
+//! Versioned on-disk schema and the ordered migrations that upgrade old documents.
+//!
+//! Migrations operate on raw JSON before typed deserialization so they can
+//! reshape fields that the current models no longer understand.
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value as JsonValue};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::storage::{self, DocumentKind};
+
+/// Schema version stamped into every document written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// A single upgrade step from `from` to `from + 1`
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(DocumentKind, &mut JsonValue),
+}
+
+/// Ordered list of migrations; each entry upgrades exactly one version
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "Convert legacy slide text/background and deprecated bold into slide objects",
+    apply: migrate_legacy_slides,
+}];
+
+/// Read the schema version of a raw document (unversioned documents are version 0)
+pub fn schema_version(document: &JsonValue) -> u32 {
+    document
+        .get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Upgrade a raw document to the current schema version.
+///
+/// Returns the version the document started at, or `None` if it was already
+/// current (or written by a newer build, which is left untouched).
+pub fn migrate_document(kind: DocumentKind, document: &mut JsonValue) -> Option<u32> {
+    let original = schema_version(document);
+    if original >= CURRENT_SCHEMA_VERSION || !document.is_object() {
+        return None;
+    }
+
+    let mut version = original;
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= original) {
+        (migration.apply)(kind, document);
+        version = migration.from + 1;
+        println!(
+            "Migrated {:?} document to schema v{}: {}",
+            kind, version, migration.description
+        );
+    }
+
+    document["schemaVersion"] = json!(version);
+    Some(original)
+}
+
+/// Parse a stored document, upgrading it if it uses an older schema.
+///
+/// When an upgrade happens the original content is copied into the backups
+/// directory and the upgraded document is returned so the caller can persist it.
+pub fn parse_document<T: DeserializeOwned>(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    content: &str,
+) -> AppResult<(T, Option<JsonValue>)> {
+    let mut document: JsonValue = serde_json::from_str(content)?;
+
+    if let Some(from_version) = migrate_document(kind, &mut document) {
+        // Make sure the upgraded document is loadable before keeping a backup
+        let item: T = serde_json::from_value(document.clone())?;
+        backup_original(app, kind, id, from_version, content)?;
+        return Ok((item, Some(document)));
+    }
+
+    Ok((serde_json::from_value(document)?, None))
+}
+
+/// Read a document file, upgrading and rewriting it if it uses an older schema
+pub fn read_document<T: DeserializeOwned>(
+    app: &AppHandle,
+    kind: DocumentKind,
+    path: &Path,
+) -> AppResult<T> {
+    let id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| AppError::invalid_path(path))?;
+    let content = fs::read_to_string(path).map_err(|e| AppError::io(e, path).with_entity(id))?;
+
+    let (item, upgraded) =
+        parse_document(app, kind, id, &content).map_err(|e| e.with_entity(id).with_path(path))?;
+    if let Some(document) = upgraded {
+        storage::write_json_file(path, &document)?;
+    }
+
+    Ok(item)
+}
+
+/// Copy a document's original content into `backups/migrations/<kind>/` before it is rewritten
+fn backup_original(
+    app: &AppHandle,
+    kind: DocumentKind,
+    id: &str,
+    from_version: u32,
+    content: &str,
+) -> AppResult<()> {
+    let backup_dir = storage::get_backups_dir(app)?
+        .join("migrations")
+        .join(kind.dir_name());
+    fs::create_dir_all(&backup_dir)?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+    let backup_path = backup_dir.join(format!("{}.v{}.{}.json", id, from_version, timestamp));
+    storage::write_file(&backup_path, content.as_bytes())
+}
+
+// ===== v0 -> v1 =====
+
+fn migrate_legacy_slides(kind: DocumentKind, document: &mut JsonValue) {
+    match kind {
+        DocumentKind::Library => {
+            if let Some(groups) = document.get_mut("slideGroups").and_then(|v| v.as_array_mut()) {
+                groups.iter_mut().for_each(migrate_slide_group);
+            }
+        }
+        DocumentKind::Playlist => {
+            if let Some(items) = document.get_mut("items").and_then(|v| v.as_array_mut()) {
+                for item in items {
+                    if let Some(group) = item.get_mut("slideGroup") {
+                        migrate_slide_group(group);
+                    }
+                }
+            }
+        }
+        DocumentKind::MediaItem | DocumentKind::MediaPlaylist => {}
+    }
+}
+
+fn migrate_slide_group(group: &mut JsonValue) {
+    let canvas_width = group
+        .pointer("/canvasSize/width")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(1920.0);
+    let canvas_height = group
+        .pointer("/canvasSize/height")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(1080.0);
+
+    if let Some(slides) = group.get_mut("slides").and_then(|v| v.as_array_mut()) {
+        for slide in slides {
+            if let Some(slide) = slide.as_object_mut() {
+                migrate_slide(slide, canvas_width, canvas_height);
+            }
+        }
+    }
+}
+
+fn migrate_slide(slide: &mut Map<String, JsonValue>, canvas_width: f64, canvas_height: f64) {
+    let text = slide.remove("text");
+    let background = slide.remove("background");
+
+    if !slide.get("objects").map(|v| v.is_array()).unwrap_or(false) {
+        slide.insert("objects".to_string(), json!([]));
+    }
+
+    // Collect bounds before mutably borrowing the objects array
+    let objects = slide["objects"].as_array().cloned().unwrap_or_default();
+    let min_z = objects
+        .iter()
+        .filter_map(|o| o.get("zIndex").and_then(|v| v.as_i64()))
+        .min()
+        .unwrap_or(1);
+    let max_z = objects
+        .iter()
+        .filter_map(|o| o.get("zIndex").and_then(|v| v.as_i64()))
+        .max()
+        .unwrap_or(0);
+
+    let mut background_color = None;
+
+    if let Some(objects) = slide.get_mut("objects").and_then(|v| v.as_array_mut()) {
+        objects.iter_mut().for_each(migrate_bold);
+
+        if let Some(background) = background {
+            let kind = background.get("type").and_then(|v| v.as_str());
+            let value = background.get("value").and_then(|v| v.as_str());
+
+            match (kind, value) {
+                (Some("color"), Some(color)) => background_color = Some(color.to_string()),
+                (Some("image"), Some(src)) => objects.insert(
+                    0,
+                    json!({
+                        "type": "image",
+                        "id": Uuid::new_v4().to_string(),
+                        "position": { "x": 0.0, "y": 0.0 },
+                        "size": { "width": canvas_width, "height": canvas_height },
+                        "rotation": 0.0,
+                        "zIndex": min_z - 1,
+                        "src": src,
+                        "imageType": "background",
+                        "objectFit": "cover",
+                    }),
+                ),
+                (Some("video"), Some(src)) => objects.insert(
+                    0,
+                    json!({
+                        "type": "video",
+                        "id": Uuid::new_v4().to_string(),
+                        "position": { "x": 0.0, "y": 0.0 },
+                        "size": { "width": canvas_width, "height": canvas_height },
+                        "rotation": 0.0,
+                        "zIndex": min_z - 1,
+                        "src": src,
+                        "videoType": "background",
+                        "thumbnail": null,
+                        "autoPlay": true,
+                        "loop_video": true,
+                    }),
+                ),
+                _ => {}
+            }
+        }
+
+        if let Some(text) = text {
+            let content = text.get("content").and_then(|v| v.as_str()).unwrap_or("");
+            let font_size = text.get("fontSize").and_then(|v| v.as_f64()).unwrap_or(48.0);
+            let color = text
+                .get("color")
+                .and_then(|v| v.as_str())
+                .unwrap_or("rgba(255, 255, 255, 1)");
+            let horizontal = text
+                .get("alignment")
+                .and_then(|v| v.as_str())
+                .unwrap_or("center");
+
+            // Same placement as a new text box in the editor: centered at 75% of the canvas
+            let width = canvas_width * 0.75;
+            let height = canvas_height * 0.75;
+
+            objects.push(json!({
+                "type": "text",
+                "id": Uuid::new_v4().to_string(),
+                "position": {
+                    "x": (canvas_width - width) / 2.0,
+                    "y": (canvas_height - height) / 2.0,
+                },
+                "size": { "width": width, "height": height },
+                "rotation": 0.0,
+                "zIndex": max_z + 1,
+                "content": content,
+                "fontSize": font_size,
+                "color": color,
+                "alignment": { "horizontal": horizontal, "vertical": "center" },
+            }));
+        }
+    }
+
+    if let Some(color) = background_color {
+        slide
+            .entry("backgroundColor")
+            .or_insert_with(|| json!(color));
+    }
+}
+
+/// Replace the deprecated `bold` flag with an explicit font weight
+fn migrate_bold(object: &mut JsonValue) {
+    let Some(object) = object.as_object_mut() else {
+        return;
+    };
+
+    if let Some(bold) = object.remove("bold") {
+        if bold.as_bool() == Some(true) && !object.contains_key("fontWeight") {
+            object.insert("fontWeight".to_string(), json!(700));
+        }
+    }
+}
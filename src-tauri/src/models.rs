@@ -331,6 +331,45 @@ pub enum SlideObject {
     Shape(ShapeObject),
 }
 
+impl SlideObject {
+    pub fn id_mut(&mut self) -> &mut String {
+        match self {
+            SlideObject::Video(o) => &mut o.id,
+            SlideObject::Image(o) => &mut o.id,
+            SlideObject::Text(o) => &mut o.id,
+            SlideObject::Shape(o) => &mut o.id,
+        }
+    }
+
+    pub fn font_family(&self) -> Option<&str> {
+        match self {
+            SlideObject::Video(o) => o.font_family.as_deref(),
+            SlideObject::Image(o) => o.font_family.as_deref(),
+            SlideObject::Text(o) => o.font_family.as_deref(),
+            SlideObject::Shape(o) => o.font_family.as_deref(),
+        }
+    }
+
+    /// Media references (image/video sources and video thumbnails)
+    pub fn media_sources(&self) -> Vec<&String> {
+        match self {
+            SlideObject::Video(o) => std::iter::once(&o.src).chain(&o.thumbnail).collect(),
+            SlideObject::Image(o) => vec![&o.src],
+            SlideObject::Text(_) | SlideObject::Shape(_) => Vec::new(),
+        }
+    }
+
+    pub fn media_sources_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            SlideObject::Video(o) => std::iter::once(&mut o.src)
+                .chain(o.thumbnail.as_mut())
+                .collect(),
+            SlideObject::Image(o) => vec![&mut o.src],
+            SlideObject::Text(_) | SlideObject::Shape(_) => Vec::new(),
+        }
+    }
+}
+
 // ===== Slide & Library Types =====
 
 // Slide tag group for categorizing slides
@@ -341,6 +380,29 @@ pub struct SlideTagGroup {
     pub color: String,
 }
 
+/// What an audio cue does when its slide goes live
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCueAction {
+    Play, // Start the cue's audio, replacing any cue already playing
+    Stop, // Stop cue audio and background music
+}
+
+// Audio started or stopped when a slide is triggered
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioCue {
+    pub action: AudioCueAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>, // 0.0 - 1.0
+    #[serde(rename = "loop", default, skip_serializing_if = "Option::is_none")]
+    pub loop_audio: Option<bool>,
+}
+
 // SlideData structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SlideData {
@@ -356,6 +418,43 @@ pub struct SlideData {
     pub text: Option<SlideText>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub background: Option<SlideBackground>,
+    #[serde(rename = "audioCue", default, skip_serializing_if = "Option::is_none")]
+    pub audio_cue: Option<AudioCue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>, // Song section, e.g. "Verse 2", set by song imports
+}
+
+impl SlideData {
+    /// Media references of the slide's objects, legacy background and audio cue
+    pub fn media_sources(&self) -> Vec<&String> {
+        let background = match &self.background {
+            Some(SlideBackground::Image(src)) | Some(SlideBackground::Video(src)) => Some(src),
+            _ => None,
+        };
+
+        self.objects
+            .iter()
+            .flatten()
+            .flat_map(|object| object.media_sources())
+            .chain(background)
+            .chain(self.audio_cue.as_ref().and_then(|cue| cue.src.as_ref()))
+            .collect()
+    }
+
+    pub fn media_sources_mut(&mut self) -> Vec<&mut String> {
+        let background = match &mut self.background {
+            Some(SlideBackground::Image(src)) | Some(SlideBackground::Video(src)) => Some(src),
+            _ => None,
+        };
+
+        self.objects
+            .iter_mut()
+            .flatten()
+            .flat_map(|object| object.media_sources_mut())
+            .chain(background)
+            .chain(self.audio_cue.as_mut().and_then(|cue| cue.src.as_mut()))
+            .collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -385,20 +484,46 @@ pub struct SlideGroup {
     pub slides: Vec<SlideData>,
     #[serde(rename = "canvasSize")]
     pub canvas_size: CanvasSize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits: Option<SongCredits>, // Set for imported songs
     #[serde(rename = "createdAt")]
     pub created_at: String,
     #[serde(rename = "updatedAt")]
     pub updated_at: String,
 }
 
+/// Credits of an imported song, written back out when it is exported
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SongCredits {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ccli_number: Option<String>,
+}
+
+impl SongCredits {
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty() && self.copyright.is_none() && self.ccli_number.is_none()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Library {
     pub id: String,
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>, // On-disk schema version, see migrations.rs
     pub name: String,
     #[serde(rename = "slideGroups")]
     pub slide_groups: Vec<SlideGroup>,
     #[serde(default)]
     pub order: Option<i32>,
+    #[serde(default)]
+    pub revision: u64, // Bumped on every save, see repository::save_library_checked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineage: Vec<String>, // Hashes of the versions this was edited from, see folder_sync
     #[serde(rename = "createdAt")]
     pub created_at: String,
     #[serde(rename = "updatedAt")]
@@ -418,10 +543,16 @@ pub struct PlaylistItem {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Playlist {
     pub id: String,
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>, // On-disk schema version, see migrations.rs
     pub name: String,
     pub items: Vec<PlaylistItem>,
     #[serde(default)]
     pub order: Option<i32>,
+    #[serde(default)]
+    pub revision: u64, // Bumped on every save, see repository::save_playlist_checked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineage: Vec<String>, // Hashes of the versions this was edited from, see folder_sync
     #[serde(rename = "createdAt")]
     pub created_at: String,
     #[serde(rename = "updatedAt")]
@@ -433,9 +564,11 @@ pub struct Playlist {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MediaItem {
     pub id: String,
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>, // On-disk schema version, see migrations.rs
     pub name: String,
     #[serde(rename = "type")]
-    pub media_type: String, // "image" or "video"
+    pub media_type: String, // "image", "video" or "audio"
     pub source: String,
     pub thumbnail: Option<String>,
     pub duration: Option<f64>,
@@ -446,11 +579,25 @@ pub struct MediaItem {
     pub metadata: Option<JsonValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hash: Option<String>, // SHA256 hash for deduplication
+    #[serde(rename = "linkedPath", default, skip_serializing_if = "Option::is_none")]
+    pub linked_path: Option<String>, // Absolute path of a file used in place instead of copied
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool, // Linked file was not found at the last check
+}
+
+impl MediaItem {
+    /// What slides refer to the item's file by: the path of a linked file, or
+    /// the file name of a copy in the media directory
+    pub fn slide_source(&self) -> &str {
+        self.linked_path.as_deref().unwrap_or(&self.source)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MediaPlaylist {
     pub id: String,
+    #[serde(rename = "schemaVersion", default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>, // On-disk schema version, see migrations.rs
     pub name: String,
     #[serde(rename = "mediaItems")]
     pub media_items: Vec<MediaItem>,
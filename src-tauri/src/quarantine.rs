@@ -0,0 +1,391 @@
+//! Quarantine for documents that fail to load, with lenient recovery.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::migrations;
+use crate::models::{
+    Library, MediaItem, MediaPlaylist, Playlist, PlaylistItem, SlideData, SlideGroup, SlideObject,
+};
+use crate::storage::{self, DocumentKind};
+use crate::storage_watcher;
+
+/// A file moved out of the data directories because it could not be loaded
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedFile {
+    pub id: String, // File name inside the quarantine directory
+    pub kind: DocumentKind,
+    pub file_name: String, // Original file name, e.g. "<library id>.json"
+    pub original_path: PathBuf,
+    pub quarantine_path: PathBuf,
+    pub error: String,
+    pub quarantined_at: String,
+}
+
+/// A document left where it is because it could not be read for now (e.g.
+/// locked by a sync tool, or not readable with the current permissions)
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub kind: DocumentKind,
+    pub path: PathBuf,
+    pub error: AppError,
+}
+
+/// Problems found while loading a set of documents
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    pub quarantined: Vec<QuarantinedFile>,
+    pub skipped: Vec<SkippedFile>, // Not loaded this time, but not broken either
+}
+
+impl RecoveryReport {
+    /// Record a document that failed to load as skipped unless its content
+    /// is broken: anything else may well load next time, so it is left in
+    /// place rather than quarantined. Returns whether it was skipped.
+    pub fn skip_unless_corrupt(
+        &mut self,
+        kind: DocumentKind,
+        path: &Path,
+        error: &AppError,
+    ) -> bool {
+        if error.kind == ErrorKind::Corrupt {
+            return false;
+        }
+        eprintln!("Skipped unreadable file {:?}: {}", path, error.message);
+        self.skipped.push(SkippedFile {
+            kind,
+            path: path.to_path_buf(),
+            error: error.clone(),
+        });
+        true
+    }
+}
+
+/// Documents loaded from disk along with anything that had to be quarantined
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadResult<T> {
+    pub items: Vec<T>,
+    pub recovery: RecoveryReport,
+}
+
+/// A nested element dropped during lenient re-parsing
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedElement {
+    pub pointer: String, // JSON pointer to the element in the original document
+    pub error: String,
+}
+
+/// Result of restoring a quarantined file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryOutcome {
+    pub file: QuarantinedFile,
+    pub dropped: Vec<DroppedElement>,
+}
+
+// ===== Loading =====
+
+/// Load every document of a kind, quarantining files that are corrupt
+pub fn load_documents<T: DeserializeOwned>(
+    app: &AppHandle,
+    kind: DocumentKind,
+) -> AppResult<LoadResult<T>> {
+    let dir = kind.dir(app)?;
+
+    let mut items = Vec::new();
+    let mut recovery = RecoveryReport::default();
+
+    for path in storage::list_json_files(&dir)? {
+        let error = match migrations::read_document(app, kind, &path) {
+            Ok(item) => {
+                items.push(item);
+                continue;
+            }
+            Err(error) => error,
+        };
+        if recovery.skip_unless_corrupt(kind, &path, &error) {
+            continue;
+        }
+
+        match quarantine_file(app, kind, &path, &error.message) {
+            Ok(file) => {
+                eprintln!("Quarantined unreadable file {:?}: {}", path, error.message);
+                recovery.quarantined.push(file);
+            }
+            Err(e) => eprintln!("Failed to quarantine file {:?}: {}", path, e.message),
+        }
+    }
+
+    Ok(LoadResult { items, recovery })
+}
+
+// ===== Quarantine Index =====
+
+fn get_index_path(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(storage::get_quarantine_dir(app)?.join("index.json"))
+}
+
+fn read_index(app: &AppHandle) -> AppResult<Vec<QuarantinedFile>> {
+    let path = get_index_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    storage::read_json_file(&path)
+}
+
+fn write_index(app: &AppHandle, index: &[QuarantinedFile]) -> AppResult<()> {
+    storage::write_json_file(&get_index_path(app)?, index)
+}
+
+/// Move a file into the quarantine directory and record why
+pub fn quarantine_file(
+    app: &AppHandle,
+    kind: DocumentKind,
+    path: &Path,
+    error: &str,
+) -> AppResult<QuarantinedFile> {
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| AppError::invalid_path(path))?
+        .to_string();
+
+    let (id, quarantine_path) = allocate_quarantine_path(app, kind, &file_name)?;
+    move_file(path, &quarantine_path)?;
+
+    record(app, id, kind, file_name, path, quarantine_path, error)
+}
+
+/// Write content that failed to load (e.g. a database row) into quarantine
+pub fn quarantine_content(
+    app: &AppHandle,
+    kind: DocumentKind,
+    file_name: &str,
+    content: &[u8],
+    original_path: &Path,
+    error: &str,
+) -> AppResult<QuarantinedFile> {
+    let (id, quarantine_path) = allocate_quarantine_path(app, kind, file_name)?;
+    storage::write_file(&quarantine_path, content)?;
+
+    record(
+        app,
+        id,
+        kind,
+        file_name.to_string(),
+        original_path,
+        quarantine_path,
+        error,
+    )
+}
+
+fn allocate_quarantine_path(
+    app: &AppHandle,
+    kind: DocumentKind,
+    file_name: &str,
+) -> AppResult<(String, PathBuf)> {
+    let quarantine_dir = storage::get_quarantine_dir(app)?.join(kind.dir_name());
+    fs::create_dir_all(&quarantine_dir)?;
+
+    // Prefix with a timestamp so repeated failures of the same file never collide
+    let id = format!(
+        "{}_{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%3f"),
+        file_name
+    );
+    let quarantine_path = quarantine_dir.join(&id);
+    Ok((id, quarantine_path))
+}
+
+fn record(
+    app: &AppHandle,
+    id: String,
+    kind: DocumentKind,
+    file_name: String,
+    original_path: &Path,
+    quarantine_path: PathBuf,
+    error: &str,
+) -> AppResult<QuarantinedFile> {
+    let file = QuarantinedFile {
+        id,
+        kind,
+        file_name,
+        original_path: original_path.to_path_buf(),
+        quarantine_path,
+        error: error.to_string(),
+        quarantined_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut index = read_index(app)?;
+    index.push(file.clone());
+    write_index(app, &index)?;
+
+    Ok(file)
+}
+
+/// List quarantined files that are still present on disk
+pub fn list_quarantined_files(app: &AppHandle) -> AppResult<Vec<QuarantinedFile>> {
+    let index = read_index(app)?;
+    Ok(index
+        .into_iter()
+        .filter(|file| file.quarantine_path.exists())
+        .collect())
+}
+
+/// Rename a file, falling back to copy + delete across volumes
+fn move_file(source: &Path, dest: &Path) -> AppResult<()> {
+    storage_watcher::record_own_write(source, None);
+    if fs::rename(source, dest).is_err() {
+        fs::copy(source, dest)?;
+        fs::remove_file(source)?;
+    }
+    Ok(())
+}
+
+// ===== Lenient Recovery =====
+
+/// Re-parse a quarantined file, dropping only the nested elements that fail to
+/// deserialize. The repaired document is returned for the caller to save.
+pub fn repair_quarantined_file(
+    app: &AppHandle,
+    id: &str,
+) -> AppResult<(QuarantinedFile, JsonValue, Vec<DroppedElement>)> {
+    let file = read_index(app)?
+        .into_iter()
+        .find(|file| file.id == id)
+        .ok_or_else(|| {
+            AppError::not_found(format!("Quarantined file not found: {}", id))
+                .with_code("QUARANTINED_FILE_NOT_FOUND")
+                .with_entity(id)
+        })?;
+
+    // Truncated or otherwise invalid JSON cannot be repaired structurally
+    let content = fs::read_to_string(&file.quarantine_path)
+        .map_err(|e| AppError::io(e, &file.quarantine_path))?;
+    let mut document: JsonValue = serde_json::from_str(&content).map_err(|e| {
+        AppError::corrupt(format!("File is not valid JSON: {}", e))
+            .with_code("INVALID_JSON")
+            .with_entity(id)
+            .with_path(&file.quarantine_path)
+    })?;
+
+    let mut dropped = Vec::new();
+    match file.kind {
+        DocumentKind::Library => {
+            if let Some(groups) = document.get_mut("slideGroups") {
+                prune_slide_groups(groups, "/slideGroups", &mut dropped);
+            }
+            validate::<Library>(&document)?;
+        }
+        DocumentKind::Playlist => {
+            if let Some(items) = document.get_mut("items").and_then(|v| v.as_array_mut()) {
+                for (i, item) in items.iter_mut().enumerate() {
+                    if let Some(group) = item.get_mut("slideGroup") {
+                        prune_slides(group, &format!("/items/{}/slideGroup", i), &mut dropped);
+                    }
+                }
+            }
+            if let Some(items) = document.get_mut("items") {
+                retain_valid::<PlaylistItem>(items, "/items", &mut dropped);
+            }
+            validate::<Playlist>(&document)?;
+        }
+        DocumentKind::MediaItem => {
+            validate::<MediaItem>(&document)?;
+        }
+        DocumentKind::MediaPlaylist => {
+            if let Some(items) = document.get_mut("mediaItems") {
+                retain_valid::<MediaItem>(items, "/mediaItems", &mut dropped);
+            }
+            validate::<MediaPlaylist>(&document)?;
+        }
+    }
+
+    Ok((file, document, dropped))
+}
+
+/// Remove a file from quarantine once its document has been restored
+pub fn release_quarantined_file(app: &AppHandle, id: &str) -> AppResult<()> {
+    let mut index = read_index(app)?;
+    if let Some(position) = index.iter().position(|file| file.id == id) {
+        let file = index.remove(position);
+        storage::delete_file(&file.quarantine_path)?;
+        write_index(app, &index)?;
+    }
+    Ok(())
+}
+
+fn validate<T: DeserializeOwned>(document: &JsonValue) -> AppResult<()> {
+    serde_json::from_value::<T>(document.clone())
+        .map(|_| ())
+        .map_err(|e| {
+            AppError::corrupt(format!("Could not recover document: {}", e))
+                .with_code("UNRECOVERABLE_DOCUMENT")
+        })
+}
+
+/// Drop broken objects, then broken slides, then broken groups
+fn prune_slide_groups(groups: &mut JsonValue, pointer: &str, dropped: &mut Vec<DroppedElement>) {
+    if let Some(items) = groups.as_array_mut() {
+        for (i, group) in items.iter_mut().enumerate() {
+            prune_slides(group, &format!("{}/{}", pointer, i), dropped);
+        }
+    }
+    retain_valid::<SlideGroup>(groups, pointer, dropped);
+}
+
+fn prune_slides(group: &mut JsonValue, pointer: &str, dropped: &mut Vec<DroppedElement>) {
+    let Some(slides) = group.get_mut("slides") else {
+        return;
+    };
+
+    if let Some(items) = slides.as_array_mut() {
+        for (i, slide) in items.iter_mut().enumerate() {
+            if let Some(objects) = slide.get_mut("objects") {
+                retain_valid::<SlideObject>(
+                    objects,
+                    &format!("{}/slides/{}/objects", pointer, i),
+                    dropped,
+                );
+            }
+        }
+    }
+    retain_valid::<SlideData>(slides, &format!("{}/slides", pointer), dropped);
+}
+
+/// Keep only the array elements that deserialize as `T`
+fn retain_valid<T: DeserializeOwned>(
+    array: &mut JsonValue,
+    pointer: &str,
+    dropped: &mut Vec<DroppedElement>,
+) {
+    let Some(items) = array.as_array_mut() else {
+        return;
+    };
+
+    let mut index = 0;
+    items.retain(|item| {
+        let keep = match serde_json::from_value::<T>(item.clone()) {
+            Ok(_) => true,
+            Err(e) => {
+                dropped.push(DroppedElement {
+                    pointer: format!("{}/{}", pointer, index),
+                    error: e.to_string(),
+                });
+                false
+            }
+        };
+        index += 1;
+        keep
+    });
+}
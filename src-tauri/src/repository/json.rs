@@ -0,0 +1,308 @@
+//! Repository backed by one JSON file per document in the app data directory.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::AppHandle;
+
+use super::{Repository, StorageEngine};
+use crate::error::{AppError, AppResult};
+use crate::migrations;
+use crate::models::{Library, MediaItem, MediaPlaylist, Playlist};
+use crate::quarantine::{self, LoadResult};
+use crate::storage::{self, DocumentKind};
+
+/// Location of the persisted media hash index
+pub fn get_hash_index_path(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(storage::get_app_data_dir(app)?
+        .join("media")
+        .join("hash-index.json"))
+}
+
+/// Content hash of every media item, so deduplication doesn't have to read
+/// every metadata file on each import
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct HashIndex {
+    hashes: BTreeMap<String, Option<String>>, // Media id -> content hash
+    #[serde(skip)]
+    ids: HashMap<String, String>, // Content hash -> media id
+}
+
+impl HashIndex {
+    /// Read every metadata file. Unreadable files are recorded without a hash
+    /// so they don't make the index look incomplete.
+    fn build(metadata_dir: &PathBuf) -> AppResult<Self> {
+        let (items, failures) = storage::read_json_files::<MediaItem>(metadata_dir)?;
+
+        let mut index = Self::default();
+        for item in items {
+            index.hashes.insert(item.id, item.hash);
+        }
+        for failure in failures {
+            eprintln!(
+                "Failed to read file {:?}: {}",
+                failure.path, failure.error.message
+            );
+            if let Some(id) = storage::get_file_stem(&failure.path) {
+                index.hashes.insert(id, None);
+            }
+        }
+        index.rebuild_ids();
+        Ok(index)
+    }
+
+    fn rebuild_ids(&mut self) {
+        self.ids = self
+            .hashes
+            .iter()
+            .filter_map(|(id, hash)| Some((hash.clone()?, id.clone())))
+            .collect();
+    }
+
+    /// Record an item's hash, returning whether anything changed
+    fn insert(&mut self, id: &str, hash: Option<&str>) -> bool {
+        let previous = self.hashes.insert(id.to_string(), hash.map(str::to_string));
+        if previous.as_ref().map(|h| h.as_deref()) == Some(hash) {
+            return false;
+        }
+        if let Some(old) = previous.flatten() {
+            if self.ids.get(&old).map(String::as_str) == Some(id) {
+                self.ids.remove(&old);
+            }
+        }
+        if let Some(hash) = hash {
+            self.ids.insert(hash.to_string(), id.to_string());
+        }
+        true
+    }
+
+    /// Whether the persisted index at `path` was written after every metadata
+    /// file and the directory last changed. Another machine's sync can replace
+    /// a file without changing how many there are.
+    fn is_current(path: &Path, metadata_dir: &PathBuf) -> AppResult<bool> {
+        let written = fs::metadata(path)?.modified()?;
+        let mut paths = storage::list_json_files(metadata_dir)?;
+        paths.push(metadata_dir.clone());
+        for path in paths {
+            if fs::metadata(&path)?.modified()? > written {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn remove(&mut self, id: &str) -> bool {
+        let Some(previous) = self.hashes.remove(id) else {
+            return false;
+        };
+        if let Some(old) = previous {
+            if self.ids.get(&old).map(String::as_str) == Some(id) {
+                self.ids.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+pub struct JsonRepository {
+    app: AppHandle,
+    hash_index: Mutex<Option<HashIndex>>, // Loaded on first use
+}
+
+impl JsonRepository {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            hash_index: Mutex::new(None),
+        }
+    }
+
+    /// Lock the hash index, loading it from disk on first use
+    fn lock_hash_index(&self) -> AppResult<MutexGuard<'_, Option<HashIndex>>> {
+        let mut guard = self
+            .hash_index
+            .lock()
+            .map_err(|e| AppError::internal(format!("Failed to lock media hash index: {}", e)))?;
+
+        if guard.is_none() {
+            let path = get_hash_index_path(&self.app)?;
+            let metadata_dir = storage::get_media_metadata_dir(&self.app)?;
+            // Left unloaded when stale, so `ensure_hash_index` rebuilds it
+            if path.exists() && HashIndex::is_current(&path, &metadata_dir)? {
+                match storage::read_json_file::<HashIndex>(&path) {
+                    Ok(mut index) => {
+                        index.rebuild_ids();
+                        *guard = Some(index);
+                    }
+                    Err(e) => eprintln!("Rebuilding unreadable media hash index: {}", e.message),
+                }
+            }
+        }
+
+        Ok(guard)
+    }
+
+    /// Rebuild the index when it is missing or doesn't cover every metadata
+    /// file (e.g. after a quarantined file was recovered, which bypasses the
+    /// repository). Returns whether it was rebuilt.
+    fn ensure_hash_index(&self, guard: &mut Option<HashIndex>) -> AppResult<bool> {
+        let metadata_dir = storage::get_media_metadata_dir(&self.app)?;
+        let file_count = storage::list_json_files(&metadata_dir)?.len();
+        if guard.as_ref().map(|index| index.hashes.len()) == Some(file_count) {
+            return Ok(false);
+        }
+
+        let index = HashIndex::build(&metadata_dir)?;
+        storage::write_json_file(&get_hash_index_path(&self.app)?, &index)?;
+        *guard = Some(index);
+        Ok(true)
+    }
+
+    /// Apply a change to the hash index and persist it if anything changed
+    fn update_hash_index(&self, change: impl FnOnce(&mut HashIndex) -> bool) -> AppResult<()> {
+        let mut guard = self.lock_hash_index()?;
+        let changed = guard.as_mut().is_some_and(change);
+        if !self.ensure_hash_index(&mut guard)? && changed {
+            if let Some(index) = guard.as_ref() {
+                storage::write_json_file(&get_hash_index_path(&self.app)?, index)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load<T: DeserializeOwned>(&self, kind: DocumentKind) -> AppResult<LoadResult<T>> {
+        quarantine::load_documents(&self.app, kind)
+    }
+
+    fn get<T: DeserializeOwned>(&self, kind: DocumentKind, id: &str) -> AppResult<Option<T>> {
+        let path = kind.dir(&self.app)?.join(format!("{}.json", id));
+        if !path.exists() {
+            return Ok(None);
+        }
+        migrations::read_document(&self.app, kind, &path).map(Some)
+    }
+
+    fn save<T: Serialize>(&self, kind: DocumentKind, id: &str, document: &T) -> AppResult<()> {
+        let path = kind.dir(&self.app)?.join(format!("{}.json", id));
+        storage::write_json_file(&path, document)
+    }
+
+    fn delete(&self, kind: DocumentKind, id: &str) -> AppResult<()> {
+        let path = kind.dir(&self.app)?.join(format!("{}.json", id));
+        storage::delete_file(&path)
+    }
+}
+
+impl Repository for JsonRepository {
+    fn engine(&self) -> StorageEngine {
+        StorageEngine::Json
+    }
+
+    fn invalidate_caches(&self) {
+        // Reloaded on next use, or rebuilt if a metadata file changed since it was written
+        *self.hash_index.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    // ===== Libraries =====
+
+    fn load_libraries(&self) -> AppResult<LoadResult<Library>> {
+        self.load(DocumentKind::Library)
+    }
+
+    fn get_library(&self, id: &str) -> AppResult<Option<Library>> {
+        self.get(DocumentKind::Library, id)
+    }
+
+    fn save_library(&self, library: &Library) -> AppResult<()> {
+        self.save(DocumentKind::Library, &library.id, library)
+    }
+
+    fn delete_library(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::Library, id)
+    }
+
+    // ===== Playlists =====
+
+    fn load_playlists(&self) -> AppResult<LoadResult<Playlist>> {
+        self.load(DocumentKind::Playlist)
+    }
+
+    fn get_playlist(&self, id: &str) -> AppResult<Option<Playlist>> {
+        self.get(DocumentKind::Playlist, id)
+    }
+
+    fn save_playlist(&self, playlist: &Playlist) -> AppResult<()> {
+        self.save(DocumentKind::Playlist, &playlist.id, playlist)
+    }
+
+    fn delete_playlist(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::Playlist, id)
+    }
+
+    // ===== Media Items =====
+
+    fn load_media_items(&self) -> AppResult<LoadResult<MediaItem>> {
+        self.load(DocumentKind::MediaItem)
+    }
+
+    fn get_media_item(&self, id: &str) -> AppResult<Option<MediaItem>> {
+        self.get(DocumentKind::MediaItem, id)
+    }
+
+    fn find_media_by_hash(&self, hash: &str) -> AppResult<Option<MediaItem>> {
+        let id = {
+            let mut guard = self.lock_hash_index()?;
+            self.ensure_hash_index(&mut guard)?;
+            guard
+                .as_ref()
+                .and_then(|index| index.ids.get(hash).cloned())
+        };
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        match self.get_media_item(&id)? {
+            Some(item) if item.hash.as_deref() == Some(hash) => Ok(Some(item)),
+            // Edited or removed outside the repository; drop the stale entry
+            // so the next lookup falls through to a rebuild
+            item => {
+                self.update_hash_index(|index| match &item {
+                    Some(item) => index.insert(&item.id, item.hash.as_deref()),
+                    None => index.remove(&id),
+                })?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn save_media_item(&self, item: &MediaItem) -> AppResult<()> {
+        self.save(DocumentKind::MediaItem, &item.id, item)?;
+        self.update_hash_index(|index| index.insert(&item.id, item.hash.as_deref()))
+    }
+
+    fn delete_media_item(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::MediaItem, id)?;
+        self.update_hash_index(|index| index.remove(id))
+    }
+
+    // ===== Media Playlists =====
+
+    fn load_media_playlists(&self) -> AppResult<LoadResult<MediaPlaylist>> {
+        self.load(DocumentKind::MediaPlaylist)
+    }
+
+    fn get_media_playlist(&self, id: &str) -> AppResult<Option<MediaPlaylist>> {
+        self.get(DocumentKind::MediaPlaylist, id)
+    }
+
+    fn save_media_playlist(&self, playlist: &MediaPlaylist) -> AppResult<()> {
+        self.save(DocumentKind::MediaPlaylist, &playlist.id, playlist)
+    }
+
+    fn delete_media_playlist(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::MediaPlaylist, id)
+    }
+}
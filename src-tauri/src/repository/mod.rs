@@ -0,0 +1,449 @@
+//! Pluggable persistence for libraries, playlists and media.
+//!
+//! Data commands talk to the active [`Repository`]; the JSON-directory and
+//! SQLite engines can be swapped at runtime with a one-shot migration.
+
+pub mod json;
+pub mod sqlite;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::models::{Library, MediaItem, MediaPlaylist, Playlist};
+use crate::quarantine::LoadResult;
+use crate::storage::{self, DocumentKind};
+
+use json::JsonRepository;
+use sqlite::SqliteRepository;
+
+/// Available storage engines
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageEngine {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+/// Storage engine configuration persisted in the settings directory
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageConfig {
+    pub engine: StorageEngine,
+}
+
+/// Persistence operations used by the data commands
+pub trait Repository: Send + Sync {
+    fn engine(&self) -> StorageEngine;
+
+    // Libraries
+    fn load_libraries(&self) -> AppResult<LoadResult<Library>>;
+    fn get_library(&self, id: &str) -> AppResult<Option<Library>>;
+    fn save_library(&self, library: &Library) -> AppResult<()>;
+    fn delete_library(&self, id: &str) -> AppResult<()>;
+
+    // Playlists
+    fn load_playlists(&self) -> AppResult<LoadResult<Playlist>>;
+    fn get_playlist(&self, id: &str) -> AppResult<Option<Playlist>>;
+    fn save_playlist(&self, playlist: &Playlist) -> AppResult<()>;
+    fn delete_playlist(&self, id: &str) -> AppResult<()>;
+
+    // Media items
+    fn load_media_items(&self) -> AppResult<LoadResult<MediaItem>>;
+    fn get_media_item(&self, id: &str) -> AppResult<Option<MediaItem>>;
+    fn find_media_by_hash(&self, hash: &str) -> AppResult<Option<MediaItem>>;
+    fn save_media_item(&self, item: &MediaItem) -> AppResult<()>;
+    fn delete_media_item(&self, id: &str) -> AppResult<()>;
+
+    // Media playlists
+    fn load_media_playlists(&self) -> AppResult<LoadResult<MediaPlaylist>>;
+    fn get_media_playlist(&self, id: &str) -> AppResult<Option<MediaPlaylist>>;
+    fn save_media_playlist(&self, playlist: &MediaPlaylist) -> AppResult<()>;
+    fn delete_media_playlist(&self, id: &str) -> AppResult<()>;
+
+    /// Drop anything cached from disk, after files were changed outside the app
+    fn invalidate_caches(&self) {}
+
+    /// Write out anything held back, so the data files can be copied
+    fn flush(&self) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+// ===== Active Repository =====
+
+/// Managed state holding the active repository
+pub struct RepositoryState {
+    repository: RwLock<Arc<dyn Repository>>,
+}
+
+impl RepositoryState {
+    /// Open the engine selected in the storage config, falling back to JSON on failure
+    pub fn load(app: &AppHandle) -> Self {
+        let config = read_config(app).unwrap_or_default();
+
+        let repository = open_repository(app, config.engine).unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to open {:?} storage, falling back to JSON: {}",
+                config.engine, e.message
+            );
+            Arc::new(JsonRepository::new(app.clone()))
+        });
+
+        Self {
+            repository: RwLock::new(repository),
+        }
+    }
+
+    fn current(&self) -> Arc<dyn Repository> {
+        match self.repository.read() {
+            Ok(repository) => repository.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn replace(&self, repository: Arc<dyn Repository>) {
+        match self.repository.write() {
+            Ok(mut current) => *current = repository,
+            Err(poisoned) => *poisoned.into_inner() = repository,
+        }
+    }
+}
+
+/// Get the active repository
+pub fn current(app: &AppHandle) -> Arc<dyn Repository> {
+    app.state::<RepositoryState>().current()
+}
+
+/// Reopen the configured engine, after the data directory changed
+pub fn reload(app: &AppHandle) -> AppResult<()> {
+    let config = read_config(app)?;
+    let repository = open_repository(app, config.engine)?;
+    app.state::<RepositoryState>().replace(repository);
+    Ok(())
+}
+
+fn open_repository(app: &AppHandle, engine: StorageEngine) -> AppResult<Arc<dyn Repository>> {
+    Ok(match engine {
+        StorageEngine::Json => Arc::new(JsonRepository::new(app.clone())),
+        StorageEngine::Sqlite => Arc::new(SqliteRepository::open(app.clone())?),
+    })
+}
+
+fn get_config_path(app: &AppHandle) -> AppResult<std::path::PathBuf> {
+    Ok(storage::get_settings_dir(app)?.join("storage.json"))
+}
+
+fn read_config(app: &AppHandle) -> AppResult<StorageConfig> {
+    let path = get_config_path(app)?;
+    if !path.exists() {
+        return Ok(StorageConfig::default());
+    }
+    storage::read_json_file(&path)
+}
+
+// ===== Engine Migration =====
+
+/// Counts of documents copied during an engine migration
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineMigrationSummary {
+    pub from: StorageEngine,
+    pub to: StorageEngine,
+    pub libraries: usize,
+    pub playlists: usize,
+    pub media_items: usize,
+    pub media_playlists: usize,
+}
+
+/// Copy every document from the active engine into `target` and switch to it.
+///
+/// The source data is left untouched so the previous engine can be selected again.
+pub fn migrate_engine(app: &AppHandle, target: StorageEngine) -> AppResult<EngineMigrationSummary> {
+    let source = current(app);
+    if source.engine() == target {
+        return Err(AppError::conflict(format!(
+            "Storage is already using the {:?} engine",
+            target
+        )));
+    }
+
+    let destination = open_repository(app, target)?;
+
+    // No save may land in the source after it was read
+    let _guard = lock_checked_saves();
+    let libraries = source.load_libraries()?.items;
+    let playlists = source.load_playlists()?.items;
+    let media_items = source.load_media_items()?.items;
+    let media_playlists = source.load_media_playlists()?.items;
+
+    // The target may still hold documents from when it was last in use
+    for library in &libraries {
+        save_library_replacing(destination.as_ref(), &mut library.clone())?;
+    }
+    for playlist in &playlists {
+        save_playlist_replacing(destination.as_ref(), &mut playlist.clone())?;
+    }
+    for item in &media_items {
+        destination.save_media_item(item)?;
+    }
+    for playlist in &media_playlists {
+        destination.save_media_playlist(playlist)?;
+    }
+
+    // Only switch once everything has been copied
+    storage::write_json_file(
+        &get_config_path(app)?,
+        &StorageConfig { engine: target },
+    )?;
+    app.state::<RepositoryState>().replace(destination);
+
+    Ok(EngineMigrationSummary {
+        from: source.engine(),
+        to: target,
+        libraries: libraries.len(),
+        playlists: playlists.len(),
+        media_items: media_items.len(),
+        media_playlists: media_playlists.len(),
+    })
+}
+
+// ===== Checked Saves =====
+
+// Keeps another save from landing between the revision check and the write
+static CHECKED_SAVES: Mutex<()> = Mutex::new(());
+
+/// Hold off checked saves while a document is read, changed and written
+/// outside the checked save functions (e.g. by a folder sync merge)
+pub fn lock_checked_saves() -> MutexGuard<'static, ()> {
+    CHECKED_SAVES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Compare the revision a client edited against the stored document
+fn check_revision<T: Serialize>(
+    kind: DocumentKind,
+    id: &str,
+    expected: u64,
+    stored: Option<(u64, &T)>,
+) -> AppResult<u64> {
+    match stored {
+        Some((revision, document)) if revision != expected => Err(AppError::conflict(format!(
+            "{:?} {} was changed elsewhere (revision {}, edited from {})",
+            kind, id, revision, expected
+        ))
+        .with_code("SAVE_CONFLICT")
+        .with_entity(id)
+        .with_current(serde_json::to_value(document)?)),
+        // A document deleted elsewhere is simply saved again
+        _ => Ok(expected + 1),
+    }
+}
+
+/// Save a library edited from `library.revision`, bumping the revision and
+/// adding the version it replaces to its lineage.
+///
+/// Fails with a `SAVE_CONFLICT` carrying the stored library if another window
+/// or machine saved it since. Returns the new revision.
+pub fn save_library_checked(repository: &dyn Repository, library: &mut Library) -> AppResult<u64> {
+    let _guard = lock_checked_saves();
+    let stored = repository.get_library(&library.id)?;
+    library.revision = check_revision(
+        DocumentKind::Library,
+        &library.id,
+        library.revision,
+        stored.as_ref().map(|l| (l.revision, l)),
+    )?;
+    if let Some(stored) = &stored {
+        library.lineage =
+            folder_sync::extend_lineage(&stored.lineage, folder_sync::version_hash(stored)?);
+    }
+    repository.save_library(library)?;
+    Ok(library.revision)
+}
+
+/// Save a playlist edited from `playlist.revision`; see [`save_library_checked`]
+pub fn save_playlist_checked(
+    repository: &dyn Repository,
+    playlist: &mut Playlist,
+) -> AppResult<u64> {
+    let _guard = lock_checked_saves();
+    let stored = repository.get_playlist(&playlist.id)?;
+    playlist.revision = check_revision(
+        DocumentKind::Playlist,
+        &playlist.id,
+        playlist.revision,
+        stored.as_ref().map(|p| (p.revision, p)),
+    )?;
+    if let Some(stored) = &stored {
+        playlist.lineage =
+            folder_sync::extend_lineage(&stored.lineage, folder_sync::version_hash(stored)?);
+    }
+    repository.save_playlist(playlist)?;
+    Ok(playlist.revision)
+}
+
+/// Revision for a document replacing the stored one: past it, so windows
+/// still holding the replaced version get a save conflict
+fn replacing_revision(revision: u64, stored: Option<u64>) -> u64 {
+    stored.map_or(revision, |stored| revision.max(stored + 1))
+}
+
+/// Save a library that replaces the stored one rather than being edited from
+/// it (a restore, recovery, import or engine copy). No revision check is made;
+/// the revision moves past the stored one and the replaced version is added to
+/// the lineage. Hold [`lock_checked_saves`] from reading the library until now.
+pub fn save_library_replacing(repository: &dyn Repository, library: &mut Library) -> AppResult<()> {
+    let stored = repository.get_library(&library.id)?;
+    library.revision = replacing_revision(library.revision, stored.as_ref().map(|l| l.revision));
+    if let Some(stored) = &stored {
+        library.lineage =
+            folder_sync::extend_lineage(&stored.lineage, folder_sync::version_hash(stored)?);
+    }
+    repository.save_library(library)
+}
+
+/// Save a playlist that replaces the stored one; see [`save_library_replacing`]
+pub fn save_playlist_replacing(
+    repository: &dyn Repository,
+    playlist: &mut Playlist,
+) -> AppResult<()> {
+    let stored = repository.get_playlist(&playlist.id)?;
+    playlist.revision = replacing_revision(playlist.revision, stored.as_ref().map(|p| p.revision));
+    if let Some(stored) = &stored {
+        playlist.lineage =
+            folder_sync::extend_lineage(&stored.lineage, folder_sync::version_hash(stored)?);
+    }
+    repository.save_playlist(playlist)
+}
+
+/// Save a raw JSON document replacing the stored one, returning it as saved.
+/// See [`save_library_replacing`]; media documents carry no revision.
+pub fn save_document_replacing(
+    repository: &dyn Repository,
+    kind: DocumentKind,
+    document: JsonValue,
+) -> AppResult<JsonValue> {
+    match kind {
+        DocumentKind::Library => {
+            let mut library: Library = serde_json::from_value(document)?;
+            save_library_replacing(repository, &mut library)?;
+            Ok(serde_json::to_value(library)?)
+        }
+        DocumentKind::Playlist => {
+            let mut playlist: Playlist = serde_json::from_value(document)?;
+            save_playlist_replacing(repository, &mut playlist)?;
+            Ok(serde_json::to_value(playlist)?)
+        }
+        DocumentKind::MediaItem | DocumentKind::MediaPlaylist => {
+            save_document(repository, kind, document.clone())?;
+            Ok(document)
+        }
+    }
+}
+
+// ===== Kind-Generic Helpers =====
+
+/// Check whether a document of the given kind exists
+pub fn document_exists(
+    repository: &dyn Repository,
+    kind: DocumentKind,
+    id: &str,
+) -> AppResult<bool> {
+    Ok(match kind {
+        DocumentKind::Library => repository.get_library(id)?.is_some(),
+        DocumentKind::Playlist => repository.get_playlist(id)?.is_some(),
+        DocumentKind::MediaItem => repository.get_media_item(id)?.is_some(),
+        DocumentKind::MediaPlaylist => repository.get_media_playlist(id)?.is_some(),
+    })
+}
+
+/// Get a document of the given kind as JSON
+pub fn get_document(
+    repository: &dyn Repository,
+    kind: DocumentKind,
+    id: &str,
+) -> AppResult<Option<JsonValue>> {
+    fn value<T: Serialize>(document: Option<T>) -> AppResult<Option<JsonValue>> {
+        Ok(document.map(serde_json::to_value).transpose()?)
+    }
+
+    match kind {
+        DocumentKind::Library => value(repository.get_library(id)?),
+        DocumentKind::Playlist => value(repository.get_playlist(id)?),
+        DocumentKind::MediaItem => value(repository.get_media_item(id)?),
+        DocumentKind::MediaPlaylist => value(repository.get_media_playlist(id)?),
+    }
+}
+
+/// Save a raw JSON document of the given kind
+pub fn save_document(
+    repository: &dyn Repository,
+    kind: DocumentKind,
+    document: JsonValue,
+) -> AppResult<()> {
+    match kind {
+        DocumentKind::Library => repository.save_library(&serde_json::from_value(document)?),
+        DocumentKind::Playlist => repository.save_playlist(&serde_json::from_value(document)?),
+        DocumentKind::MediaItem => repository.save_media_item(&serde_json::from_value(document)?),
+        DocumentKind::MediaPlaylist => {
+            repository.save_media_playlist(&serde_json::from_value(document)?)
+        }
+    }
+}
+
+/// Delete a document of the given kind
+pub fn delete_document(repository: &dyn Repository, kind: DocumentKind, id: &str) -> AppResult<()> {
+    match kind {
+        DocumentKind::Library => repository.delete_library(id),
+        DocumentKind::Playlist => repository.delete_playlist(id),
+        DocumentKind::MediaItem => repository.delete_media_item(id),
+        DocumentKind::MediaPlaylist => repository.delete_media_playlist(id),
+    }
+}
+
+/// Serialize every document of a kind as `(id, pretty JSON)` pairs
+pub fn export_documents(
+    repository: &dyn Repository,
+    kind: DocumentKind,
+) -> AppResult<Vec<(String, Vec<u8>)>> {
+    fn export<T: Serialize>(
+        items: Vec<T>,
+        id: impl Fn(&T) -> String,
+    ) -> AppResult<Vec<(String, Vec<u8>)>> {
+        items
+            .iter()
+            .map(|item| Ok((id(item), serde_json::to_vec_pretty(item)?)))
+            .collect()
+    }
+
+    match kind {
+        DocumentKind::Library => export(repository.load_libraries()?.items, |l| l.id.clone()),
+        DocumentKind::Playlist => export(repository.load_playlists()?.items, |p| p.id.clone()),
+        DocumentKind::MediaItem => export(repository.load_media_items()?.items, |m| m.id.clone()),
+        DocumentKind::MediaPlaylist => {
+            export(repository.load_media_playlists()?.items, |p| p.id.clone())
+        }
+    }
+}
+
+/// Refresh the copies of a media item held by media playlists after it changes
+pub fn update_media_playlist_copies(
+    repository: &dyn Repository,
+    item: &MediaItem,
+) -> AppResult<()> {
+    for mut playlist in repository.load_media_playlists()?.items {
+        let mut changed = false;
+        for copy in playlist.media_items.iter_mut().filter(|i| i.id == item.id) {
+            *copy = item.clone();
+            changed = true;
+        }
+        if changed {
+            repository.save_media_playlist(&playlist)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,425 @@
+//! Repository backed by a single SQLite database.
+//!
+//! Documents are stored as JSON alongside indexed columns (id, hash, tag,
+//! updated_at) so lookups do not need to deserialize every row.
+
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use tauri::AppHandle;
+
+use super::{Repository, StorageEngine};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::migrations;
+use crate::models::{Library, MediaItem, MediaPlaylist, Playlist};
+use crate::quarantine::{self, LoadResult, RecoveryReport};
+use crate::storage::{self, DocumentKind};
+
+/// Version of the table layout, stored in `PRAGMA user_version`
+const DATABASE_VERSION: i32 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS libraries (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_libraries_updated_at ON libraries(updated_at);
+
+    CREATE TABLE IF NOT EXISTS playlists (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_playlists_updated_at ON playlists(updated_at);
+
+    CREATE TABLE IF NOT EXISTS media_items (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        media_type TEXT NOT NULL,
+        hash TEXT,
+        updated_at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_media_items_hash ON media_items(hash);
+    CREATE INDEX IF NOT EXISTS idx_media_items_updated_at ON media_items(updated_at);
+
+    CREATE TABLE IF NOT EXISTS media_playlists (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_media_playlists_updated_at ON media_playlists(updated_at);
+
+    CREATE TABLE IF NOT EXISTS slide_tags (
+        library_id TEXT NOT NULL REFERENCES libraries(id) ON DELETE CASCADE,
+        slide_group_id TEXT NOT NULL,
+        slide_id TEXT NOT NULL,
+        tag_group_id TEXT NOT NULL,
+        tag_name TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_slide_tags_tag_group_id ON slide_tags(tag_group_id);
+    CREATE INDEX IF NOT EXISTS idx_slide_tags_library_id ON slide_tags(library_id);
+";
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        let (kind, code) = match err.sqlite_error_code() {
+            Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase) => {
+                (ErrorKind::Corrupt, "DATABASE_CORRUPT")
+            }
+            Some(ErrorCode::DiskFull) => (ErrorKind::Io, "DISK_FULL"),
+            Some(ErrorCode::ReadOnly) => (ErrorKind::PermissionDenied, "READ_ONLY"),
+            Some(ErrorCode::PermissionDenied) => (ErrorKind::PermissionDenied, "PERMISSION_DENIED"),
+            Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => {
+                (ErrorKind::Conflict, "DATABASE_BUSY")
+            }
+            _ => (ErrorKind::Io, "DATABASE_ERROR"),
+        };
+        AppError::new(kind, format!("Database error: {}", err)).with_code(code)
+    }
+}
+
+/// Get the database file path
+pub fn get_database_path(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(storage::get_app_data_dir(app)?.join("storage.db"))
+}
+
+pub struct SqliteRepository {
+    app: AppHandle,
+    path: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRepository {
+    /// Open (and create if needed) the database in the app data directory
+    pub fn open(app: AppHandle) -> AppResult<Self> {
+        let path = get_database_path(&app)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let conn = Connection::open(&path)?;
+        // WAL with full sync keeps committed saves durable across power loss
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = FULL;
+             PRAGMA foreign_keys = ON;",
+        )?;
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", DATABASE_VERSION)?;
+
+        Ok(Self {
+            app,
+            path,
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> AppResult<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| AppError::internal(format!("Failed to lock database: {}", e)))
+    }
+
+    fn table(kind: DocumentKind) -> &'static str {
+        match kind {
+            DocumentKind::Library => "libraries",
+            DocumentKind::Playlist => "playlists",
+            DocumentKind::MediaItem => "media_items",
+            DocumentKind::MediaPlaylist => "media_playlists",
+        }
+    }
+
+    /// Deserialize a stored row, upgrading it to the current schema if needed
+    fn parse_row<T: DeserializeOwned>(
+        &self,
+        conn: &Connection,
+        kind: DocumentKind,
+        id: &str,
+        data: &str,
+    ) -> AppResult<T> {
+        let (item, upgraded) = migrations::parse_document(&self.app, kind, id, data)?;
+        if let Some(document) = upgraded {
+            conn.execute(
+                &format!("UPDATE {} SET data = ?1 WHERE id = ?2", Self::table(kind)),
+                params![serde_json::to_string(&document)?, id],
+            )?;
+        }
+        Ok(item)
+    }
+
+    fn load<T: DeserializeOwned>(&self, kind: DocumentKind) -> AppResult<LoadResult<T>> {
+        let conn = self.conn()?;
+        let rows: Vec<(String, String)> = {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, data FROM {} ORDER BY updated_at",
+                Self::table(kind)
+            ))?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        let mut items = Vec::new();
+        let mut recovery = RecoveryReport::default();
+
+        for (id, data) in rows {
+            let error = match self.parse_row(&conn, kind, &id, &data) {
+                Ok(item) => {
+                    items.push(item);
+                    continue;
+                }
+                Err(error) => error,
+            };
+            if recovery.skip_unless_corrupt(kind, &self.path, &error) {
+                continue;
+            }
+
+            // Move the broken row out of the database so it can be repaired later
+            match quarantine::quarantine_content(
+                &self.app,
+                kind,
+                &format!("{}.json", id),
+                data.as_bytes(),
+                &self.path,
+                &error.message,
+            ) {
+                Ok(file) => {
+                    conn.execute(
+                        &format!("DELETE FROM {} WHERE id = ?1", Self::table(kind)),
+                        params![id],
+                    )?;
+                    eprintln!("Quarantined unreadable {:?} row {}: {}", kind, id, error.message);
+                    recovery.quarantined.push(file);
+                }
+                Err(e) => eprintln!("Failed to quarantine {:?} row {}: {}", kind, id, e.message),
+            }
+        }
+
+        Ok(LoadResult { items, recovery })
+    }
+
+    fn get<T: DeserializeOwned>(&self, kind: DocumentKind, id: &str) -> AppResult<Option<T>> {
+        let conn = self.conn()?;
+        let data: Option<String> = conn
+            .query_row(
+                &format!("SELECT data FROM {} WHERE id = ?1", Self::table(kind)),
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match data {
+            Some(data) => self.parse_row(&conn, kind, id, &data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Upsert a document into one of the tables with `id, name, updated_at, data` columns
+    fn save<T: Serialize>(
+        &self,
+        kind: DocumentKind,
+        id: &str,
+        name: &str,
+        updated_at: &str,
+        document: &T,
+    ) -> AppResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            &format!(
+                "INSERT INTO {} (id, name, updated_at, data) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    updated_at = excluded.updated_at,
+                    data = excluded.data",
+                Self::table(kind)
+            ),
+            params![id, name, updated_at, serde_json::to_string(document)?],
+        )?;
+        Ok(())
+    }
+
+    fn delete(&self, kind: DocumentKind, id: &str) -> AppResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            &format!("DELETE FROM {} WHERE id = ?1", Self::table(kind)),
+            params![id],
+        )?;
+        Ok(())
+    }
+}
+
+impl Repository for SqliteRepository {
+    fn engine(&self) -> StorageEngine {
+        StorageEngine::Sqlite
+    }
+
+    // ===== Libraries =====
+
+    fn load_libraries(&self) -> AppResult<LoadResult<Library>> {
+        self.load(DocumentKind::Library)
+    }
+
+    fn get_library(&self, id: &str) -> AppResult<Option<Library>> {
+        self.get(DocumentKind::Library, id)
+    }
+
+    fn save_library(&self, library: &Library) -> AppResult<()> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO libraries (id, name, updated_at, data) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                updated_at = excluded.updated_at,
+                data = excluded.data",
+            params![
+                library.id,
+                library.name,
+                library.updated_at,
+                serde_json::to_string(library)?
+            ],
+        )?;
+
+        // Rebuild the tag index for this library
+        tx.execute(
+            "DELETE FROM slide_tags WHERE library_id = ?1",
+            params![library.id],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO slide_tags (library_id, slide_group_id, slide_id, tag_group_id, tag_name)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for group in &library.slide_groups {
+                for slide in &group.slides {
+                    if let Some(tag) = &slide.tag_group {
+                        insert.execute(params![library.id, group.id, slide.id, tag.id, tag.name])?;
+                    }
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_library(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::Library, id)
+    }
+
+    // ===== Playlists =====
+
+    fn load_playlists(&self) -> AppResult<LoadResult<Playlist>> {
+        self.load(DocumentKind::Playlist)
+    }
+
+    fn get_playlist(&self, id: &str) -> AppResult<Option<Playlist>> {
+        self.get(DocumentKind::Playlist, id)
+    }
+
+    fn save_playlist(&self, playlist: &Playlist) -> AppResult<()> {
+        self.save(
+            DocumentKind::Playlist,
+            &playlist.id,
+            &playlist.name,
+            &playlist.updated_at,
+            playlist,
+        )
+    }
+
+    fn delete_playlist(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::Playlist, id)
+    }
+
+    // ===== Media Items =====
+
+    fn load_media_items(&self) -> AppResult<LoadResult<MediaItem>> {
+        self.load(DocumentKind::MediaItem)
+    }
+
+    fn get_media_item(&self, id: &str) -> AppResult<Option<MediaItem>> {
+        self.get(DocumentKind::MediaItem, id)
+    }
+
+    fn find_media_by_hash(&self, hash: &str) -> AppResult<Option<MediaItem>> {
+        let id: Option<String> = {
+            let conn = self.conn()?;
+            conn.query_row(
+                "SELECT id FROM media_items WHERE hash = ?1 LIMIT 1",
+                params![hash],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+
+        match id {
+            Some(id) => self.get_media_item(&id),
+            None => Ok(None),
+        }
+    }
+
+    fn save_media_item(&self, item: &MediaItem) -> AppResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO media_items (id, name, media_type, hash, updated_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                media_type = excluded.media_type,
+                hash = excluded.hash,
+                updated_at = excluded.updated_at,
+                data = excluded.data",
+            params![
+                item.id,
+                item.name,
+                item.media_type,
+                item.hash,
+                item.updated_at,
+                serde_json::to_string(item)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn delete_media_item(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::MediaItem, id)
+    }
+
+    // ===== Media Playlists =====
+
+    fn load_media_playlists(&self) -> AppResult<LoadResult<MediaPlaylist>> {
+        self.load(DocumentKind::MediaPlaylist)
+    }
+
+    fn get_media_playlist(&self, id: &str) -> AppResult<Option<MediaPlaylist>> {
+        self.get(DocumentKind::MediaPlaylist, id)
+    }
+
+    fn save_media_playlist(&self, playlist: &MediaPlaylist) -> AppResult<()> {
+        self.save(
+            DocumentKind::MediaPlaylist,
+            &playlist.id,
+            &playlist.name,
+            &playlist.updated_at,
+            playlist,
+        )
+    }
+
+    fn delete_media_playlist(&self, id: &str) -> AppResult<()> {
+        self.delete(DocumentKind::MediaPlaylist, id)
+    }
+
+    fn flush(&self) -> AppResult<()> {
+        // Fold the write-ahead log into the database file
+        self.conn()?
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+        Ok(())
+    }
+}
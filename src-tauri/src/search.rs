@@ -0,0 +1,647 @@
+//! In-memory full-text index over libraries, playlists and media.
+//!
+//! The index is built lazily from the active repository on the first search
+//! and then kept current by the save/delete commands. Bulk changes (restores,
+//! engine migrations) simply invalidate it so the next search rebuilds.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppResult;
+use crate::models::{Library, MediaItem, Playlist, SlideData, SlideGroup, SlideObject};
+use crate::repository;
+use crate::storage::DocumentKind;
+
+/// Default number of hits returned by a search
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Characters of context kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+/// Score applied when a query term is only found in the parent slide group title
+const CONTEXT_MATCH_SCORE: f64 = 0.5;
+
+/// Prefix matches (for the last, possibly incomplete, query term) score lower
+const PREFIX_MATCH_FACTOR: f64 = 0.6;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchHitKind {
+    SlideGroup,
+    Slide,
+    MediaItem,
+}
+
+/// Which text a hit's snippet was taken from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchField {
+    Title,
+    Content,
+    Tag,
+    MediaName,
+}
+
+impl SearchField {
+    fn weight(self) -> f64 {
+        match self {
+            SearchField::Title | SearchField::MediaName => 3.0,
+            SearchField::Tag => 2.0,
+            SearchField::Content => 1.0,
+        }
+    }
+}
+
+/// Piece of a snippet; highlighted parts are the matched words
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub library_id: Option<String>,
+    pub playlist_id: Option<String>,
+    pub playlist_item_id: Option<String>,
+    pub slide_group_id: Option<String>,
+    pub slide_id: Option<String>,
+    pub media_item_id: Option<String>,
+    pub title: String, // Slide group title or media name
+    pub field: SearchField,
+    pub snippet: Vec<SnippetPart>,
+    pub score: f64,
+}
+
+// ===== Tokenizing =====
+
+/// A normalized word and its byte range in the original text
+struct Token {
+    term: String,
+    start: usize,
+    end: usize,
+}
+
+/// Split text into lowercase alphanumeric words; apostrophes inside words are dropped
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+
+    for (index, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            let token = current.get_or_insert_with(|| Token {
+                term: String::new(),
+                start: index,
+                end: index,
+            });
+            token.term.extend(c.to_lowercase());
+            token.end = index + c.len_utf8();
+        } else if (c == '\'' || c == '\u{2019}') && current.is_some() {
+            continue;
+        } else if let Some(token) = current.take() {
+            tokens.push(token);
+        }
+    }
+    tokens.extend(current);
+
+    tokens
+}
+
+/// Parsed query terms; the last term also matches as a prefix for search-as-you-type
+struct Query {
+    terms: Vec<String>,
+}
+
+impl Query {
+    fn parse(query: &str) -> Self {
+        let mut terms: Vec<String> = Vec::new();
+        for token in tokenize(query) {
+            if !terms.contains(&token.term) {
+                terms.push(token.term);
+            }
+        }
+        Self { terms }
+    }
+
+    /// Score factor for a word matching query term `index`, if it matches at all
+    fn match_factor(&self, index: usize, word: &str) -> Option<f64> {
+        let term = &self.terms[index];
+        if word == term {
+            Some(1.0)
+        } else if index == self.terms.len() - 1 && word.starts_with(term.as_str()) {
+            Some(PREFIX_MATCH_FACTOR)
+        } else {
+            None
+        }
+    }
+
+    fn matches_any(&self, word: &str) -> bool {
+        (0..self.terms.len()).any(|i| self.match_factor(i, word).is_some())
+    }
+}
+
+// ===== Index =====
+
+#[derive(Clone)]
+struct Location {
+    kind: SearchHitKind,
+    library_id: Option<String>,
+    playlist_id: Option<String>,
+    playlist_item_id: Option<String>,
+    slide_group_id: Option<String>,
+    slide_id: Option<String>,
+    media_item_id: Option<String>,
+}
+
+struct IndexedField {
+    field: SearchField,
+    text: String,
+    tokens: Vec<Token>,
+}
+
+struct Entry {
+    location: Location,
+    title: String,
+    fields: Vec<IndexedField>,
+    context: HashSet<String>, // Words from the parent slide group title
+}
+
+#[derive(Default)]
+struct SearchIndex {
+    entries: HashMap<usize, Entry>,
+    postings: BTreeMap<String, HashSet<usize>>,
+    documents: HashMap<(DocumentKind, String), Vec<usize>>,
+    next_entry_id: usize,
+}
+
+impl SearchIndex {
+    fn build(app: &AppHandle) -> AppResult<Self> {
+        let repo = repository::current(app);
+        let mut index = Self::default();
+
+        for library in repo.load_libraries()?.items {
+            index.add_library(&library);
+        }
+        for playlist in repo.load_playlists()?.items {
+            index.add_playlist(&playlist);
+        }
+        for item in repo.load_media_items()?.items {
+            index.add_media_item(&item);
+        }
+
+        Ok(index)
+    }
+
+    fn add_library(&mut self, library: &Library) {
+        let base = Location {
+            kind: SearchHitKind::SlideGroup,
+            library_id: Some(library.id.clone()),
+            playlist_id: None,
+            playlist_item_id: None,
+            slide_group_id: None,
+            slide_id: None,
+            media_item_id: None,
+        };
+
+        let ids = library
+            .slide_groups
+            .iter()
+            .flat_map(|group| self.add_slide_group(&base, group))
+            .collect();
+        self.documents
+            .insert((DocumentKind::Library, library.id.clone()), ids);
+    }
+
+    fn add_playlist(&mut self, playlist: &Playlist) {
+        let mut ids = Vec::new();
+        for item in &playlist.items {
+            let base = Location {
+                kind: SearchHitKind::SlideGroup,
+                library_id: None,
+                playlist_id: Some(playlist.id.clone()),
+                playlist_item_id: Some(item.id.clone()),
+                slide_group_id: None,
+                slide_id: None,
+                media_item_id: None,
+            };
+            ids.extend(self.add_slide_group(&base, &item.slide_group));
+        }
+        self.documents
+            .insert((DocumentKind::Playlist, playlist.id.clone()), ids);
+    }
+
+    fn add_media_item(&mut self, item: &MediaItem) {
+        let location = Location {
+            kind: SearchHitKind::MediaItem,
+            library_id: None,
+            playlist_id: None,
+            playlist_item_id: None,
+            slide_group_id: None,
+            slide_id: None,
+            media_item_id: Some(item.id.clone()),
+        };
+        let fields = vec![indexed_field(SearchField::MediaName, &item.name)];
+        let id = self.add_entry(location, item.name.clone(), fields, HashSet::new());
+        self.documents
+            .insert((DocumentKind::MediaItem, item.id.clone()), vec![id]);
+    }
+
+    fn add_slide_group(&mut self, base: &Location, group: &SlideGroup) -> Vec<usize> {
+        let mut ids = Vec::with_capacity(group.slides.len() + 1);
+
+        let group_location = Location {
+            slide_group_id: Some(group.id.clone()),
+            ..base.clone()
+        };
+        let title_field = indexed_field(SearchField::Title, &group.title);
+        let context: HashSet<String> = title_field.tokens.iter().map(|t| t.term.clone()).collect();
+        ids.push(self.add_entry(
+            group_location.clone(),
+            group.title.clone(),
+            vec![title_field],
+            HashSet::new(),
+        ));
+
+        for slide in &group.slides {
+            let fields = slide_fields(slide);
+            if fields.is_empty() {
+                continue;
+            }
+            let location = Location {
+                kind: SearchHitKind::Slide,
+                slide_id: Some(slide.id.clone()),
+                ..group_location.clone()
+            };
+            ids.push(self.add_entry(location, group.title.clone(), fields, context.clone()));
+        }
+
+        ids
+    }
+
+    fn add_entry(
+        &mut self,
+        location: Location,
+        title: String,
+        fields: Vec<IndexedField>,
+        context: HashSet<String>,
+    ) -> usize {
+        let id = self.next_entry_id;
+        self.next_entry_id += 1;
+
+        let terms = fields
+            .iter()
+            .flat_map(|f| f.tokens.iter().map(|t| t.term.clone()))
+            .chain(context.iter().cloned());
+        for term in terms {
+            self.postings.entry(term).or_default().insert(id);
+        }
+
+        self.entries.insert(
+            id,
+            Entry {
+                location,
+                title,
+                fields,
+                context,
+            },
+        );
+        id
+    }
+
+    fn remove_document(&mut self, kind: DocumentKind, id: &str) {
+        let Some(entry_ids) = self.documents.remove(&(kind, id.to_string())) else {
+            return;
+        };
+
+        for entry_id in entry_ids {
+            let Some(entry) = self.entries.remove(&entry_id) else {
+                continue;
+            };
+            let terms = entry
+                .fields
+                .iter()
+                .flat_map(|f| f.tokens.iter().map(|t| &t.term))
+                .chain(entry.context.iter());
+            for term in terms {
+                if let Some(posting) = self.postings.get_mut(term) {
+                    posting.remove(&entry_id);
+                    if posting.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Entry ids containing a word that matches query term `index`
+    fn candidates_for(&self, query: &Query, index: usize) -> HashSet<usize> {
+        let term = &query.terms[index];
+        let is_last = index == query.terms.len() - 1;
+
+        let mut ids = HashSet::new();
+        for (word, posting) in self.postings.range(term.clone()..) {
+            if word != term && !(is_last && word.starts_with(term.as_str())) {
+                break;
+            }
+            ids.extend(posting);
+        }
+        ids
+    }
+
+    fn search(&self, query: &Query, limit: usize) -> Vec<SearchHit> {
+        if query.terms.is_empty() {
+            return Vec::new();
+        }
+
+        // Every query term must match somewhere in the entry
+        let mut candidates = self.candidates_for(query, 0);
+        for index in 1..query.terms.len() {
+            if candidates.is_empty() {
+                break;
+            }
+            let matching = self.candidates_for(query, index);
+            candidates.retain(|id| matching.contains(id));
+        }
+
+        let mut hits: Vec<SearchHit> = candidates
+            .iter()
+            .filter_map(|id| self.entries.get(id))
+            .filter_map(|entry| score_entry(entry, query))
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+fn indexed_field(field: SearchField, text: &str) -> IndexedField {
+    IndexedField {
+        field,
+        text: text.to_string(),
+        tokens: tokenize(text),
+    }
+}
+
+/// Searchable text of a slide: object contents and overlays, then its tag name
+fn slide_fields(slide: &SlideData) -> Vec<IndexedField> {
+    let mut fields: Vec<IndexedField> = slide
+        .objects
+        .iter()
+        .flatten()
+        .filter_map(|object| match object {
+            SlideObject::Text(text) => Some(text.content.as_str()),
+            SlideObject::Shape(shape) => shape.content.as_deref(),
+            SlideObject::Image(image) => image.content.as_deref(),
+            SlideObject::Video(video) => video.content.as_deref(),
+        })
+        .filter(|content| !content.trim().is_empty())
+        .map(|content| indexed_field(SearchField::Content, content))
+        .collect();
+
+    if let Some(tag_group) = &slide.tag_group {
+        fields.push(indexed_field(SearchField::Tag, &tag_group.name));
+    }
+
+    fields
+}
+
+/// Score an entry against the query, or `None` if some term does not match it
+fn score_entry(entry: &Entry, query: &Query) -> Option<SearchHit> {
+    let mut score = 0.0;
+    let mut best_field: Option<(&IndexedField, f64)> = None;
+
+    for index in 0..query.terms.len() {
+        let mut term_score: f64 = 0.0;
+
+        for field in &entry.fields {
+            let field_score: f64 = field
+                .tokens
+                .iter()
+                .filter_map(|t| query.match_factor(index, &t.term))
+                .sum::<f64>()
+                .min(3.0)
+                * field.field.weight();
+
+            term_score = term_score.max(field_score);
+            if field_score > best_field.map(|(_, s)| s).unwrap_or(0.0) {
+                best_field = Some((field, field_score));
+            }
+        }
+
+        if term_score == 0.0 {
+            if entry
+                .context
+                .iter()
+                .any(|word| query.match_factor(index, word).is_some())
+            {
+                term_score = CONTEXT_MATCH_SCORE;
+            } else {
+                return None;
+            }
+        }
+
+        score += term_score;
+    }
+
+    // Reward fields containing the whole query as a phrase
+    if query.terms.len() > 1 {
+        for field in &entry.fields {
+            let words: Vec<&str> = field.tokens.iter().map(|t| t.term.as_str()).collect();
+            let is_phrase = words.windows(query.terms.len()).any(|window| {
+                window
+                    .iter()
+                    .enumerate()
+                    .all(|(i, word)| query.match_factor(i, word).is_some())
+            });
+            if is_phrase {
+                score += field.field.weight() * query.terms.len() as f64;
+            }
+        }
+    }
+
+    // Fall back to the first field when only the group title matched
+    let field = best_field
+        .map(|(f, _)| f)
+        .or_else(|| entry.fields.first())?;
+    let location = entry.location.clone();
+
+    Some(SearchHit {
+        kind: location.kind,
+        library_id: location.library_id,
+        playlist_id: location.playlist_id,
+        playlist_item_id: location.playlist_item_id,
+        slide_group_id: location.slide_group_id,
+        slide_id: location.slide_id,
+        media_item_id: location.media_item_id,
+        title: entry.title.clone(),
+        field: field.field,
+        snippet: build_snippet(field, query),
+        score,
+    })
+}
+
+/// Cut a window of text around the first match and mark every matching word in it
+fn build_snippet(field: &IndexedField, query: &Query) -> Vec<SnippetPart> {
+    let text = field.text.as_str();
+    let matches: Vec<&Token> = field
+        .tokens
+        .iter()
+        .filter(|t| query.matches_any(&t.term))
+        .collect();
+
+    let first_start = matches.first().map(|t| t.start).unwrap_or(0);
+    // Widen to the configured context, then shrink back to whole words
+    let window_start = text[..first_start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map(|(i, _)| {
+            text[i..first_start]
+                .find(char::is_whitespace)
+                .map(|offset| i + offset)
+                .unwrap_or(i)
+        })
+        .unwrap_or(0);
+    let window_end = text[first_start..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS * 2)
+        .map(|(i, _)| {
+            let end = first_start + i;
+            text[first_start..end]
+                .rfind(char::is_whitespace)
+                .map(|offset| first_start + offset)
+                .unwrap_or(end)
+        })
+        .unwrap_or(text.len());
+
+    let mut parts = Vec::new();
+    let mut push = |slice: &str, highlighted: bool| {
+        if !slice.is_empty() {
+            parts.push(SnippetPart {
+                text: collapse_whitespace(slice),
+                highlighted,
+            });
+        }
+    };
+
+    if window_start > 0 {
+        push("…", false);
+    }
+    let mut cursor = window_start;
+    for token in matches
+        .iter()
+        .filter(|t| t.start >= window_start && t.end <= window_end)
+    {
+        push(&text[cursor..token.start], false);
+        push(&text[token.start..token.end], true);
+        cursor = token.end;
+    }
+    push(&text[cursor..window_end], false);
+    if window_end < text.len() {
+        push("…", false);
+    }
+
+    parts
+}
+
+/// Replace each run of whitespace (including line breaks) with a single space
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    collapsed
+}
+
+// ===== Managed State =====
+
+/// Managed state holding the search index; `None` until the first search
+#[derive(Default)]
+pub struct SearchState {
+    index: Mutex<Option<SearchIndex>>,
+}
+
+impl SearchState {
+    fn lock(&self) -> MutexGuard<'_, Option<SearchIndex>> {
+        match self.index.lock() {
+            Ok(index) => index,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Search libraries, playlists and media, building the index first if needed
+pub fn search(app: &AppHandle, query: &str, limit: usize) -> AppResult<Vec<SearchHit>> {
+    let state = app.state::<SearchState>();
+    let mut index = state.lock();
+
+    if index.is_none() {
+        *index = Some(SearchIndex::build(app)?);
+    }
+
+    let query = Query::parse(query);
+    Ok(index
+        .as_ref()
+        .map(|index| index.search(&query, limit))
+        .unwrap_or_default())
+}
+
+/// Apply a change to the index if it has been built
+fn update(app: &AppHandle, f: impl FnOnce(&mut SearchIndex)) {
+    if let Some(index) = app.state::<SearchState>().lock().as_mut() {
+        f(index);
+    }
+}
+
+/// Re-index a saved library
+pub fn index_library(app: &AppHandle, library: &Library) {
+    update(app, |index| {
+        index.remove_document(DocumentKind::Library, &library.id);
+        index.add_library(library);
+    });
+}
+
+/// Re-index a saved playlist
+pub fn index_playlist(app: &AppHandle, playlist: &Playlist) {
+    update(app, |index| {
+        index.remove_document(DocumentKind::Playlist, &playlist.id);
+        index.add_playlist(playlist);
+    });
+}
+
+/// Re-index a saved media item
+pub fn index_media_item(app: &AppHandle, item: &MediaItem) {
+    update(app, |index| {
+        index.remove_document(DocumentKind::MediaItem, &item.id);
+        index.add_media_item(item);
+    });
+}
+
+/// Drop a deleted document from the index
+pub fn remove_document(app: &AppHandle, kind: DocumentKind, id: &str) {
+    update(app, |index| index.remove_document(kind, id));
+}
+
+/// Discard the index after bulk changes; it is rebuilt on the next search
+pub fn invalidate(app: &AppHandle) {
+    *app.state::<SearchState>().lock() = None;
+}
@@ -0,0 +1,615 @@
+//! Rolling snapshots of the data directory with point-in-time restore.
+//!
+//! A snapshot is a zip archive of every library, playlist, media metadata,
+//! media playlist and settings file. Documents are read through the active
+//! repository so snapshots work with any storage engine. Media files
+//! themselves are not archived.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::{AppError, AppResult};
+use crate::history;
+use crate::models::MediaItem;
+use crate::repository;
+use crate::storage::{self, DocumentKind};
+use crate::storage_watcher::{self, ChangeType, StorageChange, StorageEntity};
+
+/// How often the background thread checks for changes worth snapshotting
+const AUTO_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// The newest snapshots are always kept
+const KEEP_RECENT_SNAPSHOTS: usize = 20;
+
+/// Beyond the recent ones, keep the newest snapshot of each day for this many days
+const KEEP_DAILY_SNAPSHOTS_DAYS: i64 = 30;
+
+/// A snapshot this recent is reused before a delete if it already holds the
+/// document, so deleting many items in a row doesn't archive everything each time
+const REUSE_SNAPSHOT_SECONDS: i64 = 120;
+
+/// Held while a snapshot is written
+static SNAPSHOT_WRITES: Mutex<()> = Mutex::new(());
+
+const MANIFEST_NAME: &str = "manifest.json";
+const SETTINGS_SECTION: &str = "settings";
+
+/// Settings about how this machine stores data rather than the data itself.
+/// Restoring them would leave the open storage engine and folder sync out of step.
+const MACHINE_SETTINGS: [&str; 2] = ["storage.json", "folder-sync.json"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotReason {
+    Auto,
+    Manual,
+    BeforeDelete,
+    BeforeRestore,
+}
+
+/// Summary stored inside each archive and returned by `list_snapshots`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub created_at: String,
+    pub reason: SnapshotReason,
+    pub detail: Option<String>, // e.g. "library <id>" for pre-delete snapshots
+    pub fingerprint: String,    // Hash of all archived content, used to skip duplicates
+    pub library_count: usize,
+    pub playlist_count: usize,
+    pub media_item_count: usize,
+    pub media_playlist_count: usize,
+    #[serde(default)]
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotChange {
+    Added,    // Exists now but not in the snapshot
+    Removed,  // In the snapshot but deleted since
+    Modified, // Content differs between the snapshot and now
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiffEntry {
+    pub section: String, // "libraries", "playlists", "media-metadata", "media-playlists", "settings"
+    pub id: String,      // File stem, which is the entity id for documents
+    pub name: Option<String>,
+    pub change: SnapshotChange,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub safety_snapshot_id: Option<String>,
+    pub restored_files: usize,
+    pub missing_media_files: Vec<String>, // Media items whose files are no longer on disk
+}
+
+// ===== Paths =====
+
+fn get_snapshots_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let dir = storage::get_snapshots_dir(app)?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+const DOCUMENT_KINDS: [DocumentKind; 4] = [
+    DocumentKind::Library,
+    DocumentKind::Playlist,
+    DocumentKind::MediaItem,
+    DocumentKind::MediaPlaylist,
+];
+
+/// Read every archived file from the live data, keyed by archive path
+fn collect_current_files(app: &AppHandle) -> AppResult<BTreeMap<String, Vec<u8>>> {
+    let repo = repository::current(app);
+    let mut files = BTreeMap::new();
+
+    for kind in DOCUMENT_KINDS {
+        for (id, content) in repository::export_documents(repo.as_ref(), kind)? {
+            files.insert(format!("{}/{}.json", kind.dir_name(), id), content);
+        }
+    }
+
+    for path in storage::list_json_files(&storage::get_settings_dir(app)?)? {
+        if let Some(name) = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .filter(|name| !MACHINE_SETTINGS.contains(name))
+        {
+            files.insert(format!("{}/{}", SETTINGS_SECTION, name), fs::read(&path)?);
+        }
+    }
+
+    Ok(files)
+}
+
+fn fingerprint(files: &BTreeMap<String, Vec<u8>>) -> String {
+    let mut combined = Vec::new();
+    for (name, content) in files {
+        combined.extend_from_slice(name.as_bytes());
+        combined.extend_from_slice(storage::hash_bytes(content).as_bytes());
+    }
+    storage::hash_bytes(&combined)
+}
+
+fn count_section(files: &BTreeMap<String, Vec<u8>>, kind: DocumentKind) -> usize {
+    let prefix = format!("{}/", kind.dir_name());
+    files.keys().filter(|k| k.starts_with(&prefix)).count()
+}
+
+// ===== Creating Snapshots =====
+
+/// Capture the current state of the data directory.
+///
+/// Returns `None` when the newest snapshot already holds identical content.
+pub fn create_snapshot(
+    app: &AppHandle,
+    reason: SnapshotReason,
+    detail: Option<String>,
+) -> AppResult<Option<SnapshotInfo>> {
+    let _guard = lock_snapshots();
+    let files = collect_current_files(app)?;
+    let fingerprint = fingerprint(&files);
+
+    if let Some(latest) = list_snapshots(app)?.first() {
+        if latest.fingerprint == fingerprint {
+            return Ok(None);
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let mut info = SnapshotInfo {
+        id: format!("{}-{}", now.format("%Y%m%dT%H%M%S%3fZ"), reason_slug(reason)),
+        created_at: now.to_rfc3339(),
+        reason,
+        detail,
+        fingerprint,
+        library_count: count_section(&files, DocumentKind::Library),
+        playlist_count: count_section(&files, DocumentKind::Playlist),
+        media_item_count: count_section(&files, DocumentKind::MediaItem),
+        media_playlist_count: count_section(&files, DocumentKind::MediaPlaylist),
+        size_bytes: 0,
+    };
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    writer
+        .start_file(MANIFEST_NAME, options)
+        .map_err(zip_error)?;
+    writer.write_all(&serde_json::to_vec_pretty(&info)?)?;
+
+    for (name, content) in &files {
+        writer.start_file(name.as_str(), options).map_err(zip_error)?;
+        writer.write_all(content)?;
+    }
+
+    let archive = writer.finish().map_err(zip_error)?.into_inner();
+    info.size_bytes = archive.len() as u64;
+
+    let path = get_snapshots_dir(app)?.join(format!("{}.zip", info.id));
+    storage::write_file_atomic(&path, &archive)?;
+
+    prune_snapshots(app)?;
+
+    Ok(Some(info))
+}
+
+/// Hold off snapshots, e.g. while the workspace is moved
+pub fn lock_snapshots() -> MutexGuard<'static, ()> {
+    SNAPSHOT_WRITES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Snapshot before a destructive command, describing what is about to be removed
+pub fn snapshot_before_delete(app: &AppHandle, kind: DocumentKind, id: &str) -> AppResult<()> {
+    if recent_snapshot_holds(app, kind, id)? {
+        return Ok(());
+    }
+
+    create_snapshot(
+        app,
+        SnapshotReason::BeforeDelete,
+        Some(format!("{:?} {}", kind, id)),
+    )
+    .map(|_| ())
+    .map_err(|mut e| {
+        e.message = format!("Failed to snapshot before delete: {}", e.message);
+        e
+    })
+}
+
+/// Whether the newest snapshot was taken moments ago and holds the document as it is now
+fn recent_snapshot_holds(app: &AppHandle, kind: DocumentKind, id: &str) -> AppResult<bool> {
+    let Some(latest) = list_snapshots(app)?.into_iter().next() else {
+        return Ok(false);
+    };
+    let recent = chrono::DateTime::parse_from_rfc3339(&latest.created_at)
+        .map(|created| {
+            chrono::Utc::now().signed_duration_since(created)
+                < chrono::Duration::seconds(REUSE_SNAPSHOT_SECONDS)
+        })
+        .unwrap_or(false);
+    if !recent {
+        return Ok(false);
+    }
+
+    let repo = repository::current(app);
+    let Some(current) = repository::get_document(repo.as_ref(), kind, id)? else {
+        return Ok(false);
+    };
+
+    let path = get_snapshots_dir(app)?.join(format!("{}.zip", latest.id));
+    let mut archive = ZipArchive::new(fs::File::open(&path)?).map_err(zip_error)?;
+    let Ok(mut file) = archive.by_name(&format!("{}/{}.json", kind.dir_name(), id)) else {
+        return Ok(false);
+    };
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+
+    Ok(serde_json::from_slice::<serde_json::Value>(&content).ok() == Some(current))
+}
+
+fn reason_slug(reason: SnapshotReason) -> &'static str {
+    match reason {
+        SnapshotReason::Auto => "auto",
+        SnapshotReason::Manual => "manual",
+        SnapshotReason::BeforeDelete => "before-delete",
+        SnapshotReason::BeforeRestore => "before-restore",
+    }
+}
+
+/// Older snapshots still carry machine settings, which are never restored
+fn is_machine_setting(name: &str) -> bool {
+    name.split_once('/').is_some_and(|(section, file)| {
+        section == SETTINGS_SECTION && MACHINE_SETTINGS.contains(&file)
+    })
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+    AppError::corrupt(format!("Snapshot archive error: {}", err)).with_code("INVALID_ARCHIVE")
+}
+
+/// Start the background thread that periodically snapshots changed data
+pub fn start_auto_snapshots(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(AUTO_SNAPSHOT_INTERVAL);
+
+        match create_snapshot(&app, SnapshotReason::Auto, None) {
+            Ok(Some(info)) => println!("Created automatic snapshot {}", info.id),
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to create automatic snapshot: {}", e.message),
+        }
+    });
+}
+
+// ===== Listing & Retention =====
+
+/// List snapshots, newest first
+pub fn list_snapshots(app: &AppHandle) -> AppResult<Vec<SnapshotInfo>> {
+    let dir = get_snapshots_dir(app)?;
+    let mut snapshots = Vec::new();
+
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("zip") {
+            continue;
+        }
+
+        match read_manifest(&path) {
+            Ok(mut info) => {
+                info.size_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                snapshots.push(info);
+            }
+            Err(e) => eprintln!("Failed to read snapshot {:?}: {}", path, e.message),
+        }
+    }
+
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(snapshots)
+}
+
+fn read_manifest(path: &PathBuf) -> AppResult<SnapshotInfo> {
+    let mut archive = ZipArchive::new(fs::File::open(path)?).map_err(zip_error)?;
+    let mut content = String::new();
+    archive
+        .by_name(MANIFEST_NAME)
+        .map_err(zip_error)?
+        .read_to_string(&mut content)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Apply retention: keep the newest snapshots plus one per day for recent days
+fn prune_snapshots(app: &AppHandle) -> AppResult<()> {
+    let snapshots = list_snapshots(app)?;
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(KEEP_DAILY_SNAPSHOTS_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut seen_days = HashSet::new();
+    let dir = get_snapshots_dir(app)?;
+
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        // created_at is RFC 3339, so the first ten characters are the day
+        let day = snapshot.created_at.get(..10).unwrap_or("").to_string();
+        let first_of_day = seen_days.insert(day.clone());
+
+        let keep = i < KEEP_RECENT_SNAPSHOTS || (first_of_day && day >= cutoff);
+        if !keep {
+            storage::delete_file(&dir.join(format!("{}.zip", snapshot.id)))?;
+        }
+    }
+
+    Ok(())
+}
+
+// ===== Diff & Restore =====
+
+fn open_snapshot(app: &AppHandle, id: &str) -> AppResult<BTreeMap<String, Vec<u8>>> {
+    let path = get_snapshots_dir(app)?.join(format!("{}.zip", id));
+    if !path.exists() {
+        return Err(AppError::not_found(format!("Snapshot not found: {}", id))
+            .with_code("SNAPSHOT_NOT_FOUND")
+            .with_entity(id)
+            .with_path(&path));
+    }
+
+    let mut archive = ZipArchive::new(fs::File::open(&path)?).map_err(zip_error)?;
+    let mut files = BTreeMap::new();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(zip_error)?;
+        let name = file.name().to_string();
+        if name == MANIFEST_NAME || is_machine_setting(&name) {
+            continue;
+        }
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        files.insert(name, content);
+    }
+
+    Ok(files)
+}
+
+/// Best-effort display name for an archived document
+fn document_name(content: &[u8]) -> Option<String> {
+    serde_json::from_slice::<serde_json::Value>(content)
+        .ok()
+        .and_then(|v| v.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()))
+}
+
+fn split_archive_path(name: &str) -> (String, String) {
+    let (section, file) = name.split_once('/').unwrap_or(("", name));
+    let id = file.strip_suffix(".json").unwrap_or(file);
+    (section.to_string(), id.to_string())
+}
+
+/// Compare a snapshot against the current data directory
+pub fn diff_snapshot(app: &AppHandle, id: &str) -> AppResult<Vec<SnapshotDiffEntry>> {
+    let snapshot = open_snapshot(app, id)?;
+    let current = collect_current_files(app)?;
+    let mut entries = Vec::new();
+
+    for (name, content) in &snapshot {
+        let change = match current.get(name) {
+            None => SnapshotChange::Removed,
+            Some(now) if now != content => SnapshotChange::Modified,
+            Some(_) => continue,
+        };
+        let (section, id) = split_archive_path(name);
+        entries.push(SnapshotDiffEntry {
+            section,
+            id,
+            name: document_name(content),
+            change,
+        });
+    }
+
+    for (name, content) in &current {
+        if !snapshot.contains_key(name) {
+            let (section, id) = split_archive_path(name);
+            entries.push(SnapshotDiffEntry {
+                section,
+                id,
+                name: document_name(content),
+                change: SnapshotChange::Added,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Restore a single document (e.g. one library) from a snapshot
+pub fn restore_document(
+    app: &AppHandle,
+    snapshot_id: &str,
+    kind: DocumentKind,
+    document_id: &str,
+) -> AppResult<RestoreResult> {
+    let snapshot = open_snapshot(app, snapshot_id)?;
+    let name = format!("{}/{}.json", kind.dir_name(), document_id);
+    let content = snapshot.get(&name).ok_or_else(|| {
+        AppError::not_found(format!(
+            "{:?} {} is not in snapshot {}",
+            kind, document_id, snapshot_id
+        ))
+        .with_code("NOT_IN_SNAPSHOT")
+        .with_entity(document_id)
+    })?;
+
+    let safety = create_snapshot(
+        app,
+        SnapshotReason::BeforeRestore,
+        Some(format!("{:?} {}", kind, document_id)),
+    )?;
+
+    let repo = repository::current(app);
+    let guard = repository::lock_checked_saves();
+    let existed = repository::document_exists(repo.as_ref(), kind, document_id)?;
+    let saved =
+        repository::save_document_replacing(repo.as_ref(), kind, serde_json::from_slice(content)?)?;
+    history::record_restored(app, kind, document_id, &saved);
+    drop(guard);
+
+    // Other windows would otherwise save over the restore with a stale revision
+    storage_watcher::broadcast_changes(
+        app,
+        &[StorageChange {
+            kind: kind.into(),
+            id: document_id.to_string(),
+            change: if existed {
+                ChangeType::Modified
+            } else {
+                ChangeType::Created
+            },
+        }],
+    );
+
+    let mut restored = BTreeMap::new();
+    restored.insert(name, content.clone());
+
+    Ok(RestoreResult {
+        safety_snapshot_id: safety.map(|s| s.id),
+        restored_files: 1,
+        missing_media_files: find_missing_media_files(app, &restored)?,
+    })
+}
+
+/// Replace the whole workspace with the contents of a snapshot
+pub fn restore_snapshot(app: &AppHandle, snapshot_id: &str) -> AppResult<RestoreResult> {
+    let snapshot = open_snapshot(app, snapshot_id)?;
+
+    let safety = create_snapshot(
+        app,
+        SnapshotReason::BeforeRestore,
+        Some(format!("workspace from {}", snapshot_id)),
+    )?;
+
+    let repo = repository::current(app);
+    let guard = repository::lock_checked_saves();
+    let current = collect_current_files(app)?;
+    let mut changes = Vec::new();
+
+    for kind in DOCUMENT_KINDS {
+        let prefix = format!("{}/", kind.dir_name());
+
+        // Remove documents created after the snapshot was taken
+        for name in current.keys().filter(|k| k.starts_with(&prefix)) {
+            if !snapshot.contains_key(name) {
+                let (_, id) = split_archive_path(name);
+                repository::delete_document(repo.as_ref(), kind, &id)?;
+                changes.push(StorageChange {
+                    kind: kind.into(),
+                    id,
+                    change: ChangeType::Deleted,
+                });
+            }
+        }
+
+        for (name, content) in snapshot.iter().filter(|(k, _)| k.starts_with(&prefix)) {
+            let saved = repository::save_document_replacing(
+                repo.as_ref(),
+                kind,
+                serde_json::from_slice(content)?,
+            )?;
+            let (_, id) = split_archive_path(name);
+            history::record_restored(app, kind, &id, &saved);
+            changes.push(StorageChange {
+                kind: kind.into(),
+                id,
+                change: if current.contains_key(name) {
+                    ChangeType::Modified
+                } else {
+                    ChangeType::Created
+                },
+            });
+        }
+    }
+
+    // Settings are always plain files
+    let settings_dir = storage::get_settings_dir(app)?;
+    fs::create_dir_all(&settings_dir)?;
+    let prefix = format!("{}/", SETTINGS_SECTION);
+    for (name, content) in snapshot.iter().filter(|(k, _)| k.starts_with(&prefix)) {
+        let path = settings_dir.join(&name[prefix.len()..]);
+        storage::write_file_atomic(&path, content)?;
+        if let Some(id) = storage::get_file_stem(&path) {
+            changes.push(StorageChange {
+                kind: StorageEntity::Settings,
+                id,
+                change: ChangeType::Modified,
+            });
+        }
+    }
+    drop(guard);
+
+    // Other windows would otherwise save over the restore with stale revisions
+    storage_watcher::broadcast_changes(app, &changes);
+
+    Ok(RestoreResult {
+        safety_snapshot_id: safety.map(|s| s.id),
+        restored_files: snapshot.len(),
+        missing_media_files: find_missing_media_files(app, &snapshot)?,
+    })
+}
+
+/// Pass the content of every document in every snapshot to `visit`
+pub fn visit_archived_documents(app: &AppHandle, mut visit: impl FnMut(&[u8])) -> AppResult<()> {
+    for entry in fs::read_dir(get_snapshots_dir(app)?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("zip") {
+            continue;
+        }
+
+        let mut archive = match fs::File::open(&path)
+            .map_err(AppError::from)
+            .and_then(|file| ZipArchive::new(file).map_err(zip_error))
+        {
+            Ok(archive) => archive,
+            Err(e) => {
+                eprintln!("Failed to read snapshot {:?}: {}", path, e.message);
+                continue;
+            }
+        };
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).map_err(zip_error)?;
+            if file.name() == MANIFEST_NAME {
+                continue;
+            }
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            visit(&content);
+        }
+    }
+    Ok(())
+}
+
+/// Restored media metadata may point at files that were deleted since the snapshot
+fn find_missing_media_files(
+    app: &AppHandle,
+    files: &BTreeMap<String, Vec<u8>>,
+) -> AppResult<Vec<String>> {
+    let prefix = format!("{}/", DocumentKind::MediaItem.dir_name());
+
+    Ok(files
+        .iter()
+        .filter(|(name, _)| name.starts_with(&prefix))
+        .filter_map(|(_, content)| serde_json::from_slice::<MediaItem>(content).ok())
+        .filter(|item| storage::media_item_path(app, item).map_or(true, |path| !path.exists()))
+        .map(|item| item.id)
+        .collect())
+}
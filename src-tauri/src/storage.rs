@@ -1,175 +1,535 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fmt;
 use std::fs;
-use std::io::Read;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct StorageError {
-    pub message: String,
-}
-
-impl StorageError {
-    pub fn new(message: String) -> Self {
-        StorageError { message }
-    }
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::folder_sync;
+use crate::models::MediaItem;
+use crate::storage_watcher;
+use crate::workspaces;
+
+/// Suffix for in-flight temp files written next to their target
+const TEMP_SUFFIX: &str = "tmp";
+
+/// Suffix for journal entries describing an in-flight write
+const JOURNAL_SUFFIX: &str = "journal";
+
+/// Asset URL prefixes `convertFileSrc` puts before a path on each platform
+const ASSET_URL_PREFIXES: [&str; 3] = [
+    "asset://localhost/",
+    "http://asset.localhost/",
+    "https://asset.localhost/",
+];
+
+/// Get the base data directory: the root of the active workspace
+pub fn get_app_data_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    workspaces::active_root(app)
 }
 
-impl fmt::Display for StorageError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
-    }
-}
-
-impl std::error::Error for StorageError {}
-
-impl From<std::io::Error> for StorageError {
-    fn from(err: std::io::Error) -> Self {
-        StorageError::new(err.to_string())
-    }
-}
-
-impl From<serde_json::Error> for StorageError {
-    fn from(err: serde_json::Error) -> Self {
-        StorageError::new(err.to_string())
-    }
-}
-
-pub type StorageResult<T> = Result<T, StorageError>;
-
-/// Get the base app data directory
-pub fn get_app_data_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+/// Get the platform app data directory, root of the default workspace
+pub fn get_default_data_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = app
         .path()
         .app_data_dir()
-        .map_err(|e| StorageError::new(e.to_string()))?;
+        .map_err(|e| AppError::internal(e.to_string()))?;
     Ok(app_data)
 }
 
+/// Get the file listing this machine's workspaces
+pub fn get_workspaces_file(app: &AppHandle) -> AppResult<PathBuf> {
+    let app_config = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::internal(e.to_string()))?;
+    Ok(app_config.join("workspaces.json"))
+}
+
 /// Get the libraries directory path
-pub fn get_libraries_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_libraries_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = get_app_data_dir(app)?;
     Ok(app_data.join("libraries"))
 }
 
 /// Get the playlists directory path
-pub fn get_playlists_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_playlists_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = get_app_data_dir(app)?;
     Ok(app_data.join("playlists"))
 }
 
 /// Get the media files directory path
-pub fn get_media_files_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_media_files_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = get_app_data_dir(app)?;
     Ok(app_data.join("media").join("files"))
 }
 
 /// Get the media metadata directory path
-pub fn get_media_metadata_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_media_metadata_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = get_app_data_dir(app)?;
     Ok(app_data.join("media").join("metadata"))
 }
 
 /// Get the media playlists directory path
-pub fn get_media_playlists_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_media_playlists_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = get_app_data_dir(app)?;
     Ok(app_data.join("media").join("playlists"))
 }
 
 /// Get the settings directory path
-pub fn get_settings_dir(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_settings_dir(app: &AppHandle) -> AppResult<PathBuf> {
     let app_data = get_app_data_dir(app)?;
     Ok(app_data.join("settings"))
 }
 
+/// Get the quarantine directory path for files that failed to load
+pub fn get_quarantine_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let app_data = get_app_data_dir(app)?;
+    Ok(app_data.join("quarantine"))
+}
+
+/// Get the backups directory path
+pub fn get_backups_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let app_data = get_app_data_dir(app)?;
+    Ok(app_data.join("backups"))
+}
+
+/// Get the snapshots directory path
+pub fn get_snapshots_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let app_data = get_app_data_dir(app)?;
+    Ok(app_data.join("snapshots"))
+}
+
+/// Get the directory holding the revision history of every document
+pub fn get_history_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let app_data = get_app_data_dir(app)?;
+    Ok(app_data.join("history"))
+}
+
+/// Get the directory holding this machine's copies of document versions
+/// for folder sync. Machine-local, unlike the synced data directory, and
+/// kept apart for each workspace.
+pub fn get_sync_versions_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let local_data = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| AppError::internal(e.to_string()))?;
+    Ok(local_data
+        .join("sync-versions")
+        .join(workspaces::active_id(app)))
+}
+
 /// Get the tag groups file path
-pub fn get_tag_groups_file(app: &AppHandle) -> StorageResult<PathBuf> {
+pub fn get_tag_groups_file(app: &AppHandle) -> AppResult<PathBuf> {
     let settings_dir = get_settings_dir(app)?;
     Ok(settings_dir.join("tag-groups.json"))
 }
 
-/// Ensure all required directories exist
-pub fn ensure_directories(app: &AppHandle) -> StorageResult<()> {
-    let dirs = vec![
+/// Location of a media item's file: its linked path, or its copy in the media files directory
+pub fn media_item_path(app: &AppHandle, item: &MediaItem) -> AppResult<PathBuf> {
+    if let Some(linked_path) = &item.linked_path {
+        return Ok(PathBuf::from(linked_path));
+    }
+
+    let file_name = media_file_name(&item.source).ok_or_else(|| {
+        AppError::corrupt(format!("Invalid media source: {}", item.source))
+            .with_code("INVALID_MEDIA_SOURCE")
+            .with_entity(&item.id)
+    })?;
+    Ok(get_media_files_dir(app)?.join(file_name))
+}
+
+/// Get the watched media folders file path
+pub fn get_watched_folders_file(app: &AppHandle) -> AppResult<PathBuf> {
+    let settings_dir = get_settings_dir(app)?;
+    Ok(settings_dir.join("watched-folders.json"))
+}
+
+/// Kinds of documents persisted as one JSON file per entity
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum DocumentKind {
+    Library,
+    Playlist,
+    MediaItem,
+    MediaPlaylist,
+}
+
+impl DocumentKind {
+    /// Directory holding documents of this kind
+    pub fn dir(&self, app: &AppHandle) -> AppResult<PathBuf> {
+        match self {
+            DocumentKind::Library => get_libraries_dir(app),
+            DocumentKind::Playlist => get_playlists_dir(app),
+            DocumentKind::MediaItem => get_media_metadata_dir(app),
+            DocumentKind::MediaPlaylist => get_media_playlists_dir(app),
+        }
+    }
+
+    /// Stable name used for per-kind subdirectories (quarantine, backups, ...)
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            DocumentKind::Library => "libraries",
+            DocumentKind::Playlist => "playlists",
+            DocumentKind::MediaItem => "media-metadata",
+            DocumentKind::MediaPlaylist => "media-playlists",
+        }
+    }
+}
+
+/// Get every directory that holds persisted data
+pub fn get_data_dirs(app: &AppHandle) -> AppResult<Vec<PathBuf>> {
+    Ok(vec![
         get_libraries_dir(app)?,
         get_playlists_dir(app)?,
         get_media_files_dir(app)?,
         get_media_metadata_dir(app)?,
         get_media_playlists_dir(app)?,
         get_settings_dir(app)?,
-    ];
+        get_quarantine_dir(app)?,
+        get_backups_dir(app)?,
+        get_snapshots_dir(app)?,
+        get_history_dir(app)?,
+        get_sync_versions_dir(app)?,
+    ])
+}
 
-    for dir in dirs {
+/// Ensure all required directories exist
+pub fn ensure_directories(app: &AppHandle) -> AppResult<()> {
+    for dir in get_data_dirs(app)? {
         fs::create_dir_all(&dir)?;
     }
 
     Ok(())
 }
 
+/// Roll forward or back any writes interrupted by a crash in the data
+/// directories and the folders below them.
+///
+/// Only safe while nothing else writes: a save in flight looks just like an
+/// interrupted one. Run when a workspace is opened, before any worker starts.
+pub fn recover_storage(app: &AppHandle) -> AppResult<Vec<RecoveredWrite>> {
+    let mut recovered = Vec::new();
+    for dir in get_data_dirs(app)? {
+        recover_tree(&dir, &mut recovered)?;
+    }
+    Ok(recovered)
+}
+
+/// Recover a directory and every directory below it (history, backups and
+/// version copies are kept per document)
+fn recover_tree(dir: &Path, recovered: &mut Vec<RecoveredWrite>) -> AppResult<()> {
+    recovered.extend(recover_interrupted_writes(dir)?);
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            recover_tree(&entry.path(), recovered)?;
+        }
+    }
+    Ok(())
+}
+
+/// Create the data directories and recover interrupted writes, logging each
+pub fn open_data_dirs(app: &AppHandle) -> AppResult<()> {
+    ensure_directories(app)?;
+    for write in recover_storage(app)? {
+        println!(
+            "Recovered interrupted write {:?}: {:?}",
+            write.path, write.action
+        );
+    }
+    Ok(())
+}
+
 /// Read JSON file from path
-pub fn read_json_file<T: for<'de> Deserialize<'de>>(path: &PathBuf) -> StorageResult<T> {
-    let content = fs::read_to_string(path)?;
-    let data: T = serde_json::from_str(&content)?;
+pub fn read_json_file<T: for<'de> Deserialize<'de>>(path: &PathBuf) -> AppResult<T> {
+    let content = fs::read_to_string(path).map_err(|e| AppError::io(e, path))?;
+    let data: T = serde_json::from_str(&content).map_err(|e| AppError::from(e).with_path(path))?;
     Ok(data)
 }
 
 /// Write JSON file to path
-pub fn write_json_file<T: Serialize>(path: &PathBuf, data: &T) -> StorageResult<()> {
+pub fn write_json_file<T: Serialize + ?Sized>(path: &Path, data: &T) -> AppResult<()> {
     let json = serde_json::to_string_pretty(data)?;
-    fs::write(path, json)?;
-    Ok(())
+    write_file_atomic(path, json.as_bytes())
 }
 
-/// Read all JSON files from a directory
-pub fn read_all_json_files<T: for<'de> Deserialize<'de>>(dir: &PathBuf) -> StorageResult<Vec<T>> {
-    let mut items = Vec::new();
+/// A file that could not be read or deserialized
+#[derive(Debug)]
+pub struct ReadFailure {
+    pub path: PathBuf,
+    pub error: AppError,
+}
+
+/// List all JSON files in a directory
+pub fn list_json_files(dir: &PathBuf) -> AppResult<Vec<PathBuf>> {
+    let mut paths = Vec::new();
 
     if !dir.exists() {
-        return Ok(items);
+        return Ok(paths);
     }
 
-    let entries = fs::read_dir(dir)?;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        // Conflicted copies left by sync tools are merged by folder_sync, not loaded
+        if path.is_file()
+            && path.extension().and_then(|s| s.to_str()) == Some("json")
+            && folder_sync::conflict_copy_of(&path).is_none()
+        {
+            paths.push(path);
+        }
+    }
 
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-            match read_json_file(&path) {
-                Ok(item) => items.push(item),
-                Err(e) => {
-                    eprintln!("Failed to read file {:?}: {}", path, e.message);
-                    // Continue reading other files even if one fails
-                }
-            }
+    Ok(paths)
+}
+
+/// Read all JSON files from a directory, returning the files that failed alongside the items
+pub fn read_json_files<T: for<'de> Deserialize<'de>>(
+    dir: &PathBuf,
+) -> AppResult<(Vec<T>, Vec<ReadFailure>)> {
+    let mut items = Vec::new();
+    let mut failures = Vec::new();
+
+    for path in list_json_files(dir)? {
+        match read_json_file(&path) {
+            Ok(item) => items.push(item),
+            Err(error) => failures.push(ReadFailure { path, error }),
         }
     }
 
-    Ok(items)
+    Ok((items, failures))
 }
 
 /// Delete a file
-pub fn delete_file(path: &PathBuf) -> StorageResult<()> {
+pub fn delete_file(path: &PathBuf) -> AppResult<()> {
     if path.exists() {
-        fs::remove_file(path)?;
+        storage_watcher::record_own_write(path, None);
+        fs::remove_file(path).map_err(|e| AppError::io(e, path))?;
     }
     Ok(())
 }
 
 /// Copy a file from source to destination
-pub fn copy_file(source: &PathBuf, dest: &PathBuf) -> StorageResult<()> {
-    fs::copy(source, dest)?;
-    Ok(())
+pub fn copy_file(source: &PathBuf, dest: &Path) -> AppResult<()> {
+    let (temp_path, journal_path) = begin_write(dest, None)?;
+
+    // Copied files are not hashed up front, so an interrupted copy is always rolled back
+    fs::copy(source, &temp_path).map_err(|e| AppError::io(e, dest))?;
+    fs::File::open(&temp_path)?.sync_all()?;
+
+    commit_write(dest, &temp_path, &journal_path)
 }
 
 /// Write raw bytes to a file
-pub fn write_file(path: &PathBuf, data: &[u8]) -> StorageResult<()> {
-    fs::write(path, data)?;
+pub fn write_file(path: &Path, data: &[u8]) -> AppResult<()> {
+    write_file_atomic(path, data)
+}
+
+// ===== Atomic Writes & Journal =====
+
+/// Journal entry recorded next to a target file while it is being replaced.
+///
+/// Paths are stored as file names relative to the journal's directory so the
+/// data directory can be moved without invalidating pending entries.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalEntry {
+    target: String,
+    temp: String,
+    /// SHA256 of the complete contents, if known before writing
+    sha256: Option<String>,
+    started_at: String,
+}
+
+/// Outcome of replaying a single journal entry during recovery
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredWrite {
+    pub path: PathBuf,
+    pub action: RecoveryAction,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RecoveryAction {
+    /// The temp file was complete and has been moved into place
+    RolledForward,
+    /// The temp file was incomplete and has been discarded; the previous version is kept
+    RolledBack,
+    /// The journal or temp file was left behind after the write had finished
+    CleanedUp,
+}
+
+/// Write bytes to a path via temp file + fsync + rename so readers never see a partial file
+pub fn write_file_atomic(path: &Path, data: &[u8]) -> AppResult<()> {
+    let sha256 = hash_bytes(data);
+    let (temp_path, journal_path) = begin_write(path, Some(sha256.clone()))?;
+    storage_watcher::record_own_write(path, Some(sha256));
+
+    // Failures are reported against the target; the temp file means nothing to the user
+    let mut file = fs::File::create(&temp_path).map_err(|e| AppError::io(e, path))?;
+    file.write_all(data).map_err(|e| AppError::io(e, path))?;
+    file.sync_all().map_err(|e| AppError::io(e, path))?;
+    drop(file);
+
+    commit_write(path, &temp_path, &journal_path)
+}
+
+/// Record a journal entry for an upcoming write and return the temp and journal paths
+fn begin_write(path: &Path, sha256: Option<String>) -> AppResult<(PathBuf, PathBuf)> {
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| AppError::invalid_path(path))?;
+    let dir = path.parent().ok_or_else(|| AppError::invalid_path(path))?;
+
+    // Unique per write so concurrent writers never share a temp file
+    let token = Uuid::new_v4().simple().to_string();
+    let temp_name = format!("{}.{}.{}", file_name, &token[..8], TEMP_SUFFIX);
+    let journal_path = dir.join(format!("{}.{}.{}", file_name, &token[..8], JOURNAL_SUFFIX));
+
+    let entry = JournalEntry {
+        target: file_name.to_string(),
+        temp: temp_name.clone(),
+        sha256,
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut journal = fs::File::create(&journal_path).map_err(|e| AppError::io(e, path))?;
+    journal.write_all(&serde_json::to_vec(&entry)?)?;
+    journal.sync_all()?;
+
+    Ok((dir.join(temp_name), journal_path))
+}
+
+/// Move a fully written temp file into place and retire its journal entry
+fn commit_write(path: &Path, temp_path: &Path, journal_path: &Path) -> AppResult<()> {
+    if let Err(e) = fs::rename(temp_path, path) {
+        let _ = fs::remove_file(temp_path);
+        let _ = fs::remove_file(journal_path);
+        return Err(AppError::io(e, path));
+    }
+    sync_parent_dir(path)?;
+
+    fs::remove_file(journal_path)?;
     Ok(())
 }
 
+/// Flush directory metadata so a completed rename survives power loss
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> AppResult<()> {
+    if let Some(dir) = path.parent() {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> AppResult<()> {
+    Ok(())
+}
+
+/// Replay or discard writes that were interrupted in a directory.
+///
+/// A temp file is only moved into place when its contents match the hash
+/// recorded in the journal; otherwise the previous version of the target is kept.
+pub fn recover_interrupted_writes(dir: &Path) -> AppResult<Vec<RecoveredWrite>> {
+    let mut recovered = Vec::new();
+
+    if !dir.exists() {
+        return Ok(recovered);
+    }
+
+    let mut journals = Vec::new();
+    let mut temps = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        match path.extension().and_then(|s| s.to_str()) {
+            Some(JOURNAL_SUFFIX) => journals.push(path),
+            Some(TEMP_SUFFIX) => temps.push(path),
+            _ => {}
+        }
+    }
+
+    // Replay in the order the writes started so the newest complete version wins
+    let mut entries: Vec<(PathBuf, Option<JournalEntry>)> = journals
+        .into_iter()
+        .map(|path| {
+            let entry = fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<JournalEntry>(&bytes).ok());
+            (path, entry)
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        let a_started = a.1.as_ref().map(|e| e.started_at.as_str()).unwrap_or("");
+        let b_started = b.1.as_ref().map(|e| e.started_at.as_str()).unwrap_or("");
+        a_started.cmp(b_started)
+    });
+
+    for (journal_path, entry) in entries {
+        if let Some(entry) = entry {
+            let target_path = dir.join(&entry.target);
+            let temp_path = dir.join(&entry.temp);
+            temps.retain(|t| t != &temp_path);
+
+            if temp_path.exists() {
+                let complete = match &entry.sha256 {
+                    Some(expected) => compute_file_hash(&temp_path)
+                        .map(|actual| &actual == expected)
+                        .unwrap_or(false),
+                    None => false,
+                };
+
+                if complete {
+                    fs::rename(&temp_path, &target_path)?;
+                    sync_parent_dir(&target_path)?;
+                    recovered.push(RecoveredWrite {
+                        path: target_path,
+                        action: RecoveryAction::RolledForward,
+                    });
+                } else {
+                    fs::remove_file(&temp_path)?;
+                    recovered.push(RecoveredWrite {
+                        path: target_path,
+                        action: RecoveryAction::RolledBack,
+                    });
+                }
+            } else {
+                recovered.push(RecoveredWrite {
+                    path: target_path,
+                    action: RecoveryAction::CleanedUp,
+                });
+            }
+        } else {
+            // Torn journal: the temp file had not been started yet, so there is nothing to replay
+            recovered.push(RecoveredWrite {
+                path: journal_path.clone(),
+                action: RecoveryAction::CleanedUp,
+            });
+        }
+
+        fs::remove_file(&journal_path)?;
+    }
+
+    // Temp files without a journal entry can never be verified
+    for temp_path in temps {
+        fs::remove_file(&temp_path)?;
+        recovered.push(RecoveredWrite {
+            path: temp_path,
+            action: RecoveryAction::CleanedUp,
+        });
+    }
+
+    Ok(recovered)
+}
+
 /// Get file extension
 pub fn get_file_extension(path: &PathBuf) -> Option<String> {
     path.extension()
@@ -184,8 +544,105 @@ pub fn get_file_stem(path: &PathBuf) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// File name in the media directory that a slide object or media item source refers to.
+///
+/// Sources are usually bare file names, but older documents may hold (possibly
+/// repeatedly URL-encoded) asset URLs; remote and data URLs return `None`.
+pub fn media_file_name(src: &str) -> Option<String> {
+    if src.is_empty()
+        || src.starts_with("http://")
+        || src.starts_with("https://")
+        || src.starts_with("data:")
+    {
+        return None;
+    }
+
+    decode_media_source(src)
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+}
+
+/// Path a slide source points at, for sources that are file paths or asset
+/// URLs of one, which is how slides refer to linked media
+pub fn media_source_path(src: &str) -> String {
+    let decoded = decode_media_source(src);
+    ASSET_URL_PREFIXES
+        .iter()
+        .find_map(|prefix| decoded.strip_prefix(prefix))
+        .map(|path| path.to_string())
+        .unwrap_or(decoded)
+}
+
+fn decode_media_source(src: &str) -> String {
+    let mut decoded = src.to_string();
+    loop {
+        let next = percent_decode(&decoded);
+        if next == decoded {
+            return decoded;
+        }
+        decoded = next;
+    }
+}
+
+/// Percent-encode like JavaScript's `encodeURIComponent`, which the frontend
+/// uses to build asset URLs
+pub fn encode_uri_component(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decode `%XX` escapes, keeping the input as is if the result isn't UTF-8
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| input.to_string())
+}
+
+/// Compute SHA256 hash of an in-memory buffer
+pub fn hash_bytes(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
 /// Compute SHA256 hash of a file
-pub fn compute_file_hash(path: &PathBuf) -> StorageResult<String> {
+pub fn compute_file_hash(path: &PathBuf) -> AppResult<String> {
     let mut file = fs::File::open(path)?;
     let mut hasher = Sha256::new();
     let mut buffer = [0; 8192]; // 8KB buffer
@@ -0,0 +1,304 @@
+//! Notices documents changed on disk by something other than this app.
+//!
+//! Backups being restored by hand, sync tools like Dropbox or Syncthing and
+//! manual edits all change files behind the repository's back. The document
+//! and settings directories are polled, like watched folders, since change
+//! notifications are unreliable on the synced and network folders this is
+//! meant for. A file is reported once it has stopped changing, and files this
+//! process wrote itself are recognized by their content hash and skipped.
+//! Conflicted copies left by sync tools are handed to folder sync to merge.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppResult;
+use crate::folder_sync;
+use crate::repository;
+use crate::search;
+use crate::storage::{self, DocumentKind};
+
+pub const STORAGE_CHANGED_EVENT: &str = "storage:changed";
+
+/// How often the data directories are scanned
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What changed, as reported in `storage:changed` events
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageEntity {
+    Library,
+    Playlist,
+    MediaItem,
+    MediaPlaylist,
+    Settings, // Id is the file name without extension, e.g. "tag-groups"
+}
+
+impl From<DocumentKind> for StorageEntity {
+    fn from(kind: DocumentKind) -> Self {
+        match kind {
+            DocumentKind::Library => StorageEntity::Library,
+            DocumentKind::Playlist => StorageEntity::Playlist,
+            DocumentKind::MediaItem => StorageEntity::MediaItem,
+            DocumentKind::MediaPlaylist => StorageEntity::MediaPlaylist,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeType {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+    pub kind: StorageEntity,
+    pub id: String,
+    pub change: ChangeType,
+}
+
+/// Size and modification time, compared between scans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    size: u64,
+    modified_ns: u128,
+}
+
+// ===== Own Writes =====
+
+/// Content hash of the last write this process made to each path, `None` for deletes
+fn own_writes() -> &'static Mutex<HashMap<PathBuf, Option<String>>> {
+    static OWN_WRITES: OnceLock<Mutex<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+    OWN_WRITES.get_or_init(Default::default)
+}
+
+/// Remember a write about to be made so the watcher doesn't report it.
+/// `sha256` is the new content's hash, or `None` when the file is removed.
+pub fn record_own_write(path: &Path, sha256: Option<String>) {
+    own_writes()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path.to_path_buf(), sha256);
+}
+
+/// Whether a settled file is exactly what this process last wrote there
+fn is_own_write(path: &Path, present: bool) -> bool {
+    let Some(expected) = own_writes()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(path)
+    else {
+        return false;
+    };
+    match expected {
+        Some(hash) => present && storage::compute_file_hash(&path.to_path_buf()).ok() == Some(hash),
+        None => !present,
+    }
+}
+
+// ===== Scanning =====
+
+/// Directories to watch and what their files hold
+fn watched_dirs(app: &AppHandle) -> AppResult<Vec<(PathBuf, StorageEntity)>> {
+    let mut dirs = Vec::new();
+    for kind in [
+        DocumentKind::Library,
+        DocumentKind::Playlist,
+        DocumentKind::MediaItem,
+        DocumentKind::MediaPlaylist,
+    ] {
+        dirs.push((kind.dir(app)?, kind.into()));
+    }
+    dirs.push((storage::get_settings_dir(app)?, StorageEntity::Settings));
+    Ok(dirs)
+}
+
+/// Stamp every JSON file in the watched directories. Temp and journal files
+/// of in-progress writes, and hidden files sync tools stage downloads in, are
+/// skipped.
+fn scan(dirs: &[(PathBuf, StorageEntity)]) -> HashMap<PathBuf, FileStamp> {
+    let mut stamps = HashMap::new();
+    for (dir, _) in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let visible = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| !n.starts_with('.'));
+            if !visible || path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified_ns = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            stamps.insert(
+                path,
+                FileStamp {
+                    size: metadata.len(),
+                    modified_ns,
+                },
+            );
+        }
+    }
+    stamps
+}
+
+fn describe(path: &Path, dirs: &[(PathBuf, StorageEntity)]) -> Option<(StorageEntity, String)> {
+    let dir = path.parent()?;
+    let (_, kind) = dirs.iter().find(|(d, _)| d == dir)?;
+    let id = storage::get_file_stem(&path.to_path_buf())?;
+    Some((*kind, id))
+}
+
+/// Poll the data directories, emitting `storage:changed` with every batch of
+/// outside changes
+pub fn start_watching(app: AppHandle) {
+    thread::spawn(move || {
+        let mut dirs = match watched_dirs(&app) {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                eprintln!("Failed to watch storage: {}", e.message);
+                return;
+            }
+        };
+
+        // Copies synced while the app was closed
+        let mut merged = Vec::new();
+        merge_conflict_copies(&app, &mut merged);
+        broadcast_changes(&app, &merged);
+
+        let mut known = scan(&dirs);
+        // Changed paths and their stamp at the last scan, until they settle
+        let mut pending: HashMap<PathBuf, Option<FileStamp>> = HashMap::new();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            // Start over when another workspace became active
+            match watched_dirs(&app) {
+                Ok(active) if active != dirs => {
+                    dirs = active;
+                    known = scan(&dirs);
+                    pending.clear();
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to watch storage: {}", e.message),
+            }
+            let current = scan(&dirs);
+
+            let mut changed: Vec<PathBuf> = current
+                .iter()
+                .filter(|(path, stamp)| known.get(*path) != Some(stamp))
+                .map(|(path, _)| path.clone())
+                .collect();
+            changed.extend(known.keys().filter(|p| !current.contains_key(*p)).cloned());
+
+            let mut changes = Vec::new();
+            let mut copies_changed = false;
+            let mut still_changing = HashMap::new();
+            for path in changed {
+                let stamp = current.get(&path).copied();
+                // Report a file only once it looked the same on two scans in a row
+                if pending.get(&path) != Some(&stamp) {
+                    still_changing.insert(path, stamp);
+                    continue;
+                }
+
+                let previous = match stamp {
+                    Some(stamp) => known.insert(path.clone(), stamp),
+                    None => known.remove(&path),
+                };
+                if is_own_write(&path, stamp.is_some()) {
+                    continue;
+                }
+                if folder_sync::conflict_copy_of(&path).is_some() {
+                    copies_changed = true;
+                    continue;
+                }
+                let Some((kind, id)) = describe(&path, &dirs) else {
+                    continue;
+                };
+                let change = match (previous, stamp) {
+                    (None, _) => ChangeType::Created,
+                    (Some(_), Some(_)) => ChangeType::Modified,
+                    (Some(_), None) => ChangeType::Deleted,
+                };
+                if change != ChangeType::Deleted {
+                    keep_received(&app, kind, &id);
+                }
+                changes.push(StorageChange { kind, id, change });
+            }
+            pending = still_changing;
+
+            if copies_changed {
+                merge_conflict_copies(&app, &mut changes);
+            }
+            broadcast_changes(&app, &changes);
+        }
+    });
+}
+
+/// Drop caches of documents changed outside the app and tell every window
+pub fn broadcast_changes(app: &AppHandle, changes: &[StorageChange]) {
+    if changes.is_empty() {
+        return;
+    }
+    search::invalidate(app);
+    repository::current(app).invalidate_caches();
+    if let Err(e) = app.emit(STORAGE_CHANGED_EVENT, changes) {
+        eprintln!("Failed to emit storage changes: {}", e);
+    }
+}
+
+// ===== Folder Sync =====
+
+/// Keep versions other machines wrote as merge bases for later conflicted copies
+fn keep_received(app: &AppHandle, kind: StorageEntity, id: &str) {
+    match kind {
+        StorageEntity::Library => folder_sync::record_received(app, DocumentKind::Library, id),
+        StorageEntity::Playlist => folder_sync::record_received(app, DocumentKind::Playlist, id),
+        _ => {}
+    }
+}
+
+/// Merge conflicted copies left by sync tools, adding the merged documents
+/// to `changes` and telling windows about copies that need the user
+fn merge_conflict_copies(app: &AppHandle, changes: &mut Vec<StorageChange>) {
+    let report = match folder_sync::reconcile(app) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to merge conflicted copies: {}", e.message);
+            return;
+        }
+    };
+
+    changes.extend(report.merged.into_iter().map(|(kind, id)| StorageChange {
+        kind: kind.into(),
+        id,
+        change: ChangeType::Modified,
+    }));
+    if !report.conflicts.is_empty() {
+        if let Err(e) = app.emit(folder_sync::SYNC_CONFLICTS_EVENT, &report.conflicts) {
+            eprintln!("Failed to emit sync conflicts: {}", e);
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Background generation of downscaled thumbnails and video poster frames.
+//!
+//! Media items are queued on import (and on startup when their thumbnail is
+//! missing) and processed one at a time on a worker thread, so the media grid
+//! never has to load full-resolution originals. Images are decoded with the
+//! `image` crate; video poster frames need an `ffmpeg` binary and are skipped
+//! when none is installed, leaving the frontend to capture a frame itself.
+//! Thumbnails are written beside the media as `<id>_thumb.<ext>`.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AppError, AppResult};
+use crate::media_references;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
+use crate::models::MediaItem;
+use crate::repository;
+use crate::storage;
+
+pub const THUMBNAIL_PROGRESS_EVENT: &str = "media:thumbnail-progress";
+
+/// Longest edge of a generated thumbnail, in pixels
+const THUMBNAIL_MAX_SIZE: u32 = 480;
+
+const THUMBNAIL_JPEG_QUALITY: u8 = 82;
+
+/// Poster frames are taken 10% into the video, but never later than this
+const POSTER_FRAME_MAX_OFFSET: f64 = 5.0;
+
+/// Searched after PATH; apps launched from Finder don't inherit the shell's PATH
+const FFMPEG_FALLBACK_DIRS: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ThumbnailStatus {
+    Generated,
+    Skipped, // Deleted before its turn, or a video with no decoder available
+    Failed,
+}
+
+/// Emitted after each queued item is processed
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailProgress {
+    pub media_id: String,
+    pub status: ThumbnailStatus,
+    pub media_item: Option<MediaItem>, // The updated item when generated
+    pub error: Option<String>,
+    pub completed: usize, // Processed since the queue was last empty
+    pub remaining: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: VecDeque<String>,
+    completed: usize,
+}
+
+/// Media ids waiting for a thumbnail, shared with the worker thread
+#[derive(Default)]
+pub struct ThumbnailQueue {
+    state: Mutex<QueueState>,
+    wake: Condvar,
+}
+
+// ===== Queue =====
+
+/// Queue media items for thumbnail generation, returning how many were added.
+/// Ids already waiting are not queued twice.
+pub fn enqueue<I: IntoIterator<Item = String>>(app: &AppHandle, media_ids: I) -> usize {
+    let queue = app.state::<ThumbnailQueue>();
+    let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut added = 0;
+    for id in media_ids {
+        if !state.pending.contains(&id) {
+            state.pending.push_back(id);
+            added += 1;
+        }
+    }
+
+    if added > 0 {
+        queue.wake.notify_one();
+    }
+    added
+}
+
+/// Queue every image and video whose thumbnail is unset or missing from disk
+pub fn enqueue_missing(app: &AppHandle) -> AppResult<usize> {
+    let media_files_dir = storage::get_media_files_dir(app)?;
+    let missing = repository::current(app)
+        .load_media_items()?
+        .items
+        .into_iter()
+        .filter(|item| matches!(item.media_type.as_str(), "image" | "video"))
+        .filter(|item| {
+            !item
+                .thumbnail
+                .as_deref()
+                .and_then(storage::media_file_name)
+                .is_some_and(|name| media_files_dir.join(name).exists())
+        })
+        .map(|item| item.id);
+
+    Ok(enqueue(app, missing))
+}
+
+/// Start the worker thread, first queueing anything imported before thumbnails existed
+pub fn start_worker(app: AppHandle) {
+    thread::spawn(move || {
+        if let Err(e) = enqueue_missing(&app) {
+            eprintln!("Failed to queue missing thumbnails: {}", e.message);
+        }
+
+        loop {
+            let media_id = next_media_id(&app);
+
+            // A panic while decoding one bad file must not stop the queue
+            let generated =
+                panic::catch_unwind(AssertUnwindSafe(|| generate_thumbnail(&app, &media_id)))
+                    .unwrap_or_else(|_| Err(AppError::internal("Thumbnail generation crashed")));
+            let (status, media_item, error) = match generated {
+                Ok(Some(item)) => (ThumbnailStatus::Generated, Some(item), None),
+                Ok(None) => (ThumbnailStatus::Skipped, None, None),
+                Err(e) => {
+                    eprintln!(
+                        "Failed to generate thumbnail for {}: {}",
+                        media_id, e.message
+                    );
+                    (ThumbnailStatus::Failed, None, Some(e.message))
+                }
+            };
+
+            let (completed, remaining) = {
+                let queue = app.state::<ThumbnailQueue>();
+                let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+                state.completed += 1;
+                (state.completed, state.pending.len())
+            };
+
+            let progress = ThumbnailProgress {
+                media_id,
+                status,
+                media_item,
+                error,
+                completed,
+                remaining,
+            };
+            if let Err(e) = app.emit(THUMBNAIL_PROGRESS_EVENT, &progress) {
+                eprintln!("Failed to emit thumbnail progress: {}", e);
+            }
+        }
+    });
+}
+
+/// Block until a media id is queued
+fn next_media_id(app: &AppHandle) -> String {
+    let queue = app.state::<ThumbnailQueue>();
+    let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+
+    loop {
+        if let Some(id) = state.pending.pop_front() {
+            return id;
+        }
+        state.completed = 0;
+        state = queue.wake.wait(state).unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+// ===== Generation =====
+
+/// Render and save a thumbnail for one media item.
+///
+/// Returns `None` when there is nothing to do: the item was deleted, or it is
+/// a video and no decoder is installed.
+pub fn generate_thumbnail(app: &AppHandle, media_id: &str) -> AppResult<Option<MediaItem>> {
+    let repo = repository::current(app);
+    let Some(mut media_item) = repo.get_media_item(media_id)? else {
+        return Ok(None);
+    };
+
+    let media_files_dir = storage::get_media_files_dir(app)?;
+    let source_path = storage::media_item_path(app, &media_item)?;
+    if !source_path.exists() {
+        return Err(AppError::not_found(format!(
+            "Media file not found: {}",
+            source_path.display()
+        ))
+        .with_code("FILE_NOT_FOUND")
+        .with_entity(media_id)
+        .with_path(&source_path));
+    }
+
+    // The thumbnail is unreferenced until the item is saved
+    let _imports = media_references::lock_media_imports();
+    let thumbnail = match media_item.media_type.as_str() {
+        "image" => render_image_thumbnail(&source_path, &media_files_dir, media_id)?,
+        "video" => match ffmpeg_path() {
+            Some(ffmpeg) => render_video_poster(
+                ffmpeg,
+                &source_path,
+                &media_files_dir,
+                media_id,
+                media_item.duration,
+            )?,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    // A replaced thumbnail is left for garbage collection since slides may still use it
+    media_item.thumbnail = Some(thumbnail);
+    media_item.schema_version = Some(CURRENT_SCHEMA_VERSION);
+    media_item.updated_at = chrono::Utc::now().to_rfc3339();
+    repo.save_media_item(&media_item)?;
+    repository::update_media_playlist_copies(repo.as_ref(), &media_item)?;
+
+    Ok(Some(media_item))
+}
+
+/// Downscale an image, honouring its EXIF orientation.
+/// Images with transparency are saved as PNG, everything else as JPEG.
+fn render_image_thumbnail(
+    source_path: &Path,
+    media_files_dir: &Path,
+    media_id: &str,
+) -> AppResult<String> {
+    let mut decoder = ImageReader::open(source_path)?
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(image_error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(image_error)?;
+    image.apply_orientation(orientation);
+
+    if image.width() > THUMBNAIL_MAX_SIZE || image.height() > THUMBNAIL_MAX_SIZE {
+        image = image.resize(THUMBNAIL_MAX_SIZE, THUMBNAIL_MAX_SIZE, FilterType::Triangle);
+    }
+
+    let mut bytes = Vec::new();
+    let extension = if image.color().has_alpha() {
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .map_err(image_error)?;
+        "png"
+    } else {
+        JpegEncoder::new_with_quality(&mut bytes, THUMBNAIL_JPEG_QUALITY)
+            .encode_image(&image.to_rgb8())
+            .map_err(image_error)?;
+        "jpg"
+    };
+
+    let file_name = format!("{}_thumb.{}", media_id, extension);
+    storage::write_file_atomic(&media_files_dir.join(&file_name), &bytes)?;
+
+    Ok(file_name)
+}
+
+fn image_error(err: image::ImageError) -> AppError {
+    let message = format!("Failed to process image: {}", err);
+    match err {
+        ImageError::IoError(err) => AppError::from(err),
+        ImageError::Unsupported(_) => AppError::unsupported_format(message),
+        ImageError::Decoding(_) => AppError::corrupt(message).with_code("INVALID_IMAGE"),
+        _ => AppError::internal(message),
+    }
+}
+
+/// Extract a single downscaled frame with ffmpeg, falling back to the first
+/// frame when seeking fails (e.g. the duration is unknown or wrong)
+fn render_video_poster(
+    ffmpeg: &Path,
+    source_path: &Path,
+    media_files_dir: &Path,
+    media_id: &str,
+    duration: Option<f64>,
+) -> AppResult<String> {
+    let file_name = format!("{}_thumb.jpg", media_id);
+    // Hidden so garbage collection ignores it while ffmpeg is writing
+    let partial_path = media_files_dir.join(format!(".{}_thumb.partial.jpg", media_id));
+
+    let offset = duration
+        .map(|d| (d * 0.1).min(POSTER_FRAME_MAX_OFFSET))
+        .unwrap_or(0.0);
+    let offsets = if offset > 0.0 {
+        vec![offset, 0.0]
+    } else {
+        vec![0.0]
+    };
+
+    let mut last_error = String::new();
+    for offset in offsets {
+        let output = ffmpeg_command(ffmpeg)
+            .args(["-v", "error", "-nostdin", "-y", "-ss"])
+            .arg(format!("{:.3}", offset))
+            .arg("-i")
+            .arg(source_path)
+            .args(["-frames:v", "1", "-q:v", "4", "-vf"])
+            .arg(format!(
+                "scale={0}:{0}:force_original_aspect_ratio=decrease",
+                THUMBNAIL_MAX_SIZE
+            ))
+            .arg(&partial_path)
+            .output()?;
+
+        let written = fs::metadata(&partial_path)
+            .map(|m| m.len() > 0)
+            .unwrap_or(false);
+        if output.status.success() && written {
+            fs::rename(&partial_path, media_files_dir.join(&file_name))?;
+            return Ok(file_name);
+        }
+        last_error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    }
+
+    let _ = fs::remove_file(&partial_path);
+    Err(
+        AppError::internal(format!("ffmpeg could not extract a frame: {}", last_error))
+            .with_code("FFMPEG_FAILED")
+            .with_path(source_path),
+    )
+}
+
+/// Locate ffmpeg once per run
+fn ffmpeg_path() -> Option<&'static Path> {
+    static FFMPEG: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    FFMPEG
+        .get_or_init(|| {
+            std::iter::once(PathBuf::from("ffmpeg"))
+                .chain(
+                    FFMPEG_FALLBACK_DIRS
+                        .iter()
+                        .map(|dir| Path::new(dir).join("ffmpeg")),
+                )
+                .find(|candidate| {
+                    ffmpeg_command(candidate)
+                        .arg("-version")
+                        .output()
+                        .is_ok_and(|output| output.status.success())
+                })
+        })
+        .as_deref()
+}
+
+fn ffmpeg_command(ffmpeg: &Path) -> Command {
+    #[allow(unused_mut)]
+    let mut command = Command::new(ffmpeg);
+
+    // Don't flash a console window for every frame
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    command
+}
@@ -0,0 +1,390 @@
+//! Folders whose new media files are imported into a media playlist automatically.
+//!
+//! Folders are polled rather than watched with filesystem notifications,
+//! which are unreliable on the network shares these usually live on. A file
+//! is imported once it has stopped changing, and the size and modification
+//! time of every file already handled are remembered so unchanged files are
+//! not hashed again on each pass.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::media_import::{self, ImportFailure};
+use crate::models::{MediaItem, MediaPlaylist};
+use crate::repository;
+use crate::storage::{self, DocumentKind};
+
+pub const WATCHED_FOLDER_IMPORTED_EVENT: &str = "media:watched-folder-imported";
+
+/// How often watched folders are scanned
+const SCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Files modified more recently than this may still be copying in
+const SETTLE_TIME: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolder {
+    pub id: String,
+    pub path: String,
+    pub media_playlist_id: String,
+    pub recursive: bool,
+    #[serde(default)]
+    pub link: bool, // Reference files in place instead of copying them
+    pub created_at: String,
+    pub last_scan_at: Option<String>,
+    pub last_error: Option<String>, // From the most recent scan
+}
+
+/// Size and modification time of a file that has already been imported
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct FileStamp {
+    size: u64,
+    modified_ms: u128,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct WatchedFolderRecord {
+    #[serde(flatten)]
+    folder: WatchedFolder,
+    #[serde(default)]
+    files: BTreeMap<String, FileStamp>, // Keyed by path relative to the folder
+}
+
+/// Emitted when a scan adds files to a watched folder's playlist
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolderImport {
+    pub watched_folder_id: String,
+    pub media_playlist: Option<MediaPlaylist>, // Updated playlist, when items were added
+    pub imported: Vec<MediaItem>,
+    pub failed: Vec<ImportFailure>,
+}
+
+/// Coordinates the polling thread with commands
+#[derive(Default)]
+pub struct WatchedFolderState {
+    config: Mutex<()>,   // Held while reading and writing the config file
+    scanning: Mutex<()>, // Only one scan at a time, so files aren't imported twice
+    scan_requested: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl WatchedFolderState {
+    /// Hold off scans and config changes, e.g. while the workspace is moved
+    pub fn pause(&self) -> (MutexGuard<'_, ()>, MutexGuard<'_, ()>) {
+        let scanning = self.scanning.lock().unwrap_or_else(|e| e.into_inner());
+        let config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+        (scanning, config)
+    }
+}
+
+// ===== Config =====
+
+fn read_records(app: &AppHandle) -> AppResult<Vec<WatchedFolderRecord>> {
+    let path = storage::get_watched_folders_file(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    storage::read_json_file(&path)
+}
+
+fn write_records(app: &AppHandle, records: &[WatchedFolderRecord]) -> AppResult<()> {
+    storage::write_json_file(&storage::get_watched_folders_file(app)?, records)
+}
+
+/// Read, change and write the config while holding its lock
+fn update_records<T>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<WatchedFolderRecord>) -> AppResult<T>,
+) -> AppResult<T> {
+    let state = app.state::<WatchedFolderState>();
+    let _guard = state.config.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut records = read_records(app)?;
+    let result = change(&mut records)?;
+    write_records(app, &records)?;
+    Ok(result)
+}
+
+pub fn list_watched_folders(app: &AppHandle) -> AppResult<Vec<WatchedFolder>> {
+    let state = app.state::<WatchedFolderState>();
+    let _guard = state.config.lock().unwrap_or_else(|e| e.into_inner());
+
+    Ok(read_records(app)?
+        .into_iter()
+        .map(|record| record.folder)
+        .collect())
+}
+
+/// Start watching a folder. Without a media playlist, an empty one named after
+/// the folder is created. Files already in the folder are imported on the
+/// first scan, which starts right away.
+pub fn add_watched_folder(
+    app: &AppHandle,
+    path: &str,
+    media_playlist_id: Option<&str>,
+    recursive: bool,
+    link: bool,
+) -> AppResult<WatchedFolder> {
+    let folder_path = Path::new(path);
+    if !folder_path.is_dir() {
+        return Err(AppError::not_found(format!("Folder not found: {}", path))
+            .with_code("FOLDER_NOT_FOUND")
+            .with_path(path));
+    }
+
+    let media_playlist_id = match media_playlist_id {
+        Some(id) => {
+            repository::current(app)
+                .get_media_playlist(id)?
+                .ok_or_else(|| AppError::document_not_found(DocumentKind::MediaPlaylist, id))?
+                .id
+        }
+        None => {
+            let name = media_import::folder_name(folder_path);
+            media_import::add_to_media_playlist(app, None, &name, &[])?.id
+        }
+    };
+
+    let folder = update_records(app, |records| {
+        if records
+            .iter()
+            .any(|r| Path::new(&r.folder.path) == folder_path)
+        {
+            return Err(
+                AppError::conflict(format!("Folder is already being watched: {}", path))
+                    .with_path(path),
+            );
+        }
+
+        let folder = WatchedFolder {
+            id: Uuid::new_v4().to_string(),
+            path: path.to_string(),
+            media_playlist_id,
+            recursive,
+            link,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_scan_at: None,
+            last_error: None,
+        };
+        records.push(WatchedFolderRecord {
+            folder: folder.clone(),
+            files: BTreeMap::new(),
+        });
+        Ok(folder)
+    })?;
+
+    request_scan(app);
+    Ok(folder)
+}
+
+/// Stop watching a folder; its playlist and imported media are kept
+pub fn remove_watched_folder(app: &AppHandle, id: &str) -> AppResult<()> {
+    update_records(app, |records| {
+        let count = records.len();
+        records.retain(|r| r.folder.id != id);
+        if records.len() == count {
+            return Err(
+                AppError::not_found(format!("Watched folder not found: {}", id))
+                    .with_code("WATCHED_FOLDER_NOT_FOUND")
+                    .with_entity(id),
+            );
+        }
+        Ok(())
+    })
+}
+
+// ===== Scanning =====
+
+/// Start the polling thread
+pub fn start_watching(app: AppHandle) {
+    thread::spawn(move || loop {
+        {
+            let state = app.state::<WatchedFolderState>();
+            let requested = state
+                .scan_requested
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let (mut requested, _) = state
+                .wake
+                .wait_timeout_while(requested, SCAN_INTERVAL, |requested| !*requested)
+                .unwrap_or_else(|e| e.into_inner());
+            *requested = false;
+        }
+
+        if let Err(e) = scan_watched_folders(&app) {
+            eprintln!("Failed to scan watched folders: {}", e.message);
+        }
+    });
+}
+
+/// Wake the polling thread for an immediate scan
+pub fn request_scan(app: &AppHandle) {
+    let state = app.state::<WatchedFolderState>();
+    *state
+        .scan_requested
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = true;
+    state.wake.notify_one();
+}
+
+/// Scan every watched folder now, importing new and changed files.
+///
+/// Emits `media:watched-folder-imported` for each folder where something was
+/// imported or failed, and returns the same reports.
+pub fn scan_watched_folders(app: &AppHandle) -> AppResult<Vec<WatchedFolderImport>> {
+    let state = app.state::<WatchedFolderState>();
+    let _scanning = state.scanning.lock().unwrap_or_else(|e| e.into_inner());
+
+    let records = {
+        let _guard = state.config.lock().unwrap_or_else(|e| e.into_inner());
+        read_records(app)?
+    };
+
+    let mut reports = Vec::new();
+    for mut record in records {
+        let report = match scan_folder(app, &mut record) {
+            Ok(report) => {
+                record.folder.last_error = report
+                    .as_ref()
+                    .and_then(|report| report.failed.first())
+                    .map(|f| format!("{}: {}", f.source_path, f.error));
+                report
+            }
+            Err(e) => {
+                record.folder.last_error = Some(e.message);
+                None
+            }
+        };
+        record.folder.last_scan_at = Some(chrono::Utc::now().to_rfc3339());
+
+        // Merge into the current config; the folder may have been removed meanwhile
+        update_records(app, |records| {
+            if let Some(current) = records.iter_mut().find(|r| r.folder.id == record.folder.id) {
+                current.folder.last_scan_at = record.folder.last_scan_at.clone();
+                current.folder.last_error = record.folder.last_error.clone();
+                current.files = record.files.clone();
+            }
+            Ok(())
+        })?;
+
+        if let Some(report) = report {
+            if let Err(e) = app.emit(WATCHED_FOLDER_IMPORTED_EVENT, &report) {
+                eprintln!("Failed to emit watched folder import: {}", e);
+            }
+            reports.push(report);
+        }
+    }
+
+    Ok(reports)
+}
+
+/// Import settled files that are new or changed since they were last seen.
+/// Returns `None` when there was nothing to import.
+fn scan_folder(
+    app: &AppHandle,
+    record: &mut WatchedFolderRecord,
+) -> AppResult<Option<WatchedFolderImport>> {
+    let folder = Path::new(&record.folder.path);
+    if !folder.is_dir() {
+        return Err(
+            AppError::not_found(format!("Folder not found: {}", record.folder.path))
+                .with_code("FOLDER_NOT_FOUND")
+                .with_path(folder),
+        );
+    }
+
+    let now = SystemTime::now();
+    let mut present = BTreeMap::new();
+    let mut candidates = Vec::new();
+
+    for path in media_import::collect_media_files(folder, record.folder.recursive)? {
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let modified = metadata.modified()?;
+        let stamp = FileStamp {
+            size: metadata.len(),
+            modified_ms: modified
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0),
+        };
+        let relative = path
+            .strip_prefix(folder)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+
+        if record.files.get(&relative) == Some(&stamp) {
+            present.insert(relative, stamp);
+            continue;
+        }
+        if now.duration_since(modified).unwrap_or_default() < SETTLE_TIME {
+            continue; // Picked up on a later scan
+        }
+        candidates.push((relative, stamp, path.to_string_lossy().to_string()));
+    }
+
+    // Forget files that were removed so the config doesn't grow forever
+    record.files = present;
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    let source_paths = candidates.iter().map(|(_, _, path)| path.clone()).collect();
+    let results = media_import::import_each(app, source_paths, record.folder.link);
+
+    let mut imported = Vec::new();
+    let mut failed = Vec::new();
+    let mut handled = Vec::new();
+    for ((relative, stamp, _), (source_path, result)) in candidates.into_iter().zip(results) {
+        match result {
+            Ok((item, _)) => {
+                handled.push((relative, stamp));
+                imported.push(item);
+            }
+            Err(e) => {
+                // Unsupported files are only retried once they change; anything
+                // else may be a file still syncing or locked, so try it next scan
+                if e.kind == ErrorKind::UnsupportedFormat {
+                    handled.push((relative, stamp));
+                }
+                failed.push(ImportFailure {
+                    source_path,
+                    error: e.message,
+                });
+            }
+        }
+    }
+
+    let media_playlist = if imported.is_empty() {
+        None
+    } else {
+        Some(media_import::add_to_media_playlist(
+            app,
+            Some(&record.folder.media_playlist_id),
+            &media_import::folder_name(folder),
+            &imported,
+        )?)
+    };
+    record.files.extend(handled);
+
+    Ok(Some(WatchedFolderImport {
+        watched_folder_id: record.folder.id.clone(),
+        media_playlist,
+        imported,
+        failed,
+    }))
+}
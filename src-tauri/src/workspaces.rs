@@ -0,0 +1,602 @@
+//! Named, separate data directories the app can switch between.
+//!
+//! Every storage path derives from the active workspace's root. The default
+//! workspace lives in the app data directory, where all data was kept before
+//! workspaces; others can be anywhere, such as an external drive or a shared
+//! folder. The list of workspaces belongs to the machine and is kept in the
+//! app config directory, outside every workspace.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::history;
+use crate::media_references;
+use crate::repository;
+use crate::search;
+use crate::snapshots;
+use crate::storage;
+use crate::watched_folders::WatchedFolderState;
+
+pub const WORKSPACE_CHANGED_EVENT: &str = "workspace:changed";
+
+pub const DEFAULT_WORKSPACE_ID: &str = "default";
+
+/// Entries of a workspace root that hold its data
+const WORKSPACE_ENTRIES: [&str; 11] = [
+    "libraries",
+    "playlists",
+    "media",
+    "settings",
+    "quarantine",
+    "backups",
+    "snapshots",
+    "history",
+    "storage.db",
+    "storage.db-wal",
+    "storage.db-shm",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub path: String, // Root directory
+    pub created_at: String,
+}
+
+/// Workspaces known on this machine, persisted in the app config directory
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceList {
+    pub active_id: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+/// Managed state holding the active workspace
+pub struct WorkspaceState {
+    list: Mutex<()>, // Held while reading and writing the workspace list
+    active: RwLock<Workspace>,
+}
+
+impl WorkspaceState {
+    /// Open the workspace that was active last time, falling back to the
+    /// default one when its root is gone (e.g. an unplugged drive) or the
+    /// workspace list can't be read
+    pub fn load(app: &AppHandle) -> AppResult<Self> {
+        let list = read_list(app).or_else(|e| {
+            eprintln!(
+                "Failed to read the workspace list, using the default workspace: {}",
+                e.message
+            );
+            with_default_workspace(
+                app,
+                WorkspaceList {
+                    active_id: DEFAULT_WORKSPACE_ID.to_string(),
+                    workspaces: Vec::new(),
+                },
+            )
+        })?;
+        let default = find(&list, DEFAULT_WORKSPACE_ID)?.clone();
+
+        let active = match find(&list, &list.active_id) {
+            Ok(workspace) if Path::new(&workspace.path).is_dir() => workspace.clone(),
+            Ok(workspace) => {
+                eprintln!(
+                    "Workspace {} is unavailable at {}, using the default workspace",
+                    workspace.name, workspace.path
+                );
+                default
+            }
+            Err(_) => default,
+        };
+
+        Ok(Self {
+            list: Mutex::new(()),
+            active: RwLock::new(active),
+        })
+    }
+
+    fn active(&self) -> Workspace {
+        match self.active.read() {
+            Ok(active) => active.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Make `workspace` active, returning the previously active one
+    fn replace(&self, workspace: Workspace) -> Workspace {
+        match self.active.write() {
+            Ok(mut active) => std::mem::replace(&mut *active, workspace),
+            Err(poisoned) => std::mem::replace(&mut *poisoned.into_inner(), workspace),
+        }
+    }
+}
+
+/// Root directory of the active workspace
+pub fn active_root(app: &AppHandle) -> AppResult<PathBuf> {
+    match app.try_state::<WorkspaceState>() {
+        Some(state) => Ok(PathBuf::from(state.active().path)),
+        None => storage::get_default_data_dir(app),
+    }
+}
+
+/// Id of the active workspace
+pub fn active_id(app: &AppHandle) -> String {
+    app.try_state::<WorkspaceState>()
+        .map(|state| state.active().id)
+        .unwrap_or_else(|| DEFAULT_WORKSPACE_ID.to_string())
+}
+
+// ===== Workspace List =====
+
+fn read_list(app: &AppHandle) -> AppResult<WorkspaceList> {
+    let path = storage::get_workspaces_file(app)?;
+    let stored = if path.exists() {
+        match storage::read_json_file(&path) {
+            Ok(list) => Some(list),
+            // The other workspaces' data is untouched and they can be added back
+            Err(e) if e.kind == ErrorKind::Corrupt => {
+                set_aside_list(&path, &e);
+                None
+            }
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+
+    let list = stored.unwrap_or_else(|| WorkspaceList {
+        active_id: DEFAULT_WORKSPACE_ID.to_string(),
+        workspaces: Vec::new(),
+    });
+    with_default_workspace(app, list)
+}
+
+/// Make sure the list holds the default workspace
+fn with_default_workspace(app: &AppHandle, mut list: WorkspaceList) -> AppResult<WorkspaceList> {
+    if !list.workspaces.iter().any(|w| w.id == DEFAULT_WORKSPACE_ID) {
+        list.workspaces.insert(
+            0,
+            Workspace {
+                id: DEFAULT_WORKSPACE_ID.to_string(),
+                name: "Default".to_string(),
+                path: path_string(&storage::get_default_data_dir(app)?)?,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+    }
+    Ok(list)
+}
+
+/// Move an unreadable workspace list aside so it can be looked at later
+fn set_aside_list(path: &Path, error: &AppError) {
+    let aside = path.with_extension(format!(
+        "json.{}.corrupt",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    match fs::rename(path, &aside) {
+        Ok(()) => eprintln!(
+            "Workspace list is unreadable ({}); moved it to {:?}",
+            error.message, aside
+        ),
+        Err(e) => eprintln!(
+            "Workspace list is unreadable ({}) and could not be moved aside: {}",
+            error.message, e
+        ),
+    }
+}
+
+fn write_list(app: &AppHandle, list: &WorkspaceList) -> AppResult<()> {
+    let path = storage::get_workspaces_file(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| AppError::io(e, dir))?;
+    }
+    storage::write_json_file(&path, list)
+}
+
+fn find<'a>(list: &'a WorkspaceList, id: &str) -> AppResult<&'a Workspace> {
+    list.workspaces.iter().find(|w| w.id == id).ok_or_else(|| {
+        AppError::not_found(format!("Workspace {} not found", id))
+            .with_code("WORKSPACE_NOT_FOUND")
+            .with_entity(id)
+    })
+}
+
+fn path_string(path: &Path) -> AppResult<String> {
+    path.to_str()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::invalid_path(path))
+}
+
+fn workspace_name(name: &str) -> String {
+    match name.trim() {
+        "" => "Untitled Workspace".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Check that `root` is neither a listed workspace's root nor inside one
+fn check_unlisted(list: &WorkspaceList, root: &Path) -> AppResult<()> {
+    for existing in &list.workspaces {
+        let existing_root = Path::new(&existing.path);
+        if root == existing_root {
+            return Err(AppError::conflict(format!(
+                "{:?} is already the {} workspace",
+                root, existing.name
+            ))
+            .with_code("ALREADY_EXISTS")
+            .with_entity(&existing.id)
+            .with_path(root));
+        }
+
+        // The default root is the app data directory, which also holds the
+        // roots of workspaces created without a path
+        let inside = if existing.id == DEFAULT_WORKSPACE_ID {
+            WORKSPACE_ENTRIES
+                .iter()
+                .any(|entry| root.starts_with(existing_root.join(entry)))
+        } else {
+            root.starts_with(existing_root)
+        };
+        if inside {
+            return Err(AppError::conflict(format!(
+                "{:?} is inside the {} workspace",
+                root, existing.name
+            ))
+            .with_code("INSIDE_WORKSPACE")
+            .with_entity(&existing.id)
+            .with_path(root));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `root` can hold a new workspace: missing, empty, or already a
+/// workspace (e.g. a shared folder another machine set up)
+fn check_new_root(list: &WorkspaceList, root: &Path) -> AppResult<()> {
+    check_unlisted(list, root)?;
+    if !root.exists() {
+        return Ok(());
+    }
+
+    let is_workspace = ["libraries", "playlists", "settings"]
+        .iter()
+        .any(|entry| root.join(entry).is_dir());
+    if !is_workspace && fs::read_dir(root)?.next().is_some() {
+        return Err(AppError::conflict(format!(
+            "{:?} is not empty and doesn't hold a workspace",
+            root
+        ))
+        .with_code("FOLDER_NOT_EMPTY")
+        .with_path(root));
+    }
+    Ok(())
+}
+
+// ===== Managing Workspaces =====
+
+pub fn list_workspaces(app: &AppHandle) -> AppResult<WorkspaceList> {
+    let state = app.state::<WorkspaceState>();
+    let _guard = state.list.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = read_list(app)?;
+    // Reflect a fallback to the default workspace at startup
+    list.active_id = state.active().id;
+    Ok(list)
+}
+
+/// Add a workspace rooted at `path`, or in the app data directory when not given
+pub fn create_workspace(app: &AppHandle, name: &str, path: Option<&str>) -> AppResult<Workspace> {
+    let state = app.state::<WorkspaceState>();
+    let _guard = state.list.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = read_list(app)?;
+
+    let id = Uuid::new_v4().to_string();
+    let root = match path {
+        Some(path) => PathBuf::from(path),
+        None => storage::get_default_data_dir(app)?
+            .join("workspaces")
+            .join(&id),
+    };
+    check_new_root(&list, &root)?;
+    fs::create_dir_all(&root).map_err(|e| AppError::io(e, &root))?;
+
+    let workspace = Workspace {
+        id,
+        name: workspace_name(name),
+        path: path_string(&root)?,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    list.workspaces.push(workspace.clone());
+    write_list(app, &list)?;
+    Ok(workspace)
+}
+
+pub fn rename_workspace(app: &AppHandle, id: &str, name: &str) -> AppResult<Workspace> {
+    let state = app.state::<WorkspaceState>();
+    let _guard = state.list.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = read_list(app)?;
+
+    find(&list, id)?;
+    let workspace = list
+        .workspaces
+        .iter_mut()
+        .find(|w| w.id == id)
+        .expect("workspace was found above");
+    workspace.name = workspace_name(name);
+    let workspace = workspace.clone();
+    write_list(app, &list)?;
+
+    if state.active().id == id {
+        state.replace(workspace.clone());
+    }
+    Ok(workspace)
+}
+
+/// Forget a workspace. Its files are left where they are.
+pub fn remove_workspace(app: &AppHandle, id: &str) -> AppResult<()> {
+    let state = app.state::<WorkspaceState>();
+    let _guard = state.list.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = read_list(app)?;
+
+    find(&list, id)?;
+    if id == DEFAULT_WORKSPACE_ID || state.active().id == id {
+        return Err(
+            AppError::conflict("The default and the active workspace cannot be removed")
+                .with_code("WORKSPACE_IN_USE")
+                .with_entity(id),
+        );
+    }
+    list.workspaces.retain(|w| w.id != id);
+    write_list(app, &list)
+}
+
+/// Point every path at the active workspace and reopen its storage
+fn open_active(app: &AppHandle) -> AppResult<()> {
+    storage::open_data_dirs(app)?;
+    repository::reload(app)?;
+    search::invalidate(app);
+    Ok(())
+}
+
+/// Make a workspace active. Every window is told to reload its data.
+pub fn switch_workspace(app: &AppHandle, id: &str) -> AppResult<Workspace> {
+    let state = app.state::<WorkspaceState>();
+    let _guard = state.list.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = read_list(app)?;
+
+    let workspace = find(&list, id)?.clone();
+    if !Path::new(&workspace.path).is_dir() {
+        return Err(AppError::not_found(format!(
+            "Workspace {} is unavailable at {}",
+            workspace.name, workspace.path
+        ))
+        .with_code("WORKSPACE_UNAVAILABLE")
+        .with_entity(id)
+        .with_path(&workspace.path));
+    }
+
+    {
+        // Nothing may be written to either workspace halfway through the
+        // switch, in the order these are taken elsewhere
+        let folders = app.state::<WatchedFolderState>();
+        let _writers = (
+            folders.pause(),
+            media_references::pause_media_imports(),
+            snapshots::lock_snapshots(),
+            repository::lock_checked_saves(),
+            history::lock_history(),
+        );
+        let previous = state.replace(workspace.clone());
+        if let Err(e) = open_active(app) {
+            state.replace(previous);
+            if let Err(reopen) = repository::reload(app) {
+                eprintln!(
+                    "Failed to reopen the previous workspace: {}",
+                    reopen.message
+                );
+            }
+            return Err(e);
+        }
+    }
+
+    list.active_id = workspace.id.clone();
+    write_list(app, &list)?;
+    if let Err(e) = app.emit(WORKSPACE_CHANGED_EVENT, &workspace) {
+        eprintln!("Failed to emit workspace change: {}", e);
+    }
+    Ok(workspace)
+}
+
+// ===== Moving =====
+
+/// Copy a file tree, checking every file against its source's hash
+fn copy_verified(source: &Path, destination: &Path) -> AppResult<()> {
+    if source.is_dir() {
+        fs::create_dir_all(destination).map_err(|e| AppError::io(e, destination))?;
+        for entry in fs::read_dir(source).map_err(|e| AppError::io(e, source))? {
+            let entry = entry?;
+            copy_verified(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+
+    fs::copy(source, destination).map_err(|e| AppError::io(e, destination))?;
+    fs::File::open(destination)?.sync_all()?;
+    let source_hash = storage::compute_file_hash(&source.to_path_buf())?;
+    if storage::compute_file_hash(&destination.to_path_buf())? != source_hash {
+        return Err(
+            AppError::corrupt(format!("Copy of {:?} does not match the original", source))
+                .with_code("HASH_MISMATCH")
+                .with_path(destination),
+        );
+    }
+    Ok(())
+}
+
+/// Path, size and modification time of every file in a workspace's entries
+fn tree_state(root: &Path, entries: &[&str]) -> AppResult<Vec<(PathBuf, u64, Option<SystemTime>)>> {
+    fn walk(path: &Path, state: &mut Vec<(PathBuf, u64, Option<SystemTime>)>) -> AppResult<()> {
+        let metadata = fs::metadata(path).map_err(|e| AppError::io(e, path))?;
+        if metadata.is_dir() {
+            let mut children: Vec<PathBuf> = fs::read_dir(path)
+                .map_err(|e| AppError::io(e, path))?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<_, _>>()?;
+            children.sort();
+            for child in children {
+                walk(&child, state)?;
+            }
+        } else {
+            state.push((path.to_path_buf(), metadata.len(), metadata.modified().ok()));
+        }
+        Ok(())
+    }
+
+    let mut state = Vec::new();
+    for entry in entries {
+        walk(&root.join(entry), &mut state)?;
+    }
+    Ok(state)
+}
+
+fn remove_entry(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Move a workspace's data to `destination`, which must be missing or empty.
+///
+/// Everything is copied and checked before the workspace is pointed at the
+/// new location, and the originals are only deleted afterwards.
+pub fn move_workspace(app: &AppHandle, id: &str, destination: &str) -> AppResult<Workspace> {
+    let state = app.state::<WorkspaceState>();
+    let _guard = state.list.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = read_list(app)?;
+
+    let mut workspace = find(&list, id)?.clone();
+    let source = PathBuf::from(&workspace.path);
+    let destination = PathBuf::from(destination);
+    let entries: Vec<&str> = WORKSPACE_ENTRIES
+        .into_iter()
+        .filter(|entry| source.join(entry).exists())
+        .collect();
+
+    if destination == source
+        || entries
+            .iter()
+            .any(|entry| destination.starts_with(source.join(entry)))
+    {
+        return Err(
+            AppError::conflict("A workspace cannot be moved into itself")
+                .with_code("INVALID_DESTINATION")
+                .with_path(&destination),
+        );
+    }
+    check_unlisted(&list, &destination)?;
+    if destination.exists() && fs::read_dir(&destination)?.next().is_some() {
+        return Err(
+            AppError::conflict(format!("{:?} is not empty", destination))
+                .with_code("FOLDER_NOT_EMPTY")
+                .with_path(&destination),
+        );
+    }
+
+    let active = state.active().id == id;
+    // Nothing may be written to the active workspace while it is copied. The
+    // order matches the one these are taken in elsewhere.
+    let folders = app.state::<WatchedFolderState>();
+    let writers = active.then(|| {
+        (
+            folders.pause(),
+            media_references::pause_media_imports(),
+            snapshots::lock_snapshots(),
+            repository::lock_checked_saves(),
+            history::lock_history(),
+        )
+    });
+    if active {
+        repository::current(app).flush()?;
+    }
+
+    // Anything written regardless is caught by comparing the source before and after
+    let before = tree_state(&source, &entries)?;
+    let copied = entries
+        .iter()
+        .try_for_each(|entry| copy_verified(&source.join(entry), &destination.join(entry)))
+        .and_then(|_| {
+            if tree_state(&source, &entries)? == before {
+                return Ok(());
+            }
+            Err(AppError::conflict(format!(
+                "Workspace {} changed while it was being copied; try again",
+                workspace.name
+            ))
+            .with_code("WORKSPACE_CHANGED")
+            .with_path(&source))
+        });
+    if let Err(e) = copied {
+        for entry in &entries {
+            let _ = remove_entry(&destination.join(entry));
+        }
+        return Err(e);
+    }
+
+    workspace.path = path_string(&destination)?;
+    if let Some(listed) = list.workspaces.iter_mut().find(|w| w.id == id) {
+        *listed = workspace.clone();
+    }
+    // The new root is opened before the list points at it, so a failure
+    // leaves the workspace where it was
+    let moved = if active {
+        let previous = state.replace(workspace.clone());
+        match open_active(app).and_then(|_| write_list(app, &list)) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                state.replace(previous);
+                if let Err(reopen) = open_active(app) {
+                    eprintln!(
+                        "Failed to reopen the workspace where it was: {}",
+                        reopen.message
+                    );
+                }
+                Err(e)
+            }
+        }
+    } else {
+        write_list(app, &list)
+    };
+    if let Err(e) = moved {
+        for entry in &entries {
+            let _ = remove_entry(&destination.join(entry));
+        }
+        return Err(e);
+    }
+    drop(writers);
+
+    for entry in &entries {
+        if let Err(e) = remove_entry(&source.join(entry)) {
+            eprintln!("Failed to remove moved {:?}: {}", source.join(entry), e);
+        }
+    }
+    // Roots outside the app data directory were made for the workspace
+    if id != DEFAULT_WORKSPACE_ID {
+        let _ = fs::remove_dir(&source);
+    }
+
+    if active {
+        if let Err(e) = app.emit(WORKSPACE_CHANGED_EVENT, &workspace) {
+            eprintln!("Failed to emit workspace change: {}", e);
+        }
+    }
+    Ok(workspace)
+}
@@ -146,6 +146,9 @@ export const AppDndProvider = ({ children }: AppDndProviderProps) => {
   const addSlidesToPlaylistItem = usePlaylistStore(
     (s) => s.addSlidesToPlaylistItem
   );
+  const updateSlideInPlaylistItem = usePlaylistStore(
+    (s) => s.updateSlideInPlaylistItem
+  );
 
   // ===== LIBRARY STORE =====
   const libraries = useLibraryStore((s) => s.libraries);
@@ -320,6 +323,24 @@ export const AppDndProvider = ({ children }: AppDndProviderProps) => {
 
     if (mediaItems.length === 0) return;
 
+    // DROP AUDIO ON SLIDE (attach it as the slide's audio cue)
+    if (
+      overType === "slide" &&
+      overData.playlistId &&
+      overData.playlistItemId &&
+      mediaItems.length === 1 &&
+      mediaItems[0].type === "audio"
+    ) {
+      const [audio] = mediaItems;
+      updateSlideInPlaylistItem(
+        overData.playlistId,
+        overData.playlistItemId,
+        overId,
+        { audioCue: { action: "play", mediaId: audio.id, src: audio.source } }
+      );
+      return;
+    }
+
     // DROP ON SLIDE GRID (add slides to existing playlist item)
     if (
       (overType === "slide" || overType === "slideGridEndZone") &&
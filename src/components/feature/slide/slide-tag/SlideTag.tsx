@@ -1,6 +1,7 @@
 import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
 import { SlideData, ImageObject, VideoObject } from "../types";
 import { cn } from "@/lib/utils";
+import { Music } from "lucide-react";
 import { selectTagGroupById, useSettingsStore } from "@/stores/settings/settingsStore";
 
 // TODO: Create a way for users to create 'slide tag groups' and the UI
@@ -42,6 +43,14 @@ export const SlideTag = ({
     }
   }
 
+  // Slides carrying only an audio cue are named after its media
+  const cueMedia = slide.audioCue?.mediaId
+    ? getMediaById(slide.audioCue.mediaId)
+    : undefined;
+  if (!mediaName && cueMedia) {
+    mediaName = cueMedia.name;
+  }
+
   return (
     <div
       className={cn(
@@ -58,6 +67,16 @@ export const SlideTag = ({
           <p className="text-white text-xs shrink-0">{tagGroup.name}</p>
         )}
       </div>
+      {slide.audioCue && (
+        <Music
+          className="size-3 shrink-0 text-white"
+          aria-label={
+            slide.audioCue.action === "stop"
+              ? "Stops audio"
+              : `Plays ${cueMedia?.name ?? "audio"}`
+          }
+        />
+      )}
       {mediaName && (
         <span className="text-white text-xs truncate min-w-0 text-right flex-1">
           {mediaName}
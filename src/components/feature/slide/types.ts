@@ -166,10 +166,21 @@ export type VideoObject = BaseSlideObject & {
 
 export type SlideObject = TextObject | ShapeObject | ImageObject | VideoObject;
 
+// Audio started or stopped when a slide is triggered
+export type AudioCue = {
+  action: "play" | "stop"; // 'stop' also stops background music
+  mediaId?: string; // Media library item the cue plays
+  src?: string;
+  volume?: number; // 0-1
+  loop?: boolean;
+};
+
 // New SlideData structure
 export type SlideData = {
   id: string;
   objects?: SlideObject[];
   backgroundColor?: string; // Canvas background color
   tagGroupId?: string;
+  audioCue?: AudioCue;
+  section?: string; // Song section, e.g. "Verse 2", set by song imports
 };
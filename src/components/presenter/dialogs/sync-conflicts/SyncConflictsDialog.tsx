@@ -0,0 +1,127 @@
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { useSyncConflicts } from "@/hooks/use-sync-conflicts";
+import { cn } from "@/lib/utils";
+import { MergeConflict, MergeSide } from "@/types/folder-sync";
+import { useEffect, useState } from "react";
+
+// Short summary of one side of a clashing edit
+const describeValue = (value: unknown): string => {
+  if (value === null || value === undefined) return "Deleted";
+  if (typeof value !== "object") return String(value);
+  const fields = value as Record<string, unknown>;
+  if (typeof fields.content === "string") return `"${fields.content}"`;
+  if (typeof fields.title === "string") return fields.title;
+  return "Edited";
+};
+
+const ConflictRow = ({
+  conflict,
+  side,
+  onPick,
+}: {
+  conflict: MergeConflict;
+  side?: MergeSide;
+  onPick: (side: MergeSide) => void;
+}) => {
+  const option = (value: MergeSide, title: string, content: unknown) => (
+    <button
+      type="button"
+      onClick={() => onPick(value)}
+      className={cn(
+        "flex-1 min-w-0 rounded-md border px-2 py-1 text-left",
+        side === value ? "border-primary bg-primary/10" : "border-input"
+      )}
+    >
+      <div className="text-[10px] text-muted-foreground">{title}</div>
+      <div className="truncate">{describeValue(content)}</div>
+    </button>
+  );
+
+  return (
+    <div className="flex flex-col gap-1 text-xs text-white">
+      <div className="truncate">{conflict.label}</div>
+      <div className="flex gap-2">
+        {option("current", "Current file", conflict.current)}
+        {option("copy", "Conflicted copy", conflict.copy)}
+      </div>
+    </div>
+  );
+};
+
+/**
+ * Asks which version to keep for edits made differently on two machines
+ * Shows one conflicted copy at a time until none are left
+ */
+export const SyncConflictsDialog = () => {
+  const { conflicts, resolve } = useSyncConflicts();
+  const [resolutions, setResolutions] = useState<Record<string, MergeSide>>({});
+  const conflict = conflicts[0];
+
+  useEffect(() => {
+    setResolutions({});
+  }, [conflict?.copyName]);
+
+  if (!conflict) return null;
+
+  const pickAll = (side: MergeSide) => {
+    setResolutions(
+      Object.fromEntries(conflict.conflicts.map((c) => [c.key, side]))
+    );
+  };
+
+  const allPicked = conflict.conflicts.every((c) => resolutions[c.key]);
+
+  const handleApply = async () => {
+    try {
+      await resolve(conflict, resolutions);
+    } catch (error) {
+      console.error("Failed to resolve sync conflict:", error);
+      alert(`Failed to merge "${conflict.name}": ${error}`);
+    }
+  };
+
+  return (
+    <Dialog open>
+      <DialogContent className="max-w-md! bg-shade-3" showCloseButton={false}>
+        <DialogHeader>
+          <DialogTitle>Sync Conflict</DialogTitle>
+        </DialogHeader>
+        <DialogDescription>
+          "{conflict.name}" was edited on two machines. Everything else was
+          merged; pick which version to keep for these edits.
+        </DialogDescription>
+        <div className="flex flex-col gap-3 max-h-80 overflow-y-auto">
+          {conflict.conflicts.map((c) => (
+            <ConflictRow
+              key={c.key}
+              conflict={c}
+              side={resolutions[c.key]}
+              onPick={(side) =>
+                setResolutions((current) => ({ ...current, [c.key]: side }))
+              }
+            />
+          ))}
+        </div>
+        <DialogFooter>
+          <Button variant="ghost" onClick={() => pickAll("current")}>
+            Keep All Current
+          </Button>
+          <Button variant="ghost" onClick={() => pickAll("copy")}>
+            Keep All Copy
+          </Button>
+          <Button disabled={!allPicked} onClick={handleApply}>
+            Merge
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+};
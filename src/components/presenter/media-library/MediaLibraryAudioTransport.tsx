@@ -0,0 +1,144 @@
+import { Button } from "@/components/ui/button";
+import { Slider } from "@/components/ui/slider";
+import {
+  selectSelectedPlaylist,
+  useMediaLibraryStore,
+} from "@/stores/presenter/mediaLibraryStore";
+import { useAudioPlayerStore } from "@/stores/presenter/audioPlayerStore";
+import { formatTime } from "@/components/presenter/right-sidebar/preview-panel/video-controls/utils/formatTime";
+import { cn } from "@/lib/utils";
+import {
+  Pause,
+  Play,
+  Repeat,
+  Shuffle,
+  SkipBack,
+  SkipForward,
+  Square,
+  Volume2,
+} from "lucide-react";
+
+const buttonClassName =
+  "text-gray-400 rounded-sm hover:text-gray-400 hover:bg-white/10";
+
+/**
+ * Background music transport for the audio items of a media playlist.
+ * Play starts the selected playlist; the other controls act on whatever is playing.
+ */
+export const MediaLibraryAudioTransport = () => {
+  const selectedPlaylist = useMediaLibraryStore(selectSelectedPlaylist);
+  const {
+    playlistId,
+    isPlaying,
+    currentTime,
+    duration,
+    volume,
+    loop,
+    shuffle,
+    playPlaylist,
+    togglePlayback,
+    next,
+    previous,
+    stop,
+    setVolume,
+    toggleLoop,
+    toggleShuffle,
+    getCurrentTrack,
+  } = useAudioPlayerStore();
+
+  const selectedHasAudio = Boolean(
+    selectedPlaylist?.mediaItems.some((item) => item.type === "audio")
+  );
+  if (!selectedHasAudio && !playlistId) return null;
+
+  const currentTrack = getCurrentTrack();
+  const isSelectedLoaded = playlistId === selectedPlaylist?.id;
+
+  const handlePlayPause = () => {
+    // Play switches to the selected playlist when another one is loaded
+    if (!isPlaying && selectedPlaylist && selectedHasAudio && !isSelectedLoaded) {
+      playPlaylist(selectedPlaylist.id);
+    } else {
+      togglePlayback();
+    }
+  };
+
+  return (
+    <div className="flex items-center gap-1 min-w-0">
+      {currentTrack && (
+        <span
+          className="text-[10px] text-muted-foreground truncate max-w-32"
+          title={currentTrack.name}
+        >
+          {currentTrack.name} · {formatTime(currentTime)} /{" "}
+          {formatTime(duration)}
+        </span>
+      )}
+      <Button
+        className={buttonClassName}
+        variant="ghost"
+        size="icon-xs"
+        title="Previous"
+        onClick={previous}
+        disabled={!playlistId}
+      >
+        <SkipBack className="size-3" />
+      </Button>
+      <Button
+        className={buttonClassName}
+        variant="ghost"
+        size="icon-xs"
+        title={isPlaying ? "Pause" : "Play"}
+        onClick={handlePlayPause}
+      >
+        {isPlaying ? <Pause className="size-3" /> : <Play className="size-3" />}
+      </Button>
+      <Button
+        className={buttonClassName}
+        variant="ghost"
+        size="icon-xs"
+        title="Next"
+        onClick={next}
+        disabled={!playlistId}
+      >
+        <SkipForward className="size-3" />
+      </Button>
+      <Button
+        className={buttonClassName}
+        variant="ghost"
+        size="icon-xs"
+        title="Stop"
+        onClick={stop}
+        disabled={!playlistId}
+      >
+        <Square className="size-3" />
+      </Button>
+      <Button
+        className={cn(buttonClassName, { "text-selected": loop })}
+        variant="ghost"
+        size="icon-xs"
+        title="Repeat Playlist"
+        onClick={toggleLoop}
+      >
+        <Repeat className="size-3" />
+      </Button>
+      <Button
+        className={cn(buttonClassName, { "text-selected": shuffle })}
+        variant="ghost"
+        size="icon-xs"
+        title="Shuffle"
+        onClick={toggleShuffle}
+      >
+        <Shuffle className="size-3" />
+      </Button>
+      <Volume2 className="size-3 shrink-0 text-gray-400" />
+      <Slider
+        className="w-16"
+        value={[volume]}
+        max={1}
+        step={0.01}
+        onValueChange={([value]) => setVolume(value)}
+      />
+    </div>
+  );
+};
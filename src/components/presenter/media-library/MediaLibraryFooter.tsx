@@ -1,21 +1,61 @@
 import { Button } from "@/components/ui/button";
 import { useImportMedia } from "./hooks/use-import-media";
-import { Plus } from "lucide-react";
+import { MediaLibraryAudioTransport } from "./MediaLibraryAudioTransport";
+import { FolderInput, FolderSync, Link, Plus } from "lucide-react";
 
 export const MediaLibraryFooter = () => {
-  const { isImporting, handleImport } = useImportMedia();
+  const {
+    isImporting,
+    handleImport,
+    handleLinkMedia,
+    handleImportFolder,
+    handleWatchFolder,
+  } = useImportMedia();
 
   return (
     <div className="sticky bottom-0 left-0 flex w-full h-10 items-center justify-between bg-transparent px-2">
-      <Button
-        className="text-gray-400 rounded-sm hover:text-gray-400 hover:bg-white/10"
-        variant="ghost"
-        size="icon-xs"
-        onClick={handleImport}
-        disabled={isImporting}
-      >
-        <Plus className="size-3" />
-      </Button>
+      <div className="flex items-center gap-1">
+        <Button
+          className="text-gray-400 rounded-sm hover:text-gray-400 hover:bg-white/10"
+          variant="ghost"
+          size="icon-xs"
+          onClick={handleImport}
+          disabled={isImporting}
+        >
+          <Plus className="size-3" />
+        </Button>
+        <Button
+          className="text-gray-400 rounded-sm hover:text-gray-400 hover:bg-white/10"
+          variant="ghost"
+          size="icon-xs"
+          title="Link Files"
+          onClick={handleLinkMedia}
+          disabled={isImporting}
+        >
+          <Link className="size-3" />
+        </Button>
+        <Button
+          className="text-gray-400 rounded-sm hover:text-gray-400 hover:bg-white/10"
+          variant="ghost"
+          size="icon-xs"
+          title="Import Folder"
+          onClick={handleImportFolder}
+          disabled={isImporting}
+        >
+          <FolderInput className="size-3" />
+        </Button>
+        <Button
+          className="text-gray-400 rounded-sm hover:text-gray-400 hover:bg-white/10"
+          variant="ghost"
+          size="icon-xs"
+          title="Watch Folder"
+          onClick={handleWatchFolder}
+          disabled={isImporting}
+        >
+          <FolderSync className="size-3" />
+        </Button>
+      </div>
+      <MediaLibraryAudioTransport />
     </div>
   );
 };
@@ -9,10 +9,14 @@ import { mediaItemToSlideData } from "@/stores/utils/mediaItemToSlideData";
 import { usePlaylistStore } from "@/stores/presenter/presenterStore";
 import { confirm } from "@tauri-apps/plugin-dialog";
 import { useContextMenu } from "./hooks/use-content-item-context-menu";
+import { useRelinkMedia } from "./hooks/use-relink-media";
+import { formatMediaSummary } from "./utils/formatMediaSummary";
 import { cn } from "@/lib/utils";
 import { useSortable } from "@dnd-kit/sortable";
 import { AppDragData } from "@/components/dnd/AppDndProvider";
 import { SlideTag } from "@/components/feature/slide/slide-tag/SlideTag";
+import { useAudioPlayerStore } from "@/stores/presenter/audioPlayerStore";
+import { Music } from "lucide-react";
 
 export type MediaLibraryItemProps = {
   index: number;
@@ -48,6 +52,7 @@ export const MediaLibraryItem = ({
   const addMediaItemToPlaylist = usePlaylistStore(
     (s) => s.addMediaItemToPlaylist
   );
+  const playPlaylist = useAudioPlayerStore((s) => s.playPlaylist);
   const isActive = selectedMediaId === mediaItem.id;
   // Show multi-select UI when in multi-select mode AND this item is selected
   const showMultiSelectUI = isMultiSelectMode && isSelected;
@@ -82,11 +87,16 @@ export const MediaLibraryItem = ({
     }
   };
 
+  const { handleLocateFile, handleSearchFolder } = useRelinkMedia();
+
   const { openContextMenu } = useContextMenu({
     onDelete: handleDelete,
     onAddToPlaylist: handleAddToPlaylist,
+    onLocateFile: () => handleLocateFile(mediaItem),
+    onSearchFolder: handleSearchFolder,
     id: mediaItem.id,
     playlists: playlists,
+    missing: mediaItem.missing,
   });
 
   // Combined ref handler for sortable and lasso selection
@@ -100,6 +110,10 @@ export const MediaLibraryItem = ({
       ref={handleRef}
       data-media-item
       onClick={onClick}
+      onDoubleClick={() => {
+        // Audio plays as background music from this track on
+        if (mediaItem.type === "audio") playPlaylist(playlistId, mediaItem.id);
+      }}
       onContextMenu={(e) => openContextMenu(e)}
       className={cn("cursor-pointer h-min shrink-0 relative", {
         "opacity-50": isDragging,
@@ -124,19 +138,34 @@ export const MediaLibraryItem = ({
         )}
       >
         <Slide id={mediaItem.id} data={mediaItemToSlideData(mediaItem)} />
+        {mediaItem.type === "audio" && (
+          <div className="absolute inset-0 bottom-5 flex items-center justify-center pointer-events-none">
+            <Music className="size-6 text-white/60" />
+          </div>
+        )}
         <SlideTag
           index={index}
           slide={mediaItemToSlideData(mediaItem)}
           name={mediaItem.name}
           showTagGroupName={false}
         />
+        {mediaItem.missing && (
+          <div
+            className="absolute top-1 left-1 rounded-sm bg-red-600 px-1 text-[8px] text-white"
+            title={mediaItem.linkedPath}
+          >
+            Missing
+          </div>
+        )}
       </div>
       <div
         className={cn(
           "flex items-center justify-between gap-0.5 w-full text-[8px] text-muted-foreground transition-colors",
           showMultiSelectUI ? "bg-selected/30" : "bg-shade-lighter"
         )}
-      ></div>
+      >
+        <span className="truncate px-1">{formatMediaSummary(mediaItem)}</span>
+      </div>
 
       {/* Drop indicator - after (right side) */}
       {dropPosition === "after" && (
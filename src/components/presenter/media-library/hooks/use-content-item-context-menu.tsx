@@ -3,13 +3,19 @@ import { Menu } from "@tauri-apps/api/menu";
 export const useContextMenu = ({
   onDelete,
   onAddToPlaylist,
+  onLocateFile,
+  onSearchFolder,
   id,
   playlists,
+  missing = false,
 }: {
   onDelete: () => void;
   onAddToPlaylist: (playlistId: string) => void;
+  onLocateFile?: () => void;
+  onSearchFolder?: () => void;
   id: string;
   playlists: Array<{ id: string; name: string }>;
+  missing?: boolean; // Linked file not found; offers relinking
 }) => {
   const handleDelete = () => {
     onDelete();
@@ -19,7 +25,26 @@ export const useContextMenu = ({
     onAddToPlaylist(playlistId);
   };
 
+  const relinkItems = missing
+    ? [
+        {
+          id: `${id}-locate`,
+          text: "Locate File...",
+          action: () => onLocateFile?.(),
+        },
+        {
+          id: `${id}-search-folder`,
+          text: "Search Folder for Missing Files...",
+          action: () => onSearchFolder?.(),
+        },
+        {
+          item: "Separator" as const,
+        },
+      ]
+    : [];
+
   const contextMenuItems = [
+    ...relinkItems,
     {
       id: `${id}-add-to`,
       text: "Add To",
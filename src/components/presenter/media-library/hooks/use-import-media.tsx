@@ -1,38 +1,57 @@
 import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
 import { open } from "@tauri-apps/plugin-dialog";
+import { addWatchedFolder } from "@/services/watched-folders";
 import { useState } from "react";
 
+const MEDIA_EXTENSIONS = [
+  "jpg",
+  "jpeg",
+  "png",
+  "gif",
+  "webp",
+  "bmp",
+  "mp4",
+  "webm",
+  "mov",
+  "avi",
+  "mkv",
+  "mp3",
+  "wav",
+  "m4a",
+  "flac",
+  "ogg",
+];
+
 export const useImportMedia = () => {
   const [isImporting, setIsImporting] = useState(false);
   const importMedia = useMediaLibraryStore((state) => state.importMedia);
+  const importMediaFiles = useMediaLibraryStore(
+    (state) => state.importMediaFiles
+  );
+  const importMediaFolder = useMediaLibraryStore(
+    (state) => state.importMediaFolder
+  );
 
   const handleImport = async () => {
     try {
       setIsImporting(true);
       const selected = await open({
-        multiple: false,
-        filters: [
-          {
-            name: "Media Files",
-            extensions: [
-              "jpg",
-              "jpeg",
-              "png",
-              "gif",
-              "webp",
-              "bmp",
-              "mp4",
-              "webm",
-              "mov",
-              "avi",
-              "mkv",
-            ],
-          },
-        ],
+        multiple: true,
+        filters: [{ name: "Media Files", extensions: MEDIA_EXTENSIONS }],
       });
 
       if (selected && typeof selected === "string") {
         await importMedia(selected);
+      } else if (Array.isArray(selected) && selected.length > 0) {
+        const summary = await importMediaFiles(selected);
+        if (summary.failed.length > 0) {
+          alert(
+            `Failed to import ${summary.failed.length} file(s):\n` +
+              summary.failed
+                .map((f) => `${f.sourcePath}: ${f.error}`)
+                .join("\n")
+          );
+        }
       }
     } catch (error) {
       console.error("Failed to import media:", error);
@@ -42,8 +61,75 @@ export const useImportMedia = () => {
     }
   };
 
+  // Reference files where they are instead of copying them, for large
+  // video or assets on a shared drive
+  const handleLinkMedia = async () => {
+    try {
+      setIsImporting(true);
+      const selected = await open({
+        multiple: true,
+        filters: [{ name: "Media Files", extensions: MEDIA_EXTENSIONS }],
+      });
+
+      if (Array.isArray(selected) && selected.length > 0) {
+        const summary = await importMediaFiles(selected, undefined, true);
+        if (summary.failed.length > 0) {
+          alert(
+            `Failed to link ${summary.failed.length} file(s):\n` +
+              summary.failed
+                .map((f) => `${f.sourcePath}: ${f.error}`)
+                .join("\n")
+          );
+        }
+      }
+    } catch (error) {
+      console.error("Failed to link media:", error);
+      alert(`Failed to link media: ${error}`);
+    } finally {
+      setIsImporting(false);
+    }
+  };
+
+  // Import a folder (including subfolders) as a new playlist
+  const handleImportFolder = async () => {
+    try {
+      setIsImporting(true);
+      const selected = await open({ directory: true, multiple: false });
+
+      if (selected && typeof selected === "string") {
+        const summary = await importMediaFolder(selected);
+        if (summary.failed.length > 0) {
+          alert(`Failed to import ${summary.failed.length} file(s) from folder`);
+        }
+      }
+    } catch (error) {
+      console.error("Failed to import folder:", error);
+      alert(`Failed to import folder: ${error}`);
+    } finally {
+      setIsImporting(false);
+    }
+  };
+
+  // Watch a folder; its files land in a new playlist named after it, and
+  // files added later are imported automatically
+  const handleWatchFolder = async () => {
+    try {
+      const selected = await open({ directory: true, multiple: false });
+
+      if (selected && typeof selected === "string") {
+        await addWatchedFolder(selected);
+      }
+    } catch (error) {
+      console.error("Failed to watch folder:", error);
+      alert(`Failed to watch folder: ${error}`);
+    }
+  };
+
   return {
     isImporting,
     handleImport,
+    handleLinkMedia,
+    handleImportFolder,
+    handleWatchFolder,
   };
 };
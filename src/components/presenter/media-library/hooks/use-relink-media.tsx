@@ -0,0 +1,70 @@
+import {
+  MediaItem,
+  useMediaLibraryStore,
+} from "@/stores/presenter/mediaLibraryStore";
+import { usePresenterStore } from "@/stores/presenter/presenterStore";
+import { relinkMedia, relinkMediaItem } from "@/services/media-links";
+import { CommandError } from "@/services/errors";
+import { open } from "@tauri-apps/plugin-dialog";
+
+export const useRelinkMedia = () => {
+  const applyMediaItemUpdate = useMediaLibraryStore(
+    (state) => state.applyMediaItemUpdate
+  );
+  const loadData = usePresenterStore((state) => state.loadData);
+
+  // The backend rewrites slides that used the old paths, so reload them
+  const reloadSlides = () => {
+    loadData().catch(console.error);
+  };
+
+  // Point one missing item at a file the user picks
+  const handleLocateFile = async (mediaItem: MediaItem) => {
+    try {
+      const selected = await open({
+        multiple: false,
+        title: `Locate "${mediaItem.name}"`,
+      });
+
+      if (selected && typeof selected === "string") {
+        applyMediaItemUpdate(await relinkMediaItem(mediaItem.id, selected));
+        reloadSlides();
+      }
+    } catch (error) {
+      console.error("Failed to relink media:", error);
+      if (error instanceof CommandError && error.code === "HASH_MISMATCH") {
+        alert(
+          `That file is not "${mediaItem.name}"; its content is different. Pick the original file.`
+        );
+        return;
+      }
+      alert(`Failed to relink media: ${error}`);
+    }
+  };
+
+  // Search a folder for every missing linked file
+  const handleSearchFolder = async () => {
+    try {
+      const selected = await open({ directory: true, multiple: false });
+
+      if (selected && typeof selected === "string") {
+        const report = await relinkMedia([selected]);
+        report.relinked.forEach(applyMediaItemUpdate);
+        if (report.relinked.length > 0) {
+          reloadSlides();
+        }
+        alert(
+          `Relinked ${report.relinked.length} file(s)` +
+            (report.missing.length > 0
+              ? `; ${report.missing.length} still missing`
+              : "")
+        );
+      }
+    } catch (error) {
+      console.error("Failed to relink media:", error);
+      alert(`Failed to relink media: ${error}`);
+    }
+  };
+
+  return { handleLocateFile, handleSearchFolder };
+};
@@ -0,0 +1,37 @@
+import { MediaItem } from "@/stores/presenter/mediaLibraryStore";
+import { MediaProbe } from "@/types/storage";
+
+const formatDuration = (seconds: number) => {
+  const total = Math.round(seconds);
+  const hours = Math.floor(total / 3600);
+  const mins = Math.floor((total % 3600) / 60);
+  const secs = (total % 60).toString().padStart(2, "0");
+  return hours > 0
+    ? `${hours}:${mins.toString().padStart(2, "0")}:${secs}`
+    : `${mins}:${secs}`;
+};
+
+/**
+ * What probing found out about a media file, e.g. "1080p, 4:32, no audio"
+ */
+export const formatMediaSummary = (item: MediaItem) => {
+  const probe = item.metadata as Partial<MediaProbe> | undefined;
+  const parts: string[] = [];
+
+  if (probe?.width && probe.height) {
+    parts.push(
+      item.type === "video"
+        ? `${Math.min(probe.width, probe.height)}p`
+        : `${probe.width}×${probe.height}`
+    );
+  }
+  const duration = probe?.duration ?? item.duration;
+  if (item.type !== "image" && duration) {
+    parts.push(formatDuration(duration));
+  }
+  if (item.type === "video" && probe?.hasAudio === false) {
+    parts.push("no audio");
+  }
+
+  return parts.join(", ");
+};
@@ -1,4 +1,6 @@
 import { usePresenterStore } from "@/stores/presenter/presenterStore";
+import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
+import { AudioCue } from "@/components/feature/slide/types";
 import { useSettingsStore } from "@/stores/settings/settingsStore";
 import { Menu } from "@tauri-apps/api/menu";
 import { useMemo, useRef } from "react";
@@ -45,6 +47,12 @@ export const useSlideContextMenu = ({
     }
   };
 
+  const handleAudioCue = (targetSlideId: string, audioCue?: AudioCue) => {
+    if (playlistId && playlistItemId) {
+      updateSlide(playlistId, playlistItemId, targetSlideId, { audioCue });
+    }
+  };
+
   const openContextMenu = async (e: React.MouseEvent, slideId: string) => {
     e.preventDefault();
     e.stopPropagation();
@@ -63,6 +71,29 @@ export const useSlideContextMenu = ({
       },
     }))
 
+    // Any audio in the media library can be attached as a cue
+    const audioCueItems = useMediaLibraryStore
+      .getState()
+      .getMediaByType("audio")
+      // The same item can sit in several media playlists
+      .filter(
+        (item, index, items) =>
+          items.findIndex((i) => i.id === item.id) === index
+      )
+      .map((mediaItem) => ({
+        id: `slide-audio-cue-${mediaItem.id}`,
+        text: `Play ${mediaItem.name}`,
+        action: () => {
+          if (contextMenuSlideIdRef.current) {
+            handleAudioCue(contextMenuSlideIdRef.current, {
+              action: "play",
+              mediaId: mediaItem.id,
+              src: mediaItem.source,
+            });
+          }
+        },
+      }));
+
     const contextMenuItems = [
       {
         id: "slide-cut",
@@ -121,6 +152,36 @@ export const useSlideContextMenu = ({
           },
         }],
       },
+      {
+        id: "slide-audio-cue",
+        text: "Audio Cue",
+        enabled: Boolean(playlistId && playlistItemId),
+        action: () => {},
+        items: [
+          ...audioCueItems,
+          { item: "Separator" as const },
+          {
+            id: "slide-audio-cue-stop",
+            text: "Stop All Audio",
+            action: () => {
+              if (contextMenuSlideIdRef.current) {
+                handleAudioCue(contextMenuSlideIdRef.current, {
+                  action: "stop",
+                });
+              }
+            },
+          },
+          {
+            id: "slide-audio-cue-remove",
+            text: "Remove Audio Cue",
+            action: () => {
+              if (contextMenuSlideIdRef.current) {
+                handleAudioCue(contextMenuSlideIdRef.current, undefined);
+              }
+            },
+          },
+        ],
+      },
     ];
 
     const menu = await Menu.new({ items: contextMenuItems });
@@ -3,12 +3,14 @@ import { Menu } from "@tauri-apps/api/menu";
 export const useContextMenu = ({
   onDelete,
   onAddToPlaylist,
+  onExportSong,
   id,
   playlists,
   selectedCount = 0,
 }: {
   onDelete: () => void;
   onAddToPlaylist: (playlistId: string) => void;
+  onExportSong?: () => void;
   id: string;
   playlists: Array<{ id: string; name: string }>;
   selectedCount?: number;
@@ -59,6 +61,16 @@ export const useContextMenu = ({
         text: isMultipleSelected ? `Add ${selectedCount} Items To` : "Add To",
         items: playlistSubmenuItems,
       },
+      // Export works on a single presentation
+      ...(onExportSong && !isMultipleSelected
+        ? [
+            {
+              id: `${id}-export-song`,
+              text: "Export as OpenLyrics...",
+              action: onExportSong,
+            },
+          ]
+        : []),
       {
         id: `${id}-delete`,
         text: deleteText,
@@ -0,0 +1,35 @@
+import { open } from "@tauri-apps/plugin-dialog";
+import { importPowerPoint } from "@/services/pptx";
+
+// Pick PowerPoint files and import them into a library as new presentations,
+// listing what couldn't be converted
+export const useImportPowerPoint = (libraryId: string | undefined) => {
+  const handleImportPowerPoint = async () => {
+    if (!libraryId) return;
+
+    try {
+      const selected = await open({
+        multiple: true,
+        filters: [{ name: "PowerPoint", extensions: ["pptx"] }],
+      });
+      if (!selected || selected.length === 0) return;
+
+      const paths = Array.isArray(selected) ? selected : [selected];
+      const summary = await importPowerPoint(libraryId, paths);
+      const problems = [
+        ...summary.failed.map((f) => `${f.sourcePath}: ${f.error}`),
+        ...summary.unsupported.map(
+          (u) => `Slide ${u.slide}, ${u.name}: ${u.reason}`
+        ),
+      ];
+      if (problems.length > 0) {
+        alert("Some content couldn't be imported:\n" + problems.join("\n"));
+      }
+    } catch (error) {
+      console.error("Failed to import PowerPoint:", error);
+      alert(`Failed to import PowerPoint: ${error}`);
+    }
+  };
+
+  return { handleImportPowerPoint };
+};
@@ -0,0 +1,31 @@
+import { open } from "@tauri-apps/plugin-dialog";
+import { importSongs, SONG_FILE_EXTENSIONS } from "@/services/song-import";
+
+// Pick song files and import them into a library as new presentations
+export const useImportSongs = (libraryId: string | undefined) => {
+  const handleImportSongs = async () => {
+    if (!libraryId) return;
+
+    try {
+      const selected = await open({
+        multiple: true,
+        filters: [{ name: "Songs", extensions: SONG_FILE_EXTENSIONS }],
+      });
+      if (!selected || selected.length === 0) return;
+
+      const paths = Array.isArray(selected) ? selected : [selected];
+      const summary = await importSongs(libraryId, paths);
+      if (summary.failed.length > 0) {
+        alert(
+          `Failed to import ${summary.failed.length} file(s):\n` +
+            summary.failed.map((f) => `${f.sourcePath}: ${f.error}`).join("\n")
+        );
+      }
+    } catch (error) {
+      console.error("Failed to import songs:", error);
+      alert(`Failed to import songs: ${error}`);
+    }
+  };
+
+  return { handleImportSongs };
+};
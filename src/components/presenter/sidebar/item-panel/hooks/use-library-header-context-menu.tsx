@@ -2,8 +2,12 @@ import { Menu } from "@tauri-apps/api/menu";
 
 export const useLibraryHeaderContextMenu = ({
   onNewPresentation,
+  onImportSongs,
+  onImportPowerPoint,
 }: {
   onNewPresentation: () => void;
+  onImportSongs: () => void;
+  onImportPowerPoint: () => void;
 }) => {
   const handleNewPresentation = () => {
     onNewPresentation();
@@ -19,6 +23,16 @@ export const useLibraryHeaderContextMenu = ({
         text: "New Presentation",
         action: handleNewPresentation,
       },
+      {
+        id: "import-songs",
+        text: "Import Songs...",
+        action: onImportSongs,
+      },
+      {
+        id: "import-powerpoint",
+        text: "Import PowerPoint...",
+        action: onImportPowerPoint,
+      },
     ];
 
     const menu = await Menu.new({ items: contextMenuItems });
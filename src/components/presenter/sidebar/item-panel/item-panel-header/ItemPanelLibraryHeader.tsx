@@ -2,13 +2,19 @@ import { useSelectedLibrary } from "@/stores/presenter/presenterStore";
 import { useLibraryHeaderContextMenu } from "../hooks/use-library-header-context-menu";
 import { ItemPanelHeaderBase } from "./ItemPanelHeaderBase";
 import { usePresenterContext } from "@/context/presenter";
+import { useImportSongs } from "../hooks/use-import-songs";
+import { useImportPowerPoint } from "../hooks/use-import-powerpoint";
 
 export const ItemPanelLibraryHeader = () => {
   const selectedLibrary = useSelectedLibrary();
   const { openAddPresentationDialog } = usePresenterContext();
+  const { handleImportSongs } = useImportSongs(selectedLibrary?.id);
+  const { handleImportPowerPoint } = useImportPowerPoint(selectedLibrary?.id);
 
   const { openContextMenu } = useLibraryHeaderContextMenu({
     onNewPresentation: openAddPresentationDialog,
+    onImportSongs: handleImportSongs,
+    onImportPowerPoint: handleImportPowerPoint,
   });
 
   return (
@@ -11,6 +11,8 @@ import { useAppDnd, AppDragData } from "@/components/dnd/AppDndProvider";
 import { EndDropZone } from "../../EndDropZone";
 import { usePlaylistStore, useSelectedLibrary } from "@/stores/presenter/presenterStore";
 import { useContextMenu } from "../hooks/use-context-menu";
+import { exportOpenLyrics } from "@/services/song-import";
+import { save } from "@tauri-apps/plugin-dialog";
 import { File } from "lucide-react";
 import { SidebarItem } from "../../common/SidebarItem";
 
@@ -118,9 +120,24 @@ const SortableLibraryItem = ({
     addSlideGroupToPlaylist(playlistId, selectedLibrary.id, slideGroup.id);
   };
 
+  const handleExportSong = async () => {
+    try {
+      const destination = await save({
+        defaultPath: `${slideGroup.title}.xml`,
+        filters: [{ name: "OpenLyrics", extensions: ["xml"] }],
+      });
+      if (!destination) return;
+      await exportOpenLyrics(slideGroup, destination);
+    } catch (error) {
+      console.error("Failed to export song:", error);
+      alert(`Failed to export song: ${error}`);
+    }
+  };
+
   const { openContextMenu } = useContextMenu({
     onDelete: handleDelete,
     onAddToPlaylist: handleAddToPlaylist,
+    onExportSong: handleExportSong,
     id: slideGroup.id,
     playlists: playlists,
     selectedCount: selectedIds.length,
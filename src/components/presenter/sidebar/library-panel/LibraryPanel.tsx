@@ -1,8 +1,13 @@
-import { useLibraryStore, usePlaylistStore } from "@/stores/presenter/presenterStore";
+import {
+  useLibraryStore,
+  usePlaylistStore,
+  useSelectedLibrary,
+} from "@/stores/presenter/presenterStore";
 import { Plus } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { usePresenterContext } from "@/context/presenter";
 import { useLibraryPanelContextMenu } from "./hooks/use-library-panel-context-menu";
+import { useImportOpenLpService } from "./hooks/use-import-openlp-service";
 import { LibraryPanelGroupLibraries } from "./LibraryPanelGroupLibraries";
 import { LibraryPanelGroupPlaylists } from "./LibraryPanelGroupPlaylists";
 
@@ -33,6 +38,8 @@ const LibraryPanelHeader = ({
   const playlists = usePlaylistStore((s) => s.playlists);
   const addLibrary = useLibraryStore((s) => s.addLibrary);
   const addPlaylist = usePlaylistStore((s) => s.addPlaylist);
+  const selectedLibrary = useSelectedLibrary();
+  const { handleImportService } = useImportOpenLpService(selectedLibrary?.id);
 
   const handleNewLibrary = () => {
     const now = new Date().toISOString();
@@ -68,6 +75,7 @@ const LibraryPanelHeader = ({
     onNewLibrary: handleNewLibrary,
     onNewPlaylist: handleNewPlaylist,
     onNewPresentation: handleNewPresentation,
+    onImportService: handleImportService,
   });
 
   return (
@@ -0,0 +1,29 @@
+import { open } from "@tauri-apps/plugin-dialog";
+import { importOpenLpService } from "@/services/openlp";
+
+// Pick an OpenLP service file and import it as a new playlist, adding its
+// songs to the selected library
+export const useImportOpenLpService = (libraryId: string | undefined) => {
+  const handleImportService = async () => {
+    try {
+      const selected = await open({
+        multiple: false,
+        filters: [{ name: "OpenLP Service", extensions: ["osz"] }],
+      });
+      if (!selected || typeof selected !== "string") return;
+
+      const summary = await importOpenLpService(selected, { libraryId });
+      if (summary.skipped.length > 0) {
+        alert(
+          `Skipped ${summary.skipped.length} item(s):\n` +
+            summary.skipped.map((s) => `${s.title}: ${s.reason}`).join("\n")
+        );
+      }
+    } catch (error) {
+      console.error("Failed to import OpenLP service:", error);
+      alert(`Failed to import OpenLP service: ${error}`);
+    }
+  };
+
+  return { handleImportService };
+};
@@ -4,10 +4,12 @@ export const useLibraryPanelContextMenu = ({
   onNewLibrary,
   onNewPlaylist,
   onNewPresentation,
+  onImportService,
 }: {
   onNewLibrary: () => void;
   onNewPlaylist: () => void;
   onNewPresentation: () => void;
+  onImportService: () => void;
 }) => {
   const handleNewLibrary = () => {
     onNewLibrary();
@@ -21,6 +23,10 @@ export const useLibraryPanelContextMenu = ({
     onNewPresentation();
   };
 
+  const handleImportService = () => {
+    onImportService();
+  };
+
   const openContextMenu = async (e: React.MouseEvent) => {
     e.preventDefault();
     e.stopPropagation();
@@ -41,6 +47,11 @@ export const useLibraryPanelContextMenu = ({
         text: "New Presentation",
         action: handleNewPresentation,
       },
+      {
+        id: "import-openlp-service",
+        text: "Import OpenLP Service...",
+        action: handleImportService,
+      },
     ];
 
     const menu = await Menu.new({ items: contextMenuItems });
@@ -24,15 +24,25 @@ export type SlideGroup = {
   title: string;
   slides: SlideData[];
   canvasSize: CanvasSize; // Fixed canvas dimensions (e.g., 1920x1080)
+  credits?: SongCredits; // Set for imported songs
   createdAt: string;
   updatedAt: string;
 };
 
+export type SongCredits = {
+  authors?: string[];
+  copyright?: string;
+  ccliNumber?: string;
+};
+
 export type Library = {
   id: string;
   name: string;
   slideGroups: SlideGroup[];
   order?: number;
+  schemaVersion?: number; // On-disk schema version, stamped by the backend
+  revision?: number; // Save counter, bumped by the backend on every save
+  lineage?: string[]; // Versions it was edited from, kept by the backend for folder sync
   createdAt: string;
   updatedAt: string;
 };
@@ -48,6 +58,9 @@ export type Playlist = {
   name: string;
   items: PlaylistItem[];
   order?: number;
+  schemaVersion?: number; // On-disk schema version, stamped by the backend
+  revision?: number; // Save counter, bumped by the backend on every save
+  lineage?: string[]; // Versions it was edited from, kept by the backend for folder sync
   createdAt: string;
   updatedAt: string;
 };
\ No newline at end of file
@@ -0,0 +1,42 @@
+import { Checkbox } from "@/components/ui/checkbox";
+import { Label } from "@/components/ui/label";
+import { getFolderSync, setFolderSync } from "@/services/folder-sync";
+import { useEffect, useState } from "react";
+
+export const FolderSyncSection = () => {
+  const [enabled, setEnabled] = useState(false);
+
+  useEffect(() => {
+    getFolderSync().then(setEnabled).catch(console.error);
+  }, []);
+
+  const handleChange = async (checked: boolean) => {
+    try {
+      await setFolderSync(checked);
+      setEnabled(checked);
+    } catch (error) {
+      console.error("Failed to set folder sync:", error);
+      alert(`Failed to change folder sync: ${error}`);
+    }
+  };
+
+  return (
+    <div className="max-w-2xl flex flex-col gap-1 p-4 rounded-lg bg-white/5">
+      <div className="flex items-center gap-2">
+        <Checkbox
+          id="folder-sync"
+          checked={enabled}
+          onCheckedChange={(checked) => handleChange(checked === true)}
+        />
+        <Label htmlFor="folder-sync" className="text-white text-sm">
+          Folder sync
+        </Label>
+      </div>
+      <p className="text-white/40 text-xs">
+        Turn on when the data folder is shared between machines with Dropbox,
+        Syncthing or a similar tool. Edits saved on both machines at once are
+        merged, and you are asked only when they change the same thing.
+      </p>
+    </div>
+  );
+};
@@ -0,0 +1,161 @@
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import {
+  createWorkspace,
+  listWorkspaces,
+  moveWorkspace,
+  removeWorkspace,
+  renameWorkspace,
+  switchWorkspace,
+} from "@/services/workspaces";
+import { Workspace, WorkspaceList } from "@/types/workspaces";
+import { confirm, open } from "@tauri-apps/plugin-dialog";
+import { useCallback, useEffect, useState } from "react";
+
+export const WorkspacesSection = () => {
+  const [list, setList] = useState<WorkspaceList | null>(null);
+  const [isBusy, setIsBusy] = useState(false);
+
+  const refresh = useCallback(() => {
+    listWorkspaces().then(setList).catch(console.error);
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  // Run a workspace change, reporting failures and refreshing the list
+  const run = async (action: string, change: () => Promise<unknown>) => {
+    try {
+      setIsBusy(true);
+      await change();
+    } catch (error) {
+      console.error(`Failed to ${action}:`, error);
+      alert(`Failed to ${action}: ${error}`);
+    } finally {
+      setIsBusy(false);
+      refresh();
+    }
+  };
+
+  const handleCreate = async (inFolder: boolean) => {
+    let path: string | undefined;
+    if (inFolder) {
+      const selected = await open({ directory: true, multiple: false });
+      if (!selected || typeof selected !== "string") return;
+      path = selected;
+    }
+    await run("create workspace", () =>
+      createWorkspace("New Workspace", path)
+    );
+  };
+
+  const handleRename = async (workspace: Workspace, name: string) => {
+    if (name.trim() === "" || name === workspace.name) return;
+    await run("rename workspace", () => renameWorkspace(workspace.id, name));
+  };
+
+  const handleMove = async (workspace: Workspace) => {
+    const selected = await open({ directory: true, multiple: false });
+    if (!selected || typeof selected !== "string") return;
+    await run("move workspace", () => moveWorkspace(workspace.id, selected));
+  };
+
+  const handleRemove = async (workspace: Workspace) => {
+    const confirmed = await confirm(
+      `Remove "${workspace.name}" from this list? Its files stay in ${workspace.path}.`,
+      { title: "Remove Workspace", kind: "warning", okLabel: "Remove" }
+    );
+    if (!confirmed) return;
+    await run("remove workspace", () => removeWorkspace(workspace.id));
+  };
+
+  return (
+    <div className="max-w-2xl flex flex-col gap-3 p-4 rounded-lg bg-white/5">
+      <div className="flex flex-col gap-1">
+        <span className="text-white text-sm">Workspaces</span>
+        <p className="text-white/40 text-xs">
+          Each workspace keeps its own libraries, playlists, media and
+          settings, e.g. for campuses sharing this machine. Keep one on an
+          external drive or a shared folder by creating or moving it there.
+        </p>
+      </div>
+
+      {list?.workspaces.map((workspace) => {
+        const isActive = workspace.id === list.activeId;
+        return (
+          <div
+            key={workspace.id}
+            className="flex items-center gap-2 p-2 rounded-md bg-white/5"
+          >
+            <div className="flex-1 min-w-0 flex flex-col gap-1">
+              <Input
+                defaultValue={workspace.name}
+                className="h-7 text-sm"
+                disabled={isBusy}
+                onBlur={(e) => handleRename(workspace, e.target.value)}
+              />
+              <span
+                className="text-white/40 text-xs truncate"
+                title={workspace.path}
+              >
+                {workspace.path}
+              </span>
+            </div>
+            {isActive ? (
+              <span className="text-white/60 text-xs px-2">Active</span>
+            ) : (
+              <Button
+                size="sm"
+                variant="secondary"
+                disabled={isBusy}
+                onClick={() =>
+                  run("switch workspace", () => switchWorkspace(workspace.id))
+                }
+              >
+                Switch
+              </Button>
+            )}
+            <Button
+              size="sm"
+              variant="ghost"
+              disabled={isBusy}
+              onClick={() => handleMove(workspace)}
+            >
+              Move…
+            </Button>
+            {workspace.id !== "default" && !isActive && (
+              <Button
+                size="sm"
+                variant="ghost"
+                disabled={isBusy}
+                onClick={() => handleRemove(workspace)}
+              >
+                Remove
+              </Button>
+            )}
+          </div>
+        );
+      })}
+
+      <div className="flex items-center gap-2">
+        <Button
+          size="sm"
+          variant="secondary"
+          disabled={isBusy}
+          onClick={() => handleCreate(false)}
+        >
+          New Workspace
+        </Button>
+        <Button
+          size="sm"
+          variant="ghost"
+          disabled={isBusy}
+          onClick={() => handleCreate(true)}
+        >
+          New in Folder…
+        </Button>
+      </div>
+    </div>
+  );
+};
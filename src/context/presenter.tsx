@@ -1,4 +1,5 @@
 import { AddPresentationDialog } from "@/components/presenter/dialogs/library/add-presentation/AddPresentationDialog";
+import { SyncConflictsDialog } from "@/components/presenter/dialogs/sync-conflicts/SyncConflictsDialog";
 import { createContext, ReactNode, useContext, useState } from "react";
 
 interface PresenterContextType {
@@ -25,6 +26,7 @@ export const PresenterProvider = ({ children }: { children: ReactNode }) => {
         open={addPresentationDialogOpen}
         onOpenChange={setAddPresentationDialogOpen}
       />
+      <SyncConflictsDialog />
     </PresenterContext>
   );
 };
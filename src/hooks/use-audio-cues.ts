@@ -0,0 +1,21 @@
+import { useEffect } from "react";
+import { useSelectionStore } from "@/stores/presenter/presenterStore";
+import { useAudioPlayerStore } from "@/stores/presenter/audioPlayerStore";
+
+/**
+ * Hook to play slide audio cues
+ *
+ * Every time a slide is triggered its cue runs, so triggering the same slide
+ * again restarts the sound. Slides without a cue leave audio playing.
+ */
+export function useAudioCues() {
+  const activeSlide = useSelectionStore((s) => s.activeSlide);
+  const triggerCue = useAudioPlayerStore((state) => state.triggerCue);
+
+  useEffect(() => {
+    const cue = activeSlide?.data.audioCue;
+    if (cue) {
+      triggerCue(cue);
+    }
+  }, [activeSlide, triggerCue]);
+}
@@ -0,0 +1,31 @@
+import { useEffect } from "react";
+import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
+import { onLinkedMediaChecked } from "@/services/media-links";
+
+/**
+ * Hook to mark linked media whose files went missing
+ *
+ * The backend checks linked files once at startup and saves the flags; this
+ * replaces the store's copies so missing items show as such.
+ */
+export function useMediaLinkSync() {
+  const applyMediaItemUpdate = useMediaLibraryStore(
+    (state) => state.applyMediaItemUpdate
+  );
+
+  useEffect(() => {
+    const unlisten = onLinkedMediaChecked((payload) => {
+      [...payload.missing, ...payload.restored].forEach(applyMediaItemUpdate);
+      if (payload.missing.length > 0) {
+        console.warn(
+          "useMediaLinkSync: Linked media files are missing",
+          payload.missing.map((item) => item.linkedPath)
+        );
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applyMediaItemUpdate]);
+}
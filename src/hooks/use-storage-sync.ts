@@ -0,0 +1,37 @@
+import { useEffect } from "react";
+import { usePresenterStore } from "@/stores/presenter/presenterStore";
+import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
+import { useSettingsStore } from "@/stores/settings/settingsStore";
+import { onStorageChanged } from "@/services/storage";
+
+/**
+ * Hook to reload documents changed on disk outside the app
+ *
+ * Sync tools, restored backups and manual edits change files behind the
+ * stores' backs. Each batch reloads only the stores whose documents changed.
+ */
+export function useStorageSync() {
+  useEffect(() => {
+    const unlisten = onStorageChanged((changes) => {
+      const kinds = new Set(changes.map((change) => change.kind));
+
+      if (kinds.has("library") || kinds.has("playlist")) {
+        usePresenterStore.getState().loadData().catch(console.error);
+      }
+      if (kinds.has("mediaItem") || kinds.has("mediaPlaylist")) {
+        useMediaLibraryStore.getState().loadData().catch(console.error);
+      }
+      if (
+        changes.some(
+          (change) => change.kind === "settings" && change.id === "tag-groups"
+        )
+      ) {
+        useSettingsStore.getState().loadTagGroups().catch(console.error);
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+}
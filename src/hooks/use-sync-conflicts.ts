@@ -0,0 +1,40 @@
+import { useEffect, useState } from "react";
+import {
+  checkSyncConflicts,
+  onSyncConflicts,
+  resolveSyncConflict,
+} from "@/services/folder-sync";
+import { MergeSide, SyncConflict } from "@/types/folder-sync";
+
+/**
+ * Hook to track conflicted copies whose edits need the user
+ *
+ * Copies that merge cleanly are handled by the backend; the merged
+ * documents are reloaded through useStorageSync.
+ */
+export function useSyncConflicts() {
+  const [conflicts, setConflicts] = useState<SyncConflict[]>([]);
+
+  useEffect(() => {
+    // Copies synced while the app was closed, or before this window opened
+    checkSyncConflicts().then(setConflicts).catch(console.error);
+
+    const unlisten = onSyncConflicts(setConflicts);
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const resolve = async (
+    conflict: SyncConflict,
+    resolutions: Record<string, MergeSide>
+  ) => {
+    await resolveSyncConflict(conflict.kind, conflict.copyName, resolutions);
+    setConflicts((current) =>
+      current.filter((c) => c.copyName !== conflict.copyName)
+    );
+  };
+
+  return { conflicts, resolve };
+}
@@ -0,0 +1,38 @@
+import { useEffect } from "react";
+import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
+import { onThumbnailProgress } from "@/services/thumbnails";
+
+/**
+ * Hook to apply thumbnails rendered by the backend worker
+ *
+ * Generated thumbnails replace the media item in the library store. Videos
+ * the backend could not decode fall back to capturing a frame in the webview.
+ */
+export function useThumbnailSync() {
+  const applyMediaItemUpdate = useMediaLibraryStore(
+    (state) => state.applyMediaItemUpdate
+  );
+  const captureVideoThumbnail = useMediaLibraryStore(
+    (state) => state.captureVideoThumbnail
+  );
+
+  useEffect(() => {
+    const unlisten = onThumbnailProgress((payload) => {
+      if (payload.status === "generated" && payload.mediaItem) {
+        applyMediaItemUpdate(payload.mediaItem);
+      } else if (payload.status === "skipped") {
+        captureVideoThumbnail(payload.mediaId);
+      } else if (payload.status === "failed") {
+        console.warn(
+          "useThumbnailSync: Thumbnail failed for",
+          payload.mediaId,
+          payload.error
+        );
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applyMediaItemUpdate, captureVideoThumbnail]);
+}
@@ -0,0 +1,33 @@
+import { useEffect } from "react";
+import { useMediaLibraryStore } from "@/stores/presenter/mediaLibraryStore";
+import { onWatchedFolderImported } from "@/services/watched-folders";
+
+/**
+ * Hook to show files the backend imported from watched folders
+ *
+ * The backend saves the updated media playlist itself; this only replaces
+ * the store's copy so the new items appear without a reload.
+ */
+export function useWatchedFolderSync() {
+  const applyPlaylistUpdate = useMediaLibraryStore(
+    (state) => state.applyPlaylistUpdate
+  );
+
+  useEffect(() => {
+    const unlisten = onWatchedFolderImported((payload) => {
+      if (payload.mediaPlaylist) {
+        applyPlaylistUpdate(payload.mediaPlaylist);
+      }
+      if (payload.failed.length > 0) {
+        console.warn(
+          "useWatchedFolderSync: Some files failed to import",
+          payload.failed
+        );
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applyPlaylistUpdate]);
+}
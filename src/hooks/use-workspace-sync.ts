@@ -0,0 +1,20 @@
+import { useEffect } from "react";
+import { onWorkspaceChanged } from "@/services/workspaces";
+
+/**
+ * Hook to reload the window when another workspace becomes active
+ *
+ * Every store holds the previous workspace's data, so starting over is
+ * simpler and safer than resetting each one.
+ */
+export function useWorkspaceSync() {
+  useEffect(() => {
+    const unlisten = onWorkspaceChanged(() => {
+      window.location.reload();
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+}
@@ -6,10 +6,28 @@ import { Toolbar } from "@/components/presenter/toolbar/Toolbar";
 import { ContentLayout } from "@/components/presenter/ContentLayout";
 import { ShowViewProvider } from "@/components/presenter/show/context";
 import { useSettingsSync } from "@/hooks/use-settings-sync";
+import { useThumbnailSync } from "@/hooks/use-thumbnail-sync";
+import { useWatchedFolderSync } from "@/hooks/use-watched-folder-sync";
+import { useMediaLinkSync } from "@/hooks/use-media-link-sync";
+import { useAudioCues } from "@/hooks/use-audio-cues";
+import { useStorageSync } from "@/hooks/use-storage-sync";
+import { useWorkspaceSync } from "@/hooks/use-workspace-sync";
 
 const RootLayout = () => {
   // Listen for settings changes from other windows (e.g., settings window)
   useSettingsSync();
+  // Pick up thumbnails rendered in the background
+  useThumbnailSync();
+  // Pick up files imported from watched folders
+  useWatchedFolderSync();
+  // Flag linked media whose files have gone missing
+  useMediaLinkSync();
+  // Start and stop audio cues as slides are triggered
+  useAudioCues();
+  // Reload documents changed on disk by sync tools or restores
+  useStorageSync();
+  // Start over when another workspace becomes active
+  useWorkspaceSync();
 
   return (
     <div className="flex flex-col h-screen w-screen overflow-hidden">
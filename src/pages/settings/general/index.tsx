@@ -1,6 +1,13 @@
-// TODO: add general settings page
+import { FolderSyncSection } from "@/components/settings/general/FolderSyncSection";
+import { WorkspacesSection } from "@/components/settings/general/WorkspacesSection";
+
 const GeneralPage = () => {
-  return <div className="text-white text-xs">General page</div>;
+  return (
+    <div className="flex flex-col gap-4">
+      <WorkspacesSection />
+      <FolderSyncSection />
+    </div>
+  );
 };
 
 export default GeneralPage;
@@ -4,9 +4,12 @@ import { useEffect } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { SettingsToolbar } from "@/components/settings/SettingsToolbar";
 import { SettingsSidebarProvider } from "@/components/settings/sidebar/context";
+import { useWorkspaceSync } from "@/hooks/use-workspace-sync";
 
 const RootLayout = () => {
   const navigate = useNavigate();
+  // Start over when another workspace becomes active
+  useWorkspaceSync();
 
   // Listen for navigation events from other windows
   useEffect(() => {
@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+import {
+  BundleExportSummary,
+  BundleImportSummary,
+  BundleSource,
+} from "@/types/bundle";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Export a library, slide group or playlist with its media as a .ospbundle file
+ * @param destinationPath - Where to write the bundle
+ */
+export async function exportBundle(
+  source: BundleSource,
+  destinationPath: string
+): Promise<BundleExportSummary> {
+  try {
+    return await invoke<BundleExportSummary>("export_bundle", {
+      source,
+      destinationPath,
+    });
+  } catch (error) {
+    console.error("Failed to export bundle:", error);
+    throw toCommandError("Failed to export bundle", error);
+  }
+}
+
+/**
+ * Import a .ospbundle file, reusing media that already exists locally
+ * @param targetLibraryId - Library to add a slide group bundle to; a new library is created if omitted
+ */
+export async function importBundle(
+  sourcePath: string,
+  targetLibraryId?: string
+): Promise<BundleImportSummary> {
+  try {
+    return await invoke<BundleImportSummary>("import_bundle", {
+      sourcePath,
+      targetLibraryId,
+    });
+  } catch (error) {
+    console.error("Failed to import bundle:", error);
+    throw toCommandError("Failed to import bundle", error);
+  }
+}
@@ -0,0 +1,54 @@
+import { AppError, ErrorKind } from "@/types/errors";
+
+/**
+ * Error thrown by services when a command fails
+ * Keeps the backend's kind and code so callers can branch on them
+ * instead of matching message text
+ */
+export class CommandError extends Error {
+  readonly kind: ErrorKind;
+  readonly code: string;
+  readonly entityId?: string;
+  readonly path?: string;
+  readonly current?: unknown;
+
+  constructor(message: string, error: AppError) {
+    super(message);
+    this.kind = error.kind;
+    this.code = error.code;
+    this.entityId = error.entityId;
+    this.path = error.path;
+    this.current = error.current;
+  }
+}
+
+/**
+ * Check whether a rejected command value is a structured backend error
+ */
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    "kind" in error &&
+    "code" in error &&
+    "message" in error
+  );
+}
+
+/**
+ * Wrap a rejected command value, prefixing its message with what failed
+ * Values that are not backend errors become an "internal" error
+ */
+export function toCommandError(context: string, error: unknown): CommandError {
+  if (error instanceof CommandError) {
+    return error;
+  }
+  const appError: AppError = isAppError(error)
+    ? error
+    : {
+        kind: "internal",
+        code: "INTERNAL",
+        message: error instanceof Error ? error.message : String(error),
+      };
+  return new CommandError(`${context}: ${appError.message}`, appError);
+}
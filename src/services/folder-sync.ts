@@ -0,0 +1,78 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { MergeSide, SyncConflict } from "@/types/folder-sync";
+import { DocumentKind } from "@/types/storage";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Folder sync event constants
+ * Emitted by the backend when conflicted copies have edits that clash
+ */
+export const FolderSyncEvents = {
+  CONFLICTS: "sync:conflicts",
+} as const;
+
+/**
+ * Check whether folder sync (merging conflicted copies from sync tools) is on
+ */
+export async function getFolderSync(): Promise<boolean> {
+  try {
+    return await invoke<boolean>("get_folder_sync");
+  } catch (error) {
+    console.error("Failed to get folder sync:", error);
+    throw toCommandError("Failed to get folder sync", error);
+  }
+}
+
+/**
+ * Turn folder sync on or off; it needs the JSON storage engine
+ */
+export async function setFolderSync(enabled: boolean): Promise<void> {
+  try {
+    await invoke("set_folder_sync", { enabled });
+  } catch (error) {
+    console.error("Failed to set folder sync:", error);
+    throw toCommandError("Failed to set folder sync", error);
+  }
+}
+
+/**
+ * Merge conflicted copies whose edits don't clash, returning the ones that need the user
+ * Merged documents arrive via onStorageChanged
+ */
+export async function checkSyncConflicts(): Promise<SyncConflict[]> {
+  try {
+    return await invoke<SyncConflict[]>("check_sync_conflicts");
+  } catch (error) {
+    console.error("Failed to check sync conflicts:", error);
+    throw toCommandError("Failed to check sync conflicts", error);
+  }
+}
+
+/**
+ * Merge a conflicted copy, keeping the chosen side of every clashing edit
+ * @param resolutions - Side to keep by conflict key; every conflict needs one
+ */
+export async function resolveSyncConflict(
+  kind: DocumentKind,
+  copyName: string,
+  resolutions: Record<string, MergeSide>
+): Promise<void> {
+  try {
+    await invoke("resolve_sync_conflict", { kind, copyName, resolutions });
+  } catch (error) {
+    console.error("Failed to resolve sync conflict:", error);
+    throw toCommandError("Failed to resolve sync conflict", error);
+  }
+}
+
+/**
+ * Listen for conflicted copies found by the storage watcher
+ */
+export function onSyncConflicts(
+  callback: (conflicts: SyncConflict[]) => void
+): Promise<UnlistenFn> {
+  return listen<SyncConflict[]>(FolderSyncEvents.CONFLICTS, (event) => {
+    callback(event.payload);
+  });
+}
@@ -0,0 +1,86 @@
+import { invoke } from "@tauri-apps/api/core";
+import { RevisionChange, RevisionInfo } from "@/types/history";
+import { DocumentKind } from "@/types/storage";
+import { toCommandError } from "@/services/errors";
+
+type HistoryKind = Extract<DocumentKind, "library" | "playlist">;
+
+/**
+ * List the saved revisions of a library or playlist, newest first
+ */
+export async function listRevisions(
+  kind: HistoryKind,
+  id: string
+): Promise<RevisionInfo[]> {
+  try {
+    return await invoke<RevisionInfo[]>("list_revisions", { kind, id });
+  } catch (error) {
+    console.error("Failed to list revisions:", error);
+    throw toCommandError("Failed to list revisions", error);
+  }
+}
+
+/**
+ * Get a library or playlist as it was saved at a history entry
+ * @param sequence - The entry's `sequence` from listRevisions
+ */
+export async function getRevision<T>(
+  kind: HistoryKind,
+  id: string,
+  sequence: number
+): Promise<T> {
+  try {
+    return await invoke<T>("get_revision", { kind, id, sequence });
+  } catch (error) {
+    console.error("Failed to get revision:", error);
+    throw toCommandError("Failed to get revision", error);
+  }
+}
+
+/**
+ * Compare two history entries, by `sequence`
+ * @param to - Later entry; compares with the current document when omitted
+ */
+export async function diffRevisions(
+  kind: HistoryKind,
+  id: string,
+  from: number,
+  to?: number
+): Promise<RevisionChange[]> {
+  try {
+    return await invoke<RevisionChange[]>("diff_revisions", {
+      kind,
+      id,
+      from,
+      to,
+    });
+  } catch (error) {
+    console.error("Failed to diff revisions:", error);
+    throw toCommandError("Failed to diff revisions", error);
+  }
+}
+
+/**
+ * Bring back one slide group (a playlist item for playlists) from a history entry
+ * Every window reloads the document via onStorageChanged
+ * @param sequence - The entry's `sequence` from listRevisions
+ * @returns The document's new revision
+ */
+export async function restoreSlideGroup(
+  kind: HistoryKind,
+  id: string,
+  sequence: number,
+  groupId: string
+): Promise<number> {
+  try {
+    return await invoke<number>("restore_slide_group", {
+      kind,
+      id,
+      sequence,
+      groupId,
+    });
+  } catch (error) {
+    console.error("Failed to restore slide group:", error);
+    throw toCommandError("Failed to restore slide group", error);
+  }
+}
@@ -0,0 +1,94 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { convertMediaItemToFrontend } from "@/services/storage";
+import { MediaItem } from "@/stores/presenter/mediaLibraryStore";
+import { LinkCheck, RelinkReport } from "@/types/media-links";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Linked media event constants
+ * Emitted by the backend after the startup check of linked files
+ */
+export const MediaLinkEvents = {
+  CHECKED: "media:links-checked",
+} as const;
+
+async function convertLinkCheck(check: LinkCheck): Promise<LinkCheck> {
+  return {
+    missing: await Promise.all(check.missing.map(convertMediaItemToFrontend)),
+    restored: await Promise.all(
+      check.restored.map(convertMediaItemToFrontend)
+    ),
+  };
+}
+
+/**
+ * Re-check every linked media file, flagging the ones that are missing
+ */
+export async function checkLinkedMedia(): Promise<LinkCheck> {
+  try {
+    const check = await invoke<LinkCheck>("check_linked_media");
+    return await convertLinkCheck(check);
+  } catch (error) {
+    console.error("Failed to check linked media:", error);
+    throw toCommandError("Failed to check linked media", error);
+  }
+}
+
+/**
+ * Search folders (and their subfolders) for missing linked files
+ * Files are matched by name, or by size when renamed, and verified by content hash
+ * @param mediaIds - Only relink these items; all missing items when omitted
+ */
+export async function relinkMedia(
+  searchDirs: string[],
+  mediaIds?: string[]
+): Promise<RelinkReport> {
+  try {
+    const report = await invoke<RelinkReport>("relink_media", {
+      searchDirs,
+      mediaIds,
+    });
+    return {
+      relinked: await Promise.all(
+        report.relinked.map(convertMediaItemToFrontend)
+      ),
+      missing: await Promise.all(
+        report.missing.map(convertMediaItemToFrontend)
+      ),
+    };
+  } catch (error) {
+    console.error("Failed to relink media:", error);
+    throw toCommandError("Failed to relink media", error);
+  }
+}
+
+/**
+ * Point a linked media item at a file; it must have the same content
+ */
+export async function relinkMediaItem(
+  mediaId: string,
+  path: string
+): Promise<MediaItem> {
+  try {
+    const item = await invoke<MediaItem>("relink_media_item", {
+      mediaId,
+      path,
+    });
+    return await convertMediaItemToFrontend(item);
+  } catch (error) {
+    console.error("Failed to relink media item:", error);
+    throw toCommandError("Failed to relink media item", error);
+  }
+}
+
+/**
+ * Listen for the startup check of linked media
+ */
+export function onLinkedMediaChecked(
+  callback: (payload: LinkCheck) => void
+): Promise<UnlistenFn> {
+  return listen<LinkCheck>(MediaLinkEvents.CHECKED, async (event) => {
+    callback(await convertLinkCheck(event.payload));
+  });
+}
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/core";
+import { ServiceImportSummary } from "@/types/openlp";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Import an OpenLP service file (.osz) as a new playlist
+ * Bundled images and media are added to the media library; the playlist
+ * (and library) reload in every window via onStorageChanged
+ * @param libraryId - Library to also add the service's songs to
+ * @param keepChords - Keep inline chords like "[G]" in song text
+ */
+export async function importOpenLpService(
+  sourcePath: string,
+  options: { libraryId?: string; keepChords?: boolean } = {}
+): Promise<ServiceImportSummary> {
+  try {
+    return await invoke<ServiceImportSummary>("import_openlp_service", {
+      sourcePath,
+      libraryId: options.libraryId,
+      keepChords: options.keepChords,
+    });
+  } catch (error) {
+    console.error("Failed to import OpenLP service:", error);
+    throw toCommandError("Failed to import OpenLP service", error);
+  }
+}
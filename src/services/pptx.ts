@@ -0,0 +1,24 @@
+import { invoke } from "@tauri-apps/api/core";
+import { PresentationImportSummary } from "@/types/pptx";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Import PowerPoint presentations (.pptx) into a library, one slide group
+ * per presentation, sized like its slides
+ * Pictures and videos are added to the media library; the library reloads
+ * in every window via onStorageChanged
+ */
+export async function importPowerPoint(
+  libraryId: string,
+  sourcePaths: string[]
+): Promise<PresentationImportSummary> {
+  try {
+    return await invoke<PresentationImportSummary>("import_powerpoint", {
+      libraryId,
+      sourcePaths,
+    });
+  } catch (error) {
+    console.error("Failed to import PowerPoint:", error);
+    throw toCommandError("Failed to import PowerPoint", error);
+  }
+}
@@ -0,0 +1,20 @@
+import { invoke } from "@tauri-apps/api/core";
+import { SearchHit } from "@/types/search";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Search slide group titles, slide text, tag names and media names
+ * @param limit Maximum number of hits (defaults to 50 on the backend)
+ * @returns Hits ordered by relevance
+ */
+export async function search(
+  query: string,
+  limit?: number
+): Promise<SearchHit[]> {
+  try {
+    return await invoke<SearchHit[]>("search", { query, limit });
+  } catch (error) {
+    console.error("Failed to search:", error);
+    throw toCommandError("Failed to search", error);
+  }
+}
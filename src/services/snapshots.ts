@@ -0,0 +1,77 @@
+import { invoke } from "@tauri-apps/api/core";
+import {
+  DocumentKind,
+  RestoreResult,
+  SnapshotDiffEntry,
+  SnapshotInfo,
+} from "@/types/storage";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * List snapshots of the data directory, newest first
+ */
+export async function listSnapshots(): Promise<SnapshotInfo[]> {
+  try {
+    return await invoke<SnapshotInfo[]>("list_snapshots");
+  } catch (error) {
+    console.error("Failed to list snapshots:", error);
+    throw toCommandError("Failed to list snapshots", error);
+  }
+}
+
+/**
+ * Create a manual snapshot
+ * @returns The new snapshot, or null if nothing changed since the last one
+ */
+export async function createSnapshot(): Promise<SnapshotInfo | null> {
+  try {
+    return await invoke<SnapshotInfo | null>("create_snapshot");
+  } catch (error) {
+    console.error("Failed to create snapshot:", error);
+    throw toCommandError("Failed to create snapshot", error);
+  }
+}
+
+/**
+ * Compare a snapshot against the current data
+ */
+export async function diffSnapshot(id: string): Promise<SnapshotDiffEntry[]> {
+  try {
+    return await invoke<SnapshotDiffEntry[]>("diff_snapshot", { id });
+  } catch (error) {
+    console.error("Failed to diff snapshot:", error);
+    throw toCommandError("Failed to diff snapshot", error);
+  }
+}
+
+/**
+ * Restore a single library, playlist or media document from a snapshot
+ */
+export async function restoreSnapshotDocument(
+  snapshotId: string,
+  kind: DocumentKind,
+  documentId: string
+): Promise<RestoreResult> {
+  try {
+    return await invoke<RestoreResult>("restore_snapshot_document", {
+      snapshotId,
+      kind,
+      documentId,
+    });
+  } catch (error) {
+    console.error("Failed to restore from snapshot:", error);
+    throw toCommandError("Failed to restore from snapshot", error);
+  }
+}
+
+/**
+ * Replace the whole workspace with a snapshot
+ */
+export async function restoreSnapshot(id: string): Promise<RestoreResult> {
+  try {
+    return await invoke<RestoreResult>("restore_snapshot", { id });
+  } catch (error) {
+    console.error("Failed to restore snapshot:", error);
+    throw toCommandError("Failed to restore snapshot", error);
+  }
+}
@@ -0,0 +1,59 @@
+import { invoke } from "@tauri-apps/api/core";
+import { CanvasSize, SlideGroup } from "@/components/presenter/types";
+import { SongImportSummary } from "@/types/song-import";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * File extensions the song importer reads
+ */
+export const SONG_FILE_EXTENSIONS = [
+  "txt",
+  "cho",
+  "crd",
+  "chopro",
+  "chordpro",
+  "chord",
+  "pro",
+  "xml",
+  "pro6",
+];
+
+/**
+ * Import song files into a library, one slide group per song
+ * The library reloads in every window via onStorageChanged
+ * @param canvasSize - Defaults to the library's newest slide group's size
+ * @param keepChords - Keep inline ChordPro chords like "[G]" in the slide text
+ */
+export async function importSongs(
+  libraryId: string,
+  sourcePaths: string[],
+  options: { canvasSize?: CanvasSize; keepChords?: boolean } = {}
+): Promise<SongImportSummary> {
+  try {
+    return await invoke<SongImportSummary>("import_songs", {
+      libraryId,
+      sourcePaths,
+      canvasSize: options.canvasSize,
+      keepChords: options.keepChords,
+    });
+  } catch (error) {
+    console.error("Failed to import songs:", error);
+    throw toCommandError("Failed to import songs", error);
+  }
+}
+
+/**
+ * Export a slide group as an OpenLyrics song
+ * @param destinationPath - Where to write the .xml file
+ */
+export async function exportOpenLyrics(
+  slideGroup: SlideGroup,
+  destinationPath: string
+): Promise<void> {
+  try {
+    await invoke("export_openlyrics", { slideGroup, destinationPath });
+  } catch (error) {
+    console.error("Failed to export song:", error);
+    throw toCommandError("Failed to export song", error);
+  }
+}
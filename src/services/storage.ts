@@ -1,8 +1,53 @@
 import { invoke } from "@tauri-apps/api/core";
 import { convertFileSrc } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { Library, Playlist } from "@/components/presenter/types";
 import { MediaItem, MediaPlaylist } from "@/stores/presenter/mediaLibraryStore";
 import { SlideTagGroup } from "@/components/feature/slide/slide-tag/types";
+import {
+  EngineMigrationSummary,
+  GarbageReport,
+  LoadResult,
+  MediaUsage,
+  QuarantinedFile,
+  RecoveryOutcome,
+  RecoveryReport,
+  StorageEngine,
+} from "@/types/storage";
+import {
+  FolderImportSummary,
+  MediaImportProgress,
+  MediaImportSummary,
+} from "@/types/media-import";
+import { StorageChange } from "@/types/storage-watcher";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Media import event constants
+ * Emitted by the backend for each file of a batch import
+ */
+export const MediaImportEvents = {
+  PROGRESS: "media:import-progress",
+} as const;
+
+/**
+ * Storage event constants
+ * Emitted by the backend with each batch of documents changed outside the app
+ */
+export const StorageEvents = {
+  CHANGED: "storage:changed",
+} as const;
+
+/**
+ * Listen for documents changed on disk by sync tools, restores or manual edits
+ */
+export function onStorageChanged(
+  callback: (changes: StorageChange[]) => void
+): Promise<UnlistenFn> {
+  return listen<StorageChange[]>(StorageEvents.CHANGED, (event) => {
+    callback(event.payload);
+  });
+}
 
 /**
  * Initialize storage directories
@@ -12,7 +57,97 @@ export async function initializeStorage(): Promise<void> {
     await invoke("initialize_storage");
   } catch (error) {
     console.error("Failed to initialize storage:", error);
-    throw new Error(`Storage initialization failed: ${error}`);
+    throw toCommandError("Storage initialization failed", error);
+  }
+}
+
+// Recovery operations
+
+type RecoveryReportListener = (report: RecoveryReport) => void;
+
+const recoveryReportListeners = new Set<RecoveryReportListener>();
+
+/**
+ * Subscribe to files quarantined while loading data
+ * @returns A function that removes the listener
+ */
+export function onRecoveryReport(listener: RecoveryReportListener): () => void {
+  recoveryReportListeners.add(listener);
+  return () => {
+    recoveryReportListeners.delete(listener);
+  };
+}
+
+/**
+ * Unwrap a load result, forwarding any quarantined or skipped files to listeners
+ */
+function unwrapLoadResult<T>(result: LoadResult<T>): T[] {
+  const { quarantined, skipped } = result.recovery;
+  if (quarantined.length > 0) {
+    console.warn("Quarantined unreadable files:", quarantined);
+  }
+  if (skipped.length > 0) {
+    console.warn("Skipped files that could not be read:", skipped);
+  }
+  if (quarantined.length > 0 || skipped.length > 0) {
+    recoveryReportListeners.forEach((listener) => listener(result.recovery));
+  }
+  return result.items;
+}
+
+/**
+ * List files that were moved to quarantine because they failed to load
+ */
+export async function listQuarantinedFiles(): Promise<QuarantinedFile[]> {
+  try {
+    return await invoke<QuarantinedFile[]>("list_quarantined_files");
+  } catch (error) {
+    console.error("Failed to list quarantined files:", error);
+    throw toCommandError("Failed to list quarantined files", error);
+  }
+}
+
+/**
+ * Attempt to repair a quarantined file by dropping only its broken elements
+ */
+export async function recoverQuarantinedFile(
+  id: string
+): Promise<RecoveryOutcome> {
+  try {
+    return await invoke<RecoveryOutcome>("recover_quarantined_file", { id });
+  } catch (error) {
+    console.error("Failed to recover quarantined file:", error);
+    throw toCommandError("Failed to recover quarantined file", error);
+  }
+}
+
+// Storage engine operations
+
+/**
+ * Get the storage engine currently holding libraries, playlists and media
+ */
+export async function getStorageEngine(): Promise<StorageEngine> {
+  try {
+    return await invoke<StorageEngine>("get_storage_engine");
+  } catch (error) {
+    console.error("Failed to get storage engine:", error);
+    throw toCommandError("Failed to get storage engine", error);
+  }
+}
+
+/**
+ * Copy all data into another storage engine and switch to it
+ */
+export async function migrateStorageEngine(
+  engine: StorageEngine
+): Promise<EngineMigrationSummary> {
+  try {
+    return await invoke<EngineMigrationSummary>("migrate_storage_engine", {
+      engine,
+    });
+  } catch (error) {
+    console.error("Failed to migrate storage engine:", error);
+    throw toCommandError("Failed to migrate storage engine", error);
   }
 }
 
@@ -23,23 +158,25 @@ export async function initializeStorage(): Promise<void> {
  */
 export async function loadLibraries(): Promise<Library[]> {
   try {
-    const libraries = await invoke<Library[]>("load_libraries");
-    return libraries;
+    const result = await invoke<LoadResult<Library>>("load_libraries");
+    return unwrapLoadResult(result);
   } catch (error) {
     console.error("Failed to load libraries:", error);
-    throw new Error(`Failed to load libraries: ${error}`);
+    throw toCommandError("Failed to load libraries", error);
   }
 }
 
 /**
  * Save a library to disk
+ * @returns The new revision; rejects with a "SAVE_CONFLICT" error when the
+ * library was saved elsewhere since its `revision` was loaded
  */
-export async function saveLibrary(library: Library): Promise<void> {
+export async function saveLibrary(library: Library): Promise<number> {
   try {
-    await invoke("save_library", { library });
+    return await invoke<number>("save_library", { library });
   } catch (error) {
     console.error("Failed to save library:", error);
-    throw new Error(`Failed to save library: ${error}`);
+    throw toCommandError("Failed to save library", error);
   }
 }
 
@@ -51,7 +188,7 @@ export async function deleteLibrary(id: string): Promise<void> {
     await invoke("delete_library", { id });
   } catch (error) {
     console.error("Failed to delete library:", error);
-    throw new Error(`Failed to delete library: ${error}`);
+    throw toCommandError("Failed to delete library", error);
   }
 }
 
@@ -62,23 +199,25 @@ export async function deleteLibrary(id: string): Promise<void> {
  */
 export async function loadPlaylists(): Promise<Playlist[]> {
   try {
-    const playlists = await invoke<Playlist[]>("load_playlists");
-    return playlists;
+    const result = await invoke<LoadResult<Playlist>>("load_playlists");
+    return unwrapLoadResult(result);
   } catch (error) {
     console.error("Failed to load playlists:", error);
-    throw new Error(`Failed to load playlists: ${error}`);
+    throw toCommandError("Failed to load playlists", error);
   }
 }
 
 /**
  * Save a playlist to disk
+ * @returns The new revision; rejects with a "SAVE_CONFLICT" error when the
+ * playlist was saved elsewhere since its `revision` was loaded
  */
-export async function savePlaylist(playlist: Playlist): Promise<void> {
+export async function savePlaylist(playlist: Playlist): Promise<number> {
   try {
-    await invoke("save_playlist", { playlist });
+    return await invoke<number>("save_playlist", { playlist });
   } catch (error) {
     console.error("Failed to save playlist:", error);
-    throw new Error(`Failed to save playlist: ${error}`);
+    throw toCommandError("Failed to save playlist", error);
   }
 }
 
@@ -90,7 +229,7 @@ export async function deletePlaylist(id: string): Promise<void> {
     await invoke("delete_playlist", { id });
   } catch (error) {
     console.error("Failed to delete playlist:", error);
-    throw new Error(`Failed to delete playlist: ${error}`);
+    throw toCommandError("Failed to delete playlist", error);
   }
 }
 
@@ -101,12 +240,15 @@ export async function deletePlaylist(id: string): Promise<void> {
  */
 export async function loadMediaItems(): Promise<MediaItem[]> {
   try {
-    const items = await invoke<MediaItem[]>("load_media_items");
+    const result = await invoke<LoadResult<MediaItem>>("load_media_items");
+    const items = unwrapLoadResult(result);
     // Convert string dates back to Date objects and convert source to full file URL
     const itemsWithUrls = await Promise.all(
       items.map(async (item) => ({
         ...item,
-        source: await getMediaFileUrl(item.source),
+        source: item.linkedPath
+          ? convertFileSrc(item.linkedPath)
+          : await getMediaFileUrl(item.source),
         createdAt: new Date(item.createdAt),
         updatedAt: new Date(item.updatedAt),
       }))
@@ -114,38 +256,180 @@ export async function loadMediaItems(): Promise<MediaItem[]> {
     return itemsWithUrls;
   } catch (error) {
     console.error("Failed to load media items:", error);
-    throw new Error(`Failed to load media items: ${error}`);
+    throw toCommandError("Failed to load media items", error);
   }
 }
 
 /**
  * Import a media file from the file system
+ * @param link - Reference the file where it is instead of copying it into the library
  */
-export async function importMediaFile(sourcePath: string): Promise<MediaItem> {
+export async function importMediaFile(
+  sourcePath: string,
+  link?: boolean
+): Promise<MediaItem> {
   try {
-    const item = await invoke<MediaItem>("import_media_file", { sourcePath });
-    // Convert string dates back to Date objects and convert source to full file URL
+    const item = await invoke<MediaItem>("import_media_file", {
+      sourcePath,
+      link,
+    });
+    // Duplicates come back with their thumbnail, so convert both paths to URLs
+    return await convertMediaItemToFrontend(item);
+  } catch (error) {
+    console.error("Failed to import media file:", error);
+    throw toCommandError("Failed to import media file", error);
+  }
+}
+
+/**
+ * Import many media files at once
+ * Files are hashed in parallel; progress arrives via onMediaImportProgress
+ * @param link - Reference the files where they are instead of copying them
+ */
+export async function importMediaFiles(
+  sourcePaths: string[],
+  link?: boolean
+): Promise<MediaImportSummary> {
+  try {
+    const summary = await invoke<MediaImportSummary>("import_media_files", {
+      sourcePaths,
+      link,
+    });
     return {
-      ...item,
-      source: await getMediaFileUrl(item.source),
-      createdAt: new Date(item.createdAt),
-      updatedAt: new Date(item.updatedAt),
+      ...summary,
+      imported: await Promise.all(
+        summary.imported.map(convertMediaItemToFrontend)
+      ),
+      duplicates: await Promise.all(
+        summary.duplicates.map(convertMediaItemToFrontend)
+      ),
     };
   } catch (error) {
-    console.error("Failed to import media file:", error);
-    throw new Error(`Failed to import media file: ${error}`);
+    console.error("Failed to import media files:", error);
+    throw toCommandError("Failed to import media files", error);
+  }
+}
+
+/**
+ * Import every supported file in a folder
+ * @param recursive - Include subfolders (defaults to true on the backend)
+ * @param createPlaylist - Collect the files in a new media playlist named after the folder
+ * @param link - Reference the files where they are instead of copying them
+ */
+export async function importMediaFolder(
+  folderPath: string,
+  recursive?: boolean,
+  createPlaylist?: boolean,
+  link?: boolean
+): Promise<FolderImportSummary> {
+  try {
+    const summary = await invoke<FolderImportSummary>("import_media_folder", {
+      folderPath,
+      recursive,
+      createPlaylist,
+      link,
+    });
+    return {
+      imported: await Promise.all(
+        summary.imported.map(convertMediaItemToFrontend)
+      ),
+      duplicates: await Promise.all(
+        summary.duplicates.map(convertMediaItemToFrontend)
+      ),
+      failed: summary.failed,
+      mediaPlaylist: summary.mediaPlaylist
+        ? await convertMediaPlaylistToFrontend(summary.mediaPlaylist)
+        : null,
+    };
+  } catch (error) {
+    console.error("Failed to import media folder:", error);
+    throw toCommandError("Failed to import media folder", error);
   }
 }
 
+/**
+ * Listen for per-file progress of a batch import
+ */
+export function onMediaImportProgress(
+  callback: (payload: MediaImportProgress) => void
+): Promise<UnlistenFn> {
+  return listen<MediaImportProgress>(MediaImportEvents.PROGRESS, async (event) => {
+    const payload = event.payload;
+    callback({
+      ...payload,
+      mediaItem: payload.mediaItem
+        ? await convertMediaItemToFrontend(payload.mediaItem)
+        : null,
+    });
+  });
+}
+
 /**
  * Delete a media item from disk
+ * @param force - Delete even if slides still use the media
  */
-export async function deleteMediaItem(id: string): Promise<void> {
+export async function deleteMediaItem(
+  id: string,
+  force?: boolean
+): Promise<void> {
   try {
-    await invoke("delete_media_item", { id });
+    await invoke("delete_media_item", { id, force });
   } catch (error) {
     console.error("Failed to delete media item:", error);
-    throw new Error(`Failed to delete media item: ${error}`);
+    throw toCommandError("Failed to delete media item", error);
+  }
+}
+
+/**
+ * Re-read duration, dimensions and codecs for a media item from its file
+ * @returns The updated media item
+ */
+export async function probeMediaItem(mediaId: string): Promise<MediaItem> {
+  try {
+    const item = await invoke<MediaItem>("probe_media_item", { mediaId });
+    return await convertMediaItemToFrontend(item);
+  } catch (error) {
+    console.error("Failed to probe media item:", error);
+    throw toCommandError("Failed to probe media item", error);
+  }
+}
+
+/**
+ * List the slides and media playlists that use a media item
+ */
+export async function getMediaUsage(mediaId: string): Promise<MediaUsage> {
+  try {
+    return await invoke<MediaUsage>("get_media_usage", { mediaId });
+  } catch (error) {
+    console.error("Failed to get media usage:", error);
+    throw toCommandError("Failed to get media usage", error);
+  }
+}
+
+/**
+ * List usage for every media item in the library
+ */
+export async function listMediaUsage(): Promise<MediaUsage[]> {
+  try {
+    return await invoke<MediaUsage[]>("list_media_usage");
+  } catch (error) {
+    console.error("Failed to list media usage:", error);
+    throw toCommandError("Failed to list media usage", error);
+  }
+}
+
+/**
+ * Find media files that nothing uses
+ * @param dryRun - Only report orphaned files (default); pass false to delete them
+ */
+export async function collectMediaGarbage(
+  dryRun = true
+): Promise<GarbageReport> {
+  try {
+    return await invoke<GarbageReport>("collect_media_garbage", { dryRun });
+  } catch (error) {
+    console.error("Failed to collect media garbage:", error);
+    throw toCommandError("Failed to collect media garbage", error);
   }
 }
 
@@ -158,7 +442,7 @@ export async function getMediaFilePath(fileName: string): Promise<string> {
     return path;
   } catch (error) {
     console.error("Failed to get media file path:", error);
-    throw new Error(`Failed to get media file path: ${error}`);
+    throw toCommandError("Failed to get media file path", error);
   }
 }
 
@@ -174,12 +458,12 @@ export async function getMediaFileUrl(fileName: string): Promise<string> {
     return url;
   } catch (error) {
     console.error("Failed to get media file URL:", error);
-    throw new Error(`Failed to get media file URL: ${error}`);
+    throw toCommandError("Failed to get media file URL", error);
   }
 }
 
 /**
- * Save a video thumbnail image
+ * Save a video thumbnail image and point the media item at it
  * @param mediaId - The ID of the media item
  * @param blob - The thumbnail image as a Blob
  * @returns The filename of the saved thumbnail
@@ -202,7 +486,7 @@ export async function saveThumbnail(
     return thumbnailFilename;
   } catch (error) {
     console.error("Failed to save thumbnail:", error);
-    throw new Error(`Failed to save thumbnail: ${error}`);
+    throw toCommandError("Failed to save thumbnail", error);
   }
 }
 
@@ -234,7 +518,7 @@ export async function updateMediaThumbnail(
     };
   } catch (error) {
     console.error("Failed to update media thumbnail:", error);
-    throw new Error(`Failed to update media thumbnail: ${error}`);
+    throw toCommandError("Failed to update media thumbnail", error);
   }
 }
 
@@ -273,10 +557,11 @@ function extractMediaFilename(path: string): string {
  * Helper to convert a raw media item from backend to frontend format with URLs
  * Handles both fresh items (with just filenames) and items from playlists (with potentially corrupted paths)
  */
-async function convertMediaItemToFrontend(item: MediaItem): Promise<MediaItem> {
-  // Extract the actual filename from potentially corrupted paths
-  const sourceFilename = extractMediaFilename(item.source);
-  const source = await getMediaFileUrl(sourceFilename);
+export async function convertMediaItemToFrontend(item: MediaItem): Promise<MediaItem> {
+  // Linked files are served from where they are; copies from the media directory
+  const source = item.linkedPath
+    ? convertFileSrc(item.linkedPath)
+    : await getMediaFileUrl(extractMediaFilename(item.source));
 
   let thumbnail: string | undefined;
   if (item.thumbnail) {
@@ -293,27 +578,36 @@ async function convertMediaItemToFrontend(item: MediaItem): Promise<MediaItem> {
   };
 }
 
+/**
+ * Helper to convert a raw media playlist from backend to frontend format with URLs
+ */
+export async function convertMediaPlaylistToFrontend(
+  playlist: MediaPlaylist
+): Promise<MediaPlaylist> {
+  return {
+    ...playlist,
+    mediaItems: await Promise.all(
+      playlist.mediaItems.map(convertMediaItemToFrontend)
+    ),
+    createdAt: new Date(playlist.createdAt),
+    updatedAt: new Date(playlist.updatedAt),
+  };
+}
+
 /**
  * Load all media playlists from disk
  */
 export async function loadMediaPlaylists(): Promise<MediaPlaylist[]> {
   try {
-    const playlists = await invoke<MediaPlaylist[]>("load_media_playlists");
-    // Convert all media items within playlists to proper format with URLs
-    const playlistsWithUrls = await Promise.all(
-      playlists.map(async (playlist) => ({
-        ...playlist,
-        mediaItems: await Promise.all(
-          playlist.mediaItems.map(convertMediaItemToFrontend)
-        ),
-        createdAt: new Date(playlist.createdAt),
-        updatedAt: new Date(playlist.updatedAt),
-      }))
+    const result = await invoke<LoadResult<MediaPlaylist>>(
+      "load_media_playlists"
     );
-    return playlistsWithUrls;
+    const playlists = unwrapLoadResult(result);
+    // Convert all media items within playlists to proper format with URLs
+    return await Promise.all(playlists.map(convertMediaPlaylistToFrontend));
   } catch (error) {
     console.error("Failed to load media playlists:", error);
-    throw new Error(`Failed to load media playlists: ${error}`);
+    throw toCommandError("Failed to load media playlists", error);
   }
 }
 
@@ -346,7 +640,7 @@ export async function saveMediaPlaylist(
     await invoke("save_media_playlist", { playlist: normalizedPlaylist });
   } catch (error) {
     console.error("Failed to save media playlist:", error);
-    throw new Error(`Failed to save media playlist: ${error}`);
+    throw toCommandError("Failed to save media playlist", error);
   }
 }
 
@@ -358,7 +652,7 @@ export async function deleteMediaPlaylist(id: string): Promise<void> {
     await invoke("delete_media_playlist", { id });
   } catch (error) {
     console.error("Failed to delete media playlist:", error);
-    throw new Error(`Failed to delete media playlist: ${error}`);
+    throw toCommandError("Failed to delete media playlist", error);
   }
 }
 
@@ -373,7 +667,7 @@ export async function loadTagGroups(): Promise<SlideTagGroup[]> {
     return tagGroups;
   } catch (error) {
     console.error("Failed to load tag groups:", error);
-    throw new Error(`Failed to load tag groups: ${error}`);
+    throw toCommandError("Failed to load tag groups", error);
   }
 }
 
@@ -385,6 +679,6 @@ export async function saveTagGroups(tagGroups: SlideTagGroup[]): Promise<void> {
     await invoke("save_tag_groups", { tagGroups });
   } catch (error) {
     console.error("Failed to save tag groups:", error);
-    throw new Error(`Failed to save tag groups: ${error}`);
+    throw toCommandError("Failed to save tag groups", error);
   }
 }
@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { convertMediaItemToFrontend } from "@/services/storage";
+import { ThumbnailProgress } from "@/types/thumbnails";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Thumbnail event constants
+ * Emitted by the backend thumbnail worker to every window
+ */
+export const ThumbnailEvents = {
+  PROGRESS: "media:thumbnail-progress",
+} as const;
+
+/**
+ * Queue thumbnail generation in the background
+ * @param mediaIds Items to regenerate; when omitted, every item missing a thumbnail is queued
+ * @returns How many items were queued
+ */
+export async function generateThumbnails(mediaIds?: string[]): Promise<number> {
+  try {
+    return await invoke<number>("generate_thumbnails", { mediaIds });
+  } catch (error) {
+    console.error("Failed to queue thumbnails:", error);
+    throw toCommandError("Failed to queue thumbnails", error);
+  }
+}
+
+/**
+ * Listen for thumbnail worker progress
+ * Generated items arrive with their file paths already converted to URLs
+ */
+export function onThumbnailProgress(
+  callback: (payload: ThumbnailProgress) => void
+): Promise<UnlistenFn> {
+  return listen<ThumbnailProgress>(ThumbnailEvents.PROGRESS, async (event) => {
+    const payload = event.payload;
+    callback({
+      ...payload,
+      mediaItem: payload.mediaItem
+        ? await convertMediaItemToFrontend(payload.mediaItem)
+        : null,
+    });
+  });
+}
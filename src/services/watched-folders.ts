@@ -0,0 +1,101 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import {
+  convertMediaItemToFrontend,
+  convertMediaPlaylistToFrontend,
+} from "@/services/storage";
+import { WatchedFolder, WatchedFolderImport } from "@/types/watched-folders";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Watched folder event constants
+ * Emitted by the backend after a scan imports files
+ */
+export const WatchedFolderEvents = {
+  IMPORTED: "media:watched-folder-imported",
+} as const;
+
+/**
+ * List watched media folders
+ */
+export async function listWatchedFolders(): Promise<WatchedFolder[]> {
+  try {
+    return await invoke<WatchedFolder[]>("list_watched_folders");
+  } catch (error) {
+    console.error("Failed to list watched folders:", error);
+    throw toCommandError("Failed to list watched folders", error);
+  }
+}
+
+/**
+ * Watch a folder and import new files automatically
+ * @param mediaPlaylistId - Playlist to import into; a new one named after the folder is created when omitted
+ * @param recursive - Include subfolders (defaults to true on the backend)
+ * @param link - Reference new files in place instead of copying them
+ */
+export async function addWatchedFolder(
+  path: string,
+  mediaPlaylistId?: string,
+  recursive?: boolean,
+  link?: boolean
+): Promise<WatchedFolder> {
+  try {
+    return await invoke<WatchedFolder>("add_watched_folder", {
+      path,
+      mediaPlaylistId,
+      recursive,
+      link,
+    });
+  } catch (error) {
+    console.error("Failed to add watched folder:", error);
+    throw toCommandError("Failed to add watched folder", error);
+  }
+}
+
+/**
+ * Stop watching a folder; its playlist and media are kept
+ */
+export async function removeWatchedFolder(id: string): Promise<void> {
+  try {
+    await invoke("remove_watched_folder", { id });
+  } catch (error) {
+    console.error("Failed to remove watched folder:", error);
+    throw toCommandError("Failed to remove watched folder", error);
+  }
+}
+
+/**
+ * Scan every watched folder now instead of waiting for the next poll
+ * Results also arrive via onWatchedFolderImported
+ */
+export async function scanWatchedFolders(): Promise<void> {
+  try {
+    await invoke("scan_watched_folders");
+  } catch (error) {
+    console.error("Failed to scan watched folders:", error);
+    throw toCommandError("Failed to scan watched folders", error);
+  }
+}
+
+/**
+ * Listen for files imported from watched folders
+ */
+export function onWatchedFolderImported(
+  callback: (payload: WatchedFolderImport) => void
+): Promise<UnlistenFn> {
+  return listen<WatchedFolderImport>(
+    WatchedFolderEvents.IMPORTED,
+    async (event) => {
+      const payload = event.payload;
+      callback({
+        ...payload,
+        mediaPlaylist: payload.mediaPlaylist
+          ? await convertMediaPlaylistToFrontend(payload.mediaPlaylist)
+          : null,
+        imported: await Promise.all(
+          payload.imported.map(convertMediaItemToFrontend)
+        ),
+      });
+    }
+  );
+}
@@ -0,0 +1,106 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { Workspace, WorkspaceList } from "@/types/workspaces";
+import { toCommandError } from "@/services/errors";
+
+/**
+ * Workspace event constants
+ * Emitted by the backend after another workspace became active
+ */
+export const WorkspaceEvents = {
+  CHANGED: "workspace:changed",
+} as const;
+
+/**
+ * List the workspaces on this machine and which one is active
+ */
+export async function listWorkspaces(): Promise<WorkspaceList> {
+  try {
+    return await invoke<WorkspaceList>("list_workspaces");
+  } catch (error) {
+    console.error("Failed to list workspaces:", error);
+    throw toCommandError("Failed to list workspaces", error);
+  }
+}
+
+/**
+ * Add a workspace
+ * @param path - Empty folder, or one holding a workspace; defaults to the app data directory
+ */
+export async function createWorkspace(
+  name: string,
+  path?: string
+): Promise<Workspace> {
+  try {
+    return await invoke<Workspace>("create_workspace", { name, path });
+  } catch (error) {
+    console.error("Failed to create workspace:", error);
+    throw toCommandError("Failed to create workspace", error);
+  }
+}
+
+/**
+ * Rename a workspace
+ */
+export async function renameWorkspace(
+  id: string,
+  name: string
+): Promise<Workspace> {
+  try {
+    return await invoke<Workspace>("rename_workspace", { id, name });
+  } catch (error) {
+    console.error("Failed to rename workspace:", error);
+    throw toCommandError("Failed to rename workspace", error);
+  }
+}
+
+/**
+ * Forget a workspace; its files are left on disk
+ */
+export async function removeWorkspace(id: string): Promise<void> {
+  try {
+    await invoke("remove_workspace", { id });
+  } catch (error) {
+    console.error("Failed to remove workspace:", error);
+    throw toCommandError("Failed to remove workspace", error);
+  }
+}
+
+/**
+ * Make a workspace active; every window reloads via onWorkspaceChanged
+ */
+export async function switchWorkspace(id: string): Promise<Workspace> {
+  try {
+    return await invoke<Workspace>("switch_workspace", { id });
+  } catch (error) {
+    console.error("Failed to switch workspace:", error);
+    throw toCommandError("Failed to switch workspace", error);
+  }
+}
+
+/**
+ * Move a workspace's data to another folder
+ * @param destination - Missing or empty folder
+ */
+export async function moveWorkspace(
+  id: string,
+  destination: string
+): Promise<Workspace> {
+  try {
+    return await invoke<Workspace>("move_workspace", { id, destination });
+  } catch (error) {
+    console.error("Failed to move workspace:", error);
+    throw toCommandError("Failed to move workspace", error);
+  }
+}
+
+/**
+ * Listen for another workspace becoming active
+ */
+export function onWorkspaceChanged(
+  callback: (workspace: Workspace) => void
+): Promise<UnlistenFn> {
+  return listen<Workspace>(WorkspaceEvents.CHANGED, (event) => {
+    callback(event.payload);
+  });
+}
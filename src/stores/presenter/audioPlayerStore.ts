@@ -0,0 +1,283 @@
+import { create } from "zustand";
+import { AudioCue } from "@/components/feature/slide/types";
+import { MediaItem, useMediaLibraryStore } from "./mediaLibraryStore";
+
+// How long stopping background music or a cue takes to fade out
+const FADE_OUT_MS = 1000;
+
+export interface AudioPlayerState {
+  // Background music transport (one media playlist at a time)
+  playlistId: string | null;
+  trackIds: string[]; // Audio items of the playlist, in playlist order
+  trackIndex: number;
+  isPlaying: boolean;
+  currentTime: number; // Seconds into the current track
+  duration: number;
+  volume: number; // 0-1
+  loop: boolean; // Start over after the last track
+  shuffle: boolean;
+
+  // Slide audio cue
+  activeCue: AudioCue | null;
+
+  // Transport actions
+  playPlaylist: (playlistId: string, startMediaId?: string) => void;
+  togglePlayback: () => void;
+  next: () => void;
+  previous: () => void;
+  stop: () => void;
+  seek: (time: number) => void;
+  setVolume: (volume: number) => void;
+  toggleLoop: () => void;
+  toggleShuffle: () => void;
+
+  // Cue actions
+  triggerCue: (cue: AudioCue) => void;
+  stopAll: () => void;
+
+  // Utility actions
+  getCurrentTrack: () => MediaItem | undefined;
+}
+
+// Audio elements live outside the store so state updates don't recreate them
+let musicElement: HTMLAudioElement | null = null;
+let cueElement: HTMLAudioElement | null = null;
+const fadeFrames = new WeakMap<HTMLAudioElement, number>();
+
+// Fade to silence, then pause and rewind
+const fadeOut = (element: HTMLAudioElement) => {
+  const startVolume = element.volume;
+  const startedAt = performance.now();
+
+  const step = () => {
+    const progress = (performance.now() - startedAt) / FADE_OUT_MS;
+    if (progress >= 1) {
+      fadeFrames.delete(element);
+      element.pause();
+      element.currentTime = 0;
+      element.volume = startVolume;
+      return;
+    }
+    element.volume = startVolume * (1 - progress);
+    fadeFrames.set(element, requestAnimationFrame(step));
+  };
+  fadeFrames.set(element, requestAnimationFrame(step));
+};
+
+// Playing again during a fade keeps the element from being paused under us
+const cancelFade = (element: HTMLAudioElement) => {
+  const frame = fadeFrames.get(element);
+  if (frame !== undefined) {
+    cancelAnimationFrame(frame);
+    fadeFrames.delete(element);
+  }
+};
+
+const getMusicElement = () => {
+  if (!musicElement) {
+    musicElement = new Audio();
+    musicElement.addEventListener("timeupdate", () => {
+      useAudioPlayerStore.setState({
+        currentTime: musicElement?.currentTime ?? 0,
+      });
+    });
+    musicElement.addEventListener("loadedmetadata", () => {
+      useAudioPlayerStore.setState({
+        duration: musicElement?.duration ?? 0,
+      });
+    });
+    musicElement.addEventListener("ended", () => {
+      useAudioPlayerStore.getState().next();
+    });
+  }
+  return musicElement;
+};
+
+// Prefer the library's current source so relinked files keep playing
+const resolveCueSource = (cue: AudioCue) => {
+  if (cue.mediaId) {
+    const item = useMediaLibraryStore.getState().getMediaById(cue.mediaId);
+    if (item) return item.source;
+  }
+  return cue.src;
+};
+
+const initialState = {
+  playlistId: null as string | null,
+  trackIds: [] as string[],
+  trackIndex: 0,
+  isPlaying: false,
+  currentTime: 0,
+  duration: 0,
+  volume: 1,
+  loop: true,
+  shuffle: false,
+  activeCue: null as AudioCue | null,
+};
+
+export const useAudioPlayerStore = create<AudioPlayerState>((set, get) => {
+  const playTrack = (index: number) => {
+    const track = useMediaLibraryStore
+      .getState()
+      .getMediaById(get().trackIds[index]);
+    if (!track) {
+      get().stop();
+      return;
+    }
+
+    const element = getMusicElement();
+    cancelFade(element);
+    element.src = track.source;
+    element.volume = get().volume;
+    element.play().catch((error) => {
+      console.error("Failed to play background music:", error);
+      set({ isPlaying: false });
+    });
+    set({ trackIndex: index, isPlaying: true, currentTime: 0, duration: 0 });
+  };
+
+  return {
+    ...initialState,
+
+    // Transport actions
+    playPlaylist: (playlistId, startMediaId) => {
+      const playlist = useMediaLibraryStore
+        .getState()
+        .getPlaylistById(playlistId);
+      const trackIds =
+        playlist?.mediaItems
+          .filter((item) => item.type === "audio")
+          .map((item) => item.id) ?? [];
+      if (trackIds.length === 0) return;
+
+      set({ playlistId, trackIds });
+      const startIndex = startMediaId ? trackIds.indexOf(startMediaId) : -1;
+      playTrack(
+        startIndex !== -1
+          ? startIndex
+          : get().shuffle
+            ? Math.floor(Math.random() * trackIds.length)
+            : 0
+      );
+    },
+
+    togglePlayback: () => {
+      const { isPlaying, playlistId, trackIds } = get();
+      if (!playlistId || trackIds.length === 0) return;
+
+      const element = getMusicElement();
+      if (isPlaying) {
+        element.pause();
+        set({ isPlaying: false });
+      } else if (!element.src) {
+        playTrack(get().trackIndex);
+      } else {
+        cancelFade(element);
+        element.volume = get().volume;
+        element.play().catch((error) => {
+          console.error("Failed to resume background music:", error);
+        });
+        set({ isPlaying: true });
+      }
+    },
+
+    next: () => {
+      const { trackIds, trackIndex, loop, shuffle } = get();
+      if (trackIds.length === 0) return;
+
+      if (shuffle && trackIds.length > 1) {
+        // Any track but the current one
+        const offset = 1 + Math.floor(Math.random() * (trackIds.length - 1));
+        playTrack((trackIndex + offset) % trackIds.length);
+      } else if (trackIndex + 1 < trackIds.length) {
+        playTrack(trackIndex + 1);
+      } else if (loop) {
+        playTrack(0);
+      } else {
+        get().stop();
+      }
+    },
+
+    previous: () => {
+      const { trackIds, trackIndex, currentTime } = get();
+      if (trackIds.length === 0) return;
+
+      // Restart the track unless it only just started
+      if (currentTime > 3 || trackIndex === 0) {
+        get().seek(0);
+      } else {
+        playTrack(trackIndex - 1);
+      }
+    },
+
+    stop: () => {
+      if (musicElement?.paused) {
+        musicElement.currentTime = 0;
+      } else if (musicElement) {
+        fadeOut(musicElement);
+      }
+      set({ isPlaying: false, currentTime: 0 });
+    },
+
+    seek: (time) => {
+      const element = getMusicElement();
+      element.currentTime = time;
+      set({ currentTime: time });
+    },
+
+    setVolume: (volume) => {
+      getMusicElement().volume = volume;
+      set({ volume });
+    },
+
+    toggleLoop: () => set((state) => ({ loop: !state.loop })),
+
+    toggleShuffle: () => set((state) => ({ shuffle: !state.shuffle })),
+
+    // Cue actions
+    triggerCue: (cue) => {
+      if (cue.action === "stop") {
+        get().stopAll();
+        return;
+      }
+
+      const src = resolveCueSource(cue);
+      if (!src) return;
+
+      // A new cue replaces the one playing
+      cueElement?.pause();
+      cueElement = new Audio(src);
+      cueElement.volume = cue.volume ?? 1;
+      cueElement.loop = cue.loop ?? false;
+      cueElement.addEventListener("ended", () => {
+        set({ activeCue: null });
+      });
+      cueElement.play().catch((error) => {
+        console.error("Failed to play audio cue:", error);
+        set({ activeCue: null });
+      });
+      set({ activeCue: cue });
+    },
+
+    stopAll: () => {
+      if (cueElement && !cueElement.paused) {
+        fadeOut(cueElement);
+      }
+      cueElement = null;
+      set({ activeCue: null });
+      get().stop();
+    },
+
+    // Utility actions
+    getCurrentTrack: () => {
+      const { trackIds, trackIndex } = get();
+      return useMediaLibraryStore
+        .getState()
+        .getMediaById(trackIds[trackIndex]);
+    },
+  };
+});
+
+// Selectors
+export const selectIsAudioPlaying = (state: AudioPlayerState) =>
+  state.isPlaying || state.activeCue !== null;
@@ -1,8 +1,11 @@
 import { create } from "zustand";
+import { confirm } from "@tauri-apps/plugin-dialog";
 import * as storage from "@/services/storage";
+import { CommandError } from "@/services/errors";
 import { generateVideoThumbnail } from "@/utils/generateVideoThumbnail";
+import { FolderImportSummary, MediaImportSummary } from "@/types/media-import";
 
-export type MediaType = "image" | "video";
+export type MediaType = "image" | "video" | "audio";
 
 export interface MediaItem {
   id: string;
@@ -10,11 +13,14 @@ export interface MediaItem {
   type: MediaType;
   source: string; // Path or URL to the media file
   thumbnail?: string; // Optional thumbnail path/URL
-  duration?: number; // For videos, duration in seconds
+  duration?: number; // For videos and audio, duration in seconds
   createdAt: Date;
   updatedAt: Date;
   metadata?: Record<string, unknown>; // For any additional metadata
   hash?: string; // SHA256 hash for deduplication
+  linkedPath?: string; // Absolute path of a file used in place instead of copied
+  missing?: boolean; // Linked file was not found at the last check
+  schemaVersion?: number; // On-disk schema version, stamped by the backend
 }
 
 export interface MediaPlaylist {
@@ -22,6 +28,7 @@ export interface MediaPlaylist {
   name: string;
   mediaItems: MediaItem[];
   order: number;
+  schemaVersion?: number; // On-disk schema version, stamped by the backend
   createdAt: Date;
   updatedAt: Date;
 }
@@ -46,7 +53,10 @@ export interface MediaLibraryState {
 
   // Media actions within playlists
   addMediaToPlaylist: (playlistId: string, item: MediaItem) => void;
-  removeMediaFromPlaylist: (playlistId: string, mediaId: string) => void;
+  removeMediaFromPlaylist: (
+    playlistId: string,
+    mediaId: string
+  ) => Promise<void>;
   updateMediaInPlaylist: (
     playlistId: string,
     mediaId: string,
@@ -56,6 +66,9 @@ export interface MediaLibraryState {
     playlistId: string,
     mediaItems: MediaItem[]
   ) => void;
+  applyMediaItemUpdate: (item: MediaItem) => void;
+  applyPlaylistUpdate: (playlist: MediaPlaylist) => void;
+  captureVideoThumbnail: (mediaId: string) => Promise<void>;
 
   // Selection actions
   selectMedia: (id: string | null) => void;
@@ -75,6 +88,15 @@ export interface MediaLibraryState {
   savePlaylist: (playlist: MediaPlaylist) => Promise<void>;
   createPlaylist: (name?: string) => Promise<MediaPlaylist>;
   importMedia: (sourcePath: string, playlistId?: string) => Promise<MediaItem>;
+  importMediaFiles: (
+    sourcePaths: string[],
+    playlistId?: string,
+    link?: boolean
+  ) => Promise<MediaImportSummary>;
+  importMediaFolder: (
+    folderPath: string,
+    link?: boolean
+  ) => Promise<FolderImportSummary>;
   getMediaUrl: (fileName: string) => Promise<string>;
 
   // Reset
@@ -90,6 +112,41 @@ const initialState = {
 
 const DEFAULT_PLAYLIST_NAME = "Imported Media";
 
+// Backend updates for items not in a playlist yet; the thumbnail worker can
+// finish before importMedia has added the item it just imported
+const pendingItemUpdates = new Map<string, MediaItem>();
+const pendingVideoCaptures = new Set<string>();
+
+// Slides listed when asking to delete media that is still in use
+const MAX_LISTED_USES = 10;
+
+// Ask whether to delete media anyway, listing the slides that use it
+async function confirmDeleteMediaInUse(mediaId: string): Promise<boolean> {
+  const usage = await storage.getMediaUsage(mediaId);
+  const uses = usage.references
+    .filter((reference) => reference.kind !== "mediaPlaylist")
+    .map((reference) =>
+      reference.slideGroupTitle
+        ? `${reference.documentName} › ${reference.slideGroupTitle}`
+        : reference.documentName
+    );
+  const listed = uses.slice(0, MAX_LISTED_USES).map((use) => `• ${use}`);
+  if (uses.length > MAX_LISTED_USES) {
+    listed.push(`…and ${uses.length - MAX_LISTED_USES} more`);
+  }
+
+  const message = [
+    `This media is still used by ${uses.length} slide(s):`,
+    listed.join("\n"),
+    "Delete it anyway? Those slides will show it as missing.",
+  ].join("\n\n");
+  return confirm(message, {
+    title: "Media In Use",
+    kind: "warning",
+    okLabel: "Delete Anyway",
+  });
+}
+
 export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
   ...initialState,
 
@@ -156,7 +213,24 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
     }
   },
 
-  removeMediaFromPlaylist: (playlistId, mediaId) => {
+  removeMediaFromPlaylist: async (playlistId, mediaId) => {
+    // Delete the media first: the backend refuses while slides still use it
+    try {
+      await storage.deleteMediaItem(mediaId);
+    } catch (error) {
+      if (!(error instanceof CommandError && error.code === "MEDIA_IN_USE")) {
+        console.error(error);
+        return;
+      }
+      try {
+        if (!(await confirmDeleteMediaInUse(mediaId))) return;
+        await storage.deleteMediaItem(mediaId, true);
+      } catch (forceError) {
+        console.error(forceError);
+        return;
+      }
+    }
+
     set((state) => ({
       playlists: state.playlists.map((playlist) =>
         playlist.id === playlistId
@@ -177,8 +251,6 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
     if (updatedPlaylist) {
       storage.saveMediaPlaylist(updatedPlaylist).catch(console.error);
     }
-    // Delete the actual media file from disk
-    storage.deleteMediaItem(mediaId).catch(console.error);
   },
 
   updateMediaInPlaylist: (playlistId, mediaId, updates) => {
@@ -223,6 +295,79 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
     }
   },
 
+  // Replace every copy of an item with a version the backend already saved
+  applyMediaItemUpdate: (item) => {
+    if (!get().getMediaById(item.id)) {
+      pendingItemUpdates.set(item.id, item);
+      return;
+    }
+    set((state) => ({
+      playlists: state.playlists.map((playlist) =>
+        playlist.mediaItems.some((i) => i.id === item.id)
+          ? {
+              ...playlist,
+              mediaItems: playlist.mediaItems.map((i) =>
+                i.id === item.id ? item : i
+              ),
+            }
+          : playlist
+      ),
+    }));
+  },
+
+  // Add or replace a playlist the backend already saved
+  applyPlaylistUpdate: (playlist) => {
+    const updatedPlaylist = {
+      ...playlist,
+      mediaItems: playlist.mediaItems.map((item) => {
+        const updatedItem = pendingItemUpdates.get(item.id);
+        pendingItemUpdates.delete(item.id);
+        return updatedItem ?? item;
+      }),
+    };
+    set((state) => ({
+      playlists: state.playlists.some((p) => p.id === playlist.id)
+        ? state.playlists.map((p) =>
+            p.id === playlist.id ? updatedPlaylist : p
+          )
+        : [...state.playlists, updatedPlaylist],
+    }));
+  },
+
+  // Fallback for videos when the backend has no decoder for poster frames
+  captureVideoThumbnail: async (mediaId) => {
+    const mediaItem = get().getMediaById(mediaId);
+    if (!mediaItem) {
+      pendingVideoCaptures.add(mediaId);
+      return;
+    }
+    if (mediaItem.type !== "video" || mediaItem.thumbnail) {
+      return;
+    }
+
+    try {
+      console.log("Generating thumbnail for video:", mediaId);
+      const thumbnailBlob = await generateVideoThumbnail(mediaItem.source);
+
+      if (thumbnailBlob) {
+        const thumbnailFilename = await storage.saveThumbnail(
+          mediaId,
+          thumbnailBlob
+        );
+        const updatedItem = await storage.updateMediaThumbnail(
+          mediaId,
+          thumbnailFilename
+        );
+        get().applyMediaItemUpdate(updatedItem);
+        console.log("Thumbnail generated successfully:", thumbnailFilename);
+      } else {
+        console.warn("Failed to generate thumbnail for video:", mediaId);
+      }
+    } catch (thumbnailError) {
+      console.error("Error generating thumbnail:", thumbnailError);
+    }
+  },
+
   // Selection actions
   selectMedia: (id) => set({ selectedMediaId: id }),
 
@@ -268,8 +413,13 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
     set({ isLoading: true });
     try {
       const playlists = await storage.loadMediaPlaylists();
-      // Auto-select first playlist if none selected
-      const selectedPlaylistId = playlists.length > 0 ? playlists[0].id : null;
+      // Keep the selection across reloads, else auto-select the first playlist
+      const current = get().selectedPlaylistId;
+      const selectedPlaylistId = playlists.some((p) => p.id === current)
+        ? current
+        : playlists.length > 0
+          ? playlists[0].id
+          : null;
       set({ playlists, selectedPlaylistId, isLoading: false });
     } catch (error) {
       console.error("Failed to load media playlists:", error);
@@ -343,31 +493,12 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
         return existingItem;
       }
 
-      // Generate thumbnail for video files (only for new items)
-      if (mediaItem.type === "video") {
-        try {
-          console.log("Generating thumbnail for video:", mediaItem.id);
-          const thumbnailBlob = await generateVideoThumbnail(mediaItem.source);
-
-          if (thumbnailBlob) {
-            const thumbnailFilename = await storage.saveThumbnail(
-              mediaItem.id,
-              thumbnailBlob
-            );
-            mediaItem = await storage.updateMediaThumbnail(
-              mediaItem.id,
-              thumbnailFilename
-            );
-            console.log("Thumbnail generated successfully:", thumbnailFilename);
-          } else {
-            console.warn(
-              "Failed to generate thumbnail for video:",
-              mediaItem.id
-            );
-          }
-        } catch (thumbnailError) {
-          console.error("Error generating thumbnail:", thumbnailError);
-        }
+      // Thumbnails are rendered by the backend worker; pick up one that
+      // finished before we got here
+      const updatedItem = pendingItemUpdates.get(mediaItem.id);
+      if (updatedItem) {
+        pendingItemUpdates.delete(mediaItem.id);
+        mediaItem = updatedItem;
       }
 
       // If no playlist exists, create a default one
@@ -392,6 +523,9 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
 
         // Save the new playlist to disk
         await storage.saveMediaPlaylist(newPlaylist);
+        if (pendingVideoCaptures.delete(mediaItem.id)) {
+          get().captureVideoThumbnail(mediaItem.id);
+        }
         return mediaItem;
       }
 
@@ -416,6 +550,9 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
       if (updatedPlaylist) {
         await storage.saveMediaPlaylist(updatedPlaylist);
       }
+      if (pendingVideoCaptures.delete(mediaItem.id)) {
+        get().captureVideoThumbnail(mediaItem.id);
+      }
 
       return mediaItem;
     } catch (error) {
@@ -425,6 +562,102 @@ export const useMediaLibraryStore = create<MediaLibraryState>((set, get) => ({
     }
   },
 
+  importMediaFiles: async (
+    sourcePaths: string[],
+    playlistId?: string,
+    link?: boolean
+  ) => {
+    set({ isLoading: true });
+    try {
+      const summary = await storage.importMediaFiles(sourcePaths, link);
+
+      // Duplicates already in a playlist are not added again
+      const knownIds = new Set(get().getAllMediaItems().map((item) => item.id));
+      const newItems: MediaItem[] = [];
+      for (const item of [...summary.imported, ...summary.duplicates]) {
+        if (knownIds.has(item.id)) continue;
+        knownIds.add(item.id);
+        const updatedItem = pendingItemUpdates.get(item.id);
+        pendingItemUpdates.delete(item.id);
+        newItems.push(updatedItem ?? item);
+      }
+
+      if (newItems.length === 0) {
+        set({ isLoading: false });
+        return summary;
+      }
+
+      // Add everything to the target playlist in one save, creating a default
+      // playlist if there is none
+      const targetPlaylist = get().playlists.find(
+        (p) => p.id === (playlistId || get().selectedPlaylistId)
+      );
+      const updatedPlaylist: MediaPlaylist = targetPlaylist
+        ? {
+            ...targetPlaylist,
+            mediaItems: [...targetPlaylist.mediaItems, ...newItems],
+            updatedAt: new Date(),
+          }
+        : {
+            id: crypto.randomUUID(),
+            name: DEFAULT_PLAYLIST_NAME,
+            mediaItems: newItems,
+            order: get().playlists.length + 1,
+            createdAt: new Date(),
+            updatedAt: new Date(),
+          };
+
+      set((state) => ({
+        playlists: targetPlaylist
+          ? state.playlists.map((p) =>
+              p.id === updatedPlaylist.id ? updatedPlaylist : p
+            )
+          : [...state.playlists, updatedPlaylist],
+        selectedPlaylistId: targetPlaylist
+          ? state.selectedPlaylistId
+          : updatedPlaylist.id,
+        isLoading: false,
+      }));
+
+      await storage.saveMediaPlaylist(updatedPlaylist);
+
+      for (const item of newItems) {
+        if (pendingVideoCaptures.delete(item.id)) {
+          get().captureVideoThumbnail(item.id);
+        }
+      }
+
+      return summary;
+    } catch (error) {
+      console.error("Failed to import media files:", error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  // Import a folder as a new playlist named after it
+  importMediaFolder: async (folderPath: string, link?: boolean) => {
+    set({ isLoading: true });
+    try {
+      const summary = await storage.importMediaFolder(
+        folderPath,
+        true,
+        true,
+        link
+      );
+      if (summary.mediaPlaylist) {
+        get().applyPlaylistUpdate(summary.mediaPlaylist);
+        set({ selectedPlaylistId: summary.mediaPlaylist.id });
+      }
+      set({ isLoading: false });
+      return summary;
+    } catch (error) {
+      console.error("Failed to import media folder:", error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
   getMediaUrl: async (fileName: string) => {
     try {
       return await storage.getMediaFileUrl(fileName);
@@ -480,3 +713,8 @@ export const selectVideoMedia = (state: MediaLibraryState) =>
   state.playlists.flatMap((playlist) =>
     playlist.mediaItems.filter((item) => item.type === "video")
   );
+
+export const selectAudioMedia = (state: MediaLibraryState) =>
+  state.playlists.flatMap((playlist) =>
+    playlist.mediaItems.filter((item) => item.type === "audio")
+  );
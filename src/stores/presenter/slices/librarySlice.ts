@@ -3,6 +3,7 @@ import { Library, SlideGroup } from "@/components/presenter/types";
 import { SlideData } from "@/components/feature/slide/types";
 import * as storage from "@/services/storage";
 import { createDefaultTextObject } from "../../utils/createDefaultTextObject";
+import { queueCheckedSave } from "../../utils/checkedSave";
 
 export interface LibrarySlice {
   libraries: Library[];
@@ -231,7 +232,7 @@ export const createLibrarySlice: StateCreator<
       if (needsMigration) {
         console.log("Migrating libraries to add slide group IDs...");
         await Promise.all(
-          migratedLibraries.map((lib) => storage.saveLibrary(lib))
+          migratedLibraries.map((lib) => get().saveLibraryToDisk(lib))
         );
       }
     } catch (error) {
@@ -243,7 +244,18 @@ export const createLibrarySlice: StateCreator<
 
   saveLibraryToDisk: async (library: Library) => {
     try {
-      await storage.saveLibrary(library);
+      // Saves the store's copy, which may be newer than `library` by then
+      await queueCheckedSave({
+        id: library.id,
+        getLatest: () => get().libraries.find((lib) => lib.id === library.id),
+        save: storage.saveLibrary,
+        patch: (updates) =>
+          set((state) => ({
+            libraries: state.libraries.map((lib) =>
+              lib.id === library.id ? { ...lib, ...updates } : lib
+            ),
+          })),
+      });
     } catch (error) {
       console.error("Failed to save library to disk:", error);
       throw error;
@@ -6,6 +6,7 @@ import { createDefaultTextObject } from "@/stores/utils/createDefaultTextObject"
 import { LibrarySlice } from "./librarySlice";
 import { MediaItem } from "@/stores/presenter/mediaLibraryStore";
 import { mediaItemToSlideGroup } from "@/stores/utils/mediaItemToSlideGroup";
+import { queueCheckedSave } from "@/stores/utils/checkedSave";
 
 export interface PlaylistSlice {
   playlists: Playlist[];
@@ -499,7 +500,7 @@ export const createPlaylistSlice: StateCreator<
       if (needsMigration) {
         console.log("Migrating playlists to add slide group IDs...");
         await Promise.all(
-          migratedPlaylists.map((pl) => storage.savePlaylist(pl))
+          migratedPlaylists.map((pl) => get().savePlaylistToDisk(pl))
         );
       }
     } catch (error) {
@@ -511,7 +512,18 @@ export const createPlaylistSlice: StateCreator<
 
   savePlaylistToDisk: async (playlist: Playlist) => {
     try {
-      await storage.savePlaylist(playlist);
+      // Saves the store's copy, which may be newer than `playlist` by then
+      await queueCheckedSave({
+        id: playlist.id,
+        getLatest: () => get().playlists.find((pl) => pl.id === playlist.id),
+        save: storage.savePlaylist,
+        patch: (updates) =>
+          set((state) => ({
+            playlists: state.playlists.map((pl) =>
+              pl.id === playlist.id ? { ...pl, ...updates } : pl
+            ),
+          })),
+      });
     } catch (error) {
       console.error("Failed to save playlist to disk:", error);
       throw error;
@@ -0,0 +1,69 @@
+// Helper to save libraries and playlists without clobbering saves made elsewhere
+
+import { confirm } from "@tauri-apps/plugin-dialog";
+import { CommandError } from "@/services/errors";
+
+type RevisionedDocument = { id: string; name: string; revision?: number };
+
+interface CheckedSave<T extends RevisionedDocument> {
+  id: string;
+  getLatest: () => T | undefined; // Read when the save runs; undefined once removed
+  save: (document: T) => Promise<number>; // Resolves to the new revision
+  patch: (updates: Partial<T>) => void; // Merges into the store without saving
+}
+
+// Saves in flight, so each one carries the revision the previous one returned
+const pendingSaves = new Map<string, Promise<void>>();
+
+/**
+ * Save the store's latest copy of a document once earlier saves of it finish
+ *
+ * When the document was saved in another window since it was loaded, the
+ * user either keeps their copy (saved over the other one) or loads the other.
+ */
+export function queueCheckedSave<T extends RevisionedDocument>({
+  id,
+  getLatest,
+  save,
+  patch,
+}: CheckedSave<T>): Promise<void> {
+  const run = async () => {
+    const document = getLatest();
+    if (!document) return;
+
+    try {
+      const revision = await save(document);
+      patch({ revision } as Partial<T>);
+    } catch (error) {
+      if (!(error instanceof CommandError && error.code === "SAVE_CONFLICT")) {
+        throw error;
+      }
+      const current = error.current as T;
+      const keepMine = await confirm(
+        `"${document.name}" was changed in another window since you opened it. Keep your version and replace the other one?`,
+        {
+          title: "Save Conflict",
+          kind: "warning",
+          okLabel: "Keep Mine",
+          cancelLabel: "Load Other Version",
+        }
+      );
+      if (keepMine) {
+        const latest = getLatest() ?? document;
+        const revision = await save({ ...latest, revision: current.revision });
+        patch({ revision } as Partial<T>);
+      } else {
+        patch(current);
+      }
+    }
+  };
+
+  const previous = pendingSaves.get(id) ?? Promise.resolve();
+  const next = previous.catch(() => undefined).then(run);
+  pendingSaves.set(id, next);
+  return next.finally(() => {
+    if (pendingSaves.get(id) === next) {
+      pendingSaves.delete(id);
+    }
+  });
+}
@@ -9,6 +9,7 @@ import { DEFAULT_CANVAS_PRESET } from "@/consts/canvas";
 import { MediaItem } from "../presenter/mediaLibraryStore";
 
 // Currently always show the image or video as Full HD (1920x1080)
+// Audio becomes an empty slide carrying an audio cue
 export const mediaItemToSlideData = (mediaItem: MediaItem): SlideData => {
   const imageProps = {
     id: mediaItem.id,
@@ -40,6 +41,15 @@ export const mediaItemToSlideData = (mediaItem: MediaItem): SlideData => {
     isLocked: true, // Background videos are locked (not editable in edit view)
   } satisfies VideoObject;
 
+  // Audio plays as a cue when the slide is triggered, leaving the screen as is
+  if (mediaItem.type === "audio") {
+    return {
+      id: `media-${mediaItem.id}`,
+      objects: [],
+      audioCue: { action: "play", mediaId: mediaItem.id, src: mediaItem.source },
+    };
+  }
+
   return {
     id: `media-${mediaItem.id}`,
     objects: mediaItem.type === "image" ? [imageProps] : [videoProps],
@@ -0,0 +1,30 @@
+// Bundle types (src-tauri/src/bundles.rs)
+
+export const BUNDLE_EXTENSION = "ospbundle";
+
+export type BundleKind = "library" | "slideGroup" | "playlist";
+
+export type BundleSource =
+  | { type: "library"; libraryId: string }
+  | { type: "slideGroup"; libraryId: string; slideGroupId: string }
+  | { type: "playlist"; playlistId: string };
+
+export interface BundleExportSummary {
+  path: string;
+  kind: BundleKind;
+  name: string;
+  mediaFiles: number;
+  fonts: string[];
+  sizeBytes: number;
+}
+
+export interface BundleImportSummary {
+  kind: BundleKind;
+  name: string;
+  libraryId: string | null;
+  playlistId: string | null;
+  slideGroupIds: string[];
+  mediaImported: number;
+  mediaReused: number;
+  fonts: string[];
+}
@@ -0,0 +1,21 @@
+// Error types (src-tauri/src/error.rs)
+
+export type ErrorKind =
+  | "notFound" // Document, file or folder does not exist
+  | "corrupt" // Data exists but cannot be read back
+  | "permissionDenied" // The OS refused access, or the volume is read-only
+  | "unsupportedFormat" // Unknown file type, or written by a newer version
+  | "conflict" // Clashes with existing state
+  | "windowMissing" // A window the command needs has not been created
+  | "io" // Any other file system or database failure
+  | "internal"; // A bug or an unexpected platform failure
+
+// What every command rejects with
+export interface AppError {
+  kind: ErrorKind;
+  code: string; // e.g. "LIBRARY_NOT_FOUND", "DISK_FULL" or "MEDIA_IN_USE"
+  message: string;
+  entityId?: string; // Document, media item or window concerned
+  path?: string; // File or folder concerned
+  current?: unknown; // Stored document a save conflicted with ("SAVE_CONFLICT")
+}
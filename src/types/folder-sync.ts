@@ -0,0 +1,25 @@
+// Folder sync types (src-tauri/src/folder_sync.rs)
+
+import { DocumentKind } from "@/types/storage";
+
+// Which version of a clashing edit to keep
+export type MergeSide =
+  | "current" // The document file
+  | "copy"; // The conflicted copy left by the sync tool
+
+// An edit both machines made differently since their common version
+export interface MergeConflict {
+  key: string; // Resolution key, e.g. "slideGroups[<id>].slides[<id>]"
+  label: string; // e.g. "Sunday › Amazing Grace › Slide 2"
+  current: unknown | null; // null when deleted
+  copy: unknown | null;
+}
+
+// A conflicted copy with edits the user has to choose between
+export interface SyncConflict {
+  kind: DocumentKind;
+  id: string;
+  name: string;
+  copyName: string; // File name of the conflicted copy
+  conflicts: MergeConflict[];
+}
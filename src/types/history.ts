@@ -0,0 +1,31 @@
+// History types (src-tauri/src/history.rs)
+
+// One saved revision of a library or playlist
+export interface RevisionInfo {
+  sequence: number; // Unique within the document; revisions can repeat
+  revision: number;
+  savedAt: string;
+  name: string; // Document name at the time
+  version: string; // Content hash of the stored copy
+}
+
+// A structural difference between two revisions
+// Playlist items count as slide groups, keyed by item id
+export type RevisionChange =
+  | { type: "renamed"; from: string; to: string }
+  | { type: "groupAdded"; groupId: string; title: string }
+  | { type: "groupRemoved"; groupId: string; title: string }
+  | { type: "groupRenamed"; groupId: string; from: string; to: string }
+  | { type: "groupsReordered" }
+  | { type: "slideAdded"; groupId: string; slideId: string; index: number }
+  | { type: "slideRemoved"; groupId: string; slideId: string; index: number }
+  | { type: "slidesReordered"; groupId: string }
+  | {
+      type: "textChanged";
+      groupId: string;
+      slideId: string;
+      objectId: string;
+      from: string;
+      to: string;
+    }
+  | { type: "slideChanged"; groupId: string; slideId: string }; // Layout, colors, media or tags
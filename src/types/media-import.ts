@@ -0,0 +1,34 @@
+// Media import types (src-tauri/src/media_import.rs)
+
+import { MediaItem, MediaPlaylist } from "@/stores/presenter/mediaLibraryStore";
+
+/**
+ * "duplicate" means the file's content is already in the library; the
+ * existing item is returned instead of a copy
+ */
+export type MediaImportStatus = "imported" | "duplicate" | "failed";
+
+export interface MediaImportProgress {
+  sourcePath: string;
+  status: MediaImportStatus;
+  mediaItem: MediaItem | null;
+  error: string | null;
+  completed: number;
+  total: number;
+}
+
+export interface MediaImportFailure {
+  sourcePath: string;
+  error: string;
+}
+
+// Each list keeps the order the files were given in
+export interface MediaImportSummary {
+  imported: MediaItem[];
+  duplicates: MediaItem[];
+  failed: MediaImportFailure[];
+}
+
+export interface FolderImportSummary extends MediaImportSummary {
+  mediaPlaylist: MediaPlaylist | null; // Set when a playlist was requested
+}
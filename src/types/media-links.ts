@@ -0,0 +1,14 @@
+// Linked media types (src-tauri/src/media_links.rs)
+
+import { MediaItem } from "@/stores/presenter/mediaLibraryStore";
+
+// Emitted once at startup, and returned when links are checked on demand
+export interface LinkCheck {
+  missing: MediaItem[];
+  restored: MediaItem[]; // Flagged missing before, found again
+}
+
+export interface RelinkReport {
+  relinked: MediaItem[];
+  missing: MediaItem[]; // Still not found
+}
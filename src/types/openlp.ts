@@ -0,0 +1,17 @@
+// OpenLP service import types (src-tauri/src/importers/openlp.rs)
+
+import { Playlist } from "@/components/presenter/types";
+
+export interface SkippedServiceItem {
+  title: string;
+  plugin: string; // OpenLP plugin that made the item, e.g. "presentations"
+  reason: string;
+}
+
+export interface ServiceImportSummary {
+  playlist: Playlist | null; // Null when no item could be converted
+  libraryId: string | null; // Set when songs were added to a library
+  mediaImported: number;
+  mediaReused: number; // Already in the media library with the same content
+  skipped: SkippedServiceItem[];
+}
@@ -0,0 +1,18 @@
+// PowerPoint import types (src-tauri/src/importers/pptx.rs)
+
+import { SlideGroup } from "@/components/presenter/types";
+import { MediaImportFailure } from "@/types/media-import";
+
+// Something on a slide that was left out or changed
+export interface UnsupportedContent {
+  sourcePath: string;
+  slide: number; // Slide number, from 1
+  name: string; // As PowerPoint's selection pane shows it, e.g. "Chart 3"
+  reason: string;
+}
+
+export interface PresentationImportSummary {
+  slideGroups: SlideGroup[]; // Added to the library, in file order
+  failed: MediaImportFailure[];
+  unsupported: UnsupportedContent[];
+}
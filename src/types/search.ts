@@ -0,0 +1,24 @@
+// Search types (src-tauri/src/search.rs)
+
+export type SearchHitKind = "slideGroup" | "slide" | "mediaItem";
+
+export type SearchField = "title" | "content" | "tag" | "mediaName";
+
+export interface SnippetPart {
+  text: string;
+  highlighted: boolean;
+}
+
+export interface SearchHit {
+  kind: SearchHitKind;
+  libraryId: string | null;
+  playlistId: string | null;
+  playlistItemId: string | null;
+  slideGroupId: string | null;
+  slideId: string | null;
+  mediaItemId: string | null;
+  title: string;
+  field: SearchField;
+  snippet: SnippetPart[];
+  score: number;
+}
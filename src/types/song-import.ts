@@ -0,0 +1,9 @@
+// Song import types (src-tauri/src/importers/mod.rs)
+
+import { SlideGroup } from "@/components/presenter/types";
+import { MediaImportFailure } from "@/types/media-import";
+
+export interface SongImportSummary {
+  slideGroups: SlideGroup[]; // Added to the library, in file order
+  failed: MediaImportFailure[];
+}
@@ -0,0 +1,18 @@
+// Storage watcher types (src-tauri/src/storage_watcher.rs)
+
+// "settings" ids are file names without extension, e.g. "tag-groups"
+export type StorageEntity =
+  | "library"
+  | "playlist"
+  | "mediaItem"
+  | "mediaPlaylist"
+  | "settings";
+
+export type StorageChangeType = "created" | "modified" | "deleted";
+
+// A document changed on disk by something other than this app
+export interface StorageChange {
+  kind: StorageEntity;
+  id: string;
+  change: StorageChangeType;
+}
@@ -0,0 +1,140 @@
+// Storage types shared with the Rust backend (src-tauri/src/quarantine.rs)
+
+import { AppError } from "@/types/errors";
+
+export type DocumentKind = "library" | "playlist" | "mediaItem" | "mediaPlaylist";
+
+export interface QuarantinedFile {
+  id: string; // File name inside the quarantine directory
+  kind: DocumentKind;
+  fileName: string; // Original file name, e.g. "<library id>.json"
+  originalPath: string;
+  quarantinePath: string;
+  error: string;
+  quarantinedAt: string;
+}
+
+export interface SkippedFile {
+  kind: DocumentKind;
+  path: string;
+  error: AppError;
+}
+
+export interface RecoveryReport {
+  quarantined: QuarantinedFile[];
+  skipped: SkippedFile[]; // Not loaded this time, but not broken either
+}
+
+export interface LoadResult<T> {
+  items: T[];
+  recovery: RecoveryReport;
+}
+
+export interface DroppedElement {
+  pointer: string; // JSON pointer to the element in the original document
+  error: string;
+}
+
+export interface RecoveryOutcome {
+  file: QuarantinedFile;
+  dropped: DroppedElement[];
+}
+
+// Storage engine types (src-tauri/src/repository/mod.rs)
+
+export type StorageEngine = "json" | "sqlite";
+
+export interface EngineMigrationSummary {
+  from: StorageEngine;
+  to: StorageEngine;
+  libraries: number;
+  playlists: number;
+  mediaItems: number;
+  mediaPlaylists: number;
+}
+
+// Snapshot types (src-tauri/src/snapshots.rs)
+
+export type SnapshotReason = "auto" | "manual" | "beforeDelete" | "beforeRestore";
+
+export interface SnapshotInfo {
+  id: string;
+  createdAt: string;
+  reason: SnapshotReason;
+  detail: string | null; // e.g. "Library <id>" for pre-delete snapshots
+  fingerprint: string;
+  libraryCount: number;
+  playlistCount: number;
+  mediaItemCount: number;
+  mediaPlaylistCount: number;
+  sizeBytes: number;
+}
+
+export type SnapshotChange = "added" | "removed" | "modified";
+
+export interface SnapshotDiffEntry {
+  section: string; // "libraries" | "playlists" | "media-metadata" | "media-playlists" | "settings"
+  id: string;
+  name: string | null;
+  change: SnapshotChange;
+}
+
+export interface RestoreResult {
+  safetySnapshotId: string | null;
+  restoredFiles: number;
+  missingMediaFiles: string[]; // Media item ids whose files are no longer on disk
+}
+
+// Media reference types (src-tauri/src/media_references.rs)
+
+export type MediaReferenceKind = "library" | "playlist" | "mediaPlaylist";
+
+export interface MediaReference {
+  kind: MediaReferenceKind;
+  documentId: string;
+  documentName: string;
+  slideGroupId: string | null;
+  slideGroupTitle: string | null;
+  slideId: string | null;
+  fileName: string; // Whole path for linked media
+}
+
+export interface MediaUsage {
+  mediaItemId: string;
+  fileNames: string[];
+  references: MediaReference[];
+}
+
+export type OrphanReason = "unreferenced" | "staleThumbnail";
+
+export interface OrphanedFile {
+  fileName: string;
+  sizeBytes: number;
+  reason: OrphanReason;
+}
+
+export interface GarbageReport {
+  dryRun: boolean;
+  orphanedFiles: OrphanedFile[];
+  unusedMediaItemIds: string[]; // Reported only, never deleted
+  reclaimableBytes: number;
+  reclaimedBytes: number;
+}
+
+// Media probe types (src-tauri/src/media_probe.rs), stored in MediaItem.metadata
+
+export interface MediaProbe {
+  container: string; // "mp4" | "mov" | "m4a" | "webm" | "matroska" | "avi" | "mp3" | "wav" | "flac" | "ogg" | "jpeg" | "png" | "gif" | "webp" | "bmp"
+  fileSize: number;
+  width?: number; // Display size, after orientation/rotation
+  height?: number;
+  duration?: number; // Seconds
+  frameRate?: number;
+  videoCodec?: string;
+  audioCodec?: string;
+  sampleRate?: number; // Hz, of the first audio stream
+  channels?: number;
+  hasAudio?: boolean; // Only set for video containers
+  orientation?: number; // EXIF orientation (1-8)
+  rotation?: number; // Video rotation in degrees
+}
@@ -0,0 +1,18 @@
+// Thumbnail worker types (src-tauri/src/thumbnails.rs)
+
+import { MediaItem } from "@/stores/presenter/mediaLibraryStore";
+
+/**
+ * "skipped" means the item was deleted before its turn, or it is a video and
+ * no ffmpeg is installed to extract a poster frame
+ */
+export type ThumbnailStatus = "generated" | "skipped" | "failed";
+
+export interface ThumbnailProgress {
+  mediaId: string;
+  status: ThumbnailStatus;
+  mediaItem: MediaItem | null; // The updated item when generated
+  error: string | null;
+  completed: number; // Processed since the queue was last empty
+  remaining: number;
+}
@@ -0,0 +1,23 @@
+// Watched media folder types (src-tauri/src/watched_folders.rs)
+
+import { MediaItem, MediaPlaylist } from "@/stores/presenter/mediaLibraryStore";
+import { MediaImportFailure } from "@/types/media-import";
+
+export interface WatchedFolder {
+  id: string;
+  path: string;
+  mediaPlaylistId: string; // New files are added to this media playlist
+  recursive: boolean;
+  link: boolean; // Files are referenced in place instead of copied
+  createdAt: string;
+  lastScanAt: string | null;
+  lastError: string | null; // From the most recent scan
+}
+
+// Emitted when a scan imports (or fails to import) files from a watched folder
+export interface WatchedFolderImport {
+  watchedFolderId: string;
+  mediaPlaylist: MediaPlaylist | null; // Updated playlist, when items were added
+  imported: MediaItem[];
+  failed: MediaImportFailure[];
+}
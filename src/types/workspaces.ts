@@ -0,0 +1,14 @@
+// Workspace types (src-tauri/src/workspaces.rs)
+
+// A separate set of libraries, playlists, media and settings
+export interface Workspace {
+  id: string; // "default" for the one in the app data directory
+  name: string;
+  path: string; // Root directory
+  createdAt: string;
+}
+
+export interface WorkspaceList {
+  activeId: string;
+  workspaces: Workspace[];
+}